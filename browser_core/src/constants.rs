@@ -0,0 +1,15 @@
+/// ウィンドウ全体の大きさ
+pub static WINDOW_WIDTH: i64 = 600;
+pub static WINDOW_HEIGHT: i64 = 400;
+pub static WINDOW_PADDING: i64 = 5;
+
+/// ツールバー（アドレスバーなど）の高さ
+pub static TOOLBAR_HEIGHT: i64 = 26;
+
+/// ページの内容を描画する領域（ビューポート）の大きさ
+pub static CONTENT_AREA_WIDTH: i64 = WINDOW_WIDTH - WINDOW_PADDING * 2;
+pub static CONTENT_AREA_HEIGHT: i64 = WINDOW_HEIGHT - TOOLBAR_HEIGHT - WINDOW_PADDING * 2;
+
+/// 描画に使う等幅のビットマップフォントの、1文字の大きさ（font-size: 16pxのとき）
+pub static CHAR_WIDTH: i64 = 8;
+pub static CHAR_HEIGHT: i64 = 16;
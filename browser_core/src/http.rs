@@ -3,6 +3,13 @@ use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
+/// ページがサブリソース（@importされたスタイルシートなど）を取得するための層
+/// ネットワークの実装はプラットフォームごとに異なるので、トレイトとして切り離す
+pub trait Fetcher: core::fmt::Debug {
+    /// `url`は絶対URL（"http://"から始まる）
+    fn fetch(&self, url: String) -> Result<HttpResponse, Error>;
+}
+
 #[derive(Debug, Clone)]
 pub struct HttpResponse {
     version: String,
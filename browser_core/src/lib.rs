@@ -3,6 +3,7 @@
 extern crate alloc;
 
 pub mod browser;
+pub mod constants;
 pub mod error;
 pub mod http;
 pub mod renderer;
@@ -0,0 +1,584 @@
+use crate::error::Error;
+use crate::renderer::css::cssom::find_block_end;
+use crate::renderer::css::cssom::split_by_comma;
+use crate::renderer::css::cssom::trim_whitespace;
+use crate::renderer::css::cssom::ComponentValue;
+use crate::renderer::css::serialize::serialize_number;
+use crate::renderer::css::value::Length;
+use crate::renderer::css::value::LengthUnit;
+use crate::renderer::css::value::ResolutionContext;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/css-values-4/#math-function
+/// 数学関数の名前かどうか
+pub fn is_math_function(name: &str) -> bool {
+    ["calc", "min", "max", "clamp"]
+        .iter()
+        .any(|f| f.eq_ignore_ascii_case(name))
+}
+
+/// https://www.w3.org/TR/css-values-4/#calc-type-checking
+/// 計算式の型. 長さとパーセンテージを足した式は<length-percentage>になる
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CalcType {
+    Number,
+    Length,
+    Percentage,
+    LengthPercentage,
+}
+
+impl CalcType {
+    /// https://www.w3.org/TR/css-values-4/#calc-type-checking
+    /// 足し算できるのは同じ型同士か、長さとパーセンテージの組み合わせだけ
+    fn add(self, other: CalcType) -> Option<CalcType> {
+        match (self, other) {
+            (a, b) if a == b => Some(a),
+            (CalcType::Number, _) | (_, CalcType::Number) => None,
+            _ => Some(CalcType::LengthPercentage),
+        }
+    }
+
+    /// 掛け算は少なくとも一方が数値でなければならない
+    fn multiply(self, other: CalcType) -> Option<CalcType> {
+        match (self, other) {
+            (CalcType::Number, t) | (t, CalcType::Number) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// <length-percentage>として使える型かどうか
+    pub fn is_length_percentage(&self) -> bool {
+        *self != CalcType::Number
+    }
+}
+
+/// https://www.w3.org/TR/css-values-4/#calc-internal
+/// 計算木のノード. 引き算は符号反転の足し算、割り算は逆数の掛け算として表す
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcNode {
+    Number(f64),
+    Percentage(f64),
+    Length(Length),
+    Sum(Vec<CalcNode>),
+    Negate(Box<CalcNode>),
+    Product(Vec<CalcNode>),
+    Invert(Box<CalcNode>),
+    /// https://www.w3.org/TR/css-values-4/#comp-func
+    Min(Vec<CalcNode>),
+    Max(Vec<CalcNode>),
+    /// clamp(MIN, VAL, MAX)
+    Clamp(Box<CalcNode>, Box<CalcNode>, Box<CalcNode>),
+}
+
+impl CalcNode {
+    /// https://www.w3.org/TR/css-values-4/#calc-syntax
+    /// calc()、min()、max()、clamp()の引数をパースして計算木を作る. 型が合わない式はエラーになる
+    pub fn parse(name: &str, arguments: &[ComponentValue]) -> Result<Self, Error> {
+        let node = match name.to_ascii_lowercase().as_str() {
+            "calc" => parse_sum(arguments)?,
+            "min" => CalcNode::Min(parse_arguments(arguments)?),
+            "max" => CalcNode::Max(parse_arguments(arguments)?),
+            "clamp" => {
+                let mut arguments = parse_arguments(arguments)?;
+                if arguments.len() != 3 {
+                    return Err(Error::UnexpectedInput(
+                        "clamp() requires 3 arguments".to_string(),
+                    ));
+                }
+                let max = arguments.remove(2);
+                let value = arguments.remove(1);
+                let min = arguments.remove(0);
+                CalcNode::Clamp(Box::new(min), Box::new(value), Box::new(max))
+            }
+            _ => {
+                return Err(Error::UnexpectedInput(format!(
+                    "{}() is not a math function",
+                    name
+                )))
+            }
+        };
+
+        node.calc_type().ok_or(Error::UnexpectedInput(format!(
+            "types in {}() are not compatible",
+            name
+        )))?;
+        Ok(node)
+    }
+
+    /// https://www.w3.org/TR/css-values-4/#calc-type-checking
+    /// 式の型を返す. 型が合わない場合はNoneを返す
+    pub fn calc_type(&self) -> Option<CalcType> {
+        match self {
+            CalcNode::Number(_) => Some(CalcType::Number),
+            CalcNode::Percentage(_) => Some(CalcType::Percentage),
+            CalcNode::Length(_) => Some(CalcType::Length),
+            CalcNode::Negate(node) => node.calc_type(),
+            // https://www.w3.org/TR/css-values-4/#calc-type-checking
+            // 割る数は数値でなければならない
+            CalcNode::Invert(node) => match node.calc_type()? {
+                CalcType::Number => Some(CalcType::Number),
+                _ => None,
+            },
+            CalcNode::Product(nodes) => {
+                let mut result = CalcType::Number;
+                for node in nodes {
+                    result = result.multiply(node.calc_type()?)?;
+                }
+                Some(result)
+            }
+            CalcNode::Sum(nodes) | CalcNode::Min(nodes) | CalcNode::Max(nodes) => {
+                let mut result = nodes.first()?.calc_type()?;
+                for node in &nodes[1..] {
+                    result = result.add(node.calc_type()?)?;
+                }
+                Some(result)
+            }
+            CalcNode::Clamp(min, value, max) => min
+                .calc_type()?
+                .add(value.calc_type()?)?
+                .add(max.calc_type()?),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-values-4/#serialize-a-math-function
+    /// 最上位のノードがmin()、max()、clamp()以外の場合はcalc()で囲む
+    pub fn css_text(&self) -> String {
+        match self {
+            CalcNode::Min(_) | CalcNode::Max(_) | CalcNode::Clamp(..) => self.serialize(),
+            _ => format!("calc({})", self.serialize()),
+        }
+    }
+
+    fn is_negative_value(&self) -> bool {
+        match self {
+            CalcNode::Number(value) | CalcNode::Percentage(value) => *value < 0.0,
+            CalcNode::Length(length) => length.value() < 0.0,
+            _ => false,
+        }
+    }
+
+    /// 数値の符号を反転する. 数値以外の場合はそのまま返す
+    fn negated_value(&self) -> CalcNode {
+        match self {
+            CalcNode::Number(value) => CalcNode::Number(-value),
+            CalcNode::Percentage(value) => CalcNode::Percentage(-value),
+            CalcNode::Length(length) => {
+                CalcNode::Length(Length::new(-length.value(), length.unit()))
+            }
+            node => node.clone(),
+        }
+    }
+
+    /// 計算木を式として文字列にする. 足し算は、掛け算や符号反転の中では括弧で囲む
+    fn serialize(&self) -> String {
+        let join = |nodes: &[CalcNode]| {
+            nodes
+                .iter()
+                .map(|node| node.serialize())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let operand = |node: &CalcNode| match node {
+            CalcNode::Sum(_) => format!("({})", node.serialize()),
+            _ => node.serialize(),
+        };
+
+        match self {
+            CalcNode::Number(number) => serialize_number(*number),
+            CalcNode::Percentage(percentage) => format!("{}%", serialize_number(*percentage)),
+            CalcNode::Length(length) => length.css_text(),
+            CalcNode::Sum(nodes) => {
+                let mut result = String::new();
+                for (i, node) in nodes.iter().enumerate() {
+                    match node {
+                        CalcNode::Negate(negated) if i > 0 => {
+                            result.push_str(" - ");
+                            result.push_str(&operand(negated));
+                        }
+                        // 負の値も引き算として書く
+                        node if i > 0 && node.is_negative_value() => {
+                            result.push_str(" - ");
+                            result.push_str(&node.negated_value().serialize());
+                        }
+                        node => {
+                            if i > 0 {
+                                result.push_str(" + ");
+                            }
+                            result.push_str(&node.serialize());
+                        }
+                    }
+                }
+                result
+            }
+            CalcNode::Negate(node) => format!("-1 * {}", operand(node)),
+            CalcNode::Product(nodes) => {
+                let mut result = String::new();
+                for (i, node) in nodes.iter().enumerate() {
+                    match node {
+                        CalcNode::Invert(inverted) if i > 0 => {
+                            result.push_str(" / ");
+                            result.push_str(&operand(inverted));
+                        }
+                        node => {
+                            if i > 0 {
+                                result.push_str(" * ");
+                            }
+                            result.push_str(&operand(node));
+                        }
+                    }
+                }
+                result
+            }
+            CalcNode::Invert(node) => format!("1 / {}", operand(node)),
+            CalcNode::Min(nodes) => format!("min({})", join(nodes)),
+            CalcNode::Max(nodes) => format!("max({})", join(nodes)),
+            CalcNode::Clamp(min, value, max) => format!(
+                "clamp({}, {}, {})",
+                min.serialize(),
+                value.serialize(),
+                max.serialize()
+            ),
+        }
+    }
+
+    /// パーセンテージを含むかどうか
+    pub fn has_percentage(&self) -> bool {
+        match self {
+            CalcNode::Percentage(_) => true,
+            CalcNode::Number(_) | CalcNode::Length(_) => false,
+            CalcNode::Negate(node) | CalcNode::Invert(node) => node.has_percentage(),
+            CalcNode::Sum(nodes)
+            | CalcNode::Product(nodes)
+            | CalcNode::Min(nodes)
+            | CalcNode::Max(nodes) => nodes.iter().any(|node| node.has_percentage()),
+            CalcNode::Clamp(min, value, max) => {
+                min.has_percentage() || value.has_percentage() || max.has_percentage()
+            }
+        }
+    }
+
+    /// https://www.w3.org/TR/css-values-4/#calc-computed-value
+    /// 長さをピクセル値にして、できるところまで式を簡約する
+    /// パーセンテージを含まない式は1つの値になり、含む式は使用値を求めるときまで式のまま残す
+    pub fn compute(&self, context: &ResolutionContext) -> CalcNode {
+        // パーセンテージだけの式は、基準を100として評価すれば1つのパーセンテージになる
+        if self.calc_type() == Some(CalcType::Percentage) {
+            if let Some(value) = self.evaluate(context, Some(100.0)) {
+                return CalcNode::Percentage(value);
+            }
+        }
+        if !self.has_percentage() {
+            if let Some(value) = self.evaluate(context, None) {
+                return match self.calc_type() {
+                    Some(CalcType::Number) => CalcNode::Number(value),
+                    _ => CalcNode::Length(Length::px(value)),
+                };
+            }
+        }
+
+        let compute_all =
+            |nodes: &[CalcNode]| nodes.iter().map(|node| node.compute(context)).collect();
+        match self {
+            CalcNode::Length(length) => CalcNode::Length(Length::px(length.to_px(context))),
+            CalcNode::Number(_) | CalcNode::Percentage(_) => self.clone(),
+            CalcNode::Negate(node) => CalcNode::Negate(Box::new(node.compute(context))),
+            CalcNode::Invert(node) => CalcNode::Invert(Box::new(node.compute(context))),
+            CalcNode::Sum(nodes) => CalcNode::Sum(compute_all(nodes)),
+            CalcNode::Product(nodes) => CalcNode::Product(compute_all(nodes)),
+            CalcNode::Min(nodes) => CalcNode::Min(compute_all(nodes)),
+            CalcNode::Max(nodes) => CalcNode::Max(compute_all(nodes)),
+            CalcNode::Clamp(min, value, max) => CalcNode::Clamp(
+                Box::new(min.compute(context)),
+                Box::new(value.compute(context)),
+                Box::new(max.compute(context)),
+            ),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-values-4/#calc-simplification
+    /// 式を評価してピクセル値（数値の式の場合は数値）を返す. パーセンテージは`percentage_base`を基準にする
+    /// 基準がないのにパーセンテージを含む場合や、0で割った場合はNoneを返す
+    pub fn evaluate(
+        &self,
+        context: &ResolutionContext,
+        percentage_base: Option<f64>,
+    ) -> Option<f64> {
+        let evaluate_all = |nodes: &[CalcNode]| {
+            nodes
+                .iter()
+                .map(|node| node.evaluate(context, percentage_base))
+                .collect::<Option<Vec<f64>>>()
+        };
+
+        let value = match self {
+            CalcNode::Number(number) => *number,
+            CalcNode::Percentage(percentage) => percentage_base? * percentage / 100.0,
+            CalcNode::Length(length) => length.to_px(context),
+            CalcNode::Negate(node) => -node.evaluate(context, percentage_base)?,
+            CalcNode::Invert(node) => 1.0 / node.evaluate(context, percentage_base)?,
+            CalcNode::Sum(nodes) => evaluate_all(nodes)?.iter().sum(),
+            CalcNode::Product(nodes) => evaluate_all(nodes)?.iter().product(),
+            CalcNode::Min(nodes) => evaluate_all(nodes)?.into_iter().reduce(f64::min)?,
+            CalcNode::Max(nodes) => evaluate_all(nodes)?.into_iter().reduce(f64::max)?,
+            // https://www.w3.org/TR/css-values-4/#funcdef-clamp
+            // MINとMAXが矛盾する場合はMINが優先される
+            CalcNode::Clamp(min, value, max) => {
+                let min = min.evaluate(context, percentage_base)?;
+                let value = value.evaluate(context, percentage_base)?;
+                let max = max.evaluate(context, percentage_base)?;
+                f64::max(min, f64::min(value, max))
+            }
+        };
+
+        if value.is_finite() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+/// min()、max()、clamp()のカンマ区切りの引数をパースする
+fn parse_arguments(arguments: &[ComponentValue]) -> Result<Vec<CalcNode>, Error> {
+    split_by_comma(arguments)
+        .into_iter()
+        .map(parse_sum)
+        .collect::<Result<Vec<CalcNode>, Error>>()
+}
+
+/// 計算式を構成する項と演算子
+enum CalcItem {
+    Operand(CalcNode),
+    /// 演算子と、その前後に空白があるかどうか
+    Operator(char, bool, bool),
+}
+
+/// https://www.w3.org/TR/css-values-4/#typedef-calc-sum
+/// `<calc-product> [ [ '+' | '-' ] <calc-product> ]*`
+fn parse_sum(tokens: &[ComponentValue]) -> Result<CalcNode, Error> {
+    let items = parse_items(trim_whitespace(tokens))?;
+
+    let mut terms = Vec::new();
+    let mut product = Vec::new();
+    let mut negate = false;
+    // 次の項の前にある演算子. 最初の項の前はNone
+    let mut operator = Some('+');
+    for item in items {
+        match (item, operator) {
+            (CalcItem::Operand(node), Some(op)) => {
+                if op == '/' {
+                    product.push(CalcNode::Invert(Box::new(node)));
+                } else {
+                    product.push(node);
+                }
+                operator = None;
+            }
+            // https://www.w3.org/TR/css-values-4/#calc-syntax
+            // +と-の前後には空白が必要
+            (CalcItem::Operator(op @ ('+' | '-'), true, true), None) => {
+                terms.push(finish_product(&mut product, negate));
+                negate = op == '-';
+                operator = Some(op);
+            }
+            (CalcItem::Operator(op @ ('*' | '/'), _, _), None) => {
+                operator = Some(op);
+            }
+            _ => {
+                return Err(Error::UnexpectedInput(
+                    "invalid calculation syntax".to_string(),
+                ))
+            }
+        }
+    }
+    if operator.is_some() {
+        return Err(Error::UnexpectedInput(
+            "calculation ends without an operand".to_string(),
+        ));
+    }
+    terms.push(finish_product(&mut product, negate));
+
+    if terms.len() == 1 {
+        Ok(terms.remove(0))
+    } else {
+        Ok(CalcNode::Sum(terms))
+    }
+}
+
+/// 掛け算と割り算の項をまとめて1つのノードにする
+fn finish_product(product: &mut Vec<CalcNode>, negate: bool) -> CalcNode {
+    let mut nodes = core::mem::take(product);
+    let node = if nodes.len() == 1 {
+        nodes.remove(0)
+    } else {
+        CalcNode::Product(nodes)
+    };
+    if negate {
+        CalcNode::Negate(Box::new(node))
+    } else {
+        node
+    }
+}
+
+/// トークン列を、項と演算子の列にする
+fn parse_items(tokens: &[ComponentValue]) -> Result<Vec<CalcItem>, Error> {
+    let mut items = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let operand = match &tokens[i] {
+            ComponentValue::Whitespace => {
+                i += 1;
+                continue;
+            }
+            ComponentValue::Delim(op @ ('+' | '-' | '*' | '/')) => {
+                let before = i > 0 && tokens[i - 1] == ComponentValue::Whitespace;
+                let after = tokens.get(i + 1) == Some(&ComponentValue::Whitespace);
+                items.push(CalcItem::Operator(*op, before, after));
+                i += 1;
+                continue;
+            }
+            ComponentValue::Number(number) => CalcNode::Number(*number),
+            ComponentValue::Percentage(percentage) => CalcNode::Percentage(*percentage),
+            ComponentValue::Dimension { value, unit } => {
+                CalcNode::Length(Length::new(*value, LengthUnit::from_unit(unit)?))
+            }
+            // https://www.w3.org/TR/css-values-4/#typedef-calc-value
+            // 括弧と、入れ子の数学関数
+            token @ (ComponentValue::OpenParenthesis | ComponentValue::Function(_)) => {
+                let end = find_block_end(tokens, i).ok_or(Error::UnexpectedInput(
+                    "parenthesis is not closed".to_string(),
+                ))?;
+                let inner = &tokens[i + 1..end];
+                let node = match token {
+                    ComponentValue::Function(name) if is_math_function(name) => {
+                        CalcNode::parse(name, inner)?
+                    }
+                    ComponentValue::OpenParenthesis => parse_sum(inner)?,
+                    _ => {
+                        return Err(Error::UnexpectedInput(format!(
+                            "{:?} is not allowed in a calculation",
+                            token
+                        )))
+                    }
+                };
+                i = end;
+                node
+            }
+            token => {
+                return Err(Error::UnexpectedInput(format!(
+                    "{:?} is not allowed in a calculation",
+                    token
+                )))
+            }
+        };
+        items.push(CalcItem::Operand(operand));
+        i += 1;
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::token::CssTokenizer;
+    use alloc::vec;
+
+    /// "calc(...)"のような文字列をパースする
+    fn parse(value: &str) -> Result<CalcNode, Error> {
+        let tokens: Vec<ComponentValue> = CssTokenizer::new(value.to_string()).collect();
+        let name = match tokens.first() {
+            Some(ComponentValue::Function(name)) => name.clone(),
+            _ => panic!("{} is not a function", value),
+        };
+        let end = find_block_end(&tokens, 0).expect("should be closed");
+        CalcNode::parse(&name, &tokens[1..end])
+    }
+
+    fn evaluate(value: &str, base: Option<f64>) -> Option<f64> {
+        let context = ResolutionContext::new(10.0, 16.0, 800.0, 600.0);
+        parse(value).expect("should parse").evaluate(&context, base)
+    }
+
+    #[test]
+    fn test_calc() {
+        /* 四則演算の優先順位と、単位の混ざった式の評価を確認する */
+        assert_eq!(evaluate("calc(1px + 2px * 3)", None), Some(7.0));
+        assert_eq!(evaluate("calc((1px + 2px) * 3)", None), Some(9.0));
+        assert_eq!(evaluate("calc(10px - 2px - 3px)", None), Some(5.0));
+        assert_eq!(evaluate("calc(100px / 4 / 5)", None), Some(5.0));
+        assert_eq!(evaluate("calc(2em + 1rem)", None), Some(36.0));
+        assert_eq!(evaluate("calc(100% - 2em)", Some(300.0)), Some(280.0));
+        assert_eq!(evaluate("calc(100% - 2em)", None), None);
+        assert_eq!(evaluate("calc(10px / 0)", None), None);
+        assert_eq!(
+            parse("calc(50% + 1px)").expect("should parse").calc_type(),
+            Some(CalcType::LengthPercentage)
+        );
+    }
+
+    #[test]
+    fn test_comparison_functions() {
+        /* min()、max()、clamp()と、入れ子の数学関数の評価を確認する */
+        assert_eq!(evaluate("min(10px, 2em, 50%)", Some(100.0)), Some(10.0));
+        assert_eq!(evaluate("max(10px, 2em, 50%)", Some(100.0)), Some(50.0));
+        assert_eq!(
+            evaluate("clamp(10px, 50%, 300px)", Some(1000.0)),
+            Some(300.0)
+        );
+        assert_eq!(evaluate("clamp(10px, 50%, 300px)", Some(10.0)), Some(10.0));
+        // MINがMAXより大きい場合はMINになる
+        assert_eq!(evaluate("clamp(50px, 10px, 20px)", None), Some(50.0));
+        assert_eq!(
+            evaluate("calc(min(10px, 5px) * 2 + max(1px, calc(3px)))", None),
+            Some(13.0)
+        );
+    }
+
+    #[test]
+    fn test_invalid_calc() {
+        /* 構文や型が正しくない式はエラーになることを確認する */
+        // +と-の前後に空白がない
+        assert!(parse("calc(1px+2px)").is_err());
+        assert!(parse("calc(1px -2px)").is_err());
+        // 長さと数値の足し算、長さ同士の掛け算、長さで割る割り算
+        assert!(parse("calc(1px + 2)").is_err());
+        assert!(parse("calc(1px * 2px)").is_err());
+        assert!(parse("calc(2 / 1px)").is_err());
+        assert!(parse("calc()").is_err());
+        assert!(parse("calc(1px +)").is_err());
+        assert!(parse("clamp(1px, 2px)").is_err());
+        assert!(parse("min(1px, red)").is_err());
+    }
+
+    #[test]
+    fn test_compute() {
+        /* 計算値では長さがピクセル値になり、パーセンテージを含む式だけが残ることを確認する */
+        let context = ResolutionContext::new(10.0, 16.0, 800.0, 600.0);
+        assert_eq!(
+            parse("calc(2em + 3px)")
+                .expect("should parse")
+                .compute(&context),
+            CalcNode::Length(Length::px(23.0))
+        );
+
+        let computed = parse("calc(100% - 2em)")
+            .expect("should parse")
+            .compute(&context);
+        assert_eq!(
+            computed,
+            CalcNode::Sum(vec![
+                CalcNode::Percentage(100.0),
+                CalcNode::Length(Length::px(-20.0)),
+            ])
+        );
+        // 負の値は引き算として文字列になる
+        assert_eq!(computed.css_text(), "calc(100% - 20px)");
+        // フォントサイズが変わっても、計算値の長さは変わらない
+        let other = ResolutionContext::new(50.0, 16.0, 800.0, 600.0);
+        assert_eq!(computed.evaluate(&other, Some(200.0)), Some(180.0));
+    }
+}
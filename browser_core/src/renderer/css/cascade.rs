@@ -0,0 +1,75 @@
+use crate::renderer::css::cssom::Declaration;
+use crate::renderer::css::cssom::PseudoElement;
+use crate::renderer::css::cssom::QualifiedRule;
+use crate::renderer::dom::node::Element;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/css-cascade-4/#cascade-sort
+/// 要素にセレクタが一致したルールの宣言を、優先度の低い順に並べる. この順にカスケードすると、
+/// 後の宣言ほど優先される. `pseudo`がSomeの場合は、要素の疑似要素に一致したルールの宣言を並べる
+/// 作成者のスタイルシートだけを扱うので、重要度、セレクタの詳細度、出現順の順に比べる
+pub fn cascade_sorted_declarations<'a>(
+    rules: impl IntoIterator<Item = &'a QualifiedRule>,
+    element: &Element,
+    pseudo: Option<PseudoElement>,
+) -> Vec<Declaration> {
+    let mut declarations = Vec::new();
+    for rule in rules {
+        let matched = match pseudo {
+            Some(pseudo) => rule.selector.matches_pseudo_element(element, pseudo),
+            None => rule.selector.matches(element),
+        };
+        if !matched {
+            continue;
+        }
+        let specificity = rule.selector.specificity();
+        for declaration in &rule.declarations {
+            declarations.push((declaration.important, specificity, declaration.clone()));
+        }
+    }
+
+    // 安定ソートなので、重要度と詳細度が同じ宣言は出現順のまま並ぶ
+    declarations.sort_by_key(|(important, specificity, _)| (*important, *specificity));
+    declarations
+        .into_iter()
+        .map(|(_, _, declaration)| declaration)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::cssom::CssParser;
+    use crate::renderer::css::token::CssTokenizer;
+    use alloc::string::String;
+    use alloc::string::ToString;
+
+    fn sorted(style: &str, pseudo: Option<PseudoElement>) -> Vec<String> {
+        let sheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+        let mut element = Element::new("p", Vec::new());
+        element.set_attribute("id", "x");
+        cascade_sorted_declarations(sheet.style_rules(), &element, pseudo)
+            .iter()
+            .map(|declaration| declaration.property.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_cascade_order() {
+        /* 一致した宣言が、重要度、詳細度、出現順の順に優先度の低い方から並ぶことを確認する */
+        assert_eq!(
+            sorted(
+                "#x { top: 0 } p { left: 0 } * { color: red } h1 { width: 0 } p { height: 0 !important } #x { margin: 0 }",
+                None
+            ),
+            ["color", "left", "top", "margin", "height"]
+        );
+        assert_eq!(
+            sorted(
+                "#x::before { top: 0 } p::before { left: 0 } p { color: red } ::before { width: 0 }",
+                Some(PseudoElement::Before)
+            ),
+            ["width", "left", "top"]
+        );
+    }
+}
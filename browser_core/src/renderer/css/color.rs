@@ -0,0 +1,611 @@
+use crate::error::Error;
+use crate::renderer::css::cssom::split_by_comma;
+use crate::renderer::css::cssom::trim_whitespace;
+use crate::renderer::css::cssom::ComponentValue;
+use crate::renderer::css::serialize::serialize_number;
+use crate::renderer::css::value::CssValue;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+
+/// https://www.w3.org/TR/css-color-4/#named-colors
+/// 名前付きの色と、その色の0xRRGGBB形式の値
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+/// https://www.w3.org/TR/css-color-4/#color-type
+/// sRGBの色. 各チャンネルとアルファ値を0から255の整数で保持する
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+}
+
+impl Color {
+    pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// 不透明な色を作る
+    pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::new(r, g, b, 255)
+    }
+
+    fn from_u32(code: u32) -> Self {
+        Self::rgb((code >> 16) as u8, (code >> 8) as u8, code as u8)
+    }
+
+    /// https://www.w3.org/TR/css-color-4/#named-colors
+    /// https://www.w3.org/TR/css-color-4/#transparent-color
+    pub fn from_name(name: &str) -> Result<Self, Error> {
+        if name.eq_ignore_ascii_case("transparent") {
+            return Ok(Self::transparent());
+        }
+
+        match NAMED_COLORS
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        {
+            Some((_, code)) => Ok(Self::from_u32(*code)),
+            None => Err(Error::UnexpectedInput(format!(
+                "color name {:?} is not supported yet",
+                name
+            ))),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-color-4/#hex-notation
+    /// "#rgb"、"#rgba"、"#rrggbb"、"#rrggbbaa"の形式を受け付ける
+    pub fn from_code(code: &str) -> Result<Self, Error> {
+        let invalid = || Error::UnexpectedInput(format!("invalid color code {}", code));
+
+        let digits = code.strip_prefix('#').ok_or_else(invalid)?;
+        let values = digits
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+
+        match values.len() {
+            // 省略形式では、各桁を2回繰り返したものとみなす
+            3 | 4 => Ok(Self::new(
+                values[0] * 17,
+                values[1] * 17,
+                values[2] * 17,
+                values.get(3).map_or(255, |a| a * 17),
+            )),
+            6 | 8 => Ok(Self::new(
+                values[0] * 16 + values[1],
+                values[2] * 16 + values[3],
+                values[4] * 16 + values[5],
+                if values.len() == 8 {
+                    values[6] * 16 + values[7]
+                } else {
+                    255
+                },
+            )),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-color-4/#rgb-functions
+    /// https://www.w3.org/TR/css-color-4/#the-hsl-notation
+    /// 関数トークンの名前と、括弧の内側のトークン列から色を作る
+    pub fn from_function(name: &str, arguments: &[ComponentValue]) -> Result<Self, Error> {
+        let invalid = || Error::UnexpectedInput(format!("invalid color function {}()", name));
+
+        let (components, legacy) = parse_arguments(arguments).ok_or_else(invalid)?;
+        let (channels, alpha) = match components.len() {
+            3 => (&components[..], ColorComponent::Number(1.0)),
+            4 => (&components[..3], components[3]),
+            _ => return Err(invalid()),
+        };
+        let a = alpha.alpha().ok_or_else(invalid)?;
+
+        match name.to_ascii_lowercase().as_str() {
+            "rgb" | "rgba" => {
+                // 旧来のカンマ区切りの構文では、数値とパーセンテージを混在させられない
+                if legacy
+                    && !(channels
+                        .iter()
+                        .all(|c| matches!(c, ColorComponent::Number(_)))
+                        || channels
+                            .iter()
+                            .all(|c| matches!(c, ColorComponent::Percentage(_))))
+                {
+                    return Err(invalid());
+                }
+                let r = channels[0].rgb_channel().ok_or_else(invalid)?;
+                let g = channels[1].rgb_channel().ok_or_else(invalid)?;
+                let b = channels[2].rgb_channel().ok_or_else(invalid)?;
+                Ok(Self::new(r, g, b, a))
+            }
+            "hsl" | "hsla" => {
+                let h = channels[0].hue().ok_or_else(invalid)?;
+                let s = channels[1].hsl_percentage(legacy).ok_or_else(invalid)?;
+                let l = channels[2].hsl_percentage(legacy).ok_or_else(invalid)?;
+                let (r, g, b) = hsl_to_rgb(h, s / 100.0, l / 100.0);
+                Ok(Self::new(
+                    round(r * 255.0),
+                    round(g * 255.0),
+                    round(b * 255.0),
+                    a,
+                ))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-color-4/#currentcolor-color
+    /// 型付きの値から色を求める. currentcolorは`current_color`（要素のcolorプロパティの値）になる
+    pub fn from_value(value: &CssValue, current_color: &Color) -> Result<Self, Error> {
+        match value {
+            CssValue::Color(color) => Ok(*color),
+            CssValue::Keyword(keyword) if keyword == "currentcolor" => Ok(*current_color),
+            CssValue::Keyword(keyword) => Self::from_name(keyword),
+            _ => Err(Error::UnexpectedInput(format!(
+                "{:?} is not a color",
+                value
+            ))),
+        }
+    }
+
+    pub fn white() -> Self {
+        Self::rgb(255, 255, 255)
+    }
+
+    pub fn black() -> Self {
+        Self::rgb(0, 0, 0)
+    }
+
+    /// https://www.w3.org/TR/css-color-4/#transparent-color
+    pub fn transparent() -> Self {
+        Self::new(0, 0, 0, 0)
+    }
+
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+
+    pub fn a(&self) -> u8 {
+        self.a
+    }
+
+    /// 完全に透明かどうか. 透明な色は描画を省略できる
+    pub fn is_transparent(&self) -> bool {
+        self.a == 0
+    }
+
+    /// 0xRRGGBB形式の値. アルファ値は含まない
+    pub fn code_u32(&self) -> u32 {
+        ((self.r as u32) << 16) | ((self.g as u32) << 8) | self.b as u32
+    }
+
+    /// https://www.w3.org/TR/css-color-4/#serializing-sRGB-values
+    /// 不透明な色は"rgb(r, g, b)"、それ以外は"rgba(r, g, b, alpha)"の形にする
+    pub fn css_text(&self) -> String {
+        if self.a == 255 {
+            return format!("rgb({}, {}, {})", self.r, self.g, self.b);
+        }
+
+        // アルファ値は、8ビットの値に戻せる最短の桁数（2桁か3桁）で表す
+        let a = self.a as u32;
+        let hundredths = (a * 100 + 127) / 255;
+        let alpha = if (hundredths * 255 + 50) / 100 == a {
+            hundredths as f64 / 100.0
+        } else {
+            ((a * 1000 + 127) / 255) as f64 / 1000.0
+        };
+        format!(
+            "rgba({}, {}, {}, {})",
+            self.r,
+            self.g,
+            self.b,
+            serialize_number(alpha)
+        )
+    }
+}
+
+/// 色関数の引数の1つ
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ColorComponent {
+    Number(f64),
+    Percentage(f64),
+    /// 度数法に変換した角度
+    Angle(f64),
+    /// https://www.w3.org/TR/css-color-4/#missing
+    None,
+}
+
+impl ColorComponent {
+    fn from_token(token: &ComponentValue) -> Option<Self> {
+        match token {
+            ComponentValue::Number(n) => Some(Self::Number(*n)),
+            ComponentValue::Percentage(p) => Some(Self::Percentage(*p)),
+            ComponentValue::Dimension { value, unit } => {
+                let degrees = match unit.to_ascii_lowercase().as_str() {
+                    "deg" => *value,
+                    "rad" => value * 180.0 / PI,
+                    "grad" => value * 360.0 / 400.0,
+                    "turn" => value * 360.0,
+                    _ => return None,
+                };
+                Some(Self::Angle(degrees))
+            }
+            ComponentValue::Ident(ident) if ident.eq_ignore_ascii_case("none") => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    fn rgb_channel(&self) -> Option<u8> {
+        match self {
+            Self::Number(n) => Some(round(*n)),
+            Self::Percentage(p) => Some(round(p * 255.0 / 100.0)),
+            Self::None => Some(0),
+            Self::Angle(_) => None,
+        }
+    }
+
+    fn alpha(&self) -> Option<u8> {
+        match self {
+            Self::Number(n) => Some(round(n.clamp(0.0, 1.0) * 255.0)),
+            Self::Percentage(p) => Some(round(p.clamp(0.0, 100.0) * 255.0 / 100.0)),
+            Self::None => Some(0),
+            Self::Angle(_) => None,
+        }
+    }
+
+    fn hue(&self) -> Option<f64> {
+        match self {
+            Self::Number(degrees) | Self::Angle(degrees) => Some(*degrees),
+            Self::None => Some(0.0),
+            Self::Percentage(_) => None,
+        }
+    }
+
+    /// 彩度と輝度. 旧来の構文ではパーセンテージのみ、新しい構文では数値も受け付ける
+    fn hsl_percentage(&self, legacy: bool) -> Option<f64> {
+        match self {
+            Self::Percentage(p) => Some(p.clamp(0.0, 100.0)),
+            Self::Number(n) if !legacy => Some(n.clamp(0.0, 100.0)),
+            Self::None if !legacy => Some(0.0),
+            _ => None,
+        }
+    }
+}
+
+/// 色関数の引数をパースする. 2つ目の値は旧来のカンマ区切りの構文かどうか
+fn parse_arguments(arguments: &[ComponentValue]) -> Option<(Vec<ColorComponent>, bool)> {
+    let arguments = trim_whitespace(arguments);
+
+    let groups = split_by_comma(arguments);
+    if groups.len() > 1 {
+        // 旧来の構文：rgb(r, g, b[, a])
+        let mut components = Vec::new();
+        for group in groups {
+            match trim_whitespace(group) {
+                [token] => match ColorComponent::from_token(token)? {
+                    ColorComponent::None => return None,
+                    component => components.push(component),
+                },
+                _ => return None,
+            }
+        }
+        return Some((components, true));
+    }
+
+    // 新しい構文：rgb(r g b[ / a])
+    let mut components = Vec::new();
+    let mut slash = None;
+    for token in arguments
+        .iter()
+        .filter(|t| **t != ComponentValue::Whitespace)
+    {
+        if *token == ComponentValue::Delim('/') {
+            if slash.is_some() {
+                return None;
+            }
+            slash = Some(components.len());
+            continue;
+        }
+        components.push(ColorComponent::from_token(token)?);
+    }
+
+    match slash {
+        None if components.len() == 3 => Some((components, false)),
+        Some(3) if components.len() == 4 => Some((components, false)),
+        _ => None,
+    }
+}
+
+/// https://www.w3.org/TR/css-color-4/#hsl-to-rgb
+/// 彩度と輝度は0.0から1.0の値. 戻り値の各チャンネルも0.0から1.0の値
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (f64, f64, f64) {
+    let hue = ((hue % 360.0) + 360.0) % 360.0;
+
+    let f = |n: f64| {
+        let k = (n + hue / 30.0) % 12.0;
+        let a = saturation * lightness.min(1.0 - lightness);
+        lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+
+    (f(0.0), f(8.0), f(4.0))
+}
+
+/// 0から255の範囲に収めて、最も近い整数に丸める
+fn round(value: f64) -> u8 {
+    (value.clamp(0.0, 255.0) + 0.5) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::token::CssTokenizer;
+    use alloc::string::ToString;
+
+    fn parse(value: &str) -> Result<Color, Error> {
+        let tokens: Vec<ComponentValue> = CssTokenizer::new(value.to_string()).collect();
+        Color::from_value(&CssValue::parse(&tokens)?, &Color::black())
+    }
+
+    #[test]
+    fn test_named_colors() {
+        /* 名前付きの色が大文字・小文字を区別せずに解釈されることを確認する */
+        assert_eq!(Color::from_name("red"), Ok(Color::rgb(255, 0, 0)));
+        assert_eq!(
+            Color::from_name("RebeccaPurple"),
+            Ok(Color::rgb(0x66, 0x33, 0x99))
+        );
+        assert_eq!(
+            Color::from_name("lightgoldenrodyellow"),
+            Ok(Color::rgb(0xfa, 0xfa, 0xd2))
+        );
+        assert_eq!(Color::from_name("transparent"), Ok(Color::transparent()));
+        assert!(Color::from_name("unknown").is_err());
+    }
+
+    #[test]
+    fn test_hex_notation() {
+        /* 3、4、6、8桁の16進数表記を確認する */
+        assert_eq!(Color::from_code("#fff"), Ok(Color::white()));
+        assert_eq!(Color::from_code("#0f08"), Ok(Color::new(0, 255, 0, 0x88)));
+        assert_eq!(
+            Color::from_code("#1A2b3C"),
+            Ok(Color::rgb(0x1a, 0x2b, 0x3c))
+        );
+        assert_eq!(
+            Color::from_code("#11223344"),
+            Ok(Color::new(0x11, 0x22, 0x33, 0x44))
+        );
+        assert!(Color::from_code("#ff").is_err());
+        assert!(Color::from_code("#fffff").is_err());
+        assert!(Color::from_code("#ggg").is_err());
+        assert!(Color::from_code("fff").is_err());
+    }
+
+    #[test]
+    fn test_rgb_functions() {
+        /* rgb()とrgba()の旧来の構文と新しい構文を確認する */
+        assert_eq!(parse("rgb(255, 0, 0)"), Ok(Color::rgb(255, 0, 0)));
+        assert_eq!(
+            parse("rgba(0, 0, 255, 0.5)"),
+            Ok(Color::new(0, 0, 255, 128))
+        );
+        assert_eq!(parse("rgb(100%, 50%, 0%)"), Ok(Color::rgb(255, 128, 0)));
+        assert_eq!(
+            parse("rgb(0 128 255 / 25%)"),
+            Ok(Color::new(0, 128, 255, 64))
+        );
+        assert_eq!(parse("RGB(300 -10 none)"), Ok(Color::rgb(255, 0, 0)));
+        assert_eq!(parse("rgba(10 20 30)"), Ok(Color::rgb(10, 20, 30)));
+
+        // 旧来の構文で数値とパーセンテージを混在させることはできない
+        assert!(parse("rgb(255, 50%, 0)").is_err());
+        assert!(parse("rgb(255, 0)").is_err());
+        assert!(parse("rgb(255 0 0, 1)").is_err());
+        assert!(parse("rgb(255 0 / 1 / 1)").is_err());
+    }
+
+    #[test]
+    fn test_hsl_functions() {
+        /* hsl()とhsla()の旧来の構文と新しい構文を確認する */
+        assert_eq!(parse("hsl(0, 100%, 50%)"), Ok(Color::rgb(255, 0, 0)));
+        assert_eq!(parse("hsl(120deg 100% 25%)"), Ok(Color::rgb(0, 128, 0)));
+        assert_eq!(parse("hsl(0.5turn 100% 50%)"), Ok(Color::rgb(0, 255, 255)));
+        assert_eq!(parse("hsl(-120, 100%, 50%)"), Ok(Color::rgb(0, 0, 255)));
+        assert_eq!(
+            parse("hsla(240, 100%, 50%, 0.2)"),
+            Ok(Color::new(0, 0, 255, 51))
+        );
+        assert_eq!(
+            parse("hsl(0 0% 100% / 0)"),
+            Ok(Color::new(255, 255, 255, 0))
+        );
+        assert!(parse("hsl(0, 100, 50)").is_err());
+    }
+
+    #[test]
+    fn test_current_color() {
+        /* currentcolorが、与えられた現在の色になることを確認する */
+        let tokens: Vec<ComponentValue> = CssTokenizer::new("currentColor".to_string()).collect();
+        let value = CssValue::parse(&tokens).expect("should parse");
+        assert_eq!(
+            Color::from_value(&value, &Color::rgb(1, 2, 3)),
+            Ok(Color::rgb(1, 2, 3))
+        );
+    }
+
+    #[test]
+    fn test_css_text() {
+        /* 色が"rgb()"または"rgba()"の形で文字列にされることを確認する */
+        assert_eq!(Color::rgb(255, 0, 0).css_text(), "rgb(255, 0, 0)");
+        assert_eq!(Color::transparent().css_text(), "rgba(0, 0, 0, 0)");
+        assert_eq!(
+            Color::new(0, 0, 255, 128).css_text(),
+            "rgba(0, 0, 255, 0.5)"
+        );
+        assert_eq!(
+            Color::new(0, 0, 255, 1).css_text(),
+            "rgba(0, 0, 255, 0.004)"
+        );
+    }
+}
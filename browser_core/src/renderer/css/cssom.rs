@@ -1,5 +1,20 @@
+use crate::error::Error;
+use crate::renderer::css::media::MediaEnvironment;
+use crate::renderer::css::media::MediaQueryList;
+use crate::renderer::css::property::is_valid_declaration;
+use crate::renderer::css::serialize::serialize_identifier;
+use crate::renderer::css::serialize::serialize_tokens;
+use crate::renderer::css::serialize::serialize_url;
+use crate::renderer::css::shorthand::expand_declaration;
+use crate::renderer::css::shorthand::longhands_of;
+use crate::renderer::css::supports::SupportsCondition;
 use crate::renderer::css::token::CssToken;
 use crate::renderer::css::token::CssTokenizer;
+use crate::renderer::css::value::CssValue;
+use crate::renderer::css::variable::is_custom_property;
+use crate::renderer::dom::node::Element;
+use alloc::boxed::Box;
+use alloc::format;
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::vec::Vec;
@@ -15,179 +30,336 @@ CssTokenizerはIteratorトレイトを実装したのでnextメソッドを持
 #[derive(Debug, Clone)]
 pub struct CssParser {
     t: Peekable<CssTokenizer>,
+    /// https://www.w3.org/TR/css-syntax-3/#parse-errors
+    /// パースエラーの記録. エラーがあってもパースは中断せず、仕様のエラー回復の手順に従って続ける
+    diagnostics: Vec<Error>,
 }
 
 impl CssParser {
     pub fn new(t: CssTokenizer) -> Self {
-        Self { t: t.peekable() }
+        Self {
+            t: t.peekable(),
+            diagnostics: Vec::new(),
+        }
     }
 
     pub fn parse_stylesheet(&mut self) -> StyleSheet {
         let mut sheet = StyleSheet::new();
 
         // トークン列からルールのリストを作成し、StyleSheetのフィールドに設定する
-        sheet.set_rules(self.consume_list_of_rules());
+        sheet.set_rules(self.consume_list_of_rules(true));
         sheet
     }
 
-    fn consume_list_of_rules(&mut self) -> Vec<QualifiedRule> {
+    /// パース中に見つかったエラー
+    pub fn diagnostics(&self) -> &[Error] {
+        &self.diagnostics
+    }
+
+    fn parse_error(&mut self, message: &str) {
+        self.diagnostics
+            .push(Error::UnexpectedInput(message.to_string()));
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-list-of-rules
+    /// top_levelがfalseの場合は@mediaなどのブロック内のルールとして扱い、閉じ括弧（}）で終了する
+    fn consume_list_of_rules(&mut self, top_level: bool) -> Vec<CssRule> {
         let mut rules = Vec::new();
 
         loop {
             let token = match self.t.peek() {
                 Some(t) => t,
-                None => return rules,
+                None => {
+                    if !top_level {
+                        self.parse_error("unexpected end of input in a block");
+                    }
+                    return rules;
+                }
             };
 
             match token {
-                // AtKeyword トークンが出てきた場合、ほかのCSSをインポートする
-                // @import、メディアクエリをあらわす@media などのルールがはじまることをあらわす
+                // 空白やCDO/CDC（<!-- -->）は読み飛ばす
+                CssToken::Whitespace | CssToken::Cdo | CssToken::Cdc => {
+                    self.t.next();
+                }
+                CssToken::CloseCurly if !top_level => {
+                    self.t.next();
+                    return rules;
+                }
+                // AtKeyword トークンが出てきた場合、@import、メディアクエリをあらわす@media などのルールがはじまる
                 CssToken::AtKeyword(_keyword) => {
-                    let _rule = self.consume_qualified_rule();
-                    // しかし、本書のブラウザでは@からはじまるルールはサポートしない予定なので、無視する
+                    if let Some(rule) = self.consume_at_rule(top_level) {
+                        // https://www.w3.org/TR/css-cascade-4/#at-import
+                        // @importはほかのすべてのルールより前に書かれている必要がある
+                        let is_misplaced_import = matches!(rule, CssRule::Import(_))
+                            && (!top_level
+                                || rules.iter().any(|r| !matches!(r, CssRule::Import(_))));
+                        if is_misplaced_import {
+                            self.parse_error("@import must precede all other rules");
+                        } else {
+                            rules.push(rule);
+                        }
+                    }
                 }
                 _ => {
                     // それ以外の場合は、通常のルールをパースする
-                    let rule = self.consume_qualified_rule();
-                    match rule {
-                        Some(r) => rules.push(r),
-                        None => return rules,
+                    // ルールを作れなかった場合でも、入力の終わりかブロックの終わりまでは消費されている
+                    if let Some(rule) = self.consume_qualified_rule(top_level) {
+                        rules.push(CssRule::Style(rule));
                     }
                 }
             }
         }
     }
 
-    fn consume_qualified_rule(&mut self) -> Option<QualifiedRule> {
-        let mut rule = QualifiedRule::new();
+    /// https://www.w3.org/TR/css-syntax-3/#consume-at-rule
+    /// サポートしていない@ルールは読み飛ばしてNoneを返す
+    fn consume_at_rule(&mut self, top_level: bool) -> Option<CssRule> {
+        let name = match self.t.next() {
+            Some(CssToken::AtKeyword(name)) => name.to_ascii_lowercase(),
+            _ => return None,
+        };
+
+        // プレリュード（@キーワードからブロックまたはセミコロンまで）を集める
+        let mut prelude = Vec::new();
+        let has_block = loop {
+            match self.t.peek() {
+                None => {
+                    self.parse_error("unexpected end of input in an at-rule prelude");
+                    break false;
+                }
+                Some(CssToken::SemiColon) => {
+                    self.t.next();
+                    break false;
+                }
+                Some(CssToken::OpenCurly) => {
+                    self.t.next();
+                    break true;
+                }
+                // ブロック内では、閉じ括弧はこのルールではなく外側のブロックの終わり
+                Some(CssToken::CloseCurly) if !top_level => {
+                    self.parse_error("unexpected '}' in an at-rule prelude");
+                    break false;
+                }
+                Some(_) => self.consume_component_value(&mut prelude),
+            }
+        };
 
+        match (name.as_str(), has_block) {
+            ("media", true) => Some(CssRule::Media(MediaRule {
+                media: MediaQueryList::parse(&prelude),
+                rules: self.consume_list_of_rules(false),
+            })),
+            ("supports", true) => {
+                let rules = self.consume_list_of_rules(false);
+                let condition = SupportsCondition::parse(&prelude);
+                if condition.is_none() {
+                    self.parse_error("invalid @supports condition");
+                }
+                Some(CssRule::Supports(SupportsRule {
+                    condition: condition?,
+                    rules,
+                }))
+            }
+            ("font-face", true) => Some(CssRule::FontFace(FontFaceRule {
+                declarations: self.consume_list_of_declarations(),
+            })),
+            ("import", false) => {
+                let import = ImportRule::parse(&prelude);
+                if import.is_none() {
+                    self.parse_error("invalid @import prelude");
+                }
+                import.map(CssRule::Import)
+            }
+            (_, true) => {
+                self.skip_block();
+                None
+            }
+            (_, false) => None,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-component-value
+    /// 1つのコンポーネント値を`tokens`に追加する. 開き括弧や関数の場合は、
+    /// 対応する閉じ括弧までのトークンをまとめて追加する（入力の終わりで閉じていなければそこまで）
+    fn consume_component_value(&mut self, tokens: &mut Vec<ComponentValue>) {
+        let mut closers = Vec::new();
         loop {
-            let token = match self.t.peek() {
+            let token = match self.t.next() {
                 Some(t) => t,
-                None => return None,
+                None => {
+                    if !closers.is_empty() {
+                        self.parse_error("unexpected end of input in a block");
+                    }
+                    return;
+                }
             };
 
-            match token {
-                CssToken::OpenCurly => {
-                    // 宣言ブロックとして解釈
-                    assert_eq!(self.t.next(), Some(CssToken::OpenCurly));
-                    rule.set_declarations(self.consume_list_of_declarations());
-                    return Some(rule);
+            // 対応する種類の閉じ括弧だけがブロックを閉じる
+            if closers.last() == Some(&token) {
+                closers.pop();
+            } else if let Some(closer) = mirror_token(&token) {
+                closers.push(closer);
+            }
+            tokens.push(token);
+
+            if closers.is_empty() {
+                return;
+            }
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-simple-block
+    /// 開き括弧（{）は消費済みであること. 対応する閉じ括弧までを読み飛ばす
+    fn skip_block(&mut self) {
+        let mut ignored = Vec::new();
+        loop {
+            match self.t.peek() {
+                None => {
+                    self.parse_error("unexpected end of input in a block");
+                    return;
                 }
-                _ => {
-                    // セレクタとして解釈
-                    rule.set_selector(self.consume_selector());
+                Some(CssToken::CloseCurly) => {
+                    self.t.next();
+                    return;
                 }
+                Some(_) => self.consume_component_value(&mut ignored),
             }
         }
     }
 
-    fn consume_selector(&mut self) -> Selector {
-        let token = match self.t.next() {
-            Some(t) => t,
-            None => panic!("should have a token but got None"),
-        };
+    /// https://www.w3.org/TR/css-syntax-3/#consume-qualified-rule
+    /// 宣言ブロックの前までをプレリュードとして集め、セレクタとしてパースする
+    /// 宣言ブロックがないまま入力が終わった場合や、ブロック内で閉じ括弧が出てきた場合はNoneを返す
+    fn consume_qualified_rule(&mut self, top_level: bool) -> Option<QualifiedRule> {
+        let mut prelude = Vec::new();
 
-        match token {
-            CssToken::HashToken(value) => Selector::IdSelector(value[1..].to_string()),
-            CssToken::Delim(delim) => {
-                if delim == '.' {
-                    return Selector::ClassSelector(self.consume_ident());
-                }
-                panic!("Parse error: {:?} is an unexpected token.", token);
-            }
-            CssToken::Ident(ident) => {
-                // a:hover のようなセレクタは”タイプセレクタ”として扱うため、もしコロン（:）が出てきた場合
-                // 宣言ブロックの開始直前までトークンを進める
-                if self.t.peek() == Some(&CssToken::Colon) {
-                    while self.t.peek() != Some(&CssToken::OpenCurly) {
-                        self.t.next();
-                    }
+        loop {
+            match self.t.peek() {
+                None => {
+                    self.parse_error("unexpected end of input in a rule prelude");
+                    return None;
                 }
-                Selector::TypeSelector(ident.to_string())
-            }
-            CssToken::AtKeyword(_keyword) => {
-                // @からはじまるルールを無視するために、宣言ブロックの開始直前までトークンを進める
-                while self.t.peek() != Some(&CssToken::OpenCurly) {
+                Some(CssToken::OpenCurly) => {
                     self.t.next();
+                    break;
                 }
-                Selector::UnknownSelector
-            }
-            _ => {
-                self.t.next();
-                Selector::UnknownSelector
+                Some(CssToken::CloseCurly) if !top_level => {
+                    self.parse_error("unexpected '}' in a rule prelude");
+                    return None;
+                }
+                Some(_) => self.consume_component_value(&mut prelude),
             }
         }
+
+        let mut rule = QualifiedRule::new();
+        let selector = Selector::parse(&prelude);
+        if selector == Selector::UnknownSelector {
+            self.parse_error("unsupported selector");
+        }
+        rule.set_selector(selector);
+        rule.set_declarations(self.consume_list_of_declarations());
+        Some(rule)
     }
 
+    /// https://www.w3.org/TR/css-syntax-3/#consume-list-of-declarations
+    /// 開き括弧（{）は消費済みであること. 対応する閉じ括弧までの宣言をパースする
     fn consume_list_of_declarations(&mut self) -> Vec<Declaration> {
         let mut declarations = Vec::new();
 
         loop {
             let token = match self.t.peek() {
                 Some(t) => t,
-                None => return declarations,
+                None => {
+                    self.parse_error("unexpected end of input in a declaration block");
+                    return declarations;
+                }
             };
 
             match token {
                 CssToken::CloseCurly => {
-                    assert_eq!(self.t.next(), Some(CssToken::CloseCurly));
+                    self.t.next();
                     // 今まで作成した宣言のベクタを返す
                     return declarations;
                 }
-                CssToken::SemiColon => {
-                    assert_eq!(self.t.next(), Some(CssToken::SemiColon));
-                    // 宣言が終了しただけなので、何もしない
+                // 宣言が終了しただけなので、何もしない
+                CssToken::Whitespace | CssToken::SemiColon => {
+                    self.t.next();
                 }
-                CssToken::Ident(ref _ident) => {
-                    if let Some(declaration) = self.consume_declaration() {
-                        declarations.push(declaration);
+                // 宣言ブロック内の@ルールはサポートしていないので読み飛ばす
+                CssToken::AtKeyword(_) => {
+                    self.consume_at_rule(false);
+                }
+                CssToken::Ident(_) => {
+                    let tokens = self.consume_declaration_tokens();
+                    match Self::consume_declaration(&tokens) {
+                        Some(declaration) => declarations.push(declaration),
+                        None => self.parse_error("invalid declaration"),
                     }
                 }
+                // https://www.w3.org/TR/css-syntax-3/#consume-list-of-declarations
+                // それ以外はパースエラー. 次の宣言の区切りまでを読み飛ばす
                 _ => {
-                    self.t.next();
+                    self.parse_error("unexpected token in a declaration block");
+                    self.consume_declaration_tokens();
                 }
             }
         }
     }
 
-    fn consume_declaration(&mut self) -> Option<Declaration> {
-        self.t.peek()?;
+    /// 宣言の終わり（トップレベルのセミコロンまたはブロックの終わり）までのコンポーネント値を集める
+    /// 区切りのトークンは消費しない
+    fn consume_declaration_tokens(&mut self) -> Vec<ComponentValue> {
+        let mut tokens = Vec::new();
+        loop {
+            match self.t.peek() {
+                None | Some(CssToken::SemiColon) | Some(CssToken::CloseCurly) => return tokens,
+                Some(_) => self.consume_component_value(&mut tokens),
+            }
+        }
+    }
 
+    /// https://www.w3.org/TR/css-syntax-3/#consume-declaration
+    /// 宣言1つ分のトークン列から宣言を作る. プロパティ名とコロンがなければNoneを返す
+    fn consume_declaration(tokens: &[ComponentValue]) -> Option<Declaration> {
         let mut declaration = Declaration::new();
-        declaration.set_property(self.consume_ident());
+        match tokens.first()? {
+            CssToken::Ident(ident) => declaration.set_property(ident.to_string()),
+            _ => return None,
+        }
 
         // 次のトークンがコロン以外ならパースエラーなのでNoneを返す
-        match self.t.next() {
+        let rest = trim_whitespace(&tokens[1..]);
+        match rest.first() {
             Some(CssToken::Colon) => {}
             _ => return None,
         }
 
-        // Declaration構造体の値にコンポーネント値を設定する
-        declaration.set_value(self.consume_component_value());
-
-        Some(declaration)
-    }
-
-    fn consume_ident(&mut self) -> String {
-        let token = match self.t.next() {
-            Some(t) => t,
-            None => panic!("should have a token but got None"),
-        };
-
-        match token {
-            CssToken::Ident(ref ident) => ident.to_string(),
-            _ => {
-                panic!("Parse error: {:?} is an unexpected token.", token);
+        // 末尾が"!"と"important"なら、それらを取り除いてimportantフラグを立てる
+        let mut values = trim_whitespace(&rest[1..]).to_vec();
+        if let Some(CssToken::Ident(ident)) = values.last() {
+            if ident.eq_ignore_ascii_case("important") {
+                let rest = trim_whitespace(&values[..values.len() - 1]);
+                if rest.last() == Some(&CssToken::Delim('!')) {
+                    let rest = trim_whitespace(&rest[..rest.len() - 1]).to_vec();
+                    values = rest;
+                    declaration.set_important(true);
+                }
             }
         }
+        declaration.set_value(values);
+
+        Some(declaration)
     }
+}
 
-    /// https://www.w3.org/TR/css-syntax-3/#consume-component-value
-    fn consume_component_value(&mut self) -> ComponentValue {
-        self.t
-            .next()
-            .expect("should have a token in consume_component_value")
+/// 開き括弧や関数のトークンに対応する閉じ括弧を返す
+fn mirror_token(token: &CssToken) -> Option<CssToken> {
+    match token {
+        CssToken::OpenParenthesis | CssToken::Function(_) => Some(CssToken::CloseParenthesis),
+        CssToken::OpenSquare => Some(CssToken::CloseSquare),
+        CssToken::OpenCurly => Some(CssToken::CloseCurly),
+        _ => None,
     }
 }
 
@@ -195,7 +367,7 @@ impl CssParser {
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct StyleSheet {
     /// https://drafts.csswg.org/cssom/#dom-cssstylesheet-cssrules
-    pub rules: Vec<QualifiedRule>,
+    pub rules: Vec<CssRule>,
 }
 
 impl StyleSheet {
@@ -203,9 +375,296 @@ impl StyleSheet {
         Self { rules: Vec::new() }
     }
 
-    pub fn set_rules(&mut self, rules: Vec<QualifiedRule>) {
+    pub fn set_rules(&mut self, rules: Vec<CssRule>) {
         self.rules = rules;
     }
+
+    /// https://www.w3.org/TR/cssom-1/#serialize-a-css-rule
+    /// ルールを1行ずつ文字列にする
+    pub fn css_text(&self) -> String {
+        self.rules
+            .iter()
+            .map(|rule| rule.css_text())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstylesheet-insertrule
+    /// `rule`をパースして`index`の位置に挿入し、挿入した位置を返す
+    pub fn insert_rule(&mut self, rule: &str, index: usize) -> Result<usize, Error> {
+        insert_rule(&mut self.rules, rule, index, true)
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstylesheet-deleterule
+    pub fn delete_rule(&mut self, index: usize) -> Result<(), Error> {
+        delete_rule(&mut self.rules, index)
+    }
+
+    /// トップレベルのスタイルルールを出現順に返す
+    pub fn style_rules(&self) -> impl Iterator<Item = &QualifiedRule> {
+        self.rules.iter().filter_map(|rule| match rule {
+            CssRule::Style(style_rule) => Some(style_rule),
+            _ => None,
+        })
+    }
+
+    /// 与えられた環境で@media、@supports、@importの条件を評価し、
+    /// 適用されるスタイルルールのみを出現順に平坦化したスタイルシートを返す
+    pub fn evaluate(&self, env: &MediaEnvironment) -> StyleSheet {
+        let mut sheet = StyleSheet::new();
+        collect_applicable_rules(&self.rules, env, &mut sheet.rules);
+        sheet
+    }
+}
+
+fn collect_applicable_rules(rules: &[CssRule], env: &MediaEnvironment, result: &mut Vec<CssRule>) {
+    for rule in rules {
+        match rule {
+            CssRule::Style(_) | CssRule::FontFace(_) => result.push(rule.clone()),
+            CssRule::Media(media_rule) => {
+                if media_rule.media.matches(env) {
+                    collect_applicable_rules(&media_rule.rules, env, result);
+                }
+            }
+            CssRule::Supports(supports_rule) => {
+                if supports_rule.condition.evaluate() {
+                    collect_applicable_rules(&supports_rule.rules, env, result);
+                }
+            }
+            CssRule::Import(import_rule) => {
+                if let Some(sheet) = &import_rule.stylesheet {
+                    if import_rule.media.matches(env) {
+                        collect_applicable_rules(&sheet.rules, env, result);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#insert-a-css-rule
+/// top_levelがfalseの場合は@mediaなどのブロック内のルールのリストとして扱う
+fn insert_rule(
+    rules: &mut Vec<CssRule>,
+    rule: &str,
+    index: usize,
+    top_level: bool,
+) -> Result<usize, Error> {
+    if index > rules.len() {
+        return Err(Error::UnexpectedInput(format!(
+            "index {} is out of range for {} rules",
+            index,
+            rules.len()
+        )));
+    }
+
+    // https://www.w3.org/TR/cssom-1/#parse-a-css-rule
+    // ちょうど1つのルールでなければならない
+    let mut parser = CssParser::new(CssTokenizer::new(rule.to_string()));
+    let mut parsed = parser.consume_list_of_rules(true);
+    if parsed.len() != 1 || !parser.diagnostics().is_empty() {
+        return Err(Error::UnexpectedInput(format!(
+            "failed to parse {:?} as a single rule",
+            rule
+        )));
+    }
+    let new_rule = parsed.remove(0);
+
+    // @importはほかのすべてのルールより前にしか置けず、@importの前にほかのルールは置けない
+    let is_import = matches!(new_rule, CssRule::Import(_));
+    let misplaced = if is_import {
+        !top_level
+            || rules[..index]
+                .iter()
+                .any(|r| !matches!(r, CssRule::Import(_)))
+    } else {
+        rules[index..]
+            .iter()
+            .any(|r| matches!(r, CssRule::Import(_)))
+    };
+    if misplaced {
+        return Err(Error::UnexpectedInput(
+            "@import must precede all other rules".to_string(),
+        ));
+    }
+
+    rules.insert(index, new_rule);
+    Ok(index)
+}
+
+/// https://www.w3.org/TR/cssom-1/#remove-a-css-rule
+fn delete_rule(rules: &mut Vec<CssRule>, index: usize) -> Result<(), Error> {
+    if index >= rules.len() {
+        return Err(Error::UnexpectedInput(format!(
+            "index {} is out of range for {} rules",
+            index,
+            rules.len()
+        )));
+    }
+    rules.remove(index);
+    Ok(())
+}
+
+/// https://www.w3.org/TR/cssom-1/#cssgroupingrule
+/// @mediaや@supportsのブロックを文字列にする. 中のルールは1行ずつ字下げして並べる
+fn grouping_rule_css_text(prelude: String, rules: &[CssRule]) -> String {
+    let mut result = prelude;
+    result.push_str(" {");
+    for rule in rules {
+        result.push_str("\n  ");
+        result.push_str(&rule.css_text().replace('\n', "\n  "));
+    }
+    result.push_str("\n}");
+    result
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-css-declaration-block
+fn declarations_css_text(declarations: &[Declaration]) -> String {
+    if declarations.is_empty() {
+        return "{ }".to_string();
+    }
+    let declarations = declarations
+        .iter()
+        .map(|declaration| format!("{};", declaration.css_text()))
+        .collect::<Vec<String>>()
+        .join(" ");
+    format!("{{ {} }}", declarations)
+}
+
+/// https://www.w3.org/TR/cssom-1/#cssrule
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssRule {
+    /// https://www.w3.org/TR/cssom-1/#cssstylerule
+    Style(QualifiedRule),
+    /// https://www.w3.org/TR/css-conditional-3/#cssmediarule
+    Media(MediaRule),
+    /// https://www.w3.org/TR/css-conditional-3/#the-csssupportsrule-interface
+    Supports(SupportsRule),
+    /// https://www.w3.org/TR/cssom-1/#cssimportrule
+    Import(ImportRule),
+    /// https://www.w3.org/TR/css-fonts-4/#cssfontfacerule
+    FontFace(FontFaceRule),
+}
+
+impl CssRule {
+    /// https://www.w3.org/TR/cssom-1/#dom-cssrule-csstext
+    pub fn css_text(&self) -> String {
+        match self {
+            CssRule::Style(rule) => rule.css_text(),
+            CssRule::Media(rule) => rule.css_text(),
+            CssRule::Supports(rule) => rule.css_text(),
+            CssRule::Import(rule) => rule.css_text(),
+            CssRule::FontFace(rule) => rule.css_text(),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-conditional-3/#at-media
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MediaRule {
+    pub media: MediaQueryList,
+    pub rules: Vec<CssRule>,
+}
+
+impl MediaRule {
+    /// https://www.w3.org/TR/cssom-1/#serialize-a-css-rule
+    pub fn css_text(&self) -> String {
+        grouping_rule_css_text(format!("@media {}", self.media.css_text()), &self.rules)
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssgroupingrule-insertrule
+    pub fn insert_rule(&mut self, rule: &str, index: usize) -> Result<usize, Error> {
+        insert_rule(&mut self.rules, rule, index, false)
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssgroupingrule-deleterule
+    pub fn delete_rule(&mut self, index: usize) -> Result<(), Error> {
+        delete_rule(&mut self.rules, index)
+    }
+}
+
+/// https://www.w3.org/TR/css-conditional-3/#at-supports
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupportsRule {
+    pub condition: SupportsCondition,
+    pub rules: Vec<CssRule>,
+}
+
+impl SupportsRule {
+    /// https://www.w3.org/TR/css-conditional-3/#the-csssupportsrule-interface
+    pub fn css_text(&self) -> String {
+        grouping_rule_css_text(
+            format!("@supports {}", self.condition.css_text()),
+            &self.rules,
+        )
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssgroupingrule-insertrule
+    pub fn insert_rule(&mut self, rule: &str, index: usize) -> Result<usize, Error> {
+        insert_rule(&mut self.rules, rule, index, false)
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssgroupingrule-deleterule
+    pub fn delete_rule(&mut self, index: usize) -> Result<(), Error> {
+        delete_rule(&mut self.rules, index)
+    }
+}
+
+/// https://www.w3.org/TR/css-cascade-4/#at-import
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImportRule {
+    /// @importに書かれたURL（相対URLのまま保持する）
+    pub href: String,
+    pub media: MediaQueryList,
+    /// 取得・パースされたスタイルシート. 取得前または取得に失敗した場合はNone
+    pub stylesheet: Option<StyleSheet>,
+}
+
+impl ImportRule {
+    /// @import url(...) [<media-query-list>]? ; のプレリュード部分をパースする
+    fn parse(prelude: &[CssToken]) -> Option<Self> {
+        let prelude = trim_whitespace(prelude);
+
+        let (href, rest) = match prelude.first()? {
+            CssToken::Url(url) | CssToken::StringToken(url) => (url.clone(), &prelude[1..]),
+            CssToken::Function(name) if name.eq_ignore_ascii_case("url") => {
+                let end = find_block_end(prelude, 0)?;
+                match trim_whitespace(&prelude[1..end]) {
+                    [CssToken::StringToken(url)] => (url.clone(), &prelude[end + 1..]),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            href,
+            media: MediaQueryList::parse(rest),
+            stylesheet: None,
+        })
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#serialize-a-css-rule
+    pub fn css_text(&self) -> String {
+        let media = self.media.css_text();
+        if media.is_empty() {
+            format!("@import {};", serialize_url(&self.href))
+        } else {
+            format!("@import {} {};", serialize_url(&self.href), media)
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-face-rule
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FontFaceRule {
+    pub declarations: Vec<Declaration>,
+}
+
+impl FontFaceRule {
+    pub fn css_text(&self) -> String {
+        format!("@font-face {}", declarations_css_text(&self.declarations))
+    }
 }
 
 /// https://www.w3.org/TR/css-syntax-3/#qualified-rule
@@ -236,6 +695,136 @@ impl QualifiedRule {
     pub fn set_declarations(&mut self, declarations: Vec<Declaration>) {
         self.declarations = declarations;
     }
+
+    /// https://www.w3.org/TR/cssom-1/#serialize-a-css-rule
+    pub fn css_text(&self) -> String {
+        format!(
+            "{} {}",
+            self.selector.css_text(),
+            declarations_css_text(&self.declarations)
+        )
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-getpropertyvalue
+    /// 宣言された値を文字列で返す. ショートハンドで宣言されたロングハンドは、展開した値を返す
+    /// 宣言されていない場合は空文字列を返す
+    pub fn get_property_value(&self, property: &str) -> String {
+        if let Some(declaration) = self.find_declaration(property) {
+            return serialize_tokens(&declaration.value);
+        }
+
+        let property = property.to_ascii_lowercase();
+        for declaration in self.declarations.iter().rev() {
+            let is_shorthand_of = longhands_of(&declaration.property.to_ascii_lowercase())
+                .map_or(false, |longhands| longhands.contains(&property));
+            if !is_shorthand_of {
+                continue;
+            }
+            if let Ok(longhands) = expand_declaration(&declaration.property, &declaration.value) {
+                if let Some((_, value)) = longhands.iter().find(|(name, _)| *name == property) {
+                    return value.css_text();
+                }
+            }
+        }
+
+        String::new()
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-getpropertypriority
+    pub fn get_property_priority(&self, property: &str) -> String {
+        match self.find_declaration(property) {
+            Some(declaration) if declaration.important => "important".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-setproperty
+    /// 値がプロパティの文法に一致しない場合はエラーを返し、宣言は変更しない
+    /// 値が空文字列の場合は宣言を取り除く
+    pub fn set_property(
+        &mut self,
+        property: &str,
+        value: &str,
+        priority: &str,
+    ) -> Result<(), Error> {
+        let tokens: Vec<ComponentValue> = CssTokenizer::new(value.to_string()).collect();
+        let tokens = trim_whitespace(&tokens).to_vec();
+        if tokens.is_empty() {
+            self.remove_property(property);
+            return Ok(());
+        }
+
+        let important = match priority {
+            "" => false,
+            p if p.eq_ignore_ascii_case("important") => true,
+            _ => {
+                return Err(Error::UnexpectedInput(format!(
+                    "invalid priority {:?}",
+                    priority
+                )))
+            }
+        };
+
+        // カスタムプロパティは名前の大文字・小文字を区別し、値はどんなトークン列でもよい
+        let property = if is_custom_property(property) {
+            property.to_string()
+        } else {
+            if !is_valid_declaration(property, &tokens) {
+                return Err(Error::UnexpectedInput(format!(
+                    "{:?} is not a valid value for {}",
+                    value, property
+                )));
+            }
+            property.to_ascii_lowercase()
+        };
+
+        // 同じプロパティの宣言は、最初の宣言の位置にある1つにまとめる
+        let mut declaration = Declaration::new();
+        declaration.set_property(property.clone());
+        declaration.set_value(tokens);
+        declaration.set_important(important);
+        let position = self
+            .declarations
+            .iter()
+            .position(|d| is_same_property(&d.property, &property))
+            .unwrap_or(self.declarations.len());
+        self.declarations
+            .retain(|d| !is_same_property(&d.property, &property));
+        self.declarations.insert(position, declaration);
+        Ok(())
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-removeproperty
+    /// 取り除いた宣言の値を返す. 同じプロパティの宣言が複数ある場合はすべて取り除く
+    pub fn remove_property(&mut self, property: &str) -> String {
+        let value = self.get_property_value(property);
+        self.declarations
+            .retain(|declaration| !is_same_property(&declaration.property, property));
+        value
+    }
+
+    /// 同じプロパティの宣言が複数ある場合は、カスケードで勝つ宣言（!importantの宣言か、最後の宣言）を返す
+    fn find_declaration(&self, property: &str) -> Option<&Declaration> {
+        let mut result: Option<&Declaration> = None;
+        for declaration in &self.declarations {
+            if !is_same_property(&declaration.property, property) {
+                continue;
+            }
+            if result.map_or(true, |r| declaration.important || !r.important) {
+                result = Some(declaration);
+            }
+        }
+        result
+    }
+}
+
+/// プロパティ名が同じかどうか. カスタムプロパティ以外はASCIIの大文字・小文字を区別しない
+fn is_same_property(a: &str, b: &str) -> bool {
+    if is_custom_property(a) || is_custom_property(b) {
+        a == b
+    } else {
+        a.eq_ignore_ascii_case(b)
+    }
 }
 
 /// https://www.w3.org/TR/selectors-4/
@@ -247,10 +836,162 @@ pub enum Selector {
     ClassSelector(String), // クラス名で指定
     /// https://www.w3.org/TR/selectors-4/#id-selectors
     IdSelector(String), // idで指定
+    /// https://www.w3.org/TR/selectors-4/#the-universal-selector
+    UniversalSelector, // すべての要素を指定
+    /// https://www.w3.org/TR/selectors-4/#pseudo-elements
+    /// 要素そのものではなく、要素に一致したときに生成される疑似要素を指定する
+    PseudoElementSelector(Box<Selector>, PseudoElement),
     /// パース中にエラーが起こったときに使用されるセレクタ
     UnknownSelector,
 }
 
+impl Selector {
+    /// https://www.w3.org/TR/selectors-4/#parse-selector
+    /// ルールのプレリュードをセレクタとしてパースする. サポートしていない形式の場合はUnknownSelectorを返す
+    pub fn parse(prelude: &[ComponentValue]) -> Self {
+        let prelude = trim_whitespace(prelude);
+
+        // 疑似要素はセレクタの末尾にのみ書ける
+        if let Some((base, pseudo)) = split_pseudo_element(prelude) {
+            let base = match trim_whitespace(base) {
+                [] => Selector::UniversalSelector,
+                base => Selector::parse(base),
+            };
+            return match (base, pseudo) {
+                (Selector::UnknownSelector, _) | (_, None) => Selector::UnknownSelector,
+                (base, Some(pseudo)) => Selector::PseudoElementSelector(Box::new(base), pseudo),
+            };
+        }
+
+        match prelude {
+            [CssToken::HashToken(value)] if value.len() > 1 => {
+                Selector::IdSelector(value[1..].to_string())
+            }
+            [CssToken::Delim('.'), CssToken::Ident(class)] => {
+                Selector::ClassSelector(class.to_string())
+            }
+            [CssToken::Ident(ident)] => Selector::TypeSelector(ident.to_string()),
+            [CssToken::Delim('*')] => Selector::UniversalSelector,
+            // a:hover のような疑似クラスを持つセレクタは、"タイプセレクタ"として扱う
+            [CssToken::Ident(ident), CssToken::Colon, ..] => {
+                Selector::TypeSelector(ident.to_string())
+            }
+            _ => Selector::UnknownSelector,
+        }
+    }
+}
+
+impl Selector {
+    /// https://www.w3.org/TR/selectors-4/#match-a-selector-against-an-element
+    /// タイプセレクタはASCIIの大文字・小文字を区別せず、クラスとidは区別する
+    pub fn matches(&self, element: &Element) -> bool {
+        match self {
+            Selector::TypeSelector(name) => element.kind().to_string().eq_ignore_ascii_case(name),
+            Selector::ClassSelector(name) => element.class_list().iter().any(|c| c == name),
+            Selector::IdSelector(name) => element.id().as_deref() == Some(name.as_str()),
+            Selector::UniversalSelector => true,
+            // 疑似要素のセレクタは、要素そのものには一致しない
+            Selector::PseudoElementSelector(_, _) => false,
+            Selector::UnknownSelector => false,
+        }
+    }
+
+    /// https://www.w3.org/TR/selectors-4/#specificity-rules
+    /// セレクタの詳細度. (idセレクタの数, クラスセレクタの数, タイプセレクタと疑似要素の数)の組で、
+    /// 辞書順に比べる. 全称セレクタは数えない
+    pub fn specificity(&self) -> (u32, u32, u32) {
+        match self {
+            Selector::TypeSelector(_) => (0, 0, 1),
+            Selector::ClassSelector(_) => (0, 1, 0),
+            Selector::IdSelector(_) => (1, 0, 0),
+            Selector::UniversalSelector | Selector::UnknownSelector => (0, 0, 0),
+            Selector::PseudoElementSelector(base, _) => {
+                let (a, b, c) = base.specificity();
+                (a, b, c + 1)
+            }
+        }
+    }
+
+    /// 要素の疑似要素`pseudo`に一致するか
+    pub fn matches_pseudo_element(&self, element: &Element, pseudo: PseudoElement) -> bool {
+        match self {
+            Selector::PseudoElementSelector(base, p) => *p == pseudo && base.matches(element),
+            _ => false,
+        }
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#serialize-a-selector
+    /// UnknownSelectorは空文字列になる（読み直しても、どの要素にも一致しないセレクタになる）
+    pub fn css_text(&self) -> String {
+        match self {
+            Selector::TypeSelector(name) => serialize_identifier(name),
+            Selector::ClassSelector(name) => format!(".{}", serialize_identifier(name)),
+            Selector::IdSelector(name) => format!("#{}", serialize_identifier(name)),
+            Selector::UniversalSelector => "*".to_string(),
+            Selector::PseudoElementSelector(base, pseudo) => match **base {
+                Selector::UniversalSelector => format!("::{}", pseudo.name()),
+                _ => format!("{}::{}", base.css_text(), pseudo.name()),
+            },
+            Selector::UnknownSelector => String::new(),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-pseudo-4/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PseudoElement {
+    /// https://www.w3.org/TR/css-pseudo-4/#selectordef-before
+    Before,
+    /// https://www.w3.org/TR/css-pseudo-4/#selectordef-after
+    After,
+    /// https://www.w3.org/TR/css-pseudo-4/#selectordef-marker
+    Marker,
+}
+
+impl PseudoElement {
+    /// 疑似要素の名前（ASCIIの大文字・小文字を区別しない）から変換する
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "before" => Some(PseudoElement::Before),
+            "after" => Some(PseudoElement::After),
+            "marker" => Some(PseudoElement::Marker),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PseudoElement::Before => "before",
+            PseudoElement::After => "after",
+            PseudoElement::Marker => "marker",
+        }
+    }
+}
+
+/// セレクタの末尾の疑似要素（`::name`、または古い構文の`:before`と`:after`）を切り出す
+/// 疑似要素がない場合はNoneを返し、名前をサポートしていない場合は疑似要素をNoneとして返す
+fn split_pseudo_element(
+    prelude: &[ComponentValue],
+) -> Option<(&[ComponentValue], Option<PseudoElement>)> {
+    match prelude {
+        [base @ .., CssToken::Colon, CssToken::Colon, CssToken::Ident(name)] => {
+            Some((base, PseudoElement::from_name(name)))
+        }
+        // CSS 2.1との互換性のため、:beforeと:afterは疑似要素として扱う
+        [base @ .., CssToken::Colon, CssToken::Ident(name)]
+            if base.last() != Some(&CssToken::Colon) =>
+        {
+            match PseudoElement::from_name(name) {
+                Some(pseudo @ (PseudoElement::Before | PseudoElement::After)) => {
+                    Some((base, Some(pseudo)))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 impl Default for Selector {
     fn default() -> Self {
         Selector::TypeSelector(String::new())
@@ -261,14 +1002,18 @@ impl Default for Selector {
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct Declaration {
     pub property: String,
-    pub value: ComponentValue,
+    /// 前後の空白を取り除いたコンポーネント値のリスト
+    pub value: Vec<ComponentValue>,
+    /// https://www.w3.org/TR/css-cascade-4/#importance
+    pub important: bool,
 }
 
 impl Declaration {
     pub fn new() -> Self {
         Self {
             property: String::new(),
-            value: ComponentValue::Ident(String::new()),
+            value: Vec::new(),
+            important: false,
         }
     }
 
@@ -276,14 +1021,105 @@ impl Declaration {
         self.property = property;
     }
 
-    pub fn set_value(&mut self, value: ComponentValue) {
+    pub fn set_value(&mut self, value: Vec<ComponentValue>) {
         self.value = value;
     }
+
+    pub fn set_important(&mut self, important: bool) {
+        self.important = important;
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#serialize-a-css-declaration
+    pub fn css_text(&self) -> String {
+        let property = if is_custom_property(&self.property) {
+            self.property.clone()
+        } else {
+            serialize_identifier(&self.property)
+        };
+        let mut result = format!("{}: {}", property, serialize_tokens(&self.value));
+        if self.important {
+            result.push_str(" !important");
+        }
+        result
+    }
+
+    /// 値を型付きの値としてパースする
+    pub fn parsed_value(&self) -> Result<CssValue, Error> {
+        CssValue::parse(&self.value)
+    }
 }
 
 /// プロパティの値に対するノード
 pub type ComponentValue = CssToken;
 
+/// 先頭と末尾の空白トークンを取り除いたスライスを返す
+pub(crate) fn trim_whitespace(tokens: &[CssToken]) -> &[CssToken] {
+    let start = tokens
+        .iter()
+        .position(|t| *t != CssToken::Whitespace)
+        .unwrap_or(tokens.len());
+    let end = tokens
+        .iter()
+        .rposition(|t| *t != CssToken::Whitespace)
+        .map_or(start, |i| i + 1);
+    &tokens[start..end]
+}
+
+/// 括弧の内側を除いたトップレベルのカンマでトークン列を分割する
+pub(crate) fn split_by_comma(tokens: &[CssToken]) -> Vec<&[CssToken]> {
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            CssToken::OpenParenthesis
+            | CssToken::OpenSquare
+            | CssToken::OpenCurly
+            | CssToken::Function(_) => depth += 1,
+            CssToken::CloseParenthesis | CssToken::CloseSquare | CssToken::CloseCurly => depth -= 1,
+            CssToken::Comma if depth == 0 => {
+                result.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    result.push(&tokens[start..]);
+
+    result
+}
+
+/// tokens[open]にある開き括弧（関数トークンを含む）に対応する閉じ括弧の位置を返す
+pub(crate) fn find_block_end(tokens: &[CssToken], open: usize) -> Option<usize> {
+    match tokens.get(open)? {
+        CssToken::OpenParenthesis
+        | CssToken::OpenSquare
+        | CssToken::OpenCurly
+        | CssToken::Function(_) => {}
+        _ => return None,
+    }
+
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token {
+            CssToken::OpenParenthesis
+            | CssToken::OpenSquare
+            | CssToken::OpenCurly
+            | CssToken::Function(_) => depth += 1,
+            CssToken::CloseParenthesis | CssToken::CloseSquare | CssToken::CloseCurly => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -310,10 +1146,10 @@ mod tests {
         rule.set_selector(Selector::TypeSelector("p".to_string()));
         let mut declaration = Declaration::new();
         declaration.set_property("color".to_string());
-        declaration.set_value(ComponentValue::Ident("red".to_string()));
+        declaration.set_value(vec![ComponentValue::Ident("red".to_string())]);
         rule.set_declarations(vec![declaration]);
 
-        let expected = [rule];
+        let expected = [CssRule::Style(rule)];
         assert_eq!(cssom.rules.len(), expected.len());
 
         for (i, rule) in cssom.rules.iter().enumerate() {
@@ -332,10 +1168,10 @@ mod tests {
         rule.set_selector(Selector::IdSelector("id".to_string()));
         let mut declaration = Declaration::new();
         declaration.set_property("color".to_string());
-        declaration.set_value(ComponentValue::Ident("red".to_string()));
+        declaration.set_value(vec![ComponentValue::Ident("red".to_string())]);
         rule.set_declarations(vec![declaration]);
 
-        let expected = [rule];
+        let expected = [CssRule::Style(rule)];
         assert_eq!(cssom.rules.len(), expected.len());
 
         for (i, rule) in cssom.rules.iter().enumerate() {
@@ -354,10 +1190,10 @@ mod tests {
         rule.set_selector(Selector::ClassSelector("class".to_string()));
         let mut declaration = Declaration::new();
         declaration.set_property("color".to_string());
-        declaration.set_value(ComponentValue::Ident("red".to_string()));
+        declaration.set_value(vec![ComponentValue::Ident("red".to_string())]);
         rule.set_declarations(vec![declaration]);
 
-        let expected = [rule];
+        let expected = [CssRule::Style(rule)];
         assert_eq!(cssom.rules.len(), expected.len());
 
         for (i, rule) in cssom.rules.iter().enumerate() {
@@ -365,6 +1201,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pseudo_element_selector() {
+        /* 疑似要素のセレクタが正しくパースされ、同じ文字列に戻り、詳細度が求められることを確認する */
+        let selector = |prelude: &str| {
+            let tokens: Vec<ComponentValue> = CssTokenizer::new(prelude.to_string()).collect();
+            Selector::parse(&tokens)
+        };
+
+        assert_eq!(
+            selector("p::before"),
+            Selector::PseudoElementSelector(
+                Box::new(Selector::TypeSelector("p".to_string())),
+                PseudoElement::Before
+            )
+        );
+        assert_eq!(selector(".note:after"), selector(".note::after"));
+        assert_eq!(
+            selector("::MARKER"),
+            Selector::PseudoElementSelector(
+                Box::new(Selector::UniversalSelector),
+                PseudoElement::Marker
+            )
+        );
+        assert_eq!(selector("*::before"), selector("::before"));
+        assert_eq!(selector("p::first-line"), Selector::UnknownSelector);
+        assert_eq!(
+            selector("p:marker"),
+            Selector::TypeSelector("p".to_string())
+        );
+
+        assert_eq!(selector("p::before").specificity(), (0, 0, 2));
+        assert_eq!(selector(".note::after").specificity(), (0, 1, 1));
+        assert_eq!(selector("::marker").specificity(), (0, 0, 1));
+        assert!(selector("#id").specificity() > selector(".note").specificity());
+        assert!(selector(".note").specificity() > selector("p").specificity());
+        assert_eq!(selector("*").specificity(), (0, 0, 0));
+
+        for (prelude, expected) in [
+            ("#id::after", "#id::after"),
+            ("*::before", "::before"),
+            ("li:before", "li::before"),
+        ] {
+            assert_eq!(selector(prelude).css_text(), expected);
+            assert_eq!(selector(expected), selector(prelude));
+        }
+    }
+
     #[test]
     fn test_multiple_rules() {
         /* 複数のルールを持つスタイルシートをパースする場合、そのルールが正しくパースされることを確認する */
@@ -376,24 +1259,552 @@ mod tests {
         rule1.set_selector(Selector::TypeSelector("p".to_string()));
         let mut declaration1 = Declaration::new();
         declaration1.set_property("content".to_string());
-        declaration1.set_value(ComponentValue::StringToken("Hey".to_string()));
+        declaration1.set_value(vec![ComponentValue::StringToken("Hey".to_string())]);
         rule1.set_declarations(vec![declaration1]);
 
         let mut rule2 = QualifiedRule::new();
         rule2.set_selector(Selector::TypeSelector("h1".to_string()));
         let mut declaration2 = Declaration::new();
         declaration2.set_property("font-size".to_string());
-        declaration2.set_value(ComponentValue::Number(40.0));
+        declaration2.set_value(vec![ComponentValue::Number(40.0)]);
         let mut declaration3 = Declaration::new();
         declaration3.set_property("color".to_string());
-        declaration3.set_value(ComponentValue::Ident("blue".to_string()));
+        declaration3.set_value(vec![ComponentValue::Ident("blue".to_string())]);
         rule2.set_declarations(vec![declaration2, declaration3]);
 
-        let expected = [rule1, rule2];
+        let expected = [CssRule::Style(rule1), CssRule::Style(rule2)];
         assert_eq!(cssom.rules.len(), expected.len());
 
         for (i, rule) in cssom.rules.iter().enumerate() {
             assert_eq!(&expected[i], rule);
         }
     }
+
+    #[test]
+    fn test_multiple_component_values() {
+        /* 複数のコンポーネント値を持つ宣言と、!importantがパースされることを確認する */
+        let style =
+            "p { margin: 10px 20px; width: 50% ! IMPORTANT; background: url(a.png) no-repeat }"
+                .to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        let mut rule = QualifiedRule::new();
+        rule.set_selector(Selector::TypeSelector("p".to_string()));
+        let mut declaration1 = Declaration::new();
+        declaration1.set_property("margin".to_string());
+        declaration1.set_value(vec![
+            ComponentValue::Dimension {
+                value: 10.0,
+                unit: "px".to_string(),
+            },
+            ComponentValue::Whitespace,
+            ComponentValue::Dimension {
+                value: 20.0,
+                unit: "px".to_string(),
+            },
+        ]);
+        let mut declaration2 = Declaration::new();
+        declaration2.set_property("width".to_string());
+        declaration2.set_value(vec![ComponentValue::Percentage(50.0)]);
+        declaration2.set_important(true);
+        let mut declaration3 = Declaration::new();
+        declaration3.set_property("background".to_string());
+        declaration3.set_value(vec![
+            ComponentValue::Url("a.png".to_string()),
+            ComponentValue::Whitespace,
+            ComponentValue::Ident("no-repeat".to_string()),
+        ]);
+        rule.set_declarations(vec![declaration1, declaration2, declaration3]);
+
+        assert_eq!(cssom.rules, vec![CssRule::Style(rule)]);
+        let declarations = &cssom
+            .style_rules()
+            .next()
+            .expect("rule should exist")
+            .declarations;
+        assert_eq!(
+            declarations[1].parsed_value(),
+            Ok(CssValue::Percentage(50.0))
+        );
+    }
+
+    fn color_rule(selector: Selector, color: &str) -> QualifiedRule {
+        let mut rule = QualifiedRule::new();
+        rule.set_selector(selector);
+        let mut declaration = Declaration::new();
+        declaration.set_property("color".to_string());
+        declaration.set_value(vec![ComponentValue::Ident(color.to_string())]);
+        rule.set_declarations(vec![declaration]);
+        rule
+    }
+
+    #[test]
+    fn test_media_rule() {
+        /* @mediaルールのブロック内のルールが、入れ子のルールとしてパースされることを確認する */
+        let style = "@media screen and (min-width: 600px) { p { color: red; } h1 { color: blue; } } a { color: green; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        assert_eq!(cssom.rules.len(), 2);
+        match &cssom.rules[0] {
+            CssRule::Media(media_rule) => {
+                assert_eq!(media_rule.media.queries.len(), 1);
+                assert_eq!(
+                    media_rule.rules,
+                    vec![
+                        CssRule::Style(color_rule(Selector::TypeSelector("p".to_string()), "red")),
+                        CssRule::Style(color_rule(
+                            Selector::TypeSelector("h1".to_string()),
+                            "blue"
+                        )),
+                    ]
+                );
+            }
+            rule => panic!("expected a media rule but got {:?}", rule),
+        }
+        assert_eq!(
+            cssom.rules[1],
+            CssRule::Style(color_rule(Selector::TypeSelector("a".to_string()), "green"))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_media_rule() {
+        /* 環境に応じて@mediaの条件が評価され、スタイルルールが出現順に平坦化されることを確認する */
+        let style = "p { color: red; } @media (max-width: 500px) { p { color: blue; } } @media (min-width: 500px) { @media (orientation: landscape) { p { color: green; } } }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        let narrow = cssom.evaluate(&MediaEnvironment::new(400.0, 600.0));
+        assert_eq!(
+            narrow
+                .style_rules()
+                .cloned()
+                .collect::<Vec<QualifiedRule>>(),
+            vec![
+                color_rule(Selector::TypeSelector("p".to_string()), "red"),
+                color_rule(Selector::TypeSelector("p".to_string()), "blue"),
+            ]
+        );
+
+        let wide = cssom.evaluate(&MediaEnvironment::new(800.0, 600.0));
+        assert_eq!(
+            wide.style_rules().cloned().collect::<Vec<QualifiedRule>>(),
+            vec![
+                color_rule(Selector::TypeSelector("p".to_string()), "red"),
+                color_rule(Selector::TypeSelector("p".to_string()), "green"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_supports_rule() {
+        /* @supportsの条件が、サポートしているプロパティに応じて評価されることを確認する */
+        let style = "@supports (display: block) { p { color: red; } } @supports (grid-area: auto) { h1 { color: blue; } } @supports invalid { a { color: green; } }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        // 条件が文法に一致しない@supportsルールは無視される
+        assert_eq!(cssom.rules.len(), 2);
+
+        let sheet = cssom.evaluate(&MediaEnvironment::default());
+        assert_eq!(
+            sheet.style_rules().cloned().collect::<Vec<QualifiedRule>>(),
+            vec![color_rule(Selector::TypeSelector("p".to_string()), "red")]
+        );
+    }
+
+    #[test]
+    fn test_import_rule() {
+        /* @importのURLとメディアクエリがパースされ、ほかのルールの後の@importは無視されることを確認する */
+        let style = "@import url(base.css); @import \"print.css\" print; @import url(\"wide.css\") (min-width: 600px); p { color: red; } @import url(late.css);".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        assert_eq!(cssom.rules.len(), 4);
+        let imports: Vec<&ImportRule> = cssom
+            .rules
+            .iter()
+            .filter_map(|rule| match rule {
+                CssRule::Import(import) => Some(import),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(imports.len(), 3);
+        assert_eq!(imports[0].href, "base.css");
+        assert!(imports[0].media.queries.is_empty());
+        assert_eq!(imports[1].href, "print.css");
+        assert!(!imports[1].media.matches(&MediaEnvironment::default()));
+        assert_eq!(imports[2].href, "wide.css");
+        assert!(imports[2]
+            .media
+            .matches(&MediaEnvironment::new(800.0, 600.0)));
+        assert!(!imports[2]
+            .media
+            .matches(&MediaEnvironment::new(400.0, 600.0)));
+    }
+
+    #[test]
+    fn test_evaluate_import_rule() {
+        /* 取得済みの@importのスタイルシートが、@importの位置に展開されることを確認する */
+        let style = "@import url(a.css); @import url(b.css) print; p { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let mut cssom = CssParser::new(t).parse_stylesheet();
+
+        for rule in cssom.rules.iter_mut() {
+            if let CssRule::Import(import) = rule {
+                let t = CssTokenizer::new("h1 { color: blue; }".to_string());
+                import.stylesheet = Some(CssParser::new(t).parse_stylesheet());
+            }
+        }
+
+        let sheet = cssom.evaluate(&MediaEnvironment::default());
+        assert_eq!(
+            sheet.style_rules().cloned().collect::<Vec<QualifiedRule>>(),
+            vec![
+                color_rule(Selector::TypeSelector("h1".to_string()), "blue"),
+                color_rule(Selector::TypeSelector("p".to_string()), "red"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_font_face_and_unknown_at_rules() {
+        /* @font-faceは宣言のリストとしてパースされ、未知の@ルールは読み飛ばされることを確認する */
+        let style = "@font-face { font-family: Foo; src: url(foo.woff); } @keyframes spin { from { color: red; } to { color: blue; } } @charset \"utf-8\"; p { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        assert_eq!(cssom.rules.len(), 2);
+        match &cssom.rules[0] {
+            CssRule::FontFace(font_face) => {
+                assert_eq!(font_face.declarations.len(), 2);
+                assert_eq!(font_face.declarations[0].property, "font-family");
+                assert_eq!(font_face.declarations[1].property, "src");
+            }
+            rule => panic!("expected a font-face rule but got {:?}", rule),
+        }
+        assert_eq!(
+            cssom.rules[1],
+            CssRule::Style(color_rule(Selector::TypeSelector("p".to_string()), "red"))
+        );
+    }
+
+    fn parse_with_diagnostics(style: &str) -> (StyleSheet, Vec<Error>) {
+        let mut parser = CssParser::new(CssTokenizer::new(style.to_string()));
+        let sheet = parser.parse_stylesheet();
+        (sheet, parser.diagnostics().to_vec())
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        /* 不正な宣言やルールが読み飛ばされ、後続のルールのパースが続くことを確認する */
+        // 不正な宣言は捨てられ、次の宣言から再開する
+        let (sheet, diagnostics) =
+            parse_with_diagnostics("p { color red; 12px; width: 1px; } h1 { color: blue; }");
+        let rules: Vec<&QualifiedRule> = sheet.style_rules().collect();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].declarations.len(), 1);
+        assert_eq!(rules[0].declarations[0].property, "width");
+        assert_eq!(
+            *rules[1],
+            color_rule(Selector::TypeSelector("h1".to_string()), "blue")
+        );
+        assert_eq!(diagnostics.len(), 2);
+
+        // 括弧の中のセミコロンや閉じ括弧では、宣言やブロックは終わらない
+        let (sheet, _) = parse_with_diagnostics("p { color: f(a; b}) red; } a { color: green; }");
+        let rules: Vec<&QualifiedRule> = sheet.style_rules().collect();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].declarations.len(), 1);
+        assert_eq!(
+            *rules[1],
+            color_rule(Selector::TypeSelector("a".to_string()), "green")
+        );
+
+        // サポートしていないセレクタのルールは、どの要素にも一致しないルールになる
+        let (sheet, diagnostics) =
+            parse_with_diagnostics("div > p { color: red; } .a { color: blue; }");
+        let rules: Vec<&QualifiedRule> = sheet.style_rules().collect();
+        assert_eq!(rules[0].selector, Selector::UnknownSelector);
+        assert_eq!(
+            *rules[1],
+            color_rule(Selector::ClassSelector("a".to_string()), "blue")
+        );
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_unclosed_blocks() {
+        /* 入力の終わりで閉じていないブロックが、その時点で閉じられることを確認する */
+        let (sheet, diagnostics) = parse_with_diagnostics("p { color: red");
+        assert_eq!(
+            sheet.rules,
+            vec![CssRule::Style(color_rule(
+                Selector::TypeSelector("p".to_string()),
+                "red"
+            ))]
+        );
+        assert_eq!(diagnostics.len(), 1);
+
+        let (sheet, _) = parse_with_diagnostics("@media screen { p { color: red; }");
+        assert_eq!(
+            sheet
+                .evaluate(&MediaEnvironment::default())
+                .style_rules()
+                .count(),
+            1
+        );
+
+        // 宣言ブロックのないルールは捨てられる
+        let (sheet, diagnostics) = parse_with_diagnostics("p { color: red; } h1");
+        assert_eq!(sheet.rules.len(), 1);
+        assert_eq!(diagnostics.len(), 1);
+
+        // ブロック内の余分な閉じ括弧はそのブロックを閉じ、トップレベルでは次のルールのプレリュードになる
+        let (sheet, _) =
+            parse_with_diagnostics("@media screen { p { color: red; } } } a { color: blue; }");
+        assert_eq!(sheet.rules.len(), 2);
+        let rules: Vec<&QualifiedRule> = sheet.style_rules().collect();
+        assert_eq!(rules[0].selector, Selector::UnknownSelector);
+    }
+
+    /// 不正なスタイルシートの例. どれもパニックせずにパースできなければならない
+    const MALFORMED_STYLESHEETS: &[&str] = &[
+        "{",
+        "}",
+        "}}}}",
+        "{{{{",
+        ";;;",
+        ":",
+        "p",
+        "p {",
+        "p { color",
+        "p { color:",
+        "p { color: }",
+        "p { : red }",
+        "p { !important }",
+        "p { color: red !important !important }",
+        ". { color: red }",
+        "# { color: red }",
+        "#",
+        ".",
+        "@",
+        "@media",
+        "@media {",
+        "@media screen and (",
+        "@media (min-width: ) { p { } }",
+        "@supports",
+        "@supports (display: ) { }",
+        "@supports not not (display: block) { p { color: red } }",
+        "@import",
+        "@import url(",
+        "@import url(\"a.css\" ;",
+        "@font-face",
+        "@font-face {",
+        "a { b { c { d { e",
+        "p { color: rgb(255 0 0",
+        "p { width: calc(100% - (2em + ) }",
+        "p { width: calc(1px + calc(1px + calc( }",
+        "p { margin: var(--a, var(--b, }",
+        "p { --x: {}; color: var(--x) }",
+        "p { color: \"unterminated }",
+        "p { background: url(unterminated }",
+        "p { content: \"\\\" }",
+        "/* unterminated comment",
+        "<!-- p { color: red } -->",
+        "p { color: red } ) ] } p { color: blue }",
+        "p:hover:: { }",
+        "\\",
+        "p\\ { color: red }",
+        "p { color: #; width: 1e999px; height: -1e-999px; }",
+        "p { font: bold / ; border: 1px 2px 3px 4px 5px solid; }",
+        "p { width: min(); height: clamp(1px); margin: max(,) }",
+        "\u{0}\u{ffff}\u{10ffff} { color: red }",
+        "p { color: rgb(var(--c) / 50%) }",
+    ];
+
+    /// 擬似乱数（線形合同法）でトークンを組み合わせたスタイルシートを作る
+    fn random_stylesheets(count: usize) -> Vec<String> {
+        let pieces = [
+            "p",
+            "h1",
+            ".",
+            "#",
+            "a",
+            ":",
+            ";",
+            ",",
+            "{",
+            "}",
+            "(",
+            ")",
+            "[",
+            "]",
+            " ",
+            "@media",
+            "@import",
+            "@supports",
+            "color",
+            "width",
+            "margin",
+            "red",
+            "1px",
+            "50%",
+            "calc(",
+            "var(--x",
+            "--x",
+            "!",
+            "important",
+            "\"",
+            "'",
+            "/*",
+            "*/",
+            "url(",
+            "\\",
+            "+",
+            "-",
+            "*",
+            "/",
+            "0",
+            "e",
+            "<!--",
+            "-->",
+        ];
+
+        let mut seed: u64 = 0x5eed;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize
+        };
+
+        (0..count)
+            .map(|_| {
+                let len = next() % 40;
+                (0..len).map(|_| pieces[next() % pieces.len()]).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_malformed_stylesheets() {
+        /* 不正なスタイルシートでもパニックせず、値のパースや条件の評価まで行えることを確認する */
+        let mut corpus: Vec<String> = MALFORMED_STYLESHEETS
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        corpus.extend(random_stylesheets(500));
+
+        for style in corpus {
+            let (sheet, _) = parse_with_diagnostics(&style);
+            let evaluated = sheet.evaluate(&MediaEnvironment::default());
+            for rule in evaluated.style_rules() {
+                for declaration in &rule.declarations {
+                    let _ = declaration.parsed_value();
+                    let _ = is_valid_declaration(&declaration.property, &declaration.value);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_css_text() {
+        /* スタイルシートを文字列にして読み直すと、同じスタイルシートになることを確認する */
+        let style = "@import url(\"a.css\") screen; p{color:red;margin : 10px  20px!important} #main { } .note { background: url(a.png) no-repeat; --Gap: { 1px } } @media (min-width: 600px) { h1 { font-size: 2em } @supports not (display: grid) { a { color: blue } } } @font-face { font-family: \"Foo Bar\" }";
+        let (sheet, _) = parse_with_diagnostics(style);
+
+        let expected = "@import url(\"a.css\") screen;\n\
+            p { color: red; margin: 10px 20px !important; }\n\
+            #main { }\n\
+            .note { background: url(a.png) no-repeat; --Gap: { 1px }; }\n\
+            @media (min-width: 600px) {\n  h1 { font-size: 2em; }\n  @supports not (display: grid) {\n    a { color: blue; }\n  }\n}\n\
+            @font-face { font-family: \"Foo Bar\"; }";
+        assert_eq!(sheet.css_text(), expected);
+
+        let (reparsed, diagnostics) = parse_with_diagnostics(&sheet.css_text());
+        assert_eq!(reparsed, sheet);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_insert_and_delete_rule() {
+        /* ルールの挿入と削除、および不正な挿入がエラーになることを確認する */
+        let (mut sheet, _) = parse_with_diagnostics("@import url(a.css); p { color: red; }");
+
+        assert_eq!(sheet.insert_rule("h1 { color: blue; }", 2), Ok(2));
+        assert_eq!(
+            sheet.insert_rule("@media print { a { color: red; } }", 1),
+            Ok(1)
+        );
+        assert_eq!(sheet.rules.len(), 4);
+        assert_eq!(
+            sheet.rules[3],
+            CssRule::Style(color_rule(Selector::TypeSelector("h1".to_string()), "blue"))
+        );
+
+        // 範囲外の位置、複数のルール、不正なルール、@importの位置の制約
+        assert!(sheet.insert_rule("a { }", 5).is_err());
+        assert!(sheet.insert_rule("a { } b { }", 0).is_err());
+        assert!(sheet.insert_rule("a {", 0).is_err());
+        assert!(sheet.insert_rule("", 0).is_err());
+        assert!(sheet.insert_rule("@import url(b.css);", 2).is_err());
+        assert!(sheet.insert_rule("a { }", 0).is_err());
+        assert_eq!(sheet.insert_rule("@import url(b.css);", 1), Ok(1));
+
+        match &mut sheet.rules[2] {
+            CssRule::Media(media) => {
+                assert_eq!(media.insert_rule("b { color: red; }", 1), Ok(1));
+                assert!(media.insert_rule("@import url(c.css);", 0).is_err());
+                assert_eq!(media.delete_rule(0), Ok(()));
+                assert_eq!(media.rules.len(), 1);
+            }
+            rule => panic!("expected a media rule but got {:?}", rule),
+        }
+
+        assert_eq!(sheet.delete_rule(0), Ok(()));
+        assert!(sheet.delete_rule(4).is_err());
+        assert_eq!(sheet.rules.len(), 4);
+    }
+
+    #[test]
+    fn test_style_declaration() {
+        /* 宣言の値の取得と変更が、CSSStyleDeclarationと同じように動くことを確認する */
+        let (mut sheet, _) = parse_with_diagnostics(
+            "p { color: red !important; color: blue; margin: 1px 2px; --Brand: #0af; }",
+        );
+        let rule = match &mut sheet.rules[0] {
+            CssRule::Style(rule) => rule,
+            rule => panic!("expected a style rule but got {:?}", rule),
+        };
+
+        // !importantの宣言が優先され、ロングハンドはショートハンドから求められる
+        assert_eq!(rule.get_property_value("COLOR"), "red");
+        assert_eq!(rule.get_property_priority("color"), "important");
+        assert_eq!(rule.get_property_value("margin-left"), "2px");
+        assert_eq!(rule.get_property_value("--Brand"), "#0af");
+        assert_eq!(rule.get_property_value("--brand"), "");
+        assert_eq!(rule.get_property_value("width"), "");
+
+        assert_eq!(rule.set_property("color", "green", ""), Ok(()));
+        assert_eq!(rule.get_property_value("color"), "green");
+        assert_eq!(rule.get_property_priority("color"), "");
+        assert_eq!(
+            rule.set_property("Width", "calc(100% - 2em)", "IMPORTANT"),
+            Ok(())
+        );
+        assert!(rule.set_property("width", "red", "").is_err());
+        assert!(rule.set_property("width", "1px", "high").is_err());
+        assert!(rule.set_property("unknown", "1px", "").is_err());
+        assert_eq!(rule.set_property("--Brand", "{ anything }", ""), Ok(()));
+
+        assert_eq!(rule.remove_property("margin"), "1px 2px");
+        assert_eq!(rule.set_property("--Brand", "", ""), Ok(()));
+        assert_eq!(
+            rule.css_text(),
+            "p { color: green; width: calc(100% - 2em) !important; }"
+        );
+    }
 }
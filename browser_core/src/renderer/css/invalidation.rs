@@ -0,0 +1,267 @@
+use crate::renderer::css::cssom::CssRule;
+use crate::renderer::css::cssom::QualifiedRule;
+use crate::renderer::css::cssom::Selector;
+use crate::renderer::css::token::CssToken;
+use crate::renderer::dom::node::Element;
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// スタイルの無効化に使う集合
+/// スタイルシートのセレクタが参照するタグ名・クラス名・id・属性名を集めておき、
+/// DOMの変更で要素に一致するセレクタが変わりうるか（スタイルの再計算が必要か）を判定する
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InvalidationSet {
+    /// タイプセレクタのタグ名（小文字）
+    tags: BTreeSet<String>,
+    /// クラスセレクタのクラス名
+    classes: BTreeSet<String>,
+    /// idセレクタのid
+    ids: BTreeSet<String>,
+    /// 属性セレクタの属性名（小文字）. classとidは専用の集合で扱う
+    attributes: BTreeSet<String>,
+    /// 全称セレクタを含むか. 含む場合はすべての要素が一致しうる
+    universal: bool,
+}
+
+impl InvalidationSet {
+    pub fn new() -> Self {
+        Self {
+            tags: BTreeSet::new(),
+            classes: BTreeSet::new(),
+            ids: BTreeSet::new(),
+            attributes: BTreeSet::new(),
+            universal: false,
+        }
+    }
+
+    /// ルールのリストに含まれるすべてのセレクタを加える
+    /// @mediaなどの条件は評価せず、ブロック内のルールもすべて加える
+    pub fn add_rules(&mut self, rules: &[CssRule]) {
+        for rule in rules {
+            match rule {
+                CssRule::Style(style_rule) => self.add_style_rule(style_rule),
+                CssRule::Media(media_rule) => self.add_rules(&media_rule.rules),
+                CssRule::Supports(supports_rule) => self.add_rules(&supports_rule.rules),
+                CssRule::Import(import_rule) => {
+                    if let Some(sheet) = &import_rule.stylesheet {
+                        self.add_rules(&sheet.rules);
+                    }
+                }
+                CssRule::FontFace(_) => {}
+            }
+        }
+    }
+
+    pub fn add_style_rule(&mut self, rule: &QualifiedRule) {
+        self.add_selector(&rule.selector);
+
+        // attr()で参照される属性が変わると、生成されるコンテンツが変わる
+        for declaration in &rule.declarations {
+            for pair in declaration.value.windows(2) {
+                if let [CssToken::Function(name), CssToken::Ident(attribute)] = pair {
+                    if name.eq_ignore_ascii_case("attr") {
+                        self.add_attribute(attribute);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn add_selector(&mut self, selector: &Selector) {
+        match selector {
+            Selector::TypeSelector(name) => {
+                self.tags.insert(name.to_ascii_lowercase());
+            }
+            Selector::ClassSelector(name) => {
+                self.classes.insert(name.to_string());
+            }
+            Selector::IdSelector(name) => {
+                self.ids.insert(name.to_string());
+            }
+            Selector::UniversalSelector => {
+                self.universal = true;
+            }
+            // 疑似要素のスタイルは、元になる要素のセレクタに一致するかで変わる
+            Selector::PseudoElementSelector(base, _) => self.add_selector(base),
+            // どの要素にも一致しないので、無効化の必要はない
+            Selector::UnknownSelector => {}
+        }
+    }
+
+    pub fn add_attribute(&mut self, name: &str) {
+        self.attributes.insert(name.to_ascii_lowercase());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.universal
+            && self.tags.is_empty()
+            && self.classes.is_empty()
+            && self.ids.is_empty()
+            && self.attributes.is_empty()
+    }
+
+    /// 集合に含まれるいずれかのセレクタに、要素が一致しうるか
+    pub fn may_match(&self, element: &Element) -> bool {
+        self.universal
+            || self.tags.contains(&element.kind().to_string())
+            || element
+                .class_list()
+                .iter()
+                .any(|class| self.classes.contains(class))
+            || element.id().map_or(false, |id| self.ids.contains(&id))
+            || element
+                .attributes()
+                .iter()
+                .any(|attr| self.attributes.contains(&attr.name()))
+    }
+
+    /// 属性の値が`old`から`new`に変わったとき、要素のスタイルの再計算が必要か
+    /// 値がNoneの場合は、属性が存在しないことを表す
+    pub fn is_affected_by_attribute_change(
+        &self,
+        name: &str,
+        old: Option<&str>,
+        new: Option<&str>,
+    ) -> bool {
+        if old == new {
+            return false;
+        }
+
+        match name {
+            // 追加・削除されたクラスのいずれかが、クラスセレクタに使われている場合のみ.
+            // attr(class)で参照されている場合は、値が変わるだけで生成されるコンテンツが変わる
+            "class" => {
+                let old_classes = split_classes(old);
+                let new_classes = split_classes(new);
+                self.attributes.contains(name)
+                    || old_classes
+                        .symmetric_difference(&new_classes)
+                        .any(|class| self.classes.contains(*class))
+            }
+            "id" => {
+                self.attributes.contains(name)
+                    || old.map_or(false, |id| self.ids.contains(id))
+                    || new.map_or(false, |id| self.ids.contains(id))
+            }
+            _ => self.attributes.contains(name),
+        }
+    }
+}
+
+fn split_classes(value: Option<&str>) -> BTreeSet<&str> {
+    value
+        .map(|value| value.split_ascii_whitespace().collect())
+        .unwrap_or_default()
+}
+
+/// 2つのスタイルルールのリストで、一方にしか含まれないルールのセレクタを集める
+/// スタイルシートを変更したときに、スタイルが変わりうる要素を求めるために使う
+pub fn changed_rules(old: &[QualifiedRule], new: &[QualifiedRule]) -> InvalidationSet {
+    let mut set = InvalidationSet::new();
+    let mut remaining: Vec<&QualifiedRule> = old.iter().collect();
+
+    for rule in new {
+        match remaining.iter().position(|old_rule| *old_rule == rule) {
+            Some(index) => {
+                remaining.remove(index);
+            }
+            None => set.add_style_rule(rule),
+        }
+    }
+    for rule in remaining {
+        set.add_style_rule(rule);
+    }
+
+    set
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::cssom::CssParser;
+    use crate::renderer::css::token::CssTokenizer;
+
+    fn invalidation_set(style: &str) -> InvalidationSet {
+        let sheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+        let mut set = InvalidationSet::new();
+        set.add_rules(&sheet.rules);
+        set
+    }
+
+    fn element(name: &str, class: &str, id: &str) -> Element {
+        let mut element = Element::new(name, Vec::new());
+        if !class.is_empty() {
+            element.set_attribute("class", class);
+        }
+        if !id.is_empty() {
+            element.set_attribute("id", id);
+        }
+        element
+    }
+
+    #[test]
+    fn test_may_match() {
+        /* セレクタのタグ名・クラス・idを持つ要素だけが一致しうることを確認する */
+        let set = invalidation_set(
+            "P { color: red } .warning { color: red } @media (min-width: 1px) { #main { color: red } }",
+        );
+
+        assert!(set.may_match(&element("p", "", "")));
+        assert!(set.may_match(&element("a", "note warning", "")));
+        assert!(set.may_match(&element("a", "", "main")));
+        assert!(!set.may_match(&element("a", "Warning", "Main")));
+        assert!(!set.may_match(&element("h1", "", "")));
+        assert!(invalidation_set("").is_empty());
+
+        let set = invalidation_set("h1::before { content: \"x\" }");
+        assert!(set.may_match(&element("h1", "", "")));
+        assert!(!set.may_match(&element("p", "", "")));
+        assert!(invalidation_set("::after { content: \"x\" }").may_match(&element("p", "", "")));
+    }
+
+    #[test]
+    fn test_attribute_change() {
+        /* 属性の変更で、セレクタに使われているクラスやidが増減した場合か、attr()で参照されている場合のみ無効化されることを確認する */
+        let mut set = invalidation_set(".a { color: red } #x { color: red }");
+        set.add_attribute("Title");
+
+        assert!(set.is_affected_by_attribute_change("class", None, Some("a")));
+        assert!(set.is_affected_by_attribute_change("class", Some("a b"), Some("b")));
+        assert!(!set.is_affected_by_attribute_change("class", Some("a b"), Some("b  a")));
+        assert!(!set.is_affected_by_attribute_change("class", Some("b"), Some("c")));
+        assert!(set.is_affected_by_attribute_change("id", Some("x"), Some("y")));
+        assert!(!set.is_affected_by_attribute_change("id", Some("y"), None));
+        assert!(set.is_affected_by_attribute_change("title", None, Some("")));
+        assert!(!set.is_affected_by_attribute_change("lang", None, Some("ja")));
+
+        let set = invalidation_set("a::after { content: \" (\" attr(HREF) \")\" }");
+        assert!(set.is_affected_by_attribute_change("href", None, Some("/")));
+
+        let set = invalidation_set("p::after { content: attr(class) attr(id) }");
+        assert!(set.is_affected_by_attribute_change("class", Some("b"), Some("c")));
+        assert!(set.is_affected_by_attribute_change("id", Some("y"), None));
+    }
+
+    #[test]
+    fn test_changed_rules() {
+        /* 追加・削除されたルールのセレクタだけが集められることを確認する */
+        let old = CssParser::new(CssTokenizer::new(
+            "p { color: red } .a { color: red }".to_string(),
+        ))
+        .parse_stylesheet();
+        let new = CssParser::new(CssTokenizer::new(
+            "p { color: red } .a { color: blue } h1 { color: red }".to_string(),
+        ))
+        .parse_stylesheet();
+        let old_rules: Vec<QualifiedRule> = old.style_rules().cloned().collect();
+        let new_rules: Vec<QualifiedRule> = new.style_rules().cloned().collect();
+
+        let set = changed_rules(&old_rules, &new_rules);
+        assert!(!set.may_match(&element("p", "", "")));
+        assert!(set.may_match(&element("p", "a", "")));
+        assert!(set.may_match(&element("h1", "", "")));
+        assert!(changed_rules(&old_rules, &old_rules).is_empty());
+    }
+}
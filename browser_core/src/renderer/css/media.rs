@@ -0,0 +1,801 @@
+use crate::constants::CONTENT_AREA_HEIGHT;
+use crate::constants::CONTENT_AREA_WIDTH;
+use crate::renderer::css::cssom::find_block_end;
+use crate::renderer::css::cssom::split_by_comma;
+use crate::renderer::css::cssom::trim_whitespace;
+use crate::renderer::css::serialize::serialize_identifier;
+use crate::renderer::css::serialize::serialize_number;
+use crate::renderer::css::serialize::serialize_tokens;
+use crate::renderer::css::token::CssToken;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/mediaqueries-5/#prefers-color-scheme
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+/// メディアクエリを評価するための環境. ビューポートの大きさとユーザの設定を持つ
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MediaEnvironment {
+    width: f64,
+    height: f64,
+    color_scheme: ColorScheme,
+}
+
+impl MediaEnvironment {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            color_scheme: ColorScheme::Light,
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    pub fn color_scheme(&self) -> ColorScheme {
+        self.color_scheme
+    }
+
+    pub fn set_color_scheme(&mut self, color_scheme: ColorScheme) {
+        self.color_scheme = color_scheme;
+    }
+}
+
+/// 現在のウィンドウのコンテンツ領域をビューポートとする
+impl Default for MediaEnvironment {
+    fn default() -> Self {
+        Self::new(CONTENT_AREA_WIDTH as f64, CONTENT_AREA_HEIGHT as f64)
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#typedef-media-query-list
+/// 空のリストは、すべての環境にマッチする
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MediaQueryList {
+    pub queries: Vec<MediaQuery>,
+}
+
+impl MediaQueryList {
+    pub fn new() -> Self {
+        Self {
+            queries: Vec::new(),
+        }
+    }
+
+    /// https://www.w3.org/TR/mediaqueries-4/#mq-syntax
+    /// カンマ区切りのメディアクエリをパースする. 不正なメディアクエリは"not all"として扱う
+    pub fn parse(tokens: &[CssToken]) -> Self {
+        let tokens = trim_whitespace(tokens);
+        if tokens.is_empty() {
+            return Self::new();
+        }
+
+        let queries = split_by_comma(tokens)
+            .into_iter()
+            .map(|query| MediaQuery::parse(query).unwrap_or_else(MediaQuery::not_all))
+            .collect();
+
+        Self { queries }
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#serialize-a-media-query-list
+    pub fn css_text(&self) -> String {
+        self.queries
+            .iter()
+            .map(|query| query.css_text())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// いずれかのメディアクエリが真であれば、リスト全体が真となる
+    pub fn matches(&self, env: &MediaEnvironment) -> bool {
+        self.queries.is_empty() || self.queries.iter().any(|q| q.matches(env))
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#media-query
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaQuery {
+    /// "not"修飾子. クエリ全体の結果を反転する
+    pub negated: bool,
+    pub media_type: MediaType,
+    pub condition: Option<MediaCondition>,
+}
+
+impl MediaQuery {
+    /// どの環境にもマッチしないクエリ. パースに失敗したクエリはこれに置き換えられる
+    pub fn not_all() -> Self {
+        Self {
+            negated: true,
+            media_type: MediaType::All,
+            condition: None,
+        }
+    }
+
+    fn parse(tokens: &[CssToken]) -> Option<Self> {
+        let tokens = trim_whitespace(tokens);
+
+        // 括弧や"not ("からはじまる場合は、メディアタイプを省略した<media-condition>
+        match tokens.first() {
+            Some(CssToken::OpenParenthesis) | Some(CssToken::Function(_)) => {
+                return Some(Self {
+                    negated: false,
+                    media_type: MediaType::All,
+                    condition: Some(parse_condition(tokens, true)?),
+                });
+            }
+            Some(CssToken::Ident(ident)) if ident.eq_ignore_ascii_case("not") => {
+                if let Some(CssToken::OpenParenthesis) = trim_whitespace(&tokens[1..]).first() {
+                    return Some(Self {
+                        negated: false,
+                        media_type: MediaType::All,
+                        condition: Some(parse_condition(tokens, true)?),
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        // [ not | only ]? <media-type> [ and <media-condition-without-or> ]?
+        let mut rest = tokens;
+        let mut negated = false;
+        let mut media_type = consume_ident(&mut rest)?;
+        if media_type == "not" || media_type == "only" {
+            negated = media_type == "not";
+            media_type = consume_ident(&mut rest)?;
+        }
+        let media_type = MediaType::from_str(&media_type)?;
+
+        if trim_whitespace(rest).is_empty() {
+            return Some(Self {
+                negated,
+                media_type,
+                condition: None,
+            });
+        }
+
+        // メディアタイプの後には"and"と条件のみが続く
+        if consume_ident(&mut rest)? != "and" {
+            return None;
+        }
+
+        Some(Self {
+            negated,
+            media_type,
+            condition: Some(parse_condition(rest, false)?),
+        })
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#serialize-a-media-query
+    /// メディアタイプがallで条件がある場合は、メディアタイプを省略する
+    pub fn css_text(&self) -> String {
+        let mut result = String::new();
+        if self.negated {
+            result.push_str("not ");
+        }
+        match &self.condition {
+            Some(condition) if !self.negated && self.media_type == MediaType::All => {
+                result.push_str(&condition.css_text());
+            }
+            Some(condition) => {
+                result.push_str(&self.media_type.css_text());
+                result.push_str(" and ");
+                result.push_str(&condition.css_text());
+            }
+            None => result.push_str(&self.media_type.css_text()),
+        }
+        result
+    }
+
+    pub fn matches(&self, env: &MediaEnvironment) -> bool {
+        let result = self.media_type.matches()
+            && self
+                .condition
+                .as_ref()
+                .map_or(true, |condition| condition.evaluate(env));
+
+        result != self.negated
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#media-types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaType {
+    All,
+    Screen,
+    Print,
+    /// 仕様で非推奨となったメディアタイプや未知のメディアタイプ. どの環境にもマッチしない
+    Unknown(String),
+}
+
+impl MediaType {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "all" => Some(Self::All),
+            "screen" => Some(Self::Screen),
+            "print" => Some(Self::Print),
+            // 予約語はメディアタイプとして使用できない
+            "not" | "and" | "or" | "only" | "layer" => None,
+            _ => Some(Self::Unknown(s.to_string())),
+        }
+    }
+
+    fn css_text(&self) -> String {
+        match self {
+            MediaType::All => "all".to_string(),
+            MediaType::Screen => "screen".to_string(),
+            MediaType::Print => "print".to_string(),
+            MediaType::Unknown(name) => serialize_identifier(name),
+        }
+    }
+
+    /// このブラウザは画面に描画するので、screenとallにのみマッチする
+    fn matches(&self) -> bool {
+        matches!(self, MediaType::All | MediaType::Screen)
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#typedef-media-condition
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaCondition {
+    Feature(MediaFeature),
+    Not(Box<MediaCondition>),
+    And(Vec<MediaCondition>),
+    Or(Vec<MediaCondition>),
+    /// https://www.w3.org/TR/mediaqueries-4/#typedef-general-enclosed
+    /// 将来の拡張のための構文. 常に偽として評価する. 文字列にするために括弧を含むトークン列を保持する
+    GeneralEnclosed(Vec<CssToken>),
+}
+
+impl MediaCondition {
+    pub fn css_text(&self) -> String {
+        match self {
+            MediaCondition::Feature(feature) => feature.css_text(),
+            MediaCondition::Not(condition) => format!("not {}", condition.css_text_in_parens()),
+            MediaCondition::And(conditions) => conditions
+                .iter()
+                .map(|c| c.css_text_in_parens())
+                .collect::<Vec<String>>()
+                .join(" and "),
+            MediaCondition::Or(conditions) => conditions
+                .iter()
+                .map(|c| c.css_text_in_parens())
+                .collect::<Vec<String>>()
+                .join(" or "),
+            MediaCondition::GeneralEnclosed(tokens) => serialize_tokens(tokens),
+        }
+    }
+
+    /// <media-in-parens>として文字列にする. not、and、orの条件は括弧で囲む
+    fn css_text_in_parens(&self) -> String {
+        match self {
+            MediaCondition::Not(_) | MediaCondition::And(_) | MediaCondition::Or(_) => {
+                format!("({})", self.css_text())
+            }
+            _ => self.css_text(),
+        }
+    }
+
+    pub fn evaluate(&self, env: &MediaEnvironment) -> bool {
+        match self {
+            MediaCondition::Feature(feature) => feature.evaluate(env),
+            MediaCondition::Not(condition) => !condition.evaluate(env),
+            MediaCondition::And(conditions) => conditions.iter().all(|c| c.evaluate(env)),
+            MediaCondition::Or(conditions) => conditions.iter().any(|c| c.evaluate(env)),
+            MediaCondition::GeneralEnclosed(_) => false,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#mq-range-context
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+impl Comparison {
+    /// 値と特徴名の左右を入れ替える（600px < width → width > 600px）
+    fn flip(&self) -> Self {
+        match self {
+            Comparison::Equal => Comparison::Equal,
+            Comparison::LessThan => Comparison::GreaterThan,
+            Comparison::LessThanOrEqual => Comparison::GreaterThanOrEqual,
+            Comparison::GreaterThan => Comparison::LessThan,
+            Comparison::GreaterThanOrEqual => Comparison::LessThanOrEqual,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Comparison::Equal => "=",
+            Comparison::LessThan => "<",
+            Comparison::LessThanOrEqual => "<=",
+            Comparison::GreaterThan => ">",
+            Comparison::GreaterThanOrEqual => ">=",
+        }
+    }
+
+    fn compare(&self, actual: f64, expected: f64) -> bool {
+        match self {
+            Comparison::Equal => actual == expected,
+            Comparison::LessThan => actual < expected,
+            Comparison::LessThanOrEqual => actual <= expected,
+            Comparison::GreaterThan => actual > expected,
+            Comparison::GreaterThanOrEqual => actual >= expected,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#mq-features
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaFeatureValue {
+    /// px単位に変換した長さ
+    Length(f64),
+    Number(f64),
+    Ident(String),
+}
+
+impl MediaFeatureValue {
+    fn css_text(&self) -> String {
+        match self {
+            MediaFeatureValue::Length(px) => format!("{}px", serialize_number(*px)),
+            MediaFeatureValue::Number(number) => serialize_number(*number),
+            MediaFeatureValue::Ident(ident) => serialize_identifier(ident),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#typedef-media-feature
+/// min-/max-接頭辞は範囲比較に変換して保持する
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaFeature {
+    pub name: String,
+    /// Noneの場合はブーリアンコンテキスト（例: (width)）
+    pub comparison: Option<(Comparison, MediaFeatureValue)>,
+    /// min-/max-接頭辞の構文で書かれたか. 文字列にするときに元の構文に戻す
+    pub prefixed: bool,
+}
+
+impl MediaFeature {
+    fn new(name: &str, comparison: Option<(Comparison, MediaFeatureValue)>) -> Self {
+        Self {
+            name: name.to_ascii_lowercase(),
+            comparison,
+            prefixed: false,
+        }
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#serialize-a-media-query
+    /// 書かれた構文のまま出力する（例：(min-width: 600px)、(width >= 600px)）
+    pub fn css_text(&self) -> String {
+        let name = serialize_identifier(&self.name);
+        match &self.comparison {
+            None => format!("({})", name),
+            Some((Comparison::Equal, value)) => format!("({}: {})", name, value.css_text()),
+            Some((Comparison::GreaterThanOrEqual, value)) if self.prefixed => {
+                format!("(min-{}: {})", name, value.css_text())
+            }
+            Some((Comparison::LessThanOrEqual, value)) if self.prefixed => {
+                format!("(max-{}: {})", name, value.css_text())
+            }
+            Some((comparison, value)) => {
+                format!("({} {} {})", name, comparison.as_str(), value.css_text())
+            }
+        }
+    }
+
+    pub fn evaluate(&self, env: &MediaEnvironment) -> bool {
+        match self.name.as_str() {
+            "width" => self.evaluate_range(env.width()),
+            "height" => self.evaluate_range(env.height()),
+            "orientation" => {
+                let orientation = if env.height() >= env.width() {
+                    "portrait"
+                } else {
+                    "landscape"
+                };
+                self.evaluate_ident(orientation)
+            }
+            "prefers-color-scheme" => {
+                let scheme = match env.color_scheme() {
+                    ColorScheme::Light => "light",
+                    ColorScheme::Dark => "dark",
+                };
+                self.evaluate_ident(scheme)
+            }
+            // 未知のメディア特性は偽として扱う
+            _ => false,
+        }
+    }
+
+    fn evaluate_range(&self, actual: f64) -> bool {
+        match &self.comparison {
+            None => actual != 0.0,
+            Some((comparison, MediaFeatureValue::Length(expected))) => {
+                comparison.compare(actual, *expected)
+            }
+            // 単位のない0は長さとして扱える
+            Some((comparison, MediaFeatureValue::Number(n))) if *n == 0.0 => {
+                comparison.compare(actual, 0.0)
+            }
+            Some(_) => false,
+        }
+    }
+
+    fn evaluate_ident(&self, actual: &str) -> bool {
+        match &self.comparison {
+            None => true,
+            Some((Comparison::Equal, MediaFeatureValue::Ident(expected))) => {
+                expected.eq_ignore_ascii_case(actual)
+            }
+            Some(_) => false,
+        }
+    }
+}
+
+/// 先頭の空白を読み飛ばし、識別子であれば小文字に変換して返す
+fn consume_ident(tokens: &mut &[CssToken]) -> Option<String> {
+    let trimmed = trim_whitespace(tokens);
+    match trimmed.first() {
+        Some(CssToken::Ident(ident)) => {
+            *tokens = &trimmed[1..];
+            Some(ident.to_ascii_lowercase())
+        }
+        _ => None,
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#typedef-media-condition
+/// allow_orがfalseの場合は<media-condition-without-or>としてパースする
+fn parse_condition(tokens: &[CssToken], allow_or: bool) -> Option<MediaCondition> {
+    let tokens = trim_whitespace(tokens);
+
+    if let Some(CssToken::Ident(ident)) = tokens.first() {
+        if ident.eq_ignore_ascii_case("not") {
+            let (condition, rest) = parse_in_parens(&tokens[1..])?;
+            if !trim_whitespace(rest).is_empty() {
+                return None;
+            }
+            return Some(MediaCondition::Not(Box::new(condition)));
+        }
+    }
+
+    let (first, mut rest) = parse_in_parens(tokens)?;
+    let mut conditions = Vec::from([first]);
+    let mut operator: Option<String> = None;
+
+    loop {
+        rest = trim_whitespace(rest);
+        let keyword = match rest.first() {
+            None => break,
+            Some(CssToken::Ident(ident)) => ident.to_ascii_lowercase(),
+            Some(_) => return None,
+        };
+        if keyword != "and" && !(keyword == "or" && allow_or) {
+            return None;
+        }
+        // andとorを括弧なしで混在させることはできない
+        if operator.as_ref().map_or(false, |op| *op != keyword) {
+            return None;
+        }
+        let (condition, next) = parse_in_parens(&rest[1..])?;
+        conditions.push(condition);
+        operator = Some(keyword);
+        rest = next;
+    }
+
+    match operator.as_deref() {
+        None => conditions.pop(),
+        Some("and") => Some(MediaCondition::And(conditions)),
+        _ => Some(MediaCondition::Or(conditions)),
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#typedef-media-in-parens
+/// 括弧で囲まれた条件またはメディア特性をパースし、残りのトークンとともに返す
+fn parse_in_parens(tokens: &[CssToken]) -> Option<(MediaCondition, &[CssToken])> {
+    let tokens = trim_whitespace(tokens);
+    let end = find_block_end(tokens, 0)?;
+
+    match tokens.first()? {
+        CssToken::OpenParenthesis => {}
+        CssToken::Function(_) => {
+            return Some((
+                MediaCondition::GeneralEnclosed(tokens[..=end].to_vec()),
+                &tokens[end + 1..],
+            ))
+        }
+        _ => return None,
+    }
+
+    let inner = trim_whitespace(&tokens[1..end]);
+    let rest = &tokens[end + 1..];
+
+    let nested = match inner.first() {
+        Some(CssToken::OpenParenthesis) => true,
+        Some(CssToken::Ident(ident)) => ident.eq_ignore_ascii_case("not"),
+        _ => false,
+    };
+    if nested {
+        return Some((
+            parse_condition(inner, true)
+                .unwrap_or_else(|| MediaCondition::GeneralEnclosed(tokens[..=end].to_vec())),
+            rest,
+        ));
+    }
+
+    let condition = parse_feature(inner)
+        .unwrap_or_else(|| MediaCondition::GeneralEnclosed(tokens[..=end].to_vec()));
+    Some((condition, rest))
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#typedef-media-feature
+/// 括弧の内側（例: min-width: 600px、width >= 600px、400px < width < 800px）をパースする
+fn parse_feature(tokens: &[CssToken]) -> Option<MediaCondition> {
+    // ブーリアンコンテキスト: (width)
+    if let [CssToken::Ident(name)] = tokens {
+        return Some(MediaCondition::Feature(MediaFeature::new(name, None)));
+    }
+
+    // 通常の構文: (min-width: 600px)
+    if let Some(colon) = tokens.iter().position(|t| *t == CssToken::Colon) {
+        let name = match trim_whitespace(&tokens[..colon]) {
+            [CssToken::Ident(name)] => name.to_ascii_lowercase(),
+            _ => return None,
+        };
+        let value = parse_value(trim_whitespace(&tokens[colon + 1..]))?;
+
+        let (comparison, feature_name) = if let Some(name) = name.strip_prefix("min-") {
+            (Comparison::GreaterThanOrEqual, name)
+        } else if let Some(name) = name.strip_prefix("max-") {
+            (Comparison::LessThanOrEqual, name)
+        } else {
+            (Comparison::Equal, name.as_str())
+        };
+        let mut feature = MediaFeature::new(feature_name, Some((comparison, value)));
+        feature.prefixed = comparison != Comparison::Equal;
+        return Some(MediaCondition::Feature(feature));
+    }
+
+    // 範囲構文: 比較演算子で区切られた値と特徴名
+    let mut parts = Vec::new();
+    let mut operators = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Some((comparison, len)) = parse_comparison(&tokens[i..]) {
+            parts.push(trim_whitespace(&tokens[start..i]));
+            operators.push(comparison);
+            i += len;
+            start = i;
+            continue;
+        }
+        i += 1;
+    }
+    parts.push(trim_whitespace(&tokens[start..]));
+
+    match (parts.as_slice(), operators.as_slice()) {
+        // width >= 600px
+        ([[CssToken::Ident(name)], value], [comparison]) => Some(MediaCondition::Feature(
+            MediaFeature::new(name, Some((*comparison, parse_value(value)?))),
+        )),
+        // 600px <= width
+        ([value, [CssToken::Ident(name)]], [comparison]) => Some(MediaCondition::Feature(
+            MediaFeature::new(name, Some((comparison.flip(), parse_value(value)?))),
+        )),
+        // 400px <= width <= 800px
+        ([low, [CssToken::Ident(name)], high], [first, second]) => {
+            let ascending = matches!(first, Comparison::LessThan | Comparison::LessThanOrEqual)
+                && matches!(second, Comparison::LessThan | Comparison::LessThanOrEqual);
+            let descending = matches!(
+                first,
+                Comparison::GreaterThan | Comparison::GreaterThanOrEqual
+            ) && matches!(
+                second,
+                Comparison::GreaterThan | Comparison::GreaterThanOrEqual
+            );
+            if !ascending && !descending {
+                return None;
+            }
+            Some(MediaCondition::And(Vec::from([
+                MediaCondition::Feature(MediaFeature::new(
+                    name,
+                    Some((first.flip(), parse_value(low)?)),
+                )),
+                MediaCondition::Feature(MediaFeature::new(
+                    name,
+                    Some((*second, parse_value(high)?)),
+                )),
+            ])))
+        }
+        _ => None,
+    }
+}
+
+/// 比較演算子（<, <=, >, >=, =）をパースし、消費したトークン数とともに返す
+fn parse_comparison(tokens: &[CssToken]) -> Option<(Comparison, usize)> {
+    let has_equal = tokens.get(1) == Some(&CssToken::Delim('='));
+
+    match tokens.first()? {
+        CssToken::Delim('<') if has_equal => Some((Comparison::LessThanOrEqual, 2)),
+        CssToken::Delim('<') => Some((Comparison::LessThan, 1)),
+        CssToken::Delim('>') if has_equal => Some((Comparison::GreaterThanOrEqual, 2)),
+        CssToken::Delim('>') => Some((Comparison::GreaterThan, 1)),
+        CssToken::Delim('=') => Some((Comparison::Equal, 1)),
+        _ => None,
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#typedef-mf-value
+fn parse_value(tokens: &[CssToken]) -> Option<MediaFeatureValue> {
+    match tokens {
+        [CssToken::Number(n)] => Some(MediaFeatureValue::Number(*n)),
+        [CssToken::Ident(ident)] => Some(MediaFeatureValue::Ident(ident.to_ascii_lowercase())),
+        [CssToken::Dimension { value, unit }] => {
+            // メディアクエリ内の相対単位は、初期値のフォントサイズ（16px）を基準にする
+            let px = match unit.to_ascii_lowercase().as_str() {
+                "px" => 1.0,
+                "em" | "rem" => 16.0,
+                "in" => 96.0,
+                "cm" => 96.0 / 2.54,
+                "mm" => 96.0 / 25.4,
+                "pt" => 96.0 / 72.0,
+                "pc" => 16.0,
+                _ => return None,
+            };
+            Some(MediaFeatureValue::Length(value * px))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::token::CssTokenizer;
+
+    fn parse(query: &str) -> MediaQueryList {
+        let tokens: Vec<CssToken> = CssTokenizer::new(query.to_string()).collect();
+        MediaQueryList::parse(&tokens)
+    }
+
+    #[test]
+    fn test_empty() {
+        /* 空のメディアクエリは、すべての環境にマッチすることを確認する */
+        let env = MediaEnvironment::new(800.0, 600.0);
+        assert!(parse("").matches(&env));
+    }
+
+    #[test]
+    fn test_media_type() {
+        /* メディアタイプと修飾子が正しく評価されることを確認する */
+        let env = MediaEnvironment::new(800.0, 600.0);
+        assert!(parse("screen").matches(&env));
+        assert!(parse("all").matches(&env));
+        assert!(parse("only screen").matches(&env));
+        assert!(!parse("print").matches(&env));
+        assert!(parse("not print").matches(&env));
+        assert!(!parse("not screen").matches(&env));
+        assert!(!parse("tv").matches(&env));
+        assert!(parse("print, screen").matches(&env));
+    }
+
+    #[test]
+    fn test_width_and_height() {
+        /* 幅と高さのメディア特性が、ビューポートの大きさと比較されることを確認する */
+        let env = MediaEnvironment::new(800.0, 600.0);
+        assert!(parse("(min-width: 600px)").matches(&env));
+        assert!(!parse("(max-width: 600px)").matches(&env));
+        assert!(parse("screen and (min-width: 50em) and (max-height: 600px)").matches(&env));
+        assert!(!parse("screen and (min-width: 900px)").matches(&env));
+        assert!(parse("(width: 800px)").matches(&env));
+        assert!(parse("(width)").matches(&env));
+    }
+
+    #[test]
+    fn test_range_syntax() {
+        /* Media Queries Level 4の範囲構文が正しく評価されることを確認する */
+        let env = MediaEnvironment::new(800.0, 600.0);
+        assert!(parse("(width >= 800px)").matches(&env));
+        assert!(!parse("(width > 800px)").matches(&env));
+        assert!(parse("(700px < width)").matches(&env));
+        assert!(parse("(400px <= width <= 900px)").matches(&env));
+        assert!(!parse("(400px <= width < 800px)").matches(&env));
+        assert!(parse("(1000px > width > 300px)").matches(&env));
+    }
+
+    #[test]
+    fn test_orientation() {
+        /* orientationがビューポートの縦横比から判定されることを確認する */
+        let landscape = MediaEnvironment::new(800.0, 600.0);
+        let portrait = MediaEnvironment::new(400.0, 600.0);
+        assert!(parse("(orientation: landscape)").matches(&landscape));
+        assert!(!parse("(orientation: portrait)").matches(&landscape));
+        assert!(parse("(orientation: portrait)").matches(&portrait));
+    }
+
+    #[test]
+    fn test_prefers_color_scheme() {
+        /* prefers-color-schemeがユーザの設定に従って評価されることを確認する */
+        let mut env = MediaEnvironment::new(800.0, 600.0);
+        assert!(parse("(prefers-color-scheme: light)").matches(&env));
+        assert!(!parse("(prefers-color-scheme: dark)").matches(&env));
+
+        env.set_color_scheme(ColorScheme::Dark);
+        assert!(parse("(prefers-color-scheme: dark)").matches(&env));
+    }
+
+    #[test]
+    fn test_logical_operators() {
+        /* not、and、orの組み合わせが正しく評価されることを確認する */
+        let env = MediaEnvironment::new(800.0, 600.0);
+        assert!(parse("not (max-width: 600px)").matches(&env));
+        assert!(parse("(max-width: 600px) or (orientation: landscape)").matches(&env));
+        assert!(!parse("(max-width: 600px) and (orientation: landscape)").matches(&env));
+        assert!(parse("((min-width: 600px) and (not (max-height: 100px)))").matches(&env));
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        /* 不正なメディアクエリや未知の特性は、マッチしないことを確認する */
+        let env = MediaEnvironment::new(800.0, 600.0);
+        assert!(!parse("screen and").matches(&env));
+        assert!(!parse("(min-width: 600px) and (max-width: 900px) or (color)").matches(&env));
+        assert!(!parse("(unknown-feature: 1)").matches(&env));
+        assert!(!parse("(min-width: 10foo)").matches(&env));
+        assert!(!parse("screen (min-width: 600px)").matches(&env));
+        // 不正なクエリがあっても、リスト中の他のクエリは評価される
+        assert!(parse("screen and, (min-width: 600px)").matches(&env));
+    }
+
+    #[test]
+    fn test_css_text() {
+        /* メディアクエリを文字列にして読み直すと、同じメディアクエリになることを確認する */
+        for (query, expected) in [
+            ("screen", "screen"),
+            ("NOT print", "not print"),
+            (
+                "only screen and (min-width: 600px)",
+                "screen and (min-width: 600px)",
+            ),
+            (
+                "(MAX-width: 800px) and (width >= 400px)",
+                "(max-width: 800px) and (width >= 400px)",
+            ),
+            (
+                "(orientation: landscape), print",
+                "(orientation: landscape), print",
+            ),
+            (
+                "not ((width) or (500px < height))",
+                "not ((width) or (height > 500px))",
+            ),
+            (
+                "(hover: hover) and (unknown(x))",
+                "(hover: hover) and (unknown(x))",
+            ),
+            ("screen and", "not all"),
+        ] {
+            let list = parse(query);
+            assert_eq!(list.css_text(), expected);
+            assert_eq!(parse(&list.css_text()), list);
+        }
+    }
+}
@@ -1,2 +1,13 @@
+pub mod calc;
+pub mod cascade;
+pub mod color;
 pub mod cssom;
+pub mod invalidation;
+pub mod media;
+pub mod property;
+pub mod serialize;
+pub mod shorthand;
+pub mod supports;
 pub mod token;
+pub mod value;
+pub mod variable;
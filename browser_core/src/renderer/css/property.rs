@@ -0,0 +1,1234 @@
+use crate::renderer::css::calc::CalcNode;
+use crate::renderer::css::calc::CalcType;
+use crate::renderer::css::color::Color;
+use crate::renderer::css::cssom::ComponentValue;
+use crate::renderer::css::shorthand::expand_declaration;
+use crate::renderer::css::shorthand::is_auto_track_list;
+use crate::renderer::css::shorthand::is_border_style;
+use crate::renderer::css::shorthand::is_border_width;
+use crate::renderer::css::shorthand::is_color;
+use crate::renderer::css::shorthand::is_flex_basis;
+use crate::renderer::css::shorthand::is_flex_direction;
+use crate::renderer::css::shorthand::is_flex_factor;
+use crate::renderer::css::shorthand::is_flex_wrap;
+use crate::renderer::css::shorthand::is_font_family;
+use crate::renderer::css::shorthand::is_font_size;
+use crate::renderer::css::shorthand::is_font_stretch;
+use crate::renderer::css::shorthand::is_font_style;
+use crate::renderer::css::shorthand::is_font_variant;
+use crate::renderer::css::shorthand::is_font_weight;
+use crate::renderer::css::shorthand::is_gap;
+use crate::renderer::css::shorthand::is_grid_auto_flow;
+use crate::renderer::css::shorthand::is_grid_line;
+use crate::renderer::css::shorthand::is_line_height;
+use crate::renderer::css::shorthand::is_list_style_position;
+use crate::renderer::css::shorthand::is_list_style_type;
+use crate::renderer::css::shorthand::is_margin;
+use crate::renderer::css::shorthand::is_overflow;
+use crate::renderer::css::shorthand::is_padding;
+use crate::renderer::css::shorthand::is_track_list;
+use crate::renderer::css::shorthand::longhands_of;
+use crate::renderer::css::token::CssTokenizer;
+use crate::renderer::css::value::is_css_wide_keyword;
+use crate::renderer::css::value::ComputedLength;
+use crate::renderer::css::value::CssValue;
+use crate::renderer::css::value::Length;
+use crate::renderer::css::value::ResolutionContext;
+use crate::renderer::layout::computed_style::BorderCollapse;
+use crate::renderer::layout::computed_style::BorderStyle;
+use crate::renderer::layout::computed_style::CaptionSide;
+use crate::renderer::layout::computed_style::Clear;
+use crate::renderer::layout::computed_style::ComputedStyle;
+use crate::renderer::layout::computed_style::ContentAlignment;
+use crate::renderer::layout::computed_style::DisplayType;
+use crate::renderer::layout::computed_style::Float;
+use crate::renderer::layout::computed_style::FontSize;
+use crate::renderer::layout::computed_style::ItemAlignment;
+use crate::renderer::layout::computed_style::Position;
+use crate::renderer::layout::computed_style::TableLayout;
+use crate::renderer::layout::computed_style::TextAlign;
+use crate::renderer::layout::computed_style::TextDecoration;
+use crate::renderer::layout::computed_style::VerticalAlign;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+/// https://www.w3.org/TR/css-cascade-4/#computed
+/// 計算値を求めるときに参照できる情報
+pub struct ComputeContext<'a> {
+    /// 計算中の要素のスタイル. 表でこのプロパティより前にあるプロパティは計算済み
+    pub style: &'a ComputedStyle,
+    /// 親要素のスタイル. ルート要素の場合はNone
+    pub parent: Option<&'a ComputedStyle>,
+    /// https://www.w3.org/TR/css-values-4/#viewport-relative-lengths
+    /// vwやvhの基準になる、メディア環境のビューポートの幅と高さ
+    pub viewport: (f64, f64),
+}
+
+impl ComputeContext<'_> {
+    /// 要素自身のフォントサイズを基準に、相対的な長さを解決するための情報を返す
+    pub fn resolution(&self) -> ResolutionContext {
+        ResolutionContext::new(
+            self.style.font_size(),
+            self.style.root_font_size(),
+            self.viewport.0,
+            self.viewport.1,
+        )
+    }
+}
+
+/// https://www.w3.org/TR/css-cascade-4/#property-declarations
+/// プロパティの定義. 新しいプロパティは`PROPERTIES`に1つ追加するだけでカスケードと継承の対象になる
+#[derive(Debug)]
+pub struct Property {
+    name: &'static str,
+    /// https://www.w3.org/TR/css-cascade-4/#initial-value
+    /// CSSの値として書いた初期値
+    initial: &'static str,
+    /// https://www.w3.org/TR/css-cascade-4/#inherited-property
+    inherited: bool,
+    /// 値がプロパティの文法に一致するかどうか
+    parse: fn(&CssValue) -> bool,
+    /// 指定値から計算値を求める. 計算できない場合（計算値の時点で無効な場合）はNoneを返す
+    compute: fn(&CssValue, &ComputeContext) -> Option<CssValue>,
+}
+
+impl Property {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn inherited(&self) -> bool {
+        self.inherited
+    }
+
+    pub fn initial_value(&self) -> CssValue {
+        let tokens: Vec<_> = CssTokenizer::new(self.initial.to_string()).collect();
+        CssValue::parse(&tokens).expect("initial value should be valid")
+    }
+
+    pub fn parse(&self, value: &CssValue) -> bool {
+        (self.parse)(value)
+    }
+
+    pub fn compute(&self, value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+        (self.compute)(value, context)
+    }
+}
+
+/// サポートしているロングハンドのプロパティ
+/// ほかのプロパティの計算値に依存するプロパティがあるので、依存される側を先に並べる
+/// （font-sizeは長さの計算に、colorはcurrentcolorに、border-styleはborder-widthに使われる）
+static PROPERTIES: &[Property] = &[
+    Property {
+        name: "font-size",
+        initial: "medium",
+        inherited: true,
+        parse: is_font_size,
+        compute: compute_font_size,
+    },
+    Property {
+        name: "font-style",
+        initial: "normal",
+        inherited: true,
+        parse: is_font_style,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "font-variant",
+        initial: "normal",
+        inherited: true,
+        parse: is_font_variant,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "font-weight",
+        initial: "normal",
+        inherited: true,
+        parse: is_font_weight,
+        compute: compute_font_weight,
+    },
+    Property {
+        name: "font-stretch",
+        initial: "normal",
+        inherited: true,
+        parse: is_font_stretch,
+        compute: compute_font_stretch,
+    },
+    // 初期値はユーザーエージェントに依存する
+    Property {
+        name: "font-family",
+        initial: "serif",
+        inherited: true,
+        parse: is_font_family,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "line-height",
+        initial: "normal",
+        inherited: true,
+        parse: is_line_height,
+        compute: compute_line_height,
+    },
+    Property {
+        name: "color",
+        initial: "black",
+        inherited: true,
+        parse: is_color,
+        compute: compute_color_property,
+    },
+    Property {
+        name: "display",
+        initial: "inline",
+        inherited: false,
+        parse: is_display,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "background-color",
+        initial: "transparent",
+        inherited: false,
+        parse: is_color,
+        compute: compute_color,
+    },
+    // https://www.w3.org/TR/css-text-decor-3/#line-decoration
+    // 継承しない. 子孫のテキストへは、レイアウトツリーの装飾を指定したボックスから伝える
+    Property {
+        name: "text-decoration",
+        initial: "none",
+        inherited: false,
+        parse: is_text_decoration,
+        compute: compute_as_specified,
+    },
+    // https://www.w3.org/TR/css-text-3/#text-align-property
+    Property {
+        name: "text-align",
+        initial: "start",
+        inherited: true,
+        parse: is_text_align,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "height",
+        initial: "auto",
+        inherited: false,
+        parse: is_size,
+        compute: compute_non_negative_length,
+    },
+    Property {
+        name: "width",
+        initial: "auto",
+        inherited: false,
+        parse: is_size,
+        compute: compute_non_negative_length,
+    },
+    Property {
+        name: "margin-top",
+        initial: "0",
+        inherited: false,
+        parse: is_margin,
+        compute: compute_length,
+    },
+    Property {
+        name: "margin-right",
+        initial: "0",
+        inherited: false,
+        parse: is_margin,
+        compute: compute_length,
+    },
+    Property {
+        name: "margin-bottom",
+        initial: "0",
+        inherited: false,
+        parse: is_margin,
+        compute: compute_length,
+    },
+    Property {
+        name: "margin-left",
+        initial: "0",
+        inherited: false,
+        parse: is_margin,
+        compute: compute_length,
+    },
+    Property {
+        name: "padding-top",
+        initial: "0",
+        inherited: false,
+        parse: is_padding,
+        compute: compute_non_negative_length,
+    },
+    Property {
+        name: "padding-right",
+        initial: "0",
+        inherited: false,
+        parse: is_padding,
+        compute: compute_non_negative_length,
+    },
+    Property {
+        name: "padding-bottom",
+        initial: "0",
+        inherited: false,
+        parse: is_padding,
+        compute: compute_non_negative_length,
+    },
+    Property {
+        name: "padding-left",
+        initial: "0",
+        inherited: false,
+        parse: is_padding,
+        compute: compute_non_negative_length,
+    },
+    Property {
+        name: "border-top-style",
+        initial: "none",
+        inherited: false,
+        parse: is_border_style,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "border-right-style",
+        initial: "none",
+        inherited: false,
+        parse: is_border_style,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "border-bottom-style",
+        initial: "none",
+        inherited: false,
+        parse: is_border_style,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "border-left-style",
+        initial: "none",
+        inherited: false,
+        parse: is_border_style,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "border-top-width",
+        initial: "medium",
+        inherited: false,
+        parse: is_border_width,
+        compute: compute_border_top_width,
+    },
+    Property {
+        name: "border-right-width",
+        initial: "medium",
+        inherited: false,
+        parse: is_border_width,
+        compute: compute_border_right_width,
+    },
+    Property {
+        name: "border-bottom-width",
+        initial: "medium",
+        inherited: false,
+        parse: is_border_width,
+        compute: compute_border_bottom_width,
+    },
+    Property {
+        name: "border-left-width",
+        initial: "medium",
+        inherited: false,
+        parse: is_border_width,
+        compute: compute_border_left_width,
+    },
+    Property {
+        name: "border-top-color",
+        initial: "currentcolor",
+        inherited: false,
+        parse: is_color,
+        compute: compute_color,
+    },
+    Property {
+        name: "border-right-color",
+        initial: "currentcolor",
+        inherited: false,
+        parse: is_color,
+        compute: compute_color,
+    },
+    Property {
+        name: "border-bottom-color",
+        initial: "currentcolor",
+        inherited: false,
+        parse: is_color,
+        compute: compute_color,
+    },
+    Property {
+        name: "border-left-color",
+        initial: "currentcolor",
+        inherited: false,
+        parse: is_color,
+        compute: compute_color,
+    },
+    // https://www.w3.org/TR/css-content-3/#content-property
+    // 疑似要素にのみ適用する. 要素の内容を置き換える<image>などはサポートしていない
+    Property {
+        name: "content",
+        initial: "normal",
+        inherited: false,
+        parse: is_content,
+        compute: compute_as_specified,
+    },
+    // https://www.w3.org/TR/css-lists-3/#counter-properties
+    Property {
+        name: "counter-reset",
+        initial: "none",
+        inherited: false,
+        parse: is_counter_list,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "counter-increment",
+        initial: "none",
+        inherited: false,
+        parse: is_counter_list,
+        compute: compute_as_specified,
+    },
+    // https://www.w3.org/TR/css-lists-3/#property-index
+    // list-style-imageはサポートしていない
+    Property {
+        name: "list-style-type",
+        initial: "disc",
+        inherited: true,
+        parse: is_list_style_type,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "list-style-position",
+        initial: "outside",
+        inherited: true,
+        parse: is_list_style_position,
+        compute: compute_as_specified,
+    },
+    // https://www.w3.org/TR/css-flexbox-1/#property-index
+    Property {
+        name: "flex-direction",
+        initial: "row",
+        inherited: false,
+        parse: is_flex_direction,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "flex-wrap",
+        initial: "nowrap",
+        inherited: false,
+        parse: is_flex_wrap,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "flex-grow",
+        initial: "0",
+        inherited: false,
+        parse: is_flex_factor,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "flex-shrink",
+        initial: "1",
+        inherited: false,
+        parse: is_flex_factor,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "flex-basis",
+        initial: "auto",
+        inherited: false,
+        parse: is_flex_basis,
+        compute: compute_flex_basis,
+    },
+    Property {
+        name: "order",
+        initial: "0",
+        inherited: false,
+        parse: is_integer,
+        compute: compute_as_specified,
+    },
+    // https://www.w3.org/TR/css-align-3/#property-index
+    Property {
+        name: "justify-content",
+        initial: "normal",
+        inherited: false,
+        parse: is_content_alignment,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "align-content",
+        initial: "normal",
+        inherited: false,
+        parse: is_content_alignment,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "align-items",
+        initial: "normal",
+        inherited: false,
+        parse: is_item_alignment,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "align-self",
+        initial: "auto",
+        inherited: false,
+        parse: is_self_alignment,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "row-gap",
+        initial: "normal",
+        inherited: false,
+        parse: is_gap,
+        compute: compute_gap,
+    },
+    Property {
+        name: "column-gap",
+        initial: "normal",
+        inherited: false,
+        parse: is_gap,
+        compute: compute_gap,
+    },
+    Property {
+        name: "justify-items",
+        initial: "legacy",
+        inherited: false,
+        parse: is_justify_items,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "justify-self",
+        initial: "auto",
+        inherited: false,
+        parse: is_self_alignment,
+        compute: compute_as_specified,
+    },
+    // https://www.w3.org/TR/css-grid-2/#property-index
+    Property {
+        name: "grid-template-columns",
+        initial: "none",
+        inherited: false,
+        parse: is_track_list,
+        compute: compute_track_list,
+    },
+    Property {
+        name: "grid-template-rows",
+        initial: "none",
+        inherited: false,
+        parse: is_track_list,
+        compute: compute_track_list,
+    },
+    Property {
+        name: "grid-auto-columns",
+        initial: "auto",
+        inherited: false,
+        parse: is_auto_track_list,
+        compute: compute_track_list,
+    },
+    Property {
+        name: "grid-auto-rows",
+        initial: "auto",
+        inherited: false,
+        parse: is_auto_track_list,
+        compute: compute_track_list,
+    },
+    Property {
+        name: "grid-auto-flow",
+        initial: "row",
+        inherited: false,
+        parse: is_grid_auto_flow,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "grid-column-start",
+        initial: "auto",
+        inherited: false,
+        parse: is_grid_line,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "grid-column-end",
+        initial: "auto",
+        inherited: false,
+        parse: is_grid_line,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "grid-row-start",
+        initial: "auto",
+        inherited: false,
+        parse: is_grid_line,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "grid-row-end",
+        initial: "auto",
+        inherited: false,
+        parse: is_grid_line,
+        compute: compute_as_specified,
+    },
+    // https://www.w3.org/TR/css-position-3/#property-index
+    Property {
+        name: "position",
+        initial: "static",
+        inherited: false,
+        parse: is_position,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "top",
+        initial: "auto",
+        inherited: false,
+        parse: is_margin,
+        compute: compute_length,
+    },
+    Property {
+        name: "right",
+        initial: "auto",
+        inherited: false,
+        parse: is_margin,
+        compute: compute_length,
+    },
+    Property {
+        name: "bottom",
+        initial: "auto",
+        inherited: false,
+        parse: is_margin,
+        compute: compute_length,
+    },
+    Property {
+        name: "left",
+        initial: "auto",
+        inherited: false,
+        parse: is_margin,
+        compute: compute_length,
+    },
+    // https://www.w3.org/TR/CSS2/visuren.html#z-index
+    Property {
+        name: "z-index",
+        initial: "auto",
+        inherited: false,
+        parse: is_z_index,
+        compute: compute_as_specified,
+    },
+    // https://www.w3.org/TR/CSS2/visuren.html#floats
+    Property {
+        name: "float",
+        initial: "none",
+        inherited: false,
+        parse: is_float,
+        compute: compute_float,
+    },
+    Property {
+        name: "clear",
+        initial: "none",
+        inherited: false,
+        parse: is_clear,
+        compute: compute_as_specified,
+    },
+    // https://www.w3.org/TR/css-overflow-3/#property-index
+    Property {
+        name: "overflow-x",
+        initial: "visible",
+        inherited: false,
+        parse: is_overflow,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "overflow-y",
+        initial: "visible",
+        inherited: false,
+        parse: is_overflow,
+        compute: compute_as_specified,
+    },
+    // https://www.w3.org/TR/CSS2/tables.html#property-index
+    Property {
+        name: "border-collapse",
+        initial: "separate",
+        inherited: true,
+        parse: is_border_collapse,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "border-spacing",
+        initial: "0",
+        inherited: true,
+        parse: is_border_spacing,
+        compute: compute_border_spacing,
+    },
+    Property {
+        name: "table-layout",
+        initial: "auto",
+        inherited: false,
+        parse: is_table_layout,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "caption-side",
+        initial: "top",
+        inherited: true,
+        parse: is_caption_side,
+        compute: compute_as_specified,
+    },
+    Property {
+        name: "vertical-align",
+        initial: "baseline",
+        inherited: false,
+        parse: is_vertical_align,
+        compute: compute_as_specified,
+    },
+];
+
+/// 計算する順に並んだ、すべてのロングハンドのプロパティ
+pub fn properties() -> &'static [Property] {
+    PROPERTIES
+}
+
+/// プロパティ名（ASCIIの大文字・小文字を区別しない）から定義を探す
+pub fn lookup_property(name: &str) -> Option<&'static Property> {
+    PROPERTIES
+        .iter()
+        .find(|property| property.name.eq_ignore_ascii_case(name))
+}
+
+/// ロングハンドまたはショートハンドとしてサポートしているプロパティかどうか
+pub fn is_supported_property(name: &str) -> bool {
+    lookup_property(name).is_some() || longhands_of(&name.to_ascii_lowercase()).is_some()
+}
+
+/// 宣言がサポートしているプロパティで、値がその文法に一致するかどうか
+pub fn is_valid_declaration(property: &str, value: &[ComponentValue]) -> bool {
+    if !is_supported_property(property) {
+        return false;
+    }
+    let longhands = match expand_declaration(property, value) {
+        Ok(longhands) => longhands,
+        Err(_) => return false,
+    };
+
+    longhands.iter().all(|(name, value)| match value {
+        CssValue::Keyword(keyword) if is_css_wide_keyword(keyword) => true,
+        _ => lookup_property(name).map_or(false, |property| property.parse(value)),
+    })
+}
+
+fn is_display(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if DisplayType::from_str(keyword).is_ok())
+}
+
+fn is_text_decoration(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if TextDecoration::from_str(keyword).is_ok())
+}
+
+fn is_text_align(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if TextAlign::from_str(keyword).is_ok())
+}
+
+/// https://www.w3.org/TR/css-position-3/#position-property
+fn is_position(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if Position::from_str(keyword).is_ok())
+}
+
+/// https://www.w3.org/TR/CSS2/visuren.html#float-position
+fn is_float(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if Float::from_str(keyword).is_ok())
+}
+
+/// https://www.w3.org/TR/CSS2/visuren.html#flow-control
+fn is_clear(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if Clear::from_str(keyword).is_ok())
+}
+
+/// https://www.w3.org/TR/CSS2/tables.html#borders
+fn is_border_collapse(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if BorderCollapse::from_str(keyword).is_ok())
+}
+
+/// https://www.w3.org/TR/CSS2/tables.html#separated-borders
+/// 負でない長さを1つか2つ. 1つの場合は、横と縦の両方の間隔になる
+fn is_border_spacing(value: &CssValue) -> bool {
+    let is_length =
+        |value: &CssValue| is_border_width(value) && !matches!(value, CssValue::Keyword(_));
+    match value {
+        CssValue::List(list) => list.len() == 2 && list.iter().all(is_length),
+        value => is_length(value),
+    }
+}
+
+/// https://www.w3.org/TR/CSS2/tables.html#width-layout
+fn is_table_layout(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if TableLayout::from_str(keyword).is_ok())
+}
+
+/// https://www.w3.org/TR/CSS2/tables.html#caption-position
+fn is_caption_side(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if CaptionSide::from_str(keyword).is_ok())
+}
+
+/// https://www.w3.org/TR/CSS2/visudet.html#propdef-vertical-align
+/// キーワードだけをサポートする
+fn is_vertical_align(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if VerticalAlign::from_str(keyword).is_ok())
+}
+
+/// https://www.w3.org/TR/CSS2/visuren.html#propdef-z-index
+/// auto | 整数
+fn is_z_index(value: &CssValue) -> bool {
+    *value == CssValue::Keyword("auto".to_string()) || is_integer(value)
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#order-property
+fn is_integer(value: &CssValue) -> bool {
+    matches!(value, CssValue::Number(n) if *n == (*n as i64) as f64)
+}
+
+/// https://www.w3.org/TR/css-align-3/#propdef-justify-content
+fn is_content_alignment(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if ContentAlignment::from_str(keyword).is_ok())
+}
+
+/// https://www.w3.org/TR/css-align-3/#propdef-align-items
+fn is_item_alignment(value: &CssValue) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if ItemAlignment::from_str(keyword).is_ok())
+}
+
+/// https://www.w3.org/TR/css-align-3/#propdef-align-self
+/// auto | align-itemsと同じ値
+fn is_self_alignment(value: &CssValue) -> bool {
+    *value == CssValue::Keyword("auto".to_string()) || is_item_alignment(value)
+}
+
+/// https://www.w3.org/TR/css-align-3/#propdef-justify-items
+/// legacy | align-itemsと同じ値
+fn is_justify_items(value: &CssValue) -> bool {
+    *value == CssValue::Keyword("legacy".to_string()) || is_item_alignment(value)
+}
+
+/// https://www.w3.org/TR/css-sizing-3/#preferred-size-properties
+fn is_size(value: &CssValue) -> bool {
+    is_padding(value) || *value == CssValue::Keyword("auto".to_string())
+}
+
+/// https://www.w3.org/TR/css-content-3/#content-property
+/// normal | none | [ <string> | <counter> | attr() ]+
+fn is_content(value: &CssValue) -> bool {
+    let is_content_item = |value: &CssValue| {
+        matches!(
+            value,
+            CssValue::String(_) | CssValue::Attr(_) | CssValue::Counter { .. }
+        )
+    };
+
+    match value {
+        CssValue::Keyword(keyword) => keyword == "normal" || keyword == "none",
+        CssValue::List(list) => list.iter().all(is_content_item),
+        value => is_content_item(value),
+    }
+}
+
+/// https://www.w3.org/TR/css-lists-3/#propdef-counter-reset
+/// none | [ <counter-name> <integer>? ]+
+fn is_counter_list(value: &CssValue) -> bool {
+    let is_counter_name = |value: &CssValue| matches!(value, CssValue::Keyword(name) if name != "none" && !is_css_wide_keyword(name));
+
+    match value {
+        CssValue::Keyword(keyword) if keyword == "none" => true,
+        CssValue::List(list) => {
+            let mut expect_name = true;
+            for value in list {
+                match value {
+                    CssValue::Number(n) if !expect_name && *n == (*n as i64) as f64 => {
+                        expect_name = true;
+                    }
+                    value if is_counter_name(value) => expect_name = false,
+                    _ => return false,
+                }
+            }
+            true
+        }
+        value => is_counter_name(value),
+    }
+}
+
+/// https://www.w3.org/TR/css-cascade-4/#computed-as-specified
+fn compute_as_specified(value: &CssValue, _context: &ComputeContext) -> Option<CssValue> {
+    Some(value.clone())
+}
+
+/// https://www.w3.org/TR/CSS2/visuren.html#dis-pos-flo
+/// 絶対位置指定されたボックスは、フロートにならない
+/// https://www.w3.org/TR/css-flexbox-1/#flex-containers
+/// フレックスアイテムとグリッドアイテムも、フロートとして扱わない
+fn compute_float(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    let is_item = context.parent.map_or(false, |parent| {
+        parent.display().is_flex_container() || parent.display().is_grid_container()
+    });
+    if is_item || context.style.position().is_out_of_flow() {
+        return Some(CssValue::Keyword("none".to_string()));
+    }
+    Some(value.clone())
+}
+
+/// https://www.w3.org/TR/CSS2/tables.html#separated-borders
+/// 横と縦の間隔を、絶対的なピクセル値の2つの長さのリストにする
+fn compute_border_spacing(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    let lengths = match value {
+        CssValue::List(list) => list.clone(),
+        value => vec![value.clone(), value.clone()],
+    };
+    let lengths = lengths
+        .iter()
+        .map(|length| compute_non_negative_length(length, context))
+        .collect::<Option<Vec<CssValue>>>()?;
+    Some(CssValue::List(lengths))
+}
+
+/// 長さを絶対的なピクセル値にする. パーセンテージとautoはそのまま残す
+fn compute_length(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    match value.to_computed_length(&context.resolution())? {
+        ComputedLength::Auto => Some(CssValue::Keyword("auto".to_string())),
+        ComputedLength::Px(px) => Some(CssValue::Length(Length::px(px))),
+        ComputedLength::Percentage(percentage) => Some(CssValue::Percentage(percentage)),
+        ComputedLength::Calc(node) => Some(CssValue::Calc(node)),
+    }
+}
+
+/// https://www.w3.org/TR/css-values-4/#calc-range
+/// 負の値を取れないプロパティの長さ. 計算式の結果が負になる場合は0に丸める
+fn compute_non_negative_length(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    match compute_length(value, context)? {
+        CssValue::Length(length) if length.value() < 0.0 => Some(CssValue::Length(Length::px(0.0))),
+        CssValue::Calc(node) => Some(CssValue::Calc(CalcNode::Max(vec![
+            node,
+            CalcNode::Length(Length::px(0.0)),
+        ]))),
+        value => Some(value),
+    }
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-basis-property
+/// contentはそのまま残し、それ以外はwidthと同じく計算する
+fn compute_flex_basis(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    match value {
+        CssValue::Keyword(keyword) if keyword == "content" => Some(value.clone()),
+        _ => compute_non_negative_length(value, context),
+    }
+}
+
+/// https://www.w3.org/TR/css-align-3/#column-row-gap
+/// normalはそのまま残し、それ以外は負にならない長さとして計算する
+fn compute_gap(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    match value {
+        CssValue::Keyword(keyword) if keyword == "normal" => Some(value.clone()),
+        _ => compute_non_negative_length(value, context),
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#track-sizing
+/// トラックのリストの中の長さを絶対的なピクセル値にする. パーセンテージ、fr、キーワードはそのまま残す
+fn compute_track_list(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    match value {
+        CssValue::List(list) => Some(CssValue::List(
+            list.iter()
+                .map(|value| compute_track_list(value, context))
+                .collect::<Option<Vec<CssValue>>>()?,
+        )),
+        CssValue::MinMax(min, max) => Some(CssValue::MinMax(
+            Box::new(compute_track_list(min, context)?),
+            Box::new(compute_track_list(max, context)?),
+        )),
+        CssValue::Repeat(count, tracks) => Some(CssValue::Repeat(
+            count.clone(),
+            Box::new(compute_track_list(tracks, context)?),
+        )),
+        CssValue::Length(_) | CssValue::Number(_) | CssValue::Calc(_) => {
+            compute_non_negative_length(value, context)
+        }
+        value => Some(value.clone()),
+    }
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-size-prop
+/// 計算値は絶対的な長さ. em、パーセンテージ、相対的なキーワードは親要素のフォントサイズを基準にする
+fn compute_font_size(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    let parent_font_size = context
+        .parent
+        .map_or(FontSize::Medium.to_px(), |parent| parent.font_size());
+
+    // emは親要素のフォントサイズを基準にする
+    let resolution = ResolutionContext::new(
+        parent_font_size,
+        context.style.root_font_size(),
+        context.viewport.0,
+        context.viewport.1,
+    );
+
+    let px = match value {
+        CssValue::Keyword(keyword) => match keyword.as_str() {
+            // https://www.w3.org/TR/css-fonts-4/#relative-size-value
+            "larger" => parent_font_size * 1.2,
+            "smaller" => parent_font_size / 1.2,
+            _ => FontSize::from_str(keyword).ok()?.to_px(),
+        },
+        CssValue::Percentage(percentage) => parent_font_size * percentage / 100.0,
+        CssValue::Length(length) => length.to_px(&resolution),
+        CssValue::Number(number) if *number == 0.0 => 0.0,
+        CssValue::Calc(node) => node.evaluate(&resolution, Some(parent_font_size))?.max(0.0),
+        _ => return None,
+    };
+
+    Some(CssValue::Length(Length::px(px)))
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-weight-prop
+/// 計算値は数値. bolderとlighterは親要素の太さから表に従って決める
+fn compute_font_weight(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    let parent_weight = context.parent.map_or(400.0, |parent| parent.font_weight());
+
+    let weight = match value {
+        CssValue::Keyword(keyword) => match keyword.as_str() {
+            "normal" => 400.0,
+            "bold" => 700.0,
+            // https://www.w3.org/TR/css-fonts-4/#relative-weights
+            "bolder" if parent_weight < 350.0 => 400.0,
+            "bolder" if parent_weight < 550.0 => 700.0,
+            "bolder" if parent_weight < 900.0 => 900.0,
+            "bolder" => parent_weight,
+            "lighter" if parent_weight < 100.0 => parent_weight,
+            "lighter" if parent_weight < 550.0 => 100.0,
+            "lighter" if parent_weight < 750.0 => 400.0,
+            "lighter" => 700.0,
+            _ => return None,
+        },
+        CssValue::Number(weight) => *weight,
+        CssValue::Calc(node) => node
+            .evaluate(&context.resolution(), None)?
+            .clamp(1.0, 1000.0),
+        _ => return None,
+    };
+
+    Some(CssValue::Number(weight))
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-stretch-prop
+/// 計算値はパーセンテージ. キーワードは表に従ってパーセンテージにする
+fn compute_font_stretch(value: &CssValue, _context: &ComputeContext) -> Option<CssValue> {
+    let percentage = match value {
+        CssValue::Keyword(keyword) => match keyword.as_str() {
+            "ultra-condensed" => 50.0,
+            "extra-condensed" => 62.5,
+            "condensed" => 75.0,
+            "semi-condensed" => 87.5,
+            "normal" => 100.0,
+            "semi-expanded" => 112.5,
+            "expanded" => 125.0,
+            "extra-expanded" => 150.0,
+            "ultra-expanded" => 200.0,
+            _ => return None,
+        },
+        CssValue::Percentage(percentage) => *percentage,
+        _ => return None,
+    };
+
+    Some(CssValue::Percentage(percentage))
+}
+
+/// https://www.w3.org/TR/css-inline-3/#line-height-property
+/// 長さとパーセンテージは要素のフォントサイズを基準にピクセル値にする
+/// 数値は継承した先のフォントサイズに掛けるので、数値のまま残す
+fn compute_line_height(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    let font_size = context.style.font_size();
+
+    let computed = match value {
+        CssValue::Keyword(keyword) if keyword == "normal" => value.clone(),
+        CssValue::Number(number) => CssValue::Number(*number),
+        CssValue::Length(length) => {
+            CssValue::Length(Length::px(length.to_px(&context.resolution())))
+        }
+        CssValue::Percentage(percentage) => {
+            CssValue::Length(Length::px(font_size * percentage / 100.0))
+        }
+        CssValue::Calc(node) if value.calc_type() == Some(CalcType::Number) => {
+            CssValue::Number(node.evaluate(&context.resolution(), None)?.max(0.0))
+        }
+        CssValue::Calc(node) => CssValue::Length(Length::px(
+            node.evaluate(&context.resolution(), Some(font_size))?
+                .max(0.0),
+        )),
+        _ => return None,
+    };
+
+    Some(computed)
+}
+
+/// https://www.w3.org/TR/css-color-4/#resolving-color-values
+/// currentcolorは要素のcolorプロパティの計算値になる
+fn compute_color(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    let color = Color::from_value(value, &context.style.color()).ok()?;
+    Some(CssValue::Color(color))
+}
+
+/// https://www.w3.org/TR/css-color-4/#resolving-other-colors
+/// colorプロパティ自身のcurrentcolorは、親要素のcolorの値（inherit）になる
+fn compute_color_property(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    let current_color = context
+        .parent
+        .map_or(Color::black(), |parent| parent.color());
+    let color = Color::from_value(value, &current_color).ok()?;
+    Some(CssValue::Color(color))
+}
+
+/// https://www.w3.org/TR/css-backgrounds-3/#border-width
+/// キーワードをピクセル値にする. ボーダーのスタイルがnoneかhiddenの場合は0になる
+fn compute_border_width(
+    value: &CssValue,
+    context: &ComputeContext,
+    style: BorderStyle,
+) -> Option<CssValue> {
+    let px = match value {
+        _ if !style.is_visible() => 0.0,
+        CssValue::Keyword(keyword) => match keyword.as_str() {
+            "thin" => 1.0,
+            "medium" => 3.0,
+            "thick" => 5.0,
+            _ => return None,
+        },
+        CssValue::Length(length) => length.to_px(&context.resolution()),
+        CssValue::Number(number) if *number == 0.0 => 0.0,
+        CssValue::Calc(node) => node.evaluate(&context.resolution(), None)?.max(0.0),
+        _ => return None,
+    };
+
+    Some(CssValue::Length(Length::px(px)))
+}
+
+fn compute_border_top_width(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    compute_border_width(value, context, context.style.border_top_style())
+}
+
+fn compute_border_right_width(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    compute_border_width(value, context, context.style.border_right_style())
+}
+
+fn compute_border_bottom_width(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    compute_border_width(value, context, context.style.border_bottom_style())
+}
+
+fn compute_border_left_width(value: &CssValue, context: &ComputeContext) -> Option<CssValue> {
+    compute_border_width(value, context, context.style.border_left_style())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initial_values() {
+        /* すべてのプロパティの初期値が、そのプロパティの値として正しいことを確認する */
+        for property in properties() {
+            assert!(
+                property.parse(&property.initial_value()),
+                "invalid initial value for {}",
+                property.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        /* プロパティ名で定義を探せることを確認する */
+        assert!(lookup_property("Color").expect("should exist").inherited());
+        assert!(!lookup_property("width").expect("should exist").inherited());
+        assert!(lookup_property("margin").is_none());
+        assert!(is_supported_property("margin"));
+        assert!(!is_supported_property("grid-area"));
+    }
+
+    #[test]
+    fn test_valid_declaration() {
+        /* 値がプロパティの文法に一致するかどうかを確認する */
+        let valid = |property: &str, value: &str| {
+            let tokens: Vec<ComponentValue> = CssTokenizer::new(value.to_string()).collect();
+            is_valid_declaration(property, &tokens)
+        };
+        assert!(valid("display", "block"));
+        assert!(valid("border", "1px solid red"));
+        assert!(valid("color", "inherit"));
+        assert!(!valid("display", "flexible"));
+        assert!(valid("text-align", "center"));
+        assert!(!valid("text-align", "middle"));
+        assert!(!valid("width", "red"));
+        assert!(valid("width", "calc(100% - 2em)"));
+        assert!(valid("padding", "clamp(1px, 2%, 3px) 0"));
+        assert!(valid("border-width", "calc(1px + 1em)"));
+        assert!(!valid("border-width", "calc(1px + 1%)"));
+        assert!(!valid("width", "calc(2 * 3)"));
+        assert!(!valid("unknown", "1px"));
+        assert!(valid("font", "bold 12px/1.5 \"Helvetica Neue\", serif"));
+        assert!(valid("font-weight", "calc(100 * 3)"));
+        assert!(!valid("font-weight", "1001"));
+        assert!(valid("line-height", "calc(1em + 2px)"));
+        assert!(!valid("font-family", "10px"));
+        assert!(valid("content", "\"→ \" attr(title) counter(item)"));
+        assert!(valid("content", "none"));
+        assert!(!valid("content", "10px"));
+        assert!(valid("counter-reset", "section 2 item"));
+        assert!(valid("counter-increment", "item -1"));
+        assert!(!valid("counter-reset", "section 1.5"));
+        assert!(!valid("counter-increment", "1 item"));
+        assert!(valid("list-style-type", "japanese-informal"));
+        assert!(!valid("list-style-type", "lower-greek"));
+        assert!(valid("list-style-position", "inside"));
+        assert!(valid("display", "inline-flex"));
+        assert!(valid("flex", "1 1 0"));
+        assert!(valid("flex-basis", "content"));
+        assert!(!valid("flex-grow", "-1"));
+        assert!(valid("order", "-2"));
+        assert!(!valid("order", "1.5"));
+        assert!(valid("justify-content", "space-between"));
+        assert!(!valid("align-items", "auto"));
+        assert!(valid("align-self", "auto"));
+        assert!(valid("gap", "10px 5%"));
+        assert!(!valid("row-gap", "-1px"));
+        assert!(valid("justify-items", "legacy"));
+        assert!(valid("justify-self", "center"));
+        assert!(valid("display", "inline-grid"));
+        assert!(valid(
+            "grid-template-columns",
+            "100px repeat(2, 1fr minmax(10%, auto)) max-content"
+        ));
+        assert!(valid(
+            "grid-template-rows",
+            "repeat(auto-fill, minmax(50px, 1fr))"
+        ));
+        assert!(!valid("grid-template-rows", "repeat(auto-fill, 1fr)"));
+        assert!(!valid("grid-template-columns", "minmax(1fr, 10px)"));
+        assert!(!valid("grid-template-columns", "repeat(0, 10px)"));
+        assert!(!valid("grid-template-columns", "-1fr"));
+        assert!(valid("grid-auto-rows", "10px auto"));
+        assert!(valid("grid-auto-flow", "dense column"));
+        assert!(!valid("grid-auto-flow", "row column"));
+        assert!(valid("grid-row-end", "span 3"));
+        assert!(!valid("grid-row-end", "span 0"));
+        assert!(valid("position", "sticky"));
+        assert!(!valid("position", "center"));
+        assert!(valid("top", "-10%"));
+        assert!(valid("inset", "0 auto"));
+        assert!(valid("z-index", "-1"));
+        assert!(!valid("z-index", "1.5"));
+        assert!(valid("float", "left"));
+        assert!(!valid("float", "center"));
+        assert!(valid("clear", "both"));
+        assert!(valid("overflow", "hidden auto"));
+        assert!(valid("display", "flow-root"));
+        assert!(valid("display", "table-row-group"));
+        assert!(valid("border-collapse", "collapse"));
+        assert!(valid("border-spacing", "2px 0"));
+        assert!(!valid("border-spacing", "1px 2px 3px"));
+        assert!(!valid("border-spacing", "10%"));
+        assert!(valid("table-layout", "fixed"));
+        assert!(valid("caption-side", "bottom"));
+        assert!(valid("vertical-align", "middle"));
+    }
+}
@@ -0,0 +1,300 @@
+use crate::renderer::css::token::CssToken;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+
+/// https://www.w3.org/TR/cssom-1/#serialize-an-identifier
+pub fn serialize_identifier(ident: &str) -> String {
+    let chars: alloc::vec::Vec<char> = ident.chars().collect();
+    let mut result = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            '\0' => result.push('\u{FFFD}'),
+            '\u{1}'..='\u{1F}' | '\u{7F}' => result.push_str(&escape_code_point(c)),
+            // 先頭の数字と、"-"に続く数字はエスケープする
+            '0'..='9' if i == 0 || (i == 1 && chars[0] == '-') => {
+                result.push_str(&escape_code_point(c))
+            }
+            // "-"だけの識別子
+            '-' if i == 0 && chars.len() == 1 => result.push_str("\\-"),
+            c if c >= '\u{80}' || c == '-' || c == '_' || c.is_ascii_alphanumeric() => {
+                result.push(c)
+            }
+            c => {
+                result.push('\\');
+                result.push(c);
+            }
+        }
+    }
+
+    result
+}
+
+/// ハッシュトークンの名前. 識別子と違って、数字からはじまってもよい
+fn serialize_name(name: &str) -> String {
+    let mut result = String::new();
+    for c in name.chars() {
+        match c {
+            c if c >= '\u{80}' || c == '-' || c == '_' || c.is_ascii_alphanumeric() => {
+                result.push(c)
+            }
+            '\0' => result.push('\u{FFFD}'),
+            '\u{1}'..='\u{1F}' | '\u{7F}' => result.push_str(&escape_code_point(c)),
+            c => {
+                result.push('\\');
+                result.push(c);
+            }
+        }
+    }
+    result
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-string
+pub fn serialize_string(s: &str) -> String {
+    let mut result = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '\0' => result.push('\u{FFFD}'),
+            '\u{1}'..='\u{1F}' | '\u{7F}' => result.push_str(&escape_code_point(c)),
+            '"' | '\\' => {
+                result.push('\\');
+                result.push(c);
+            }
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-url
+pub fn serialize_url(url: &str) -> String {
+    format!("url({})", serialize_string(url))
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#consume-url-token
+/// 引用符のないurl()として書けるように、空白や括弧、引用符をエスケープする
+fn serialize_url_token(url: &str) -> String {
+    let mut result = String::from("url(");
+    for c in url.chars() {
+        match c {
+            '\0' => result.push('\u{FFFD}'),
+            '\u{1}'..='\u{20}' | '\u{7F}' => result.push_str(&escape_code_point(c)),
+            '"' | '\'' | '(' | ')' | '\\' => {
+                result.push('\\');
+                result.push(c);
+            }
+            c => result.push(c),
+        }
+    }
+    result.push(')');
+    result
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-css-value
+/// 数値を最短の形で表す（整数の場合は小数点以下を省く）
+pub fn serialize_number(value: f64) -> String {
+    if value == 0.0 {
+        // -0も0として出力する
+        return "0".to_string();
+    }
+    format!("{}", value)
+}
+
+/// https://www.w3.org/TR/cssom-1/#escape-a-character-as-code-point
+fn escape_code_point(c: char) -> String {
+    format!("\\{:x} ", c as u32)
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#serialization
+/// トークン列を文字列にする. 続けて書くと別のトークンとして読まれてしまう組み合わせの間にはコメントを挟む
+pub fn serialize_tokens(tokens: &[CssToken]) -> String {
+    let mut result = String::new();
+    let mut previous: Option<&CssToken> = None;
+
+    for token in tokens {
+        if let Some(previous) = previous {
+            if needs_separator(previous, token) {
+                result.push_str("/**/");
+            }
+        }
+        result.push_str(&serialize_token(token));
+        previous = Some(token);
+    }
+
+    result
+}
+
+fn serialize_token(token: &CssToken) -> String {
+    match token {
+        CssToken::HashToken(value) => {
+            format!(
+                "#{}",
+                serialize_name(value.strip_prefix('#').unwrap_or(value))
+            )
+        }
+        CssToken::Delim(c) if *c == '\\' => "\\\n".to_string(),
+        CssToken::Delim(c) => c.to_string(),
+        CssToken::Number(value) => serialize_number(*value),
+        CssToken::Percentage(value) => format!("{}%", serialize_number(*value)),
+        CssToken::Dimension { value, unit } => {
+            let unit = serialize_identifier(unit);
+            // "e3"のような単位は指数表記に読めるので、先頭の"e"をエスケープする
+            let mut chars = unit.chars();
+            let first = chars.next();
+            let rest = chars.as_str();
+            let is_exponent = rest.starts_with(|c: char| c.is_ascii_digit())
+                || (rest.starts_with(['+', '-'])
+                    && rest[1..].starts_with(|c: char| c.is_ascii_digit()));
+            match first {
+                Some(e @ ('e' | 'E')) if is_exponent => {
+                    format!(
+                        "{}{}{}",
+                        serialize_number(*value),
+                        escape_code_point(e),
+                        rest
+                    )
+                }
+                _ => format!("{}{}", serialize_number(*value), unit),
+            }
+        }
+        CssToken::Colon => ":".to_string(),
+        CssToken::SemiColon => ";".to_string(),
+        CssToken::Comma => ",".to_string(),
+        CssToken::OpenParenthesis => "(".to_string(),
+        CssToken::CloseParenthesis => ")".to_string(),
+        CssToken::OpenSquare => "[".to_string(),
+        CssToken::CloseSquare => "]".to_string(),
+        CssToken::OpenCurly => "{".to_string(),
+        CssToken::CloseCurly => "}".to_string(),
+        CssToken::Ident(ident) => serialize_identifier(ident),
+        CssToken::Function(name) => format!("{}(", serialize_identifier(name)),
+        CssToken::StringToken(s) => serialize_string(s),
+        // 不正なトークンは、読み直したときにも不正になる形で出力する
+        CssToken::BadString => "\"\n".to_string(),
+        CssToken::Url(url) => serialize_url_token(url),
+        CssToken::BadUrl => "url(()".to_string(),
+        CssToken::AtKeyword(name) => format!("@{}", serialize_identifier(name)),
+        CssToken::Whitespace => " ".to_string(),
+        CssToken::Cdo => "<!--".to_string(),
+        CssToken::Cdc => "-->".to_string(),
+        CssToken::UnicodeRange { start, end } if start == end => format!("U+{:X}", start),
+        CssToken::UnicodeRange { start, end } => format!("U+{:X}-{:X}", start, end),
+    }
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#serialization
+/// 2つのトークンの間にコメントが必要かどうか（仕様の表に従う）
+fn needs_separator(first: &CssToken, second: &CssToken) -> bool {
+    let is_ident = |t: &CssToken| matches!(t, CssToken::Ident(_));
+    let is_numeric = |t: &CssToken| {
+        matches!(
+            t,
+            CssToken::Number(_) | CssToken::Percentage(_) | CssToken::Dimension { .. }
+        )
+    };
+    let is_ident_like = |t: &CssToken| {
+        matches!(
+            t,
+            CssToken::Ident(_) | CssToken::Function(_) | CssToken::Url(_) | CssToken::BadUrl
+        )
+    };
+    let is_delim = |t: &CssToken, c: char| *t == CssToken::Delim(c);
+
+    match first {
+        CssToken::Ident(_) => {
+            is_ident_like(second)
+                || is_delim(second, '-')
+                || is_numeric(second)
+                || matches!(second, CssToken::UnicodeRange { .. } | CssToken::Cdc)
+                || *second == CssToken::OpenParenthesis
+        }
+        CssToken::AtKeyword(_) | CssToken::HashToken(_) | CssToken::Dimension { .. } => {
+            is_ident_like(second)
+                || is_delim(second, '-')
+                || is_numeric(second)
+                || matches!(second, CssToken::UnicodeRange { .. } | CssToken::Cdc)
+        }
+        CssToken::Delim('#') | CssToken::Delim('-') => {
+            is_ident_like(second)
+                || is_delim(second, '-')
+                || is_numeric(second)
+                || matches!(second, CssToken::UnicodeRange { .. })
+        }
+        CssToken::Number(_) => {
+            is_ident_like(second)
+                || is_numeric(second)
+                || is_delim(second, '%')
+                || matches!(second, CssToken::UnicodeRange { .. })
+        }
+        CssToken::Delim('@') => {
+            is_ident_like(second)
+                || is_delim(second, '-')
+                || matches!(second, CssToken::UnicodeRange { .. })
+        }
+        CssToken::UnicodeRange { .. } => {
+            is_ident(second) || is_numeric(second) || is_delim(second, '?')
+        }
+        CssToken::Delim('.') | CssToken::Delim('+') => is_numeric(second),
+        CssToken::Delim('$')
+        | CssToken::Delim('*')
+        | CssToken::Delim('^')
+        | CssToken::Delim('~')
+        | CssToken::Delim('|') => is_delim(second, '='),
+        CssToken::Delim('/') => is_delim(second, '*'),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::token::CssTokenizer;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_serialize_identifier_and_string() {
+        /* 識別子と文字列が、必要な箇所だけエスケープされることを確認する */
+        assert_eq!(serialize_identifier("sans-serif"), "sans-serif");
+        assert_eq!(serialize_identifier("1st"), "\\31 st");
+        assert_eq!(serialize_identifier("-2x"), "-\\32 x");
+        assert_eq!(serialize_identifier("-"), "\\-");
+        assert_eq!(serialize_identifier("a b"), "a\\ b");
+        assert_eq!(serialize_string("say \"hi\"\\"), "\"say \\\"hi\\\"\\\\\"");
+        assert_eq!(serialize_url("a.png"), "url(\"a.png\")");
+        assert_eq!(serialize_number(1.0), "1");
+        assert_eq!(serialize_number(-0.5), "-0.5");
+    }
+
+    #[test]
+    fn test_serialize_tokens() {
+        /* トークン列を文字列にして読み直すと、同じトークン列になることを確認する */
+        for style in [
+            "10px solid rgb(0 0 0 / 50%)",
+            "url(a.png) \"Helvetica Neue\", sans-serif url(a\\ \\(b\\))",
+            "calc(100% - 2em)",
+            "#fff !important",
+            "a:hover>b",
+            "1e3px 10\\65 m 1\\65 3 #0af #-x",
+        ] {
+            let tokens: Vec<CssToken> = CssTokenizer::new(style.to_string()).collect();
+            let serialized = serialize_tokens(&tokens);
+            let reparsed: Vec<CssToken> = CssTokenizer::new(serialized.clone()).collect();
+            assert_eq!(tokens, reparsed, "{} -> {}", style, serialized);
+        }
+
+        // 空白を取り除いたトークン列でも、元の区切りが失われない
+        let tokens = vec![
+            CssToken::Ident("a".to_string()),
+            CssToken::Ident("b".to_string()),
+            CssToken::Number(1.0),
+            CssToken::Percentage(2.0),
+        ];
+        let serialized = serialize_tokens(&tokens);
+        assert_eq!(serialized, "a/**/b/**/1/**/2%");
+        let reparsed: Vec<CssToken> = CssTokenizer::new(serialized).collect();
+        assert_eq!(tokens, reparsed);
+    }
+}
@@ -0,0 +1,1355 @@
+use crate::error::Error;
+use crate::renderer::css::calc::CalcType;
+use crate::renderer::css::color::Color;
+use crate::renderer::css::cssom::split_by_comma;
+use crate::renderer::css::cssom::trim_whitespace;
+use crate::renderer::css::cssom::ComponentValue;
+use crate::renderer::css::value::is_css_wide_keyword;
+use crate::renderer::css::value::CssValue;
+use crate::renderer::css::value::Length;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// 上、右、下、左の順の辺の名前
+const SIDES: [&str; 4] = ["top", "right", "bottom", "left"];
+
+/// https://www.w3.org/TR/css-backgrounds-3/#typedef-line-style
+pub const BORDER_STYLES: [&str; 10] = [
+    "none", "hidden", "dotted", "dashed", "solid", "double", "groove", "ridge", "inset", "outset",
+];
+
+/// https://www.w3.org/TR/css-fonts-4/#absolute-size-value
+/// https://www.w3.org/TR/css-fonts-4/#relative-size-value
+pub const FONT_SIZE_KEYWORDS: [&str; 10] = [
+    "xx-small",
+    "x-small",
+    "small",
+    "medium",
+    "large",
+    "x-large",
+    "xx-large",
+    "xxx-large",
+    "larger",
+    "smaller",
+];
+
+/// https://www.w3.org/TR/css-fonts-4/#font-stretch-prop
+pub const FONT_STRETCH_KEYWORDS: [&str; 8] = [
+    "ultra-condensed",
+    "extra-condensed",
+    "condensed",
+    "semi-condensed",
+    "semi-expanded",
+    "expanded",
+    "extra-expanded",
+    "ultra-expanded",
+];
+
+/// https://www.w3.org/TR/css-fonts-4/#generic-font-families
+const GENERIC_FONT_FAMILIES: [&str; 8] = [
+    "serif",
+    "sans-serif",
+    "cursive",
+    "fantasy",
+    "monospace",
+    "system-ui",
+    "emoji",
+    "math",
+];
+
+/// https://www.w3.org/TR/css-counter-styles-3/#predefined-counters
+/// サポートしているカウンタースタイルの名前
+pub const LIST_STYLE_TYPES: [&str; 11] = [
+    "none",
+    "disc",
+    "circle",
+    "square",
+    "decimal",
+    "lower-alpha",
+    "upper-alpha",
+    "lower-latin",
+    "upper-latin",
+    "cjk-decimal",
+    "japanese-informal",
+];
+
+/// 展開されたロングハンドのプロパティ名と値
+pub type Longhand = (String, CssValue);
+
+/// https://www.w3.org/TR/css-cascade-4/#shorthand
+/// 宣言をロングハンドのリストに展開する. ショートハンドでないプロパティはそのまま返す
+/// 値がプロパティの文法に一致しない場合はエラーを返す（その宣言は無視される）
+pub fn expand_declaration(
+    property: &str,
+    value: &[ComponentValue],
+) -> Result<Vec<Longhand>, Error> {
+    let property = property.to_ascii_lowercase();
+    let value = trim_whitespace(value);
+
+    let longhands = match longhands_of(&property) {
+        Some(longhands) => longhands,
+        // 空白区切りの識別子を1つのフォント名として読む
+        None if property == "font-family" => {
+            return Ok(vec![(property, parse_font_family(value)?)])
+        }
+        None => return Ok(vec![(property, CssValue::parse(value)?)]),
+    };
+
+    // CSS全体のキーワードは、すべてのロングハンドに同じ値を設定する
+    if let Ok(CssValue::Keyword(keyword)) = CssValue::parse(value) {
+        if is_css_wide_keyword(&keyword) {
+            return Ok(longhands
+                .into_iter()
+                .map(|longhand| (longhand, CssValue::Keyword(keyword.clone())))
+                .collect());
+        }
+    }
+
+    match property.as_str() {
+        "margin" => expand_sides("margin-", "", value, is_margin),
+        // https://www.w3.org/TR/css-position-3/#inset-shorthand
+        "inset" => expand_sides("", "", value, is_margin),
+        "padding" => expand_sides("padding-", "", value, is_padding),
+        "border-width" => expand_sides("border-", "-width", value, is_border_width),
+        "border-style" => expand_sides("border-", "-style", value, is_border_style),
+        "border-color" => expand_sides("border-", "-color", value, is_color),
+        "border" => {
+            let (width, style, color) = parse_border_side(value)?;
+            let mut result = Vec::new();
+            for side in SIDES {
+                result.push((format!("border-{}-width", side), width.clone()));
+                result.push((format!("border-{}-style", side), style.clone()));
+                result.push((format!("border-{}-color", side), color.clone()));
+            }
+            Ok(result)
+        }
+        "border-top" | "border-right" | "border-bottom" | "border-left" => {
+            let (width, style, color) = parse_border_side(value)?;
+            Ok(vec![
+                (format!("{}-width", property), width),
+                (format!("{}-style", property), style),
+                (format!("{}-color", property), color),
+            ])
+        }
+        "font" => expand_font(value),
+        "background" => expand_background(value),
+        "flex" => expand_flex(value),
+        "flex-flow" => expand_flex_flow(value),
+        "list-style" => expand_list_style(value),
+        "overflow" => {
+            // https://www.w3.org/TR/css-overflow-3/#propdef-overflow
+            // 2つ目の値が省略された場合は、1つ目の値と同じになる
+            let items = parse_items(value)?;
+            let (x, y) = match items.as_slice() {
+                [both] => (both.clone(), both.clone()),
+                [x, y] => (x.clone(), y.clone()),
+                _ => return Err(invalid(&property, value)),
+            };
+            if !is_overflow(&x) || !is_overflow(&y) {
+                return Err(invalid(&property, value));
+            }
+            Ok(vec![
+                ("overflow-x".to_string(), x),
+                ("overflow-y".to_string(), y),
+            ])
+        }
+        "gap" => {
+            // 列の間隔が省略された場合は、行の間隔と同じになる
+            let items = parse_items(value)?;
+            match items.as_slice() {
+                [row] if is_gap(row) => Ok(vec![
+                    ("row-gap".to_string(), row.clone()),
+                    ("column-gap".to_string(), row.clone()),
+                ]),
+                [row, column] if is_gap(row) && is_gap(column) => Ok(vec![
+                    ("row-gap".to_string(), row.clone()),
+                    ("column-gap".to_string(), column.clone()),
+                ]),
+                _ => Err(invalid(&property, value)),
+            }
+        }
+        "grid-column" | "grid-row" => {
+            // https://www.w3.org/TR/css-grid-2/#placement-shorthands
+            // 終わりの線が省略された場合は、autoになる
+            let lines = split_by_slash(value)
+                .into_iter()
+                .map(CssValue::parse)
+                .collect::<Result<Vec<CssValue>, Error>>()?;
+            let (start, end) = match lines.as_slice() {
+                [start] => (start.clone(), CssValue::Keyword("auto".to_string())),
+                [start, end] => (start.clone(), end.clone()),
+                _ => return Err(invalid(&property, value)),
+            };
+            if !is_grid_line(&start) || !is_grid_line(&end) {
+                return Err(invalid(&property, value));
+            }
+            Ok(vec![
+                (format!("{}-start", property), start),
+                (format!("{}-end", property), end),
+            ])
+        }
+        _ => Err(invalid(&property, value)),
+    }
+}
+
+/// ショートハンドプロパティを構成するロングハンドの名前を返す
+pub fn longhands_of(property: &str) -> Option<Vec<String>> {
+    let sides = |prefix: &str, suffix: &str| -> Vec<String> {
+        SIDES
+            .iter()
+            .map(|side| format!("{}{}{}", prefix, side, suffix))
+            .collect()
+    };
+
+    let longhands = match property {
+        "margin" => sides("margin-", ""),
+        "inset" => sides("", ""),
+        "padding" => sides("padding-", ""),
+        "border-width" => sides("border-", "-width"),
+        "border-style" => sides("border-", "-style"),
+        "border-color" => sides("border-", "-color"),
+        "border" => SIDES
+            .iter()
+            .flat_map(|side| {
+                ["-width", "-style", "-color"]
+                    .iter()
+                    .map(move |suffix| format!("border-{}{}", side, suffix))
+            })
+            .collect(),
+        "border-top" | "border-right" | "border-bottom" | "border-left" => {
+            ["-width", "-style", "-color"]
+                .iter()
+                .map(|suffix| format!("{}{}", property, suffix))
+                .collect()
+        }
+        "font" => [
+            "font-style",
+            "font-variant",
+            "font-weight",
+            "font-stretch",
+            "font-size",
+            "line-height",
+            "font-family",
+        ]
+        .iter()
+        .map(|longhand| longhand.to_string())
+        .collect(),
+        "background" => [
+            "background-color",
+            "background-image",
+            "background-repeat",
+            "background-attachment",
+            "background-position",
+            "background-size",
+        ]
+        .iter()
+        .map(|longhand| longhand.to_string())
+        .collect(),
+        "flex" => ["flex-grow", "flex-shrink", "flex-basis"]
+            .iter()
+            .map(|longhand| longhand.to_string())
+            .collect(),
+        "flex-flow" => ["flex-direction", "flex-wrap"]
+            .iter()
+            .map(|longhand| longhand.to_string())
+            .collect(),
+        "list-style" => ["list-style-type", "list-style-position"]
+            .iter()
+            .map(|longhand| longhand.to_string())
+            .collect(),
+        "overflow" => ["overflow-x", "overflow-y"]
+            .iter()
+            .map(|longhand| longhand.to_string())
+            .collect(),
+        "gap" => ["row-gap", "column-gap"]
+            .iter()
+            .map(|longhand| longhand.to_string())
+            .collect(),
+        "grid-column" => ["grid-column-start", "grid-column-end"]
+            .iter()
+            .map(|longhand| longhand.to_string())
+            .collect(),
+        "grid-row" => ["grid-row-start", "grid-row-end"]
+            .iter()
+            .map(|longhand| longhand.to_string())
+            .collect(),
+        _ => return None,
+    };
+
+    Some(longhands)
+}
+
+fn invalid(property: &str, value: &[ComponentValue]) -> Error {
+    Error::UnexpectedInput(format!("invalid value {:?} for {}", value, property))
+}
+
+/// 値を空白区切りの値のリストとしてパースする
+fn parse_items(value: &[ComponentValue]) -> Result<Vec<CssValue>, Error> {
+    match CssValue::parse(value)? {
+        CssValue::List(list) => Ok(list),
+        CssValue::CommaList(_) => Err(Error::UnexpectedInput(format!(
+            "unexpected comma in {:?}",
+            value
+        ))),
+        item => Ok(vec![item]),
+    }
+}
+
+/// トップレベルの"/"でトークン列を分割する
+fn split_by_slash(value: &[ComponentValue]) -> Vec<&[ComponentValue]> {
+    value.split(|t| *t == ComponentValue::Delim('/')).collect()
+}
+
+/// https://www.w3.org/TR/css-box-4/#margin-shorthand
+/// 1から4つの値を、上、右、下、左の辺に割り当てる
+fn expand_sides(
+    prefix: &str,
+    suffix: &str,
+    value: &[ComponentValue],
+    is_valid: fn(&CssValue) -> bool,
+) -> Result<Vec<Longhand>, Error> {
+    let items = parse_items(value)?;
+    if !items.iter().all(is_valid) {
+        return Err(invalid(&format!("{}*{}", prefix, suffix), value));
+    }
+
+    // 値が省略された辺は、反対側の辺の値を使う
+    let [top, right, bottom, left] = match items.as_slice() {
+        [all] => [all, all, all, all],
+        [vertical, horizontal] => [vertical, horizontal, vertical, horizontal],
+        [top, horizontal, bottom] => [top, horizontal, bottom, horizontal],
+        [top, right, bottom, left] => [top, right, bottom, left],
+        _ => return Err(invalid(&format!("{}*{}", prefix, suffix), value)),
+    };
+
+    Ok([top, right, bottom, left]
+        .iter()
+        .zip(SIDES)
+        .map(|(item, side)| (format!("{}{}{}", prefix, side, suffix), (*item).clone()))
+        .collect())
+}
+
+/// https://www.w3.org/TR/css-backgrounds-3/#propdef-border-top
+/// `<line-width> || <line-style> || <color>`をパースし、省略された値には初期値を使う
+fn parse_border_side(value: &[ComponentValue]) -> Result<(CssValue, CssValue, CssValue), Error> {
+    let mut width = None;
+    let mut style = None;
+    let mut color = None;
+
+    for item in parse_items(value)? {
+        if width.is_none() && is_border_width(&item) {
+            width = Some(item);
+        } else if style.is_none() && is_border_style(&item) {
+            style = Some(item);
+        } else if color.is_none() && is_color(&item) {
+            color = Some(item);
+        } else {
+            return Err(invalid("border", value));
+        }
+    }
+
+    Ok((
+        width.unwrap_or(CssValue::Keyword("medium".to_string())),
+        style.unwrap_or(CssValue::Keyword("none".to_string())),
+        color.unwrap_or(CssValue::Keyword("currentcolor".to_string())),
+    ))
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-prop
+/// `[ <font-style> || <font-variant-css2> || <font-weight> || <font-stretch-css3> ]?
+///  <font-size> [ / <line-height> ]? <font-family>`
+fn expand_font(value: &[ComponentValue]) -> Result<Vec<Longhand>, Error> {
+    // font-sizeの前までの値と、line-heightとfont-familyの値に分ける
+    let (before_size_and_size, after_size) = match split_by_slash(value).as_slice() {
+        [all] => (*all, None),
+        [before, after] => (*before, Some(*after)),
+        _ => return Err(invalid("font", value)),
+    };
+
+    let normal = || CssValue::Keyword("normal".to_string());
+    let mut style = None;
+    let mut variant = None;
+    let mut weight = None;
+    let mut stretch = None;
+    let mut size = None;
+    let mut rest: &[ComponentValue] = &[];
+
+    // font-sizeが見つかるまで、スタイル、バリアント、太さ、幅を順不同で読む
+    let tokens = trim_whitespace(before_size_and_size);
+    for (i, token) in tokens.iter().enumerate() {
+        if *token == ComponentValue::Whitespace {
+            continue;
+        }
+        let item = CssValue::parse(core::slice::from_ref(token))?;
+        if is_font_size(&item) {
+            size = Some(item);
+            rest = &tokens[i + 1..];
+            break;
+        }
+
+        let keyword = match &item {
+            CssValue::Keyword(keyword) => keyword.as_str(),
+            // https://www.w3.org/TR/css-fonts-4/#font-weight-absolute-values
+            CssValue::Number(weight_value) if (1.0..=1000.0).contains(weight_value) => {
+                if weight.replace(item.clone()).is_some() {
+                    return Err(invalid("font", value));
+                }
+                continue;
+            }
+            _ => return Err(invalid("font", value)),
+        };
+
+        // "normal"はまだ指定されていない値に割り当てる（どれに割り当てても結果は同じ）
+        let slot = match keyword {
+            "normal" => [&mut style, &mut variant, &mut weight, &mut stretch]
+                .into_iter()
+                .find(|slot| slot.is_none()),
+            "italic" | "oblique" if style.is_none() => Some(&mut style),
+            "small-caps" if variant.is_none() => Some(&mut variant),
+            "bold" | "bolder" | "lighter" if weight.is_none() => Some(&mut weight),
+            k if FONT_STRETCH_KEYWORDS.contains(&k) && stretch.is_none() => Some(&mut stretch),
+            _ => None,
+        };
+        match slot {
+            Some(slot) => *slot = Some(item.clone()),
+            None => return Err(invalid("font", value)),
+        }
+    }
+
+    let size = size.ok_or_else(|| invalid("font", value))?;
+
+    let (line_height, family) = match after_size {
+        None => (normal(), rest),
+        Some(after) => {
+            // "/"の直前にはfont-sizeだけがあり、直後にline-heightがある
+            if !trim_whitespace(rest).is_empty() {
+                return Err(invalid("font", value));
+            }
+            let after = trim_whitespace(after);
+            let line_height = match after.first() {
+                Some(token) => CssValue::parse(core::slice::from_ref(token))?,
+                None => return Err(invalid("font", value)),
+            };
+            if !is_line_height(&line_height) {
+                return Err(invalid("font", value));
+            }
+            (line_height, &after[1..])
+        }
+    };
+
+    let family = parse_font_family(family)?;
+
+    Ok(vec![
+        ("font-style".to_string(), style.unwrap_or_else(normal)),
+        ("font-variant".to_string(), variant.unwrap_or_else(normal)),
+        ("font-weight".to_string(), weight.unwrap_or_else(normal)),
+        ("font-stretch".to_string(), stretch.unwrap_or_else(normal)),
+        ("font-size".to_string(), size),
+        ("line-height".to_string(), line_height),
+        ("font-family".to_string(), family),
+    ])
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-family-prop
+/// カンマ区切りのフォント名のリスト. 引用符のないフォント名は、空白区切りの識別子を連結したもの
+/// フォント名は大文字・小文字を保ち、総称ファミリーはキーワード（小文字）にする
+pub fn parse_font_family(value: &[ComponentValue]) -> Result<CssValue, Error> {
+    let families = split_by_comma(value);
+
+    let mut result = Vec::new();
+    for family in &families {
+        let name = match trim_whitespace(family) {
+            [ComponentValue::StringToken(name)] => CssValue::String(name.clone()),
+            // CSS全体のキーワードは、ほかのフォント名と並べられない
+            [ComponentValue::Ident(ident)]
+                if is_css_wide_keyword(&ident.to_ascii_lowercase()) && families.len() == 1 =>
+            {
+                CssValue::Keyword(ident.to_ascii_lowercase())
+            }
+            [ComponentValue::Ident(ident)]
+                if GENERIC_FONT_FAMILIES.contains(&ident.to_ascii_lowercase().as_str()) =>
+            {
+                CssValue::Keyword(ident.to_ascii_lowercase())
+            }
+            tokens => {
+                let mut names = Vec::new();
+                for token in tokens {
+                    match token {
+                        ComponentValue::Whitespace => {}
+                        ComponentValue::Ident(ident)
+                            if !is_css_wide_keyword(&ident.to_ascii_lowercase()) =>
+                        {
+                            names.push(ident.clone())
+                        }
+                        _ => return Err(invalid("font-family", value)),
+                    }
+                }
+                if names.is_empty() {
+                    return Err(invalid("font-family", value));
+                }
+                CssValue::String(names.join(" "))
+            }
+        };
+        result.push(name);
+    }
+
+    match result.len() {
+        1 => Ok(result.remove(0)),
+        _ => Ok(CssValue::CommaList(result)),
+    }
+}
+
+/// https://www.w3.org/TR/css-backgrounds-3/#background
+/// 1つのレイヤーだけをサポートする
+fn expand_background(value: &[ComponentValue]) -> Result<Vec<Longhand>, Error> {
+    // https://www.w3.org/TR/css-backgrounds-3/#typedef-bg-size
+    // background-sizeは、background-positionの直後に"/"を挟んでのみ指定できる
+    let (mut items, size_and_rest) = match split_by_slash(value).as_slice() {
+        [all] => (parse_items(all)?, Vec::new()),
+        [before, after] => (parse_items(before)?, parse_items(after)?),
+        _ => return Err(invalid("background", value)),
+    };
+    let position_end = items.len();
+    let size_count = size_and_rest
+        .iter()
+        .take(2)
+        .take_while(|item| is_background_size(item))
+        .count();
+    if !size_and_rest.is_empty() && size_count == 0 {
+        return Err(invalid("background", value));
+    }
+    let size: Vec<CssValue> = size_and_rest[..size_count].to_vec();
+    items.extend_from_slice(&size_and_rest[size_count..]);
+
+    let mut color = None;
+    let mut image = None;
+    let mut repeat: Vec<CssValue> = Vec::new();
+    let mut attachment = None;
+    let mut position: Vec<CssValue> = Vec::new();
+    let mut position_last = 0;
+
+    let mut i = 0;
+    while i < items.len() {
+        let item = &items[i];
+        if color.is_none() && is_color(item) {
+            color = Some(item.clone());
+        } else if image.is_none() && is_background_image(item) {
+            image = Some(item.clone());
+        } else if repeat.is_empty() && is_background_repeat(item) {
+            repeat.push(item.clone());
+            // repeat-xとrepeat-y以外は2つ目の値を取れる
+            if let (Some(next), CssValue::Keyword(keyword)) = (items.get(i + 1), item) {
+                if keyword != "repeat-x" && keyword != "repeat-y" && is_background_repeat(next) {
+                    repeat.push(next.clone());
+                    i += 1;
+                }
+            }
+        } else if attachment.is_none() && is_background_attachment(item) {
+            attachment = Some(item.clone());
+        } else if position.is_empty() && is_background_position(item) {
+            // 位置は連続した1つまたは2つの値
+            position.push(item.clone());
+            if let Some(next) = items.get(i + 1) {
+                if is_background_position(next) {
+                    position.push(next.clone());
+                    i += 1;
+                }
+            }
+            position_last = i + 1;
+        } else {
+            return Err(invalid("background", value));
+        }
+        i += 1;
+    }
+
+    if !size.is_empty() && (position.is_empty() || position_last != position_end) {
+        return Err(invalid("background", value));
+    }
+
+    Ok(vec![
+        (
+            "background-color".to_string(),
+            color.unwrap_or(CssValue::Keyword("transparent".to_string())),
+        ),
+        (
+            "background-image".to_string(),
+            image.unwrap_or(CssValue::Keyword("none".to_string())),
+        ),
+        (
+            "background-repeat".to_string(),
+            if repeat.is_empty() {
+                CssValue::Keyword("repeat".to_string())
+            } else {
+                list_or_single(repeat)
+            },
+        ),
+        (
+            "background-attachment".to_string(),
+            attachment.unwrap_or(CssValue::Keyword("scroll".to_string())),
+        ),
+        (
+            "background-position".to_string(),
+            if position.is_empty() {
+                CssValue::List(vec![CssValue::Percentage(0.0), CssValue::Percentage(0.0)])
+            } else {
+                list_or_single(position)
+            },
+        ),
+        (
+            "background-size".to_string(),
+            if size.is_empty() {
+                CssValue::Keyword("auto".to_string())
+            } else {
+                list_or_single(size)
+            },
+        ),
+    ])
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-property
+/// `none | [ <'flex-grow'> <'flex-shrink'>? || <'flex-basis'> ]`
+/// flex-growを指定してflex-basisを省略した場合、flex-basisは0になる
+fn expand_flex(value: &[ComponentValue]) -> Result<Vec<Longhand>, Error> {
+    let items = parse_items(value)?;
+    if let [CssValue::Keyword(keyword)] = items.as_slice() {
+        if keyword == "none" {
+            return Ok(vec![
+                ("flex-grow".to_string(), CssValue::Number(0.0)),
+                ("flex-shrink".to_string(), CssValue::Number(0.0)),
+                (
+                    "flex-basis".to_string(),
+                    CssValue::Keyword("auto".to_string()),
+                ),
+            ]);
+        }
+    }
+
+    let mut grow = None;
+    let mut shrink = None;
+    let mut basis = None;
+    // 直前の値がflex-growで、flex-shrinkを続けて書けるか
+    let mut after_grow = false;
+    for item in items {
+        if grow.is_none() && is_flex_factor(&item) {
+            grow = Some(item);
+            after_grow = true;
+            continue;
+        }
+        if after_grow && shrink.is_none() && is_flex_factor(&item) {
+            shrink = Some(item);
+        } else if basis.is_none() && is_flex_basis(&item) {
+            basis = Some(item);
+        } else {
+            return Err(invalid("flex", value));
+        }
+        after_grow = false;
+    }
+
+    let basis = match (&grow, basis) {
+        (_, Some(basis)) => basis,
+        (Some(_), None) => CssValue::Length(Length::px(0.0)),
+        (None, None) => return Err(invalid("flex", value)),
+    };
+    Ok(vec![
+        (
+            "flex-grow".to_string(),
+            grow.unwrap_or(CssValue::Number(1.0)),
+        ),
+        (
+            "flex-shrink".to_string(),
+            shrink.unwrap_or(CssValue::Number(1.0)),
+        ),
+        ("flex-basis".to_string(), basis),
+    ])
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-flow-property
+/// `<'flex-direction'> || <'flex-wrap'>`
+fn expand_flex_flow(value: &[ComponentValue]) -> Result<Vec<Longhand>, Error> {
+    let mut direction = None;
+    let mut wrap = None;
+    for item in parse_items(value)? {
+        if direction.is_none() && is_flex_direction(&item) {
+            direction = Some(item);
+        } else if wrap.is_none() && is_flex_wrap(&item) {
+            wrap = Some(item);
+        } else {
+            return Err(invalid("flex-flow", value));
+        }
+    }
+
+    Ok(vec![
+        (
+            "flex-direction".to_string(),
+            direction.unwrap_or(CssValue::Keyword("row".to_string())),
+        ),
+        (
+            "flex-wrap".to_string(),
+            wrap.unwrap_or(CssValue::Keyword("nowrap".to_string())),
+        ),
+    ])
+}
+
+/// https://www.w3.org/TR/css-lists-3/#list-style-property
+/// `<'list-style-position'> || <'list-style-image'> || <'list-style-type'>`
+/// list-style-imageはサポートしていないので、noneはlist-style-typeの値として扱う
+fn expand_list_style(value: &[ComponentValue]) -> Result<Vec<Longhand>, Error> {
+    let mut list_style_type = None;
+    let mut position = None;
+    for item in parse_items(value)? {
+        if list_style_type.is_none() && is_list_style_type(&item) {
+            list_style_type = Some(item);
+        } else if position.is_none() && is_list_style_position(&item) {
+            position = Some(item);
+        } else {
+            return Err(invalid("list-style", value));
+        }
+    }
+
+    Ok(vec![
+        (
+            "list-style-type".to_string(),
+            list_style_type.unwrap_or(CssValue::Keyword("disc".to_string())),
+        ),
+        (
+            "list-style-position".to_string(),
+            position.unwrap_or(CssValue::Keyword("outside".to_string())),
+        ),
+    ])
+}
+
+fn list_or_single(mut items: Vec<CssValue>) -> CssValue {
+    if items.len() == 1 {
+        items.remove(0)
+    } else {
+        CssValue::List(items)
+    }
+}
+
+fn is_keyword(value: &CssValue, keywords: &[&str]) -> bool {
+    matches!(value, CssValue::Keyword(keyword) if keywords.contains(&keyword.as_str()))
+}
+
+/// 長さまたはパーセンテージ（単位のない0を含む）かどうか
+fn is_length_percentage(value: &CssValue, allow_negative: bool) -> bool {
+    match value {
+        CssValue::Length(length) => allow_negative || length.value() >= 0.0,
+        CssValue::Percentage(percentage) => allow_negative || *percentage >= 0.0,
+        CssValue::Number(number) => *number == 0.0,
+        // https://www.w3.org/TR/css-values-4/#calc-range
+        // 計算式の結果が範囲外の場合は、パースエラーではなく計算値の時点で範囲内に丸める
+        CssValue::Calc(_) => value
+            .calc_type()
+            .map_or(false, |t| t.is_length_percentage()),
+        _ => false,
+    }
+}
+
+/// https://www.w3.org/TR/css-box-4/#margin-physical
+pub fn is_margin(value: &CssValue) -> bool {
+    is_length_percentage(value, true) || is_keyword(value, &["auto"])
+}
+
+/// https://www.w3.org/TR/css-box-4/#padding-physical
+pub fn is_padding(value: &CssValue) -> bool {
+    is_length_percentage(value, false)
+}
+
+/// https://www.w3.org/TR/css-backgrounds-3/#typedef-line-width
+pub fn is_border_width(value: &CssValue) -> bool {
+    match value {
+        CssValue::Length(length) => length.value() >= 0.0,
+        CssValue::Number(number) => *number == 0.0,
+        CssValue::Calc(_) => value.calc_type() == Some(CalcType::Length),
+        _ => is_keyword(value, &["thin", "medium", "thick"]),
+    }
+}
+
+pub fn is_border_style(value: &CssValue) -> bool {
+    is_keyword(value, &BORDER_STYLES)
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-direction-property
+pub fn is_flex_direction(value: &CssValue) -> bool {
+    is_keyword(value, &["row", "row-reverse", "column", "column-reverse"])
+}
+
+/// https://www.w3.org/TR/css-overflow-3/#overflow-control
+pub fn is_overflow(value: &CssValue) -> bool {
+    is_keyword(value, &["visible", "hidden", "clip", "scroll", "auto"])
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-wrap-property
+pub fn is_flex_wrap(value: &CssValue) -> bool {
+    is_keyword(value, &["nowrap", "wrap", "wrap-reverse"])
+}
+
+/// https://www.w3.org/TR/css-counter-styles-3/#predefined-counters
+pub fn is_list_style_type(value: &CssValue) -> bool {
+    is_keyword(value, &LIST_STYLE_TYPES)
+}
+
+/// https://www.w3.org/TR/css-lists-3/#list-style-position-property
+pub fn is_list_style_position(value: &CssValue) -> bool {
+    is_keyword(value, &["inside", "outside"])
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-grow-property
+/// flex-growとflex-shrinkの値. 負でない数値
+pub fn is_flex_factor(value: &CssValue) -> bool {
+    matches!(value, CssValue::Number(number) if *number >= 0.0)
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-basis-property
+/// content | <'width'>
+pub fn is_flex_basis(value: &CssValue) -> bool {
+    is_length_percentage(value, false) || is_keyword(value, &["auto", "content"])
+}
+
+/// https://www.w3.org/TR/css-align-3/#column-row-gap
+/// normal | <length-percentage [0,∞]>
+pub fn is_gap(value: &CssValue) -> bool {
+    is_length_percentage(value, false) || is_keyword(value, &["normal"])
+}
+
+/// https://www.w3.org/TR/css-grid-2/#track-sizing
+/// none | <track-list>. トラックの大きさとrepeat()を空白で区切って並べる
+/// 回数がauto-fillのrepeat()は1つだけ書け、その中のトラックは固定の大きさでなければならない
+pub fn is_track_list(value: &CssValue) -> bool {
+    let entries = match value {
+        CssValue::Keyword(keyword) if keyword == "none" => return true,
+        CssValue::List(list) => list.as_slice(),
+        value => core::slice::from_ref(value),
+    };
+
+    let mut auto_repeats = 0;
+    for entry in entries {
+        let valid = match entry {
+            CssValue::Repeat(count, tracks) => {
+                let tracks = match tracks.as_ref() {
+                    CssValue::List(list) => list.as_slice(),
+                    tracks => core::slice::from_ref(tracks),
+                };
+                match count.as_ref() {
+                    CssValue::Number(n) => {
+                        *n >= 1.0 && *n == (*n as i64) as f64 && tracks.iter().all(is_track_size)
+                    }
+                    CssValue::Keyword(keyword) if keyword == "auto-fill" => {
+                        auto_repeats += 1;
+                        tracks.iter().all(is_fixed_track_size)
+                    }
+                    _ => false,
+                }
+            }
+            entry => is_track_size(entry),
+        };
+        if !valid {
+            return false;
+        }
+    }
+    auto_repeats <= 1
+}
+
+/// https://www.w3.org/TR/css-grid-2/#typedef-track-size
+/// <track-breadth> | minmax( <inflexible-breadth> , <track-breadth> )
+pub fn is_track_size(value: &CssValue) -> bool {
+    match value {
+        CssValue::MinMax(min, max) => is_track_breadth(min, false) && is_track_breadth(max, true),
+        value => is_track_breadth(value, true),
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#typedef-fixed-size
+/// 最小値と最大値の少なくとも一方が、長さまたはパーセンテージの大きさ
+fn is_fixed_track_size(value: &CssValue) -> bool {
+    match value {
+        CssValue::MinMax(min, max) => {
+            (is_length_percentage(min, false) && is_track_breadth(max, true))
+                || (is_track_breadth(min, false) && is_length_percentage(max, false))
+        }
+        value => is_length_percentage(value, false),
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#typedef-track-breadth
+/// `allow_flex`がfalseの場合は、frの値を受け付けない
+fn is_track_breadth(value: &CssValue, allow_flex: bool) -> bool {
+    match value {
+        CssValue::Flex(flex) => allow_flex && *flex >= 0.0,
+        value => {
+            is_length_percentage(value, false)
+                || is_keyword(value, &["auto", "min-content", "max-content"])
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#auto-tracks
+/// grid-auto-columnsとgrid-auto-rowsの値. トラックの大きさのリスト
+pub fn is_auto_track_list(value: &CssValue) -> bool {
+    match value {
+        CssValue::List(list) => list.iter().all(is_track_size),
+        value => is_track_size(value),
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#typedef-grid-row-start-grid-line
+/// auto | <integer> | span <integer>. 名前の付いた線はサポートしていない
+/// 線の番号は0以外、spanの数は正の整数
+pub fn is_grid_line(value: &CssValue) -> bool {
+    let is_integer =
+        |value: &CssValue| matches!(value, CssValue::Number(n) if *n == (*n as i64) as f64);
+    match value {
+        CssValue::Keyword(keyword) => keyword == "auto",
+        CssValue::Number(n) => is_integer(value) && *n != 0.0,
+        CssValue::List(list) => match list.as_slice() {
+            [CssValue::Keyword(span), count @ CssValue::Number(n)]
+            | [count @ CssValue::Number(n), CssValue::Keyword(span)] => {
+                span == "span" && is_integer(count) && *n > 0.0
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#grid-auto-flow-property
+/// [ row | column ] || dense
+pub fn is_grid_auto_flow(value: &CssValue) -> bool {
+    match value {
+        CssValue::List(list) => match list.as_slice() {
+            [first, second] => {
+                let is_direction = |value: &CssValue| is_keyword(value, &["row", "column"]);
+                let is_dense = |value: &CssValue| is_keyword(value, &["dense"]);
+                (is_direction(first) && is_dense(second))
+                    || (is_dense(first) && is_direction(second))
+            }
+            _ => false,
+        },
+        value => is_keyword(value, &["row", "column", "dense"]),
+    }
+}
+
+/// https://www.w3.org/TR/css-color-4/#typedef-color
+pub fn is_color(value: &CssValue) -> bool {
+    match value {
+        CssValue::Color(_) => true,
+        CssValue::Keyword(keyword) => {
+            keyword == "currentcolor" || Color::from_name(keyword).is_ok()
+        }
+        _ => false,
+    }
+}
+
+pub fn is_font_size(value: &CssValue) -> bool {
+    is_length_percentage(value, false) || is_keyword(value, &FONT_SIZE_KEYWORDS)
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-style-prop
+pub fn is_font_style(value: &CssValue) -> bool {
+    is_keyword(value, &["normal", "italic", "oblique"])
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-variant-css2-values
+pub fn is_font_variant(value: &CssValue) -> bool {
+    is_keyword(value, &["normal", "small-caps"])
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-weight-prop
+pub fn is_font_weight(value: &CssValue) -> bool {
+    match value {
+        CssValue::Number(weight) => (1.0..=1000.0).contains(weight),
+        CssValue::Calc(_) => value.calc_type() == Some(CalcType::Number),
+        _ => is_keyword(value, &["normal", "bold", "bolder", "lighter"]),
+    }
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-stretch-prop
+pub fn is_font_stretch(value: &CssValue) -> bool {
+    match value {
+        CssValue::Percentage(percentage) => *percentage >= 0.0,
+        _ => is_keyword(value, &["normal"]) || is_keyword(value, &FONT_STRETCH_KEYWORDS),
+    }
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-family-prop
+/// `parse_font_family`で読んだ、フォント名（文字列）と総称ファミリー（キーワード）のリスト
+pub fn is_font_family(value: &CssValue) -> bool {
+    let is_family = |family: &CssValue| {
+        matches!(family, CssValue::String(_))
+            || matches!(family, CssValue::Keyword(keyword) if !is_css_wide_keyword(keyword))
+    };
+    match value {
+        CssValue::CommaList(list) => list.iter().all(is_family),
+        family => is_family(family),
+    }
+}
+
+/// https://www.w3.org/TR/css-inline-3/#line-height-property
+pub fn is_line_height(value: &CssValue) -> bool {
+    match value {
+        CssValue::Number(number) => *number >= 0.0,
+        CssValue::Calc(_) if value.calc_type() == Some(CalcType::Number) => true,
+        _ => is_length_percentage(value, false) || is_keyword(value, &["normal"]),
+    }
+}
+
+fn is_background_image(value: &CssValue) -> bool {
+    matches!(value, CssValue::Url(_)) || is_keyword(value, &["none"])
+}
+
+fn is_background_repeat(value: &CssValue) -> bool {
+    is_keyword(
+        value,
+        &[
+            "repeat-x",
+            "repeat-y",
+            "repeat",
+            "space",
+            "round",
+            "no-repeat",
+        ],
+    )
+}
+
+fn is_background_attachment(value: &CssValue) -> bool {
+    is_keyword(value, &["scroll", "fixed", "local"])
+}
+
+fn is_background_position(value: &CssValue) -> bool {
+    is_length_percentage(value, true)
+        || is_keyword(value, &["left", "right", "top", "bottom", "center"])
+}
+
+fn is_background_size(value: &CssValue) -> bool {
+    is_length_percentage(value, false) || is_keyword(value, &["auto", "cover", "contain"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::token::CssTokenizer;
+
+    fn expand(property: &str, value: &str) -> Result<Vec<Longhand>, Error> {
+        let tokens: Vec<ComponentValue> = CssTokenizer::new(value.to_string()).collect();
+        expand_declaration(property, &tokens)
+    }
+
+    fn px(value: f64) -> CssValue {
+        CssValue::Length(Length::px(value))
+    }
+
+    fn keyword(keyword: &str) -> CssValue {
+        CssValue::Keyword(keyword.to_string())
+    }
+
+    fn longhands(list: &[(&str, CssValue)]) -> Vec<Longhand> {
+        list.iter()
+            .map(|(property, value)| (property.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_longhand() {
+        /* ショートハンドでないプロパティはそのまま返されることを確認する */
+        assert_eq!(
+            expand("Width", "10px"),
+            Ok(longhands(&[("width", px(10.0))]))
+        );
+    }
+
+    #[test]
+    fn test_one_to_four_values() {
+        /* 1から4つの値が、上、右、下、左の辺に割り当てられることを確認する */
+        assert_eq!(
+            expand("margin", "1px"),
+            Ok(longhands(&[
+                ("margin-top", px(1.0)),
+                ("margin-right", px(1.0)),
+                ("margin-bottom", px(1.0)),
+                ("margin-left", px(1.0)),
+            ]))
+        );
+        assert_eq!(
+            expand("margin", "1px auto"),
+            Ok(longhands(&[
+                ("margin-top", px(1.0)),
+                ("margin-right", keyword("auto")),
+                ("margin-bottom", px(1.0)),
+                ("margin-left", keyword("auto")),
+            ]))
+        );
+        assert_eq!(
+            expand("padding", "1px 2px 3px"),
+            Ok(longhands(&[
+                ("padding-top", px(1.0)),
+                ("padding-right", px(2.0)),
+                ("padding-bottom", px(3.0)),
+                ("padding-left", px(2.0)),
+            ]))
+        );
+        assert_eq!(
+            expand("border-style", "solid dashed none dotted"),
+            Ok(longhands(&[
+                ("border-top-style", keyword("solid")),
+                ("border-right-style", keyword("dashed")),
+                ("border-bottom-style", keyword("none")),
+                ("border-left-style", keyword("dotted")),
+            ]))
+        );
+
+        assert!(expand("margin", "1px 2px 3px 4px 5px").is_err());
+        assert!(expand("padding", "auto").is_err());
+        assert!(expand("padding", "-1px").is_err());
+        assert!(expand("border-width", "10%").is_err());
+    }
+
+    #[test]
+    fn test_border() {
+        /* borderの値が順不同で解釈され、省略された値に初期値が使われることを確認する */
+        let expected = [
+            ("border-top-width", px(2.0)),
+            ("border-top-style", keyword("solid")),
+            ("border-top-color", keyword("currentcolor")),
+        ];
+        assert_eq!(expand("border-top", "solid 2px"), Ok(longhands(&expected)));
+
+        let all = expand("border", "red thick dotted").expect("should expand");
+        assert_eq!(all.len(), 12);
+        assert_eq!(all[11], ("border-left-color".to_string(), keyword("red")));
+        assert_eq!(all[9], ("border-left-width".to_string(), keyword("thick")));
+
+        assert!(expand("border", "solid dotted").is_err());
+        assert!(expand("border", "1px 2px").is_err());
+    }
+
+    #[test]
+    fn test_font() {
+        /* fontの値がロングハンドに展開されることを確認する */
+        assert_eq!(
+            expand(
+                "font",
+                "italic bold 12px/1.5 \"Helvetica Neue\", Times New Roman, serif"
+            ),
+            Ok(longhands(&[
+                ("font-style", keyword("italic")),
+                ("font-variant", keyword("normal")),
+                ("font-weight", keyword("bold")),
+                ("font-stretch", keyword("normal")),
+                ("font-size", px(12.0)),
+                ("line-height", CssValue::Number(1.5)),
+                (
+                    "font-family",
+                    CssValue::CommaList(vec![
+                        CssValue::String("Helvetica Neue".to_string()),
+                        CssValue::String("Times New Roman".to_string()),
+                        keyword("serif"),
+                    ])
+                ),
+            ]))
+        );
+        assert_eq!(
+            expand("font", "700 large sans-serif"),
+            Ok(longhands(&[
+                ("font-style", keyword("normal")),
+                ("font-variant", keyword("normal")),
+                ("font-weight", CssValue::Number(700.0)),
+                ("font-stretch", keyword("normal")),
+                ("font-size", keyword("large")),
+                ("line-height", keyword("normal")),
+                ("font-family", keyword("sans-serif")),
+            ]))
+        );
+
+        // font-sizeとfont-familyは必須
+        assert!(expand("font", "bold serif").is_err());
+        assert!(expand("font", "12px").is_err());
+        assert!(expand("font", "italic italic 12px serif").is_err());
+    }
+
+    #[test]
+    fn test_background() {
+        /* backgroundの値がロングハンドに展開されることを確認する */
+        assert_eq!(
+            expand("background", "url(a.png) no-repeat center / cover #fff"),
+            Ok(longhands(&[
+                ("background-color", CssValue::Color(Color::white())),
+                ("background-image", CssValue::Url("a.png".to_string())),
+                ("background-repeat", keyword("no-repeat")),
+                ("background-attachment", keyword("scroll")),
+                ("background-position", keyword("center")),
+                ("background-size", keyword("cover")),
+            ]))
+        );
+
+        let expanded = expand("background", "red").expect("should expand");
+        assert_eq!(
+            expanded[0],
+            ("background-color".to_string(), keyword("red"))
+        );
+        assert_eq!(
+            expanded[2],
+            ("background-repeat".to_string(), keyword("repeat"))
+        );
+
+        let expanded =
+            expand("background", "url(a.png) 10px 20% / 50% auto repeat-x").expect("should expand");
+        assert_eq!(
+            expanded[4],
+            (
+                "background-position".to_string(),
+                CssValue::List(vec![px(10.0), CssValue::Percentage(20.0)])
+            )
+        );
+        assert_eq!(
+            expanded[5],
+            (
+                "background-size".to_string(),
+                CssValue::List(vec![CssValue::Percentage(50.0), keyword("auto")])
+            )
+        );
+
+        assert!(expand("background", "red blue").is_err());
+        assert!(expand("background", "red / cover").is_err());
+        assert!(expand("background", "url(a.png), url(b.png)").is_err());
+    }
+
+    #[test]
+    fn test_flex() {
+        /* flex、flex-flow、gap、list-styleの値がロングハンドに展開されることを確認する */
+        let flex = |grow: f64, shrink: f64, basis: CssValue| {
+            Ok(longhands(&[
+                ("flex-grow", CssValue::Number(grow)),
+                ("flex-shrink", CssValue::Number(shrink)),
+                ("flex-basis", basis),
+            ]))
+        };
+        assert_eq!(expand("flex", "none"), flex(0.0, 0.0, keyword("auto")));
+        assert_eq!(expand("flex", "auto"), flex(1.0, 1.0, keyword("auto")));
+        assert_eq!(expand("flex", "2"), flex(2.0, 1.0, px(0.0)));
+        assert_eq!(expand("flex", "1 0"), flex(1.0, 0.0, px(0.0)));
+        assert_eq!(expand("flex", "30px 2"), flex(2.0, 1.0, px(30.0)));
+        assert_eq!(
+            expand("flex", "0 0 content"),
+            flex(0.0, 0.0, keyword("content"))
+        );
+        assert!(expand("flex", "1 2 3").is_err());
+        assert!(expand("flex", "-1").is_err());
+
+        assert_eq!(
+            expand("flex-flow", "wrap column"),
+            Ok(longhands(&[
+                ("flex-direction", keyword("column")),
+                ("flex-wrap", keyword("wrap")),
+            ]))
+        );
+        assert!(expand("flex-flow", "row column").is_err());
+
+        assert_eq!(
+            expand("list-style", "inside lower-alpha"),
+            Ok(longhands(&[
+                ("list-style-type", keyword("lower-alpha")),
+                ("list-style-position", keyword("inside")),
+            ]))
+        );
+        assert_eq!(
+            expand("list-style", "none"),
+            Ok(longhands(&[
+                ("list-style-type", keyword("none")),
+                ("list-style-position", keyword("outside")),
+            ]))
+        );
+        assert!(expand("list-style", "disc square").is_err());
+
+        assert_eq!(
+            expand("gap", "10px"),
+            Ok(longhands(&[
+                ("row-gap", px(10.0)),
+                ("column-gap", px(10.0))
+            ]))
+        );
+        assert_eq!(
+            expand("gap", "normal 5%"),
+            Ok(longhands(&[
+                ("row-gap", keyword("normal")),
+                ("column-gap", CssValue::Percentage(5.0)),
+            ]))
+        );
+        assert!(expand("gap", "1px 2px 3px").is_err());
+    }
+
+    #[test]
+    fn test_grid_placement() {
+        /* grid-columnとgrid-rowが、始まりと終わりの線に展開されることを確認する */
+        let span = |n: f64| CssValue::List(vec![keyword("span"), CssValue::Number(n)]);
+        assert_eq!(
+            expand("grid-column", "1 / span 2"),
+            Ok(longhands(&[
+                ("grid-column-start", CssValue::Number(1.0)),
+                ("grid-column-end", span(2.0)),
+            ]))
+        );
+        assert_eq!(
+            expand("grid-row", "-1"),
+            Ok(longhands(&[
+                ("grid-row-start", CssValue::Number(-1.0)),
+                ("grid-row-end", keyword("auto")),
+            ]))
+        );
+        assert!(expand("grid-column", "1 / 2 / 3").is_err());
+        assert!(expand("grid-column", "0").is_err());
+        assert!(expand("grid-row", "span -1").is_err());
+    }
+
+    #[test]
+    fn test_overflow() {
+        /* overflowが、overflow-xとoverflow-yに展開されることを確認する */
+        assert_eq!(
+            expand("overflow", "hidden"),
+            Ok(longhands(&[
+                ("overflow-x", keyword("hidden")),
+                ("overflow-y", keyword("hidden")),
+            ]))
+        );
+        assert_eq!(
+            expand("overflow", "clip auto"),
+            Ok(longhands(&[
+                ("overflow-x", keyword("clip")),
+                ("overflow-y", keyword("auto")),
+            ]))
+        );
+        assert!(expand("overflow", "hidden scroll auto").is_err());
+        assert!(expand("overflow", "none").is_err());
+    }
+
+    #[test]
+    fn test_inset() {
+        /* insetが、top・right・bottom・leftに展開されることを確認する */
+        assert_eq!(
+            expand("inset", "10px auto"),
+            Ok(longhands(&[
+                ("top", CssValue::Length(Length::px(10.0))),
+                ("right", keyword("auto")),
+                ("bottom", CssValue::Length(Length::px(10.0))),
+                ("left", keyword("auto")),
+            ]))
+        );
+        assert!(expand("inset", "red").is_err());
+    }
+
+    #[test]
+    fn test_css_wide_keywords() {
+        /* CSS全体のキーワードは、すべてのロングハンドに設定されることを確認する */
+        assert_eq!(
+            expand("padding", "inherit"),
+            Ok(longhands(&[
+                ("padding-top", keyword("inherit")),
+                ("padding-right", keyword("inherit")),
+                ("padding-bottom", keyword("inherit")),
+                ("padding-left", keyword("inherit")),
+            ]))
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::renderer::css::cssom::find_block_end;
+use crate::renderer::css::cssom::trim_whitespace;
+use crate::renderer::css::property::is_valid_declaration;
+use crate::renderer::css::serialize::serialize_identifier;
+use crate::renderer::css::serialize::serialize_tokens;
+use crate::renderer::css::token::CssToken;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/css-conditional-3/#typedef-supports-condition
+#[derive(Debug, Clone, PartialEq)]
+pub enum SupportsCondition {
+    Not(Box<SupportsCondition>),
+    And(Vec<SupportsCondition>),
+    Or(Vec<SupportsCondition>),
+    /// https://www.w3.org/TR/css-conditional-3/#typedef-supports-decl
+    Declaration {
+        property: String,
+        value: Vec<CssToken>,
+    },
+    /// https://www.w3.org/TR/css-conditional-3/#typedef-general-enclosed
+    /// 将来の拡張のための構文（selector()など）. 常に偽として評価する
+    /// 文字列にするために括弧を含むトークン列を保持する
+    GeneralEnclosed(Vec<CssToken>),
+}
+
+impl SupportsCondition {
+    /// https://www.w3.org/TR/css-conditional-3/#at-supports
+    /// 条件が文法に一致しない場合はNoneを返す（その@supportsルールは無視される）
+    pub fn parse(tokens: &[CssToken]) -> Option<Self> {
+        let tokens = trim_whitespace(tokens);
+
+        if let Some(CssToken::Ident(ident)) = tokens.first() {
+            if ident.eq_ignore_ascii_case("not") {
+                let (condition, rest) = parse_in_parens(&tokens[1..])?;
+                if !trim_whitespace(rest).is_empty() {
+                    return None;
+                }
+                return Some(SupportsCondition::Not(Box::new(condition)));
+            }
+        }
+
+        let (first, mut rest) = parse_in_parens(tokens)?;
+        let mut conditions = Vec::from([first]);
+        let mut operator: Option<String> = None;
+
+        loop {
+            rest = trim_whitespace(rest);
+            let keyword = match rest.first() {
+                None => break,
+                Some(CssToken::Ident(ident)) => ident.to_ascii_lowercase(),
+                Some(_) => return None,
+            };
+            if keyword != "and" && keyword != "or" {
+                return None;
+            }
+            // andとorを括弧なしで混在させることはできない
+            if operator.as_ref().map_or(false, |op| *op != keyword) {
+                return None;
+            }
+            let (condition, next) = parse_in_parens(&rest[1..])?;
+            conditions.push(condition);
+            operator = Some(keyword);
+            rest = next;
+        }
+
+        match operator.as_deref() {
+            None => conditions.pop(),
+            Some("and") => Some(SupportsCondition::And(conditions)),
+            _ => Some(SupportsCondition::Or(conditions)),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-conditional-3/#dom-cssconditionrule-conditiontext
+    pub fn css_text(&self) -> String {
+        match self {
+            SupportsCondition::Not(condition) => {
+                format!("not {}", condition.css_text_in_parens())
+            }
+            SupportsCondition::And(conditions) => conditions
+                .iter()
+                .map(|c| c.css_text_in_parens())
+                .collect::<Vec<String>>()
+                .join(" and "),
+            SupportsCondition::Or(conditions) => conditions
+                .iter()
+                .map(|c| c.css_text_in_parens())
+                .collect::<Vec<String>>()
+                .join(" or "),
+            SupportsCondition::Declaration { property, value } => format!(
+                "({}: {})",
+                serialize_identifier(property),
+                serialize_tokens(value)
+            ),
+            SupportsCondition::GeneralEnclosed(tokens) => serialize_tokens(tokens),
+        }
+    }
+
+    /// <supports-in-parens>として文字列にする. not、and、orの条件は括弧で囲む
+    fn css_text_in_parens(&self) -> String {
+        match self {
+            SupportsCondition::Not(_) | SupportsCondition::And(_) | SupportsCondition::Or(_) => {
+                format!("({})", self.css_text())
+            }
+            _ => self.css_text(),
+        }
+    }
+
+    pub fn evaluate(&self) -> bool {
+        match self {
+            SupportsCondition::Not(condition) => !condition.evaluate(),
+            SupportsCondition::And(conditions) => conditions.iter().all(|c| c.evaluate()),
+            SupportsCondition::Or(conditions) => conditions.iter().any(|c| c.evaluate()),
+            // プロパティをサポートしていて、値がその文法に一致する場合に真になる
+            SupportsCondition::Declaration { property, value } => {
+                is_valid_declaration(property, value)
+            }
+            SupportsCondition::GeneralEnclosed(_) => false,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-conditional-3/#typedef-supports-in-parens
+/// 括弧で囲まれた条件または宣言をパースし、残りのトークンとともに返す
+fn parse_in_parens(tokens: &[CssToken]) -> Option<(SupportsCondition, &[CssToken])> {
+    let tokens = trim_whitespace(tokens);
+    let end = find_block_end(tokens, 0)?;
+    let rest = &tokens[end + 1..];
+
+    match tokens.first()? {
+        CssToken::OpenParenthesis => {}
+        CssToken::Function(_) => {
+            return Some((
+                SupportsCondition::GeneralEnclosed(tokens[..=end].to_vec()),
+                rest,
+            ))
+        }
+        _ => return None,
+    }
+
+    let inner = trim_whitespace(&tokens[1..end]);
+    match inner.first() {
+        Some(CssToken::OpenParenthesis) => {
+            return Some((SupportsCondition::parse(inner)?, rest));
+        }
+        Some(CssToken::Ident(ident)) if ident.eq_ignore_ascii_case("not") => {
+            return Some((SupportsCondition::parse(inner)?, rest));
+        }
+        _ => {}
+    }
+
+    // <supports-decl> = ( <declaration> )
+    if let Some(CssToken::Ident(property)) = inner.first() {
+        let after_property = trim_whitespace(&inner[1..]);
+        if after_property.first() == Some(&CssToken::Colon) {
+            let value = trim_whitespace(&after_property[1..]).to_vec();
+            return Some((
+                SupportsCondition::Declaration {
+                    property: property.to_ascii_lowercase(),
+                    value,
+                },
+                rest,
+            ));
+        }
+    }
+
+    Some((
+        SupportsCondition::GeneralEnclosed(tokens[..=end].to_vec()),
+        rest,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::token::CssTokenizer;
+    use alloc::string::ToString;
+
+    fn parse(condition: &str) -> Option<SupportsCondition> {
+        let tokens: Vec<CssToken> = CssTokenizer::new(condition.to_string()).collect();
+        SupportsCondition::parse(&tokens)
+    }
+
+    #[test]
+    fn test_declaration() {
+        /* サポートしているプロパティのみ真になることを確認する */
+        assert!(parse("(display: block)").expect("should parse").evaluate());
+        assert!(parse("(COLOR: red)").expect("should parse").evaluate());
+        assert!(!parse("(grid-area: auto)").expect("should parse").evaluate());
+        assert!(!parse("(color:)").expect("should parse").evaluate());
+        assert!(parse("(margin: 0 auto)").expect("should parse").evaluate());
+        assert!(!parse("(display: flexible)")
+            .expect("should parse")
+            .evaluate());
+    }
+
+    #[test]
+    fn test_logical_operators() {
+        /* not、and、orの組み合わせが正しく評価されることを確認する */
+        assert!(parse("not (grid-area: auto)")
+            .expect("should parse")
+            .evaluate());
+        assert!(!parse("(color: red) and (grid-area: auto)")
+            .expect("should parse")
+            .evaluate());
+        assert!(parse("(color: red) or (grid-area: auto)")
+            .expect("should parse")
+            .evaluate());
+        assert!(parse("((color: red) and (not (grid-area: auto)))")
+            .expect("should parse")
+            .evaluate());
+    }
+
+    #[test]
+    fn test_general_enclosed() {
+        /* selector()などの未知の構文は偽として評価されることを確認する */
+        assert!(!parse("selector(a > b)").expect("should parse").evaluate());
+        assert!(!parse("(unknown)").expect("should parse").evaluate());
+    }
+
+    #[test]
+    fn test_invalid() {
+        /* 文法に一致しない条件はパースに失敗することを確認する */
+        assert!(parse("").is_none());
+        assert!(parse("color: red").is_none());
+        assert!(parse("(color: red) and (display: block) or (width: 1px)").is_none());
+        assert!(parse("(color: red) (display: block)").is_none());
+    }
+
+    #[test]
+    fn test_css_text() {
+        /* @supportsの条件を文字列にして読み直すと、同じ条件になることを確認する */
+        for (condition, expected) in [
+            ("(DISPLAY:block)", "(display: block)"),
+            ("not (color: red)", "not (color: red)"),
+            (
+                "(display: block) and ((color: red) or selector(a > b))",
+                "(display: block) and ((color: red) or selector(a > b))",
+            ),
+        ] {
+            let parsed = parse(condition).expect("should parse");
+            assert_eq!(parsed.css_text(), expected);
+            assert_eq!(parse(&parsed.css_text()), Some(parsed));
+        }
+    }
+}
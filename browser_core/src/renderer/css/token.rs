@@ -1,32 +1,61 @@
 use alloc::string::String;
 use alloc::vec::Vec;
 
+/// https://www.w3.org/TR/css-syntax-3/#tokenization
 #[derive(Debug, Clone, PartialEq)]
 pub enum CssToken {
     /// https://www.w3.org/TR/css-syntax-3/#typedef-hash-token
+    /// 値には先頭の'#'を含める
     HashToken(String),
     /// https://www.w3.org/TR/css-syntax-3/#typedef-delim-token
     Delim(char),
     /// https://www.w3.org/TR/css-syntax-3/#typedef-number-token
     Number(f64),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-percentage-token
+    Percentage(f64),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-dimension-token
+    Dimension { value: f64, unit: String },
     /// https://www.w3.org/TR/css-syntax-3/#typedef-colon-token
     Colon,
     /// https://www.w3.org/TR/css-syntax-3/#typedef-semicolon-token
     SemiColon,
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-comma-token
+    Comma,
     /// https://www.w3.org/TR/css-syntax-3/#tokendef-open-paren
     OpenParenthesis,
     /// https://www.w3.org/TR/css-syntax-3/#tokendef-close-paren
     CloseParenthesis,
+    /// https://www.w3.org/TR/css-syntax-3/#tokendef-open-square
+    OpenSquare,
+    /// https://www.w3.org/TR/css-syntax-3/#tokendef-close-square
+    CloseSquare,
     /// https://www.w3.org/TR/css-syntax-3/#tokendef-open-curly
     OpenCurly,
     /// https://www.w3.org/TR/css-syntax-3/#tokendef-close-curly
     CloseCurly,
     /// https://www.w3.org/TR/css-syntax-3/#typedef-ident-token
     Ident(String),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-function-token
+    /// 値は関数名. 開き括弧は含まない
+    Function(String),
     /// https://www.w3.org/TR/css-syntax-3/#typedef-string-token
     StringToken(String),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-bad-string-token
+    BadString,
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-url-token
+    Url(String),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-bad-url-token
+    BadUrl,
     /// https://www.w3.org/TR/css-syntax-3/#typedef-at-keyword-token
     AtKeyword(String),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-whitespace-token
+    Whitespace,
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-cdo-token
+    Cdo,
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-cdc-token
+    Cdc,
+    /// https://www.w3.org/TR/2014/CR-css-syntax-3-20140220/#typedef-unicode-range-token
+    UnicodeRange { start: u32, end: u32 },
 }
 
 impl Default for CssToken {
@@ -45,171 +74,559 @@ impl CssTokenizer {
     pub fn new(css: String) -> Self {
         Self {
             pos: 0,
-            input: css.chars().collect(),
+            input: Self::preprocess(&css),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#input-preprocessing
+    /// 改行コードをLFに統一し、NULL文字を置換文字（U+FFFD）に置き換える
+    fn preprocess(css: &str) -> Vec<char> {
+        let mut input = Vec::new();
+        let mut chars = css.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' => {
+                    if chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                    input.push('\n');
+                }
+                '\u{c}' => input.push('\n'),
+                '\0' => input.push('\u{fffd}'),
+                _ => input.push(c),
+            }
+        }
+
+        input
+    }
+
+    /// 現在の位置からn文字先の文字を返す（消費はしない）
+    fn peek_char(&self, n: usize) -> Option<char> {
+        self.input.get(self.pos + n).copied()
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-comments
+    /// コメント（/* */）を読み飛ばす. 閉じられていないコメントは入力の最後までとみなす
+    fn consume_comments(&mut self) {
+        while self.peek_char(0) == Some('/') && self.peek_char(1) == Some('*') {
+            self.pos += 2;
+            loop {
+                match self.peek_char(0) {
+                    Some('*') if self.peek_char(1) == Some('/') => {
+                        self.pos += 2;
+                        break;
+                    }
+                    Some(_) => self.pos += 1,
+                    None => return,
+                }
+            }
+        }
+    }
+
+    fn consume_whitespace(&mut self) {
+        while let Some(c) = self.peek_char(0) {
+            if !is_whitespace(c) {
+                break;
+            }
+            self.pos += 1;
         }
     }
 
     /// https://www.w3.org/TR/css-syntax-3/#consume-a-string-token
-    /// 入力を文字として解釈
-    fn consume_string_token(&mut self) -> String {
+    /// 入力を文字として解釈. 開始の引用符は消費済みであること
+    fn consume_string_token(&mut self, ending: char) -> CssToken {
         let mut s = String::new();
 
         loop {
-            if self.pos >= self.input.len() {
-                return s;
-            }
-
+            let c = match self.peek_char(0) {
+                Some(c) => c,
+                // パースエラー. それまでの文字列を返す
+                None => return CssToken::StringToken(s),
+            };
             self.pos += 1;
-            let c = self.input[self.pos];
+
             match c {
-                '"' | '\'' => break,
+                c if c == ending => return CssToken::StringToken(s),
+                '\n' => {
+                    // パースエラー. 改行は再消費し、不正な文字列トークンを返す
+                    self.pos -= 1;
+                    return CssToken::BadString;
+                }
+                '\\' => match self.peek_char(0) {
+                    None => {}
+                    Some('\n') => self.pos += 1,
+                    Some(_) => s.push(self.consume_escaped_code_point()),
+                },
                 _ => s.push(c),
             }
         }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-an-escaped-code-point
+    /// バックスラッシュは消費済みであること
+    fn consume_escaped_code_point(&mut self) -> char {
+        let c = match self.peek_char(0) {
+            Some(c) => c,
+            None => return '\u{fffd}',
+        };
+        self.pos += 1;
+
+        if !c.is_ascii_hexdigit() {
+            return c;
+        }
+
+        // 最大6桁の16進数を文字コードとして解釈する
+        let mut code = c.to_digit(16).unwrap_or(0);
+        let mut digits = 1;
+        while digits < 6 {
+            match self.peek_char(0) {
+                Some(h) if h.is_ascii_hexdigit() => {
+                    code = code * 16 + h.to_digit(16).unwrap_or(0);
+                    digits += 1;
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+        // 16進数の直後の空白は1つだけ読み飛ばす
+        if let Some(w) = self.peek_char(0) {
+            if is_whitespace(w) {
+                self.pos += 1;
+            }
+        }
 
-        s
+        if code == 0 {
+            return '\u{fffd}';
+        }
+        // サロゲートや範囲外の値はchar::from_u32がNoneを返す
+        char::from_u32(code).unwrap_or('\u{fffd}')
     }
 
     /// https://www.w3.org/TR/css-syntax-3/#consume-number
     /// https://www.w3.org/TR/css-syntax-3/#consume-a-numeric-token
     /// 入力を数字として解釈
-    fn consume_numeric_token(&mut self) -> f64 {
-        let mut num = 0f64;
-        let mut floating = false;
-        let mut floating_digit = 1f64;
+    fn consume_numeric_token(&mut self) -> CssToken {
+        let value = self.consume_number();
 
-        loop {
-            if self.pos >= self.input.len() {
-                return num;
+        if would_start_ident_sequence(self.peek_char(0), self.peek_char(1), self.peek_char(2)) {
+            let unit = self.consume_name();
+            return CssToken::Dimension { value, unit };
+        }
+
+        if self.peek_char(0) == Some('%') {
+            self.pos += 1;
+            return CssToken::Percentage(value);
+        }
+
+        CssToken::Number(value)
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-number
+    fn consume_number(&mut self) -> f64 {
+        let mut repr = String::new();
+
+        if let Some(c @ ('+' | '-')) = self.peek_char(0) {
+            repr.push(c);
+            self.pos += 1;
+        }
+        self.consume_digits(&mut repr);
+
+        if self.peek_char(0) == Some('.') && is_digit(self.peek_char(1)) {
+            repr.push('.');
+            self.pos += 1;
+            self.consume_digits(&mut repr);
+        }
+
+        // 指数部（1e3, 1E-3など）
+        if let Some(e @ ('e' | 'E')) = self.peek_char(0) {
+            let sign = self.peek_char(1);
+            if is_digit(sign) {
+                repr.push(e);
+                self.pos += 1;
+                self.consume_digits(&mut repr);
+            } else if matches!(sign, Some('+' | '-')) && is_digit(self.peek_char(2)) {
+                repr.push(e);
+                repr.push(sign.unwrap_or('+'));
+                self.pos += 2;
+                self.consume_digits(&mut repr);
             }
+        }
 
-            let c = self.input[self.pos];
+        repr.parse::<f64>().unwrap_or(0.0)
+    }
 
-            match c {
-                '0'..='9' => {
-                    if floating {
-                        floating_digit *= 1f64 / 10f64;
-                        num += (c.to_digit(10).unwrap() as f64) * floating_digit
-                    } else {
-                        num = num * 10.0 + (c.to_digit(10).unwrap() as f64);
-                    }
+    fn consume_digits(&mut self, repr: &mut String) {
+        while let Some(c) = self.peek_char(0) {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            repr.push(c);
+            self.pos += 1;
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-name
+    /// 入力を名前として解釈
+    fn consume_name(&mut self) -> String {
+        let mut s = String::new();
+
+        loop {
+            match self.peek_char(0) {
+                Some(c) if is_ident_code_point(c) => {
+                    s.push(c);
                     self.pos += 1;
                 }
-                '.' => {
-                    floating = true;
+                Some('\\') if is_valid_escape(Some('\\'), self.peek_char(1)) => {
                     self.pos += 1;
+                    s.push(self.consume_escaped_code_point());
                 }
-                _ => break,
+                _ => return s,
             }
         }
-        num
     }
 
-    /// https://www.w3.org/TR/css-syntax-3/#consume-ident-like-token
-    /// https://www.w3.org/TR/css-syntax-3/#consume-name
-    /// 入力を識別子として解釈
-    fn consume_ident_token(&mut self) -> String {
-        let mut s = String::new();
-        s.push(self.input[self.pos]);
+    /// https://www.w3.org/TR/css-syntax-3/#consume-an-ident-like-token
+    /// 入力を識別子、関数、URLのいずれかとして解釈
+    fn consume_ident_like_token(&mut self) -> CssToken {
+        let name = self.consume_name();
+
+        if self.peek_char(0) != Some('(') {
+            return CssToken::Ident(name);
+        }
+        self.pos += 1;
+
+        if !name.eq_ignore_ascii_case("url") {
+            return CssToken::Function(name);
+        }
+
+        // url( の後に引用符で囲まれた文字列が続く場合は、通常の関数として扱う
+        while is_whitespace_char(self.peek_char(0)) && is_whitespace_char(self.peek_char(1)) {
+            self.pos += 1;
+        }
+        let next = self.peek_char(0);
+        let quoted = |c: Option<char>| matches!(c, Some('"' | '\''));
+        if quoted(next) || (is_whitespace_char(next) && quoted(self.peek_char(1))) {
+            return CssToken::Function(name);
+        }
+
+        self.consume_url_token()
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-a-url-token
+    fn consume_url_token(&mut self) -> CssToken {
+        let mut url = String::new();
+        self.consume_whitespace();
 
         loop {
+            let c = match self.peek_char(0) {
+                Some(c) => c,
+                // パースエラー. それまでのURLを返す
+                None => return CssToken::Url(url),
+            };
             self.pos += 1;
-            let c = self.input[self.pos];
+
             match c {
-                'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => {
-                    s.push(c);
+                ')' => return CssToken::Url(url),
+                c if is_whitespace(c) => {
+                    self.consume_whitespace();
+                    match self.peek_char(0) {
+                        Some(')') => {
+                            self.pos += 1;
+                            return CssToken::Url(url);
+                        }
+                        None => return CssToken::Url(url),
+                        Some(_) => {
+                            self.consume_remnants_of_bad_url();
+                            return CssToken::BadUrl;
+                        }
+                    }
+                }
+                '"' | '\'' | '(' => {
+                    self.consume_remnants_of_bad_url();
+                    return CssToken::BadUrl;
+                }
+                c if is_non_printable(c) => {
+                    self.consume_remnants_of_bad_url();
+                    return CssToken::BadUrl;
+                }
+                '\\' => {
+                    if is_valid_escape(Some('\\'), self.peek_char(0)) {
+                        url.push(self.consume_escaped_code_point());
+                    } else {
+                        self.consume_remnants_of_bad_url();
+                        return CssToken::BadUrl;
+                    }
+                }
+                _ => url.push(c),
+            }
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-the-remnants-of-a-bad-url
+    fn consume_remnants_of_bad_url(&mut self) {
+        loop {
+            let c = match self.peek_char(0) {
+                Some(c) => c,
+                None => return,
+            };
+            self.pos += 1;
+
+            match c {
+                ')' => return,
+                '\\' if is_valid_escape(Some('\\'), self.peek_char(0)) => {
+                    self.consume_escaped_code_point();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// https://www.w3.org/TR/2014/CR-css-syntax-3-20140220/#consume-a-unicode-range-token
+    /// "U+"は消費済みであること
+    fn consume_unicode_range_token(&mut self) -> CssToken {
+        let mut start = String::new();
+        while start.len() < 6 {
+            match self.peek_char(0) {
+                Some(c) if c.is_ascii_hexdigit() => {
+                    start.push(c);
+                    self.pos += 1;
                 }
                 _ => break,
             }
         }
 
-        s
+        // ワイルドカード（U+4??など）は範囲として扱う
+        let mut has_question_mark = false;
+        while start.len() < 6 && self.peek_char(0) == Some('?') {
+            start.push('?');
+            has_question_mark = true;
+            self.pos += 1;
+        }
+        if has_question_mark {
+            return CssToken::UnicodeRange {
+                start: parse_hex(&start.replace('?', "0")),
+                end: parse_hex(&start.replace('?', "F")),
+            };
+        }
+
+        let start = parse_hex(&start);
+        if self.peek_char(0) == Some('-')
+            && self.peek_char(1).map_or(false, |c| c.is_ascii_hexdigit())
+        {
+            self.pos += 1;
+            let mut end = String::new();
+            while end.len() < 6 {
+                match self.peek_char(0) {
+                    Some(c) if c.is_ascii_hexdigit() => {
+                        end.push(c);
+                        self.pos += 1;
+                    }
+                    _ => break,
+                }
+            }
+            return CssToken::UnicodeRange {
+                start,
+                end: parse_hex(&end),
+            };
+        }
+
+        CssToken::UnicodeRange { start, end: start }
     }
 }
 
 impl Iterator for CssTokenizer {
     type Item = CssToken;
 
-    /// https://www.w3.org/TR/css-syntax-3/#consume-a-string-token
+    /// https://www.w3.org/TR/css-syntax-3/#consume-token
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if self.pos >= self.input.len() {
-                return None;
-            }
+        self.consume_comments();
 
-            let c = self.input[self.pos];
-
-            let token = match c {
-                '(' => CssToken::OpenParenthesis,
-                ')' => CssToken::CloseParenthesis,
-                ',' => CssToken::Delim(','),
-                '.' => CssToken::Delim('.'),
-                ':' => CssToken::Colon,
-                ';' => CssToken::SemiColon,
-                '{' => CssToken::OpenCurly,
-                '}' => CssToken::CloseCurly,
-                ' ' | '\n' => {
-                    self.pos += 1;
-                    continue;
-                }
-                '"' | '\'' => {
-                    let value = self.consume_string_token();
-                    CssToken::StringToken(value)
+        let c = self.peek_char(0)?;
+        self.pos += 1;
+
+        let token = match c {
+            c if is_whitespace(c) => {
+                self.consume_whitespace();
+                CssToken::Whitespace
+            }
+            '"' | '\'' => self.consume_string_token(c),
+            '#' => {
+                // 後続が名前として解釈できる場合はハッシュトークン、そうでなければ記号トークン
+                if self.peek_char(0).map_or(false, is_ident_code_point)
+                    || is_valid_escape(self.peek_char(0), self.peek_char(1))
+                {
+                    let mut value = String::from("#");
+                    value.push_str(&self.consume_name());
+                    CssToken::HashToken(value)
+                } else {
+                    CssToken::Delim('#')
                 }
-                '0'..='9' => {
-                    let t = CssToken::Number(self.consume_numeric_token());
+            }
+            '(' => CssToken::OpenParenthesis,
+            ')' => CssToken::CloseParenthesis,
+            '+' | '.' => {
+                if would_start_number(Some(c), self.peek_char(0), self.peek_char(1)) {
                     self.pos -= 1;
-                    t
+                    self.consume_numeric_token()
+                } else {
+                    CssToken::Delim(c)
                 }
-                '#' => {
-                    // ハッシュタグ（#） = IDセレクタとして扱う
-                    // 本来はハッシュタグの後に続く文字によってハッシュトークンor記号トークンを返すが、今回は常にハッシュトークンで固定する
-                    let value = self.consume_ident_token();
+            }
+            ',' => CssToken::Comma,
+            '-' => {
+                if would_start_number(Some(c), self.peek_char(0), self.peek_char(1)) {
                     self.pos -= 1;
-                    CssToken::HashToken(value)
-                }
-                '-' => {
-                    // 負の値は取り扱わないので、ハイフン = 識別子としてのみ扱う
-                    // 本来はハイフンの後に続く文字によって数字トークン、コメントアウト終了トークン、識別子トークン、記号トークンなどを返すが、今回は常にIdentで固定する
-                    let t = CssToken::Ident(self.consume_ident_token());
+                    self.consume_numeric_token()
+                } else if self.peek_char(0) == Some('-') && self.peek_char(1) == Some('>') {
+                    self.pos += 2;
+                    CssToken::Cdc
+                } else if would_start_ident_sequence(Some(c), self.peek_char(0), self.peek_char(1))
+                {
                     self.pos -= 1;
-                    t
+                    self.consume_ident_like_token()
+                } else {
+                    CssToken::Delim('-')
                 }
-                '@' => {
-                    if self.input[self.pos + 1].is_ascii_alphabetic()
-                        && self.input[self.pos + 2].is_alphabetic()
-                        && self.input[self.pos + 3].is_alphabetic()
-                    {
-                        // skip '@'
-                        self.pos += 1;
-                        let t = CssToken::AtKeyword(self.consume_ident_token());
-                        self.pos -= 1;
-                        t
-                    } else {
-                        CssToken::Delim('@')
-                    }
+            }
+            ':' => CssToken::Colon,
+            ';' => CssToken::SemiColon,
+            '<' => {
+                if self.peek_char(0) == Some('!')
+                    && self.peek_char(1) == Some('-')
+                    && self.peek_char(2) == Some('-')
+                {
+                    self.pos += 3;
+                    CssToken::Cdo
+                } else {
+                    CssToken::Delim('<')
                 }
-                'a'..='z' | 'A'..='Z' | '_' => {
-                    // 小文字、大文字、_ならIdentを返す
-                    let t = CssToken::Ident(self.consume_ident_token());
-                    self.pos -= 1;
-                    t
+            }
+            '@' => {
+                if would_start_ident_sequence(
+                    self.peek_char(0),
+                    self.peek_char(1),
+                    self.peek_char(2),
+                ) {
+                    CssToken::AtKeyword(self.consume_name())
+                } else {
+                    CssToken::Delim('@')
                 }
-                _ => {
-                    unimplemented!("char {} is not supported yet", c);
+            }
+            '[' => CssToken::OpenSquare,
+            ']' => CssToken::CloseSquare,
+            '{' => CssToken::OpenCurly,
+            '}' => CssToken::CloseCurly,
+            '\\' => {
+                if is_valid_escape(Some(c), self.peek_char(0)) {
+                    self.pos -= 1;
+                    self.consume_ident_like_token()
+                } else {
+                    // パースエラー
+                    CssToken::Delim('\\')
                 }
-            };
+            }
+            '0'..='9' => {
+                self.pos -= 1;
+                self.consume_numeric_token()
+            }
+            'u' | 'U'
+                if self.peek_char(0) == Some('+')
+                    && self
+                        .peek_char(1)
+                        .map_or(false, |c| c.is_ascii_hexdigit() || c == '?') =>
+            {
+                self.pos += 1;
+                self.consume_unicode_range_token()
+            }
+            c if is_ident_start_code_point(c) => {
+                self.pos -= 1;
+                self.consume_ident_like_token()
+            }
+            _ => CssToken::Delim(c),
+        };
 
-            self.pos += 1;
-            return Some(token);
+        Some(token)
+    }
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#whitespace
+fn is_whitespace(c: char) -> bool {
+    c == '\n' || c == '\t' || c == ' '
+}
+
+fn is_whitespace_char(c: Option<char>) -> bool {
+    c.map_or(false, is_whitespace)
+}
+
+fn is_digit(c: Option<char>) -> bool {
+    c.map_or(false, |c| c.is_ascii_digit())
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#ident-start-code-point
+fn is_ident_start_code_point(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c >= '\u{80}'
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#ident-code-point
+fn is_ident_code_point(c: char) -> bool {
+    is_ident_start_code_point(c) || c.is_ascii_digit() || c == '-'
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#non-printable-code-point
+fn is_non_printable(c: char) -> bool {
+    matches!(c, '\u{0}'..='\u{8}' | '\u{b}' | '\u{e}'..='\u{1f}' | '\u{7f}')
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#starts-with-a-valid-escape
+fn is_valid_escape(first: Option<char>, second: Option<char>) -> bool {
+    first == Some('\\') && second != Some('\n')
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#would-start-an-identifier
+fn would_start_ident_sequence(
+    first: Option<char>,
+    second: Option<char>,
+    third: Option<char>,
+) -> bool {
+    match first {
+        Some('-') => {
+            second.map_or(false, |c| is_ident_start_code_point(c) || c == '-')
+                || is_valid_escape(second, third)
         }
+        Some('\\') => is_valid_escape(first, second),
+        Some(c) => is_ident_start_code_point(c),
+        None => false,
+    }
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#starts-with-a-number
+fn would_start_number(first: Option<char>, second: Option<char>, third: Option<char>) -> bool {
+    match first {
+        Some('+' | '-') => is_digit(second) || (second == Some('.') && is_digit(third)),
+        Some('.') => is_digit(second),
+        Some(c) => c.is_ascii_digit(),
+        None => false,
     }
 }
 
+fn parse_hex(s: &str) -> u32 {
+    u32::from_str_radix(s, 16).unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use alloc::string::ToString;
+    use alloc::vec;
+
+    fn tokenize(css: &str) -> Vec<CssToken> {
+        CssTokenizer::new(css.to_string()).collect()
+    }
+
+    fn ident(s: &str) -> CssToken {
+        CssToken::Ident(s.to_string())
+    }
 
     #[test]
     fn test_empty() {
@@ -226,11 +643,15 @@ mod tests {
         let mut t = CssTokenizer::new(style);
         let expected = [
             CssToken::Ident("p".to_string()),
+            CssToken::Whitespace,
             CssToken::OpenCurly,
+            CssToken::Whitespace,
             CssToken::Ident("color".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::Ident("red".to_string()),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::CloseCurly,
         ];
         for e in expected {
@@ -246,11 +667,15 @@ mod tests {
         let mut t = CssTokenizer::new(style);
         let expected = [
             CssToken::HashToken("#id".to_string()),
+            CssToken::Whitespace,
             CssToken::OpenCurly,
+            CssToken::Whitespace,
             CssToken::Ident("color".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::Ident("red".to_string()),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::CloseCurly,
         ];
         for e in expected {
@@ -267,11 +692,15 @@ mod tests {
         let expected = [
             CssToken::Delim('.'),
             CssToken::Ident("class".to_string()),
+            CssToken::Whitespace,
             CssToken::OpenCurly,
+            CssToken::Whitespace,
             CssToken::Ident("color".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::Ident("red".to_string()),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::CloseCurly,
         ];
         for e in expected {
@@ -287,22 +716,33 @@ mod tests {
         let mut t = CssTokenizer::new(style);
         let expected = [
             CssToken::Ident("p".to_string()),
+            CssToken::Whitespace,
             CssToken::OpenCurly,
+            CssToken::Whitespace,
             CssToken::Ident("content".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::StringToken("Hey".to_string()),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::CloseCurly,
+            CssToken::Whitespace,
             CssToken::Ident("h1".to_string()),
+            CssToken::Whitespace,
             CssToken::OpenCurly,
+            CssToken::Whitespace,
             CssToken::Ident("font-size".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::Number(40.0),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::Ident("color".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::Ident("blue".to_string()),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::CloseCurly,
         ];
         for e in expected {
@@ -310,4 +750,406 @@ mod tests {
         }
         assert!(t.next().is_none());
     }
+
+    /*
+    以下のテストケースは css-parsing-tests（https://github.com/SimonSapin/css-parsing-tests）の
+    component_value_list.json などから抜粋したもの
+    */
+
+    #[test]
+    fn test_comments() {
+        /* コメントは読み飛ばされ、閉じられていないコメントは入力の最後まで続くことを確認する */
+        assert_eq!(
+            tokenize("/*/*///** /* **/*//* "),
+            vec![
+                CssToken::Delim('/'),
+                CssToken::Delim('*'),
+                CssToken::Delim('/')
+            ]
+        );
+        assert_eq!(
+            tokenize("a/* comment */b /* unclosed"),
+            vec![ident("a"), ident("b"), CssToken::Whitespace]
+        );
+    }
+
+    #[test]
+    fn test_whitespace() {
+        /* タブ、CR、改ページを含む連続した空白が、1つの空白トークンになることを確認する */
+        assert_eq!(
+            tokenize("a \t\r\n\u{c}  b"),
+            vec![ident("a"), CssToken::Whitespace, ident("b")]
+        );
+    }
+
+    #[test]
+    fn test_idents_and_escapes() {
+        /* エスケープを含む識別子が正しく解釈されることを確認する */
+        assert_eq!(
+            tokenize("red0 -red --red -\\-red\\ blue 0red -0red \u{0}red _Red .red rêd r\\êd \u{7f}\u{80}\u{81}"),
+            vec![
+                ident("red0"),
+                CssToken::Whitespace,
+                ident("-red"),
+                CssToken::Whitespace,
+                ident("--red"),
+                CssToken::Whitespace,
+                ident("--red blue"),
+                CssToken::Whitespace,
+                CssToken::Dimension {
+                    value: 0.0,
+                    unit: "red".to_string()
+                },
+                CssToken::Whitespace,
+                CssToken::Dimension {
+                    value: -0.0,
+                    unit: "red".to_string()
+                },
+                CssToken::Whitespace,
+                ident("\u{fffd}red"),
+                CssToken::Whitespace,
+                ident("_Red"),
+                CssToken::Whitespace,
+                CssToken::Delim('.'),
+                ident("red"),
+                CssToken::Whitespace,
+                ident("rêd"),
+                CssToken::Whitespace,
+                ident("rêd"),
+                CssToken::Whitespace,
+                CssToken::Delim('\u{7f}'),
+                ident("\u{80}\u{81}"),
+            ]
+        );
+        assert_eq!(
+            tokenize("\\30 red \\00030 red \\30red \\0 \\110000 \\d800"),
+            vec![
+                ident("0red"),
+                CssToken::Whitespace,
+                ident("0red"),
+                CssToken::Whitespace,
+                ident("0red"),
+                CssToken::Whitespace,
+                ident("\u{fffd}\u{fffd}\u{fffd}"),
+            ]
+        );
+        assert_eq!(
+            tokenize("\\\n"),
+            vec![CssToken::Delim('\\'), CssToken::Whitespace]
+        );
+    }
+
+    #[test]
+    fn test_functions() {
+        /* 識別子の直後に括弧が続く場合、関数トークンになることを確認する */
+        assert_eq!(
+            tokenize("rgba0() -rgba() --rgba() -\\-rgba() 0rgba() rgba ()"),
+            vec![
+                CssToken::Function("rgba0".to_string()),
+                CssToken::CloseParenthesis,
+                CssToken::Whitespace,
+                CssToken::Function("-rgba".to_string()),
+                CssToken::CloseParenthesis,
+                CssToken::Whitespace,
+                CssToken::Function("--rgba".to_string()),
+                CssToken::CloseParenthesis,
+                CssToken::Whitespace,
+                CssToken::Function("--rgba".to_string()),
+                CssToken::CloseParenthesis,
+                CssToken::Whitespace,
+                CssToken::Dimension {
+                    value: 0.0,
+                    unit: "rgba".to_string()
+                },
+                CssToken::OpenParenthesis,
+                CssToken::CloseParenthesis,
+                CssToken::Whitespace,
+                ident("rgba"),
+                CssToken::Whitespace,
+                CssToken::OpenParenthesis,
+                CssToken::CloseParenthesis,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_at_keywords() {
+        /* @の後に識別子が続く場合のみ、@キーワードトークンになることを確認する */
+        assert_eq!(
+            tokenize(
+                "@media0 @-Media @--media @0media @-0media @_media @.media @medİa @\\30 media\\"
+            ),
+            vec![
+                CssToken::AtKeyword("media0".to_string()),
+                CssToken::Whitespace,
+                CssToken::AtKeyword("-Media".to_string()),
+                CssToken::Whitespace,
+                CssToken::AtKeyword("--media".to_string()),
+                CssToken::Whitespace,
+                CssToken::Delim('@'),
+                CssToken::Dimension {
+                    value: 0.0,
+                    unit: "media".to_string()
+                },
+                CssToken::Whitespace,
+                CssToken::Delim('@'),
+                CssToken::Dimension {
+                    value: -0.0,
+                    unit: "media".to_string()
+                },
+                CssToken::Whitespace,
+                CssToken::AtKeyword("_media".to_string()),
+                CssToken::Whitespace,
+                CssToken::Delim('@'),
+                CssToken::Delim('.'),
+                ident("media"),
+                CssToken::Whitespace,
+                CssToken::AtKeyword("medİa".to_string()),
+                CssToken::Whitespace,
+                CssToken::AtKeyword("0media\u{fffd}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash() {
+        /* #の後に名前が続く場合のみ、ハッシュトークンになることを確認する */
+        assert_eq!(
+            tokenize("#red0 #-Red #--red #-\\-red #0red #-0red #_Red #.red #rêd #\\.red\\"),
+            vec![
+                CssToken::HashToken("#red0".to_string()),
+                CssToken::Whitespace,
+                CssToken::HashToken("#-Red".to_string()),
+                CssToken::Whitespace,
+                CssToken::HashToken("#--red".to_string()),
+                CssToken::Whitespace,
+                CssToken::HashToken("#--red".to_string()),
+                CssToken::Whitespace,
+                CssToken::HashToken("#0red".to_string()),
+                CssToken::Whitespace,
+                CssToken::HashToken("#-0red".to_string()),
+                CssToken::Whitespace,
+                CssToken::HashToken("#_Red".to_string()),
+                CssToken::Whitespace,
+                CssToken::Delim('#'),
+                CssToken::Delim('.'),
+                ident("red"),
+                CssToken::Whitespace,
+                CssToken::HashToken("#rêd".to_string()),
+                CssToken::Whitespace,
+                CssToken::HashToken("#.red\u{fffd}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strings() {
+        /* 文字列内のエスケープ、改行による不正な文字列、閉じられていない文字列を確認する */
+        assert_eq!(
+            tokenize("p:before { content: 'a\\\nb' \"\\\"c\\41 \" }"),
+            vec![
+                ident("p"),
+                CssToken::Colon,
+                ident("before"),
+                CssToken::Whitespace,
+                CssToken::OpenCurly,
+                CssToken::Whitespace,
+                ident("content"),
+                CssToken::Colon,
+                CssToken::Whitespace,
+                CssToken::StringToken("ab".to_string()),
+                CssToken::Whitespace,
+                CssToken::StringToken("\"cA".to_string()),
+                CssToken::Whitespace,
+                CssToken::CloseCurly,
+            ]
+        );
+        assert_eq!(
+            tokenize("'bad\nstring' \"unclosed\\"),
+            vec![
+                CssToken::BadString,
+                CssToken::Whitespace,
+                ident("string"),
+                CssToken::StringToken(" \"unclosed".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_urls() {
+        /* url()がURLトークン、不正なURLトークン、関数トークンに正しく分類されることを確認する */
+        assert_eq!(
+            tokenize("url(foo.png) URL( \"quoted\" ) url(  bar.png  ) url(a b) url(a\"b) url(a\\)b) url(\\\n)x url(unclosed"),
+            vec![
+                CssToken::Url("foo.png".to_string()),
+                CssToken::Whitespace,
+                CssToken::Function("URL".to_string()),
+                CssToken::Whitespace,
+                CssToken::StringToken("quoted".to_string()),
+                CssToken::Whitespace,
+                CssToken::CloseParenthesis,
+                CssToken::Whitespace,
+                CssToken::Url("bar.png".to_string()),
+                CssToken::Whitespace,
+                CssToken::BadUrl,
+                CssToken::Whitespace,
+                CssToken::BadUrl,
+                CssToken::Whitespace,
+                CssToken::Url("a)b".to_string()),
+                CssToken::Whitespace,
+                CssToken::BadUrl,
+                ident("x"),
+                CssToken::Whitespace,
+                CssToken::Url("unclosed".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        /* 数値、パーセンテージ、寸法が正しく解釈されることを確認する */
+        assert_eq!(
+            tokenize("12 +34 -45 .67 +.89 -.01 2.3 +45.0 -0.67 1e2 1E+2 2e-1 3e 4e+ 50% 6px -7.5em 8\\70 x"),
+            vec![
+                CssToken::Number(12.0),
+                CssToken::Whitespace,
+                CssToken::Number(34.0),
+                CssToken::Whitespace,
+                CssToken::Number(-45.0),
+                CssToken::Whitespace,
+                CssToken::Number(0.67),
+                CssToken::Whitespace,
+                CssToken::Number(0.89),
+                CssToken::Whitespace,
+                CssToken::Number(-0.01),
+                CssToken::Whitespace,
+                CssToken::Number(2.3),
+                CssToken::Whitespace,
+                CssToken::Number(45.0),
+                CssToken::Whitespace,
+                CssToken::Number(-0.67),
+                CssToken::Whitespace,
+                CssToken::Number(100.0),
+                CssToken::Whitespace,
+                CssToken::Number(100.0),
+                CssToken::Whitespace,
+                CssToken::Number(0.2),
+                CssToken::Whitespace,
+                CssToken::Dimension {
+                    value: 3.0,
+                    unit: "e".to_string()
+                },
+                CssToken::Whitespace,
+                CssToken::Dimension {
+                    value: 4.0,
+                    unit: "e".to_string()
+                },
+                CssToken::Delim('+'),
+                CssToken::Whitespace,
+                CssToken::Percentage(50.0),
+                CssToken::Whitespace,
+                CssToken::Dimension {
+                    value: 6.0,
+                    unit: "px".to_string()
+                },
+                CssToken::Whitespace,
+                CssToken::Dimension {
+                    value: -7.5,
+                    unit: "em".to_string()
+                },
+                CssToken::Whitespace,
+                CssToken::Dimension {
+                    value: 8.0,
+                    unit: "px".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            tokenize("1.5. +. -.a"),
+            vec![
+                CssToken::Number(1.5),
+                CssToken::Delim('.'),
+                CssToken::Whitespace,
+                CssToken::Delim('+'),
+                CssToken::Delim('.'),
+                CssToken::Whitespace,
+                CssToken::Delim('-'),
+                CssToken::Delim('.'),
+                ident("a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_delimiters_and_blocks() {
+        /* 括弧類、CDO/CDC、その他の記号が正しく解釈されることを確認する */
+        assert_eq!(
+            tokenize("<!-- [a]{b}(c), ! ~ | -->"),
+            vec![
+                CssToken::Cdo,
+                CssToken::Whitespace,
+                CssToken::OpenSquare,
+                ident("a"),
+                CssToken::CloseSquare,
+                CssToken::OpenCurly,
+                ident("b"),
+                CssToken::CloseCurly,
+                CssToken::OpenParenthesis,
+                ident("c"),
+                CssToken::CloseParenthesis,
+                CssToken::Comma,
+                CssToken::Whitespace,
+                CssToken::Delim('!'),
+                CssToken::Whitespace,
+                CssToken::Delim('~'),
+                CssToken::Whitespace,
+                CssToken::Delim('|'),
+                CssToken::Whitespace,
+                CssToken::Cdc,
+            ]
+        );
+        assert_eq!(
+            tokenize("<! -> <!-"),
+            vec![
+                CssToken::Delim('<'),
+                CssToken::Delim('!'),
+                CssToken::Whitespace,
+                CssToken::Delim('-'),
+                CssToken::Delim('>'),
+                CssToken::Whitespace,
+                CssToken::Delim('<'),
+                CssToken::Delim('!'),
+                CssToken::Delim('-'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unicode_range() {
+        /* unicode-rangeの単一値、範囲、ワイルドカードが正しく解釈されることを確認する */
+        assert_eq!(
+            tokenize("U+26 u+0-7F U+4?? U+1F600-1F64F"),
+            vec![
+                CssToken::UnicodeRange {
+                    start: 0x26,
+                    end: 0x26
+                },
+                CssToken::Whitespace,
+                CssToken::UnicodeRange {
+                    start: 0x0,
+                    end: 0x7f
+                },
+                CssToken::Whitespace,
+                CssToken::UnicodeRange {
+                    start: 0x400,
+                    end: 0x4ff
+                },
+                CssToken::Whitespace,
+                CssToken::UnicodeRange {
+                    start: 0x1f600,
+                    end: 0x1f64f
+                },
+            ]
+        );
+    }
 }
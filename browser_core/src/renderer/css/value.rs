@@ -0,0 +1,704 @@
+use crate::constants::CONTENT_AREA_HEIGHT;
+use crate::constants::CONTENT_AREA_WIDTH;
+use crate::error::Error;
+use crate::renderer::css::calc::is_math_function;
+use crate::renderer::css::calc::CalcNode;
+use crate::renderer::css::calc::CalcType;
+use crate::renderer::css::color::Color;
+use crate::renderer::css::cssom::find_block_end;
+use crate::renderer::css::cssom::split_by_comma;
+use crate::renderer::css::cssom::trim_whitespace;
+use crate::renderer::css::cssom::ComponentValue;
+use crate::renderer::css::serialize::serialize_identifier;
+use crate::renderer::css::serialize::serialize_number;
+use crate::renderer::css::serialize::serialize_string;
+use crate::renderer::css::serialize::serialize_url;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// 基準となるフォントサイズ（medium）
+pub const DEFAULT_FONT_SIZE: f64 = 16.0;
+
+/// https://www.w3.org/TR/css-values-4/#common-keywords
+/// すべてのプロパティが値として取れるキーワード
+pub fn is_css_wide_keyword(keyword: &str) -> bool {
+    ["inherit", "initial", "unset", "revert"]
+        .iter()
+        .any(|k| k.eq_ignore_ascii_case(keyword))
+}
+
+/// https://www.w3.org/TR/css-values-4/#lengths
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LengthUnit {
+    /// https://www.w3.org/TR/css-values-4/#px
+    Px,
+    /// https://www.w3.org/TR/css-values-4/#em
+    Em,
+    /// https://www.w3.org/TR/css-values-4/#rem
+    Rem,
+    /// https://www.w3.org/TR/css-values-4/#vw
+    Vw,
+    /// https://www.w3.org/TR/css-values-4/#vh
+    Vh,
+    /// https://www.w3.org/TR/css-values-4/#cm
+    Cm,
+    /// https://www.w3.org/TR/css-values-4/#mm
+    Mm,
+    /// https://www.w3.org/TR/css-values-4/#in
+    In,
+    /// https://www.w3.org/TR/css-values-4/#pt
+    Pt,
+    /// https://www.w3.org/TR/css-values-4/#pc
+    Pc,
+}
+
+impl LengthUnit {
+    pub fn from_unit(s: &str) -> Result<Self, Error> {
+        match s.to_ascii_lowercase().as_str() {
+            "px" => Ok(Self::Px),
+            "em" => Ok(Self::Em),
+            "rem" => Ok(Self::Rem),
+            "vw" => Ok(Self::Vw),
+            "vh" => Ok(Self::Vh),
+            "cm" => Ok(Self::Cm),
+            "mm" => Ok(Self::Mm),
+            "in" => Ok(Self::In),
+            "pt" => Ok(Self::Pt),
+            "pc" => Ok(Self::Pc),
+            _ => Err(Error::UnexpectedInput(format!(
+                "length unit {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Px => "px",
+            Self::Em => "em",
+            Self::Rem => "rem",
+            Self::Vw => "vw",
+            Self::Vh => "vh",
+            Self::Cm => "cm",
+            Self::Mm => "mm",
+            Self::In => "in",
+            Self::Pt => "pt",
+            Self::Pc => "pc",
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-values-4/#length-value
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Length {
+    value: f64,
+    unit: LengthUnit,
+}
+
+impl Length {
+    pub fn new(value: f64, unit: LengthUnit) -> Self {
+        Self { value, unit }
+    }
+
+    pub fn px(value: f64) -> Self {
+        Self::new(value, LengthUnit::Px)
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn unit(&self) -> LengthUnit {
+        self.unit
+    }
+
+    /// https://www.w3.org/TR/css-values-4/#absolute-lengths
+    /// https://www.w3.org/TR/css-values-4/#relative-lengths
+    /// 長さを絶対的なピクセル値に変換する
+    pub fn to_px(&self, context: &ResolutionContext) -> f64 {
+        match self.unit {
+            LengthUnit::Px => self.value,
+            LengthUnit::Em => self.value * context.font_size(),
+            LengthUnit::Rem => self.value * context.root_font_size(),
+            LengthUnit::Vw => self.value * context.viewport_width() / 100.0,
+            LengthUnit::Vh => self.value * context.viewport_height() / 100.0,
+            // 1in = 2.54cm = 96px
+            LengthUnit::Cm => self.value * 96.0 / 2.54,
+            LengthUnit::Mm => self.value * 96.0 / 25.4,
+            LengthUnit::In => self.value * 96.0,
+            // 1pt = 1/72in、1pc = 12pt
+            LengthUnit::Pt => self.value * 96.0 / 72.0,
+            LengthUnit::Pc => self.value * 96.0 / 6.0,
+        }
+    }
+
+    pub fn css_text(&self) -> String {
+        format!("{}{}", serialize_number(self.value), self.unit.as_str())
+    }
+}
+
+/// 相対的な長さを解決するために必要な情報
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ResolutionContext {
+    /// 要素自身のフォントサイズ（font-sizeの場合は親要素のフォントサイズ）
+    font_size: f64,
+    /// ルート要素のフォントサイズ
+    root_font_size: f64,
+    viewport_width: f64,
+    viewport_height: f64,
+}
+
+impl ResolutionContext {
+    pub fn new(
+        font_size: f64,
+        root_font_size: f64,
+        viewport_width: f64,
+        viewport_height: f64,
+    ) -> Self {
+        Self {
+            font_size,
+            root_font_size,
+            viewport_width,
+            viewport_height,
+        }
+    }
+
+    pub fn font_size(&self) -> f64 {
+        self.font_size
+    }
+
+    pub fn set_font_size(&mut self, font_size: f64) {
+        self.font_size = font_size;
+    }
+
+    pub fn root_font_size(&self) -> f64 {
+        self.root_font_size
+    }
+
+    pub fn viewport_width(&self) -> f64 {
+        self.viewport_width
+    }
+
+    pub fn viewport_height(&self) -> f64 {
+        self.viewport_height
+    }
+}
+
+impl Default for ResolutionContext {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_FONT_SIZE,
+            DEFAULT_FONT_SIZE,
+            CONTENT_AREA_WIDTH as f64,
+            CONTENT_AREA_HEIGHT as f64,
+        )
+    }
+}
+
+/// https://www.w3.org/TR/css-values-4/
+/// 宣言の値をトークン列から型付きの値にしたもの（指定値）
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssValue {
+    /// https://www.w3.org/TR/css-values-4/#keywords
+    /// キーワードはASCIIの大文字・小文字を区別しないので、小文字で保持する
+    Keyword(String),
+    Length(Length),
+    /// https://www.w3.org/TR/css-values-4/#percentages
+    Percentage(f64),
+    /// https://www.w3.org/TR/css-values-4/#numbers
+    Number(f64),
+    /// https://www.w3.org/TR/css-grid-2/#fr-unit
+    /// グリッドのトラックの大きさに使う、余白の配分の比率（`fr`単位）
+    Flex(f64),
+    /// https://www.w3.org/TR/css-color-4/#color-type
+    /// 16進数表記と色関数の色. 名前付きの色やcurrentcolorはキーワードとして保持する
+    Color(Color),
+    /// https://www.w3.org/TR/css-values-4/#strings
+    String(String),
+    /// https://www.w3.org/TR/css-values-4/#urls
+    Url(String),
+    /// https://www.w3.org/TR/css-values-4/#math
+    /// calc()、min()、max()、clamp()の計算式
+    Calc(CalcNode),
+    /// https://www.w3.org/TR/css-values-5/#attr-notation
+    /// 要素の属性の値. 属性名は小文字で保持する
+    Attr(String),
+    /// https://www.w3.org/TR/css-lists-3/#counter-functions
+    /// counter()とcounters(). counters()の場合は`separator`に入れ子のカウンターの区切り文字を持つ
+    /// カウンター名はキーワードと同じく小文字で保持する
+    Counter {
+        name: String,
+        separator: Option<String>,
+        style: Option<String>,
+    },
+    /// https://www.w3.org/TR/css-grid-2/#valdef-grid-template-columns-minmax
+    /// minmax(). トラックの大きさの最小値と最大値
+    MinMax(Box<CssValue>, Box<CssValue>),
+    /// https://www.w3.org/TR/css-grid-2/#repeat-notation
+    /// repeat(). 繰り返す回数（整数またはauto-fill）と、繰り返すトラックのリスト
+    Repeat(Box<CssValue>, Box<CssValue>),
+    /// 空白区切りの値のリスト（例：`margin: 10px 20px`）
+    List(Vec<CssValue>),
+    /// カンマ区切りの値のリスト（例：`font-family: Arial, sans-serif`）
+    CommaList(Vec<CssValue>),
+}
+
+impl CssValue {
+    /// 宣言の値のトークン列をパースする
+    pub fn parse(values: &[ComponentValue]) -> Result<Self, Error> {
+        let values = trim_whitespace(values);
+
+        let groups = split_by_comma(values);
+        if groups.len() > 1 {
+            let list = groups
+                .into_iter()
+                .map(Self::parse_space_separated)
+                .collect::<Result<Vec<CssValue>, Error>>()?;
+            return Ok(CssValue::CommaList(list));
+        }
+
+        Self::parse_space_separated(values)
+    }
+
+    fn parse_space_separated(values: &[ComponentValue]) -> Result<Self, Error> {
+        let mut list = Vec::new();
+        let mut i = 0;
+        while i < values.len() {
+            match &values[i] {
+                ComponentValue::Whitespace => {}
+                ComponentValue::Function(name) => {
+                    let end = find_block_end(values, i).ok_or(Error::UnexpectedInput(format!(
+                        "function {}() is not closed",
+                        name
+                    )))?;
+                    list.push(Self::parse_function(name, &values[i + 1..end])?);
+                    i = end;
+                }
+                value => list.push(Self::parse_component_value(value)?),
+            }
+            i += 1;
+        }
+
+        match list.len() {
+            0 => Err(Error::UnexpectedInput("empty value".to_string())),
+            1 => Ok(list.remove(0)),
+            _ => Ok(CssValue::List(list)),
+        }
+    }
+
+    fn parse_function(name: &str, arguments: &[ComponentValue]) -> Result<Self, Error> {
+        match name.to_ascii_lowercase().as_str() {
+            "rgb" | "rgba" | "hsl" | "hsla" => {
+                Ok(CssValue::Color(Color::from_function(name, arguments)?))
+            }
+            // https://www.w3.org/TR/css-values-4/#urls
+            // 引用符で囲んだURLは、url-tokenではなく関数として読まれる
+            "url" => match trim_whitespace(arguments) {
+                [ComponentValue::StringToken(url)] => Ok(CssValue::Url(url.clone())),
+                _ => Err(Error::UnexpectedInput("invalid url()".to_string())),
+            },
+            _ if is_math_function(name) => Ok(CssValue::Calc(CalcNode::parse(name, arguments)?)),
+            "attr" => match trim_whitespace(arguments) {
+                [ComponentValue::Ident(attribute)] => {
+                    Ok(CssValue::Attr(attribute.to_ascii_lowercase()))
+                }
+                _ => Err(Error::UnexpectedInput("invalid attr()".to_string())),
+            },
+            "counter" | "counters" => Self::parse_counter(name, arguments),
+            "minmax" | "repeat" => {
+                let arguments = split_by_comma(arguments);
+                let (first, second) = match arguments.as_slice() {
+                    [first, second] => (
+                        Self::parse_space_separated(trim_whitespace(first))?,
+                        Self::parse_space_separated(trim_whitespace(second))?,
+                    ),
+                    _ => return Err(Error::UnexpectedInput(format!("invalid {}()", name))),
+                };
+                if name.eq_ignore_ascii_case("minmax") {
+                    Ok(CssValue::MinMax(Box::new(first), Box::new(second)))
+                } else {
+                    Ok(CssValue::Repeat(Box::new(first), Box::new(second)))
+                }
+            }
+            _ => Err(Error::UnexpectedInput(format!(
+                "function {}() is not supported yet",
+                name
+            ))),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-lists-3/#counter-functions
+    /// `counter( <counter-name>, <counter-style>? )`
+    /// `counters( <counter-name>, <string>, <counter-style>? )`
+    fn parse_counter(name: &str, arguments: &[ComponentValue]) -> Result<Self, Error> {
+        let invalid = || Error::UnexpectedInput(format!("invalid {}()", name));
+        let is_counters = name.eq_ignore_ascii_case("counters");
+        let arguments: Vec<&[ComponentValue]> = split_by_comma(arguments)
+            .into_iter()
+            .map(trim_whitespace)
+            .collect();
+
+        let counter_name = match arguments.first() {
+            Some([ComponentValue::Ident(counter_name)]) => counter_name.to_ascii_lowercase(),
+            _ => return Err(invalid()),
+        };
+        let (separator, rest) = if is_counters {
+            match arguments.get(1) {
+                Some([ComponentValue::StringToken(separator)]) => {
+                    (Some(separator.clone()), &arguments[2..])
+                }
+                _ => return Err(invalid()),
+            }
+        } else {
+            (None, &arguments[1..])
+        };
+        let style = match rest {
+            [] => None,
+            [[ComponentValue::Ident(style)]] => Some(style.to_ascii_lowercase()),
+            _ => return Err(invalid()),
+        };
+
+        Ok(CssValue::Counter {
+            name: counter_name,
+            separator,
+            style,
+        })
+    }
+
+    fn parse_component_value(value: &ComponentValue) -> Result<Self, Error> {
+        match value {
+            ComponentValue::Ident(ident) => Ok(CssValue::Keyword(ident.to_ascii_lowercase())),
+            ComponentValue::Dimension { value, unit } if unit.eq_ignore_ascii_case("fr") => {
+                Ok(CssValue::Flex(*value))
+            }
+            ComponentValue::Dimension { value, unit } => Ok(CssValue::Length(Length::new(
+                *value,
+                LengthUnit::from_unit(unit)?,
+            ))),
+            ComponentValue::Percentage(value) => Ok(CssValue::Percentage(*value)),
+            ComponentValue::Number(value) => Ok(CssValue::Number(*value)),
+            ComponentValue::HashToken(hash) => Ok(CssValue::Color(Color::from_code(hash)?)),
+            ComponentValue::StringToken(s) => Ok(CssValue::String(s.clone())),
+            ComponentValue::Url(url) => Ok(CssValue::Url(url.clone())),
+            _ => Err(Error::UnexpectedInput(format!(
+                "value {:?} is not supported yet",
+                value
+            ))),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-cascade-4/#computed
+    /// 長さ、パーセンテージ、autoの値を計算値に変換する
+    /// 長さとして解釈できない値の場合はNoneを返す
+    pub fn to_computed_length(&self, context: &ResolutionContext) -> Option<ComputedLength> {
+        match self {
+            CssValue::Keyword(keyword) if keyword == "auto" => Some(ComputedLength::Auto),
+            CssValue::Length(length) => Some(ComputedLength::Px(length.to_px(context))),
+            CssValue::Percentage(percentage) => Some(ComputedLength::Percentage(*percentage)),
+            // https://www.w3.org/TR/css-values-4/#zero-value
+            // 単位のない0は長さとして扱う
+            CssValue::Number(number) if *number == 0.0 => Some(ComputedLength::Px(0.0)),
+            CssValue::Calc(node) if node.calc_type()?.is_length_percentage() => {
+                match node.compute(context) {
+                    CalcNode::Length(length) => Some(ComputedLength::Px(length.value())),
+                    CalcNode::Percentage(percentage) => {
+                        Some(ComputedLength::Percentage(percentage))
+                    }
+                    node => Some(ComputedLength::Calc(node)),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#serialize-a-css-component-value
+    pub fn css_text(&self) -> String {
+        match self {
+            CssValue::Keyword(keyword) => serialize_identifier(keyword),
+            CssValue::Length(length) => length.css_text(),
+            CssValue::Percentage(percentage) => format!("{}%", serialize_number(*percentage)),
+            CssValue::Number(number) => serialize_number(*number),
+            CssValue::Flex(flex) => format!("{}fr", serialize_number(*flex)),
+            CssValue::Color(color) => color.css_text(),
+            CssValue::String(s) => serialize_string(s),
+            CssValue::Url(url) => serialize_url(url),
+            CssValue::Calc(node) => node.css_text(),
+            CssValue::Attr(attribute) => format!("attr({})", serialize_identifier(attribute)),
+            CssValue::Counter {
+                name,
+                separator,
+                style,
+            } => {
+                let mut arguments = vec![serialize_identifier(name)];
+                let function = match separator {
+                    Some(separator) => {
+                        arguments.push(serialize_string(separator));
+                        "counters"
+                    }
+                    None => "counter",
+                };
+                if let Some(style) = style {
+                    arguments.push(serialize_identifier(style));
+                }
+                format!("{}({})", function, arguments.join(", "))
+            }
+            CssValue::MinMax(min, max) => format!("minmax({}, {})", min.css_text(), max.css_text()),
+            CssValue::Repeat(count, tracks) => {
+                format!("repeat({}, {})", count.css_text(), tracks.css_text())
+            }
+            CssValue::List(list) => list
+                .iter()
+                .map(|value| value.css_text())
+                .collect::<Vec<String>>()
+                .join(" "),
+            CssValue::CommaList(list) => list
+                .iter()
+                .map(|value| value.css_text())
+                .collect::<Vec<String>>()
+                .join(", "),
+        }
+    }
+
+    /// 計算式の型を返す. 計算式でない場合はNoneを返す
+    pub fn calc_type(&self) -> Option<CalcType> {
+        match self {
+            CssValue::Calc(node) => node.calc_type(),
+            _ => None,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-values-4/#typedef-length-percentage
+/// 計算値としての長さ. 絶対的なピクセル値に解決済みで、パーセンテージだけは
+/// 包含ブロックの大きさが決まるレイアウト時（使用値の計算時）に解決する
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComputedLength {
+    Auto,
+    Px(f64),
+    Percentage(f64),
+    /// パーセンテージを含む計算式. 長さはピクセル値に解決済み
+    Calc(CalcNode),
+}
+
+impl ComputedLength {
+    /// https://www.w3.org/TR/css-cascade-4/#used
+    /// `base`を基準にして使用値を求める. autoの場合はNoneを返す
+    pub fn resolve(&self, base: f64) -> Option<f64> {
+        match self {
+            ComputedLength::Auto => None,
+            ComputedLength::Px(px) => Some(*px),
+            ComputedLength::Percentage(percentage) => Some(base * percentage / 100.0),
+            ComputedLength::Calc(node) => node.evaluate(&ResolutionContext::default(), Some(base)),
+        }
+    }
+
+    pub fn is_auto(&self) -> bool {
+        *self == ComputedLength::Auto
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::token::CssTokenizer;
+    use alloc::vec;
+
+    fn parse(value: &str) -> Result<CssValue, Error> {
+        let tokens: Vec<ComponentValue> = CssTokenizer::new(value.to_string()).collect();
+        CssValue::parse(&tokens)
+    }
+
+    #[test]
+    fn test_single_values() {
+        /* 1つのトークンからなる値が、それぞれの型にパースされることを確認する */
+        assert_eq!(parse("Block"), Ok(CssValue::Keyword("block".to_string())));
+        assert_eq!(parse("10px"), Ok(CssValue::Length(Length::px(10.0))));
+        assert_eq!(
+            parse("1.5EM"),
+            Ok(CssValue::Length(Length::new(1.5, LengthUnit::Em)))
+        );
+        assert_eq!(parse("50%"), Ok(CssValue::Percentage(50.0)));
+        assert_eq!(parse("0"), Ok(CssValue::Number(0.0)));
+        assert_eq!(parse("#FF0000"), Ok(CssValue::Color(Color::rgb(255, 0, 0))));
+        assert_eq!(
+            parse("rgb(0 0 255)"),
+            Ok(CssValue::Color(Color::rgb(0, 0, 255)))
+        );
+        assert_eq!(parse("\"Hey\""), Ok(CssValue::String("Hey".to_string())));
+        assert_eq!(parse("url(a.png)"), Ok(CssValue::Url("a.png".to_string())));
+        assert_eq!(parse("2FR"), Ok(CssValue::Flex(2.0)));
+    }
+
+    #[test]
+    fn test_grid_functions() {
+        /* minmax()とrepeat()が引数の値とともにパースされ、元の表記に戻せることを確認する */
+        let minmax = parse("minmax(100px, 1fr)");
+        assert_eq!(
+            minmax,
+            Ok(CssValue::MinMax(
+                Box::new(CssValue::Length(Length::px(100.0))),
+                Box::new(CssValue::Flex(1.0)),
+            ))
+        );
+        let repeat = parse("repeat(3, 10px auto)").unwrap();
+        assert_eq!(
+            repeat,
+            CssValue::Repeat(
+                Box::new(CssValue::Number(3.0)),
+                Box::new(CssValue::List(vec![
+                    CssValue::Length(Length::px(10.0)),
+                    CssValue::Keyword("auto".to_string()),
+                ])),
+            )
+        );
+        assert_eq!(repeat.css_text(), "repeat(3, 10px auto)");
+        assert!(parse("minmax(10px)").is_err());
+    }
+
+    #[test]
+    fn test_lists() {
+        /* 空白区切り、カンマ区切りの値がリストとしてパースされることを確認する */
+        assert_eq!(
+            parse(" 10px  20px "),
+            Ok(CssValue::List(vec![
+                CssValue::Length(Length::px(10.0)),
+                CssValue::Length(Length::px(20.0)),
+            ]))
+        );
+        assert_eq!(
+            parse("\"Helvetica Neue\", Arial, sans-serif"),
+            Ok(CssValue::CommaList(vec![
+                CssValue::String("Helvetica Neue".to_string()),
+                CssValue::Keyword("arial".to_string()),
+                CssValue::Keyword("sans-serif".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        /* サポートしていない単位や空の値はエラーになることを確認する */
+        assert!(parse("").is_err());
+        assert!(parse("10foo").is_err());
+        assert!(parse("10px ;").is_err());
+        assert!(parse("attr(\"title\")").is_err());
+        assert!(parse("counter(a, b, c)").is_err());
+        assert!(parse("counters(a)").is_err());
+    }
+
+    #[test]
+    fn test_length_to_px() {
+        /* 相対的な長さが、コンテキストに応じてピクセル値に変換されることを確認する */
+        let context = ResolutionContext::new(20.0, 16.0, 800.0, 600.0);
+        assert_eq!(Length::px(12.0).to_px(&context), 12.0);
+        assert_eq!(Length::new(2.0, LengthUnit::Em).to_px(&context), 40.0);
+        assert_eq!(Length::new(2.0, LengthUnit::Rem).to_px(&context), 32.0);
+        assert_eq!(Length::new(50.0, LengthUnit::Vw).to_px(&context), 400.0);
+        assert_eq!(Length::new(10.0, LengthUnit::Vh).to_px(&context), 60.0);
+        assert_eq!(Length::new(1.0, LengthUnit::In).to_px(&context), 96.0);
+        assert_eq!(Length::new(12.0, LengthUnit::Pt).to_px(&context), 16.0);
+    }
+
+    #[test]
+    fn test_computed_length() {
+        /* 計算値への変換と、パーセンテージの使用値の解決を確認する */
+        let context = ResolutionContext::default();
+        assert_eq!(
+            parse("2em")
+                .expect("should parse")
+                .to_computed_length(&context),
+            Some(ComputedLength::Px(32.0))
+        );
+        assert_eq!(
+            parse("auto")
+                .expect("should parse")
+                .to_computed_length(&context),
+            Some(ComputedLength::Auto)
+        );
+        assert_eq!(
+            parse("0")
+                .expect("should parse")
+                .to_computed_length(&context),
+            Some(ComputedLength::Px(0.0))
+        );
+        assert_eq!(
+            parse("10")
+                .expect("should parse")
+                .to_computed_length(&context),
+            None
+        );
+
+        let width = parse("50%")
+            .expect("should parse")
+            .to_computed_length(&context)
+            .expect("should be a length");
+        assert_eq!(width.resolve(300.0), Some(150.0));
+        assert_eq!(ComputedLength::Auto.resolve(300.0), None);
+    }
+
+    #[test]
+    fn test_calc_length() {
+        /* 計算式の長さが計算値で簡約され、パーセンテージは使用値の計算時に解決されることを確認する */
+        let context = ResolutionContext::new(10.0, 16.0, 800.0, 600.0);
+        assert_eq!(
+            parse("calc(1em + 2px)")
+                .expect("should parse")
+                .to_computed_length(&context),
+            Some(ComputedLength::Px(12.0))
+        );
+        assert_eq!(
+            parse("calc(2 * 25%)")
+                .expect("should parse")
+                .to_computed_length(&context),
+            Some(ComputedLength::Percentage(50.0))
+        );
+        assert_eq!(
+            parse("calc(2 * 3)")
+                .expect("should parse")
+                .to_computed_length(&context),
+            None
+        );
+
+        let width = parse("clamp(100px, calc(100% - 2em), 500px)")
+            .expect("should parse")
+            .to_computed_length(&context)
+            .expect("should be a length");
+        assert_eq!(width.resolve(300.0), Some(280.0));
+        assert_eq!(width.resolve(50.0), Some(100.0));
+        assert_eq!(width.resolve(1000.0), Some(500.0));
+    }
+
+    #[test]
+    fn test_css_text() {
+        /* 値を文字列にして読み直すと、同じ値になることを確認する */
+        for value in [
+            "block",
+            "1.5em",
+            "-50%",
+            "#0000ff",
+            "\"Helvetica Neue\", Arial, sans-serif",
+            "url(a.png) no-repeat",
+            "calc(100% - 2em)",
+            "clamp(10px, 2 * (50% + 1em) / 3, 300px)",
+            "\"→ \" attr(title) counter(section) counters(Item, \".\", upper-roman)",
+        ] {
+            let parsed = parse(value).expect("should parse");
+            let serialized = parsed.css_text();
+            assert_eq!(parse(&serialized), Ok(parsed), "{}", serialized);
+        }
+        assert_eq!(
+            parse("#00F").expect("should parse").css_text(),
+            "rgb(0, 0, 255)"
+        );
+        assert_eq!(
+            parse("calc(1px - (2px + 3%))")
+                .expect("should parse")
+                .css_text(),
+            "calc(1px - (2px + 3%))"
+        );
+    }
+}
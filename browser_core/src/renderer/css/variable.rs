@@ -0,0 +1,202 @@
+use crate::renderer::css::cssom::find_block_end;
+use crate::renderer::css::cssom::trim_whitespace;
+use crate::renderer::css::cssom::ComponentValue;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/css-variables-1/#custom-property
+/// "--"から始まるプロパティ名かどうか
+pub fn is_custom_property(property: &str) -> bool {
+    property.starts_with("--")
+}
+
+/// https://www.w3.org/TR/css-variables-1/#using-variables
+/// トークン列にvar()が含まれるかどうか
+pub fn contains_var(tokens: &[ComponentValue]) -> bool {
+    tokens
+        .iter()
+        .any(|t| matches!(t, ComponentValue::Function(name) if name.eq_ignore_ascii_case("var")))
+}
+
+/// https://www.w3.org/TR/css-variables-1/#substitute-a-var
+/// トークン列の中のvar()を、`lookup`で得られるカスタムプロパティの値で置き換える
+/// 参照したカスタムプロパティが無効で、フォールバックもない場合はNoneを返す
+/// （その宣言は計算値の時点で無効になる）
+pub fn substitute_var(
+    tokens: &[ComponentValue],
+    lookup: &mut dyn FnMut(&str) -> Option<Vec<ComponentValue>>,
+) -> Option<Vec<ComponentValue>> {
+    let mut result = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match &tokens[i] {
+            ComponentValue::Function(name) if name.eq_ignore_ascii_case("var") => {
+                let end = find_block_end(tokens, i)?;
+                let (property, fallback) = parse_var_arguments(&tokens[i + 1..end])?;
+
+                match lookup(&property) {
+                    Some(value) => result.extend(value),
+                    None => result.extend(substitute_var(fallback?, lookup)?),
+                }
+                i = end + 1;
+            }
+            token => {
+                result.push(token.clone());
+                i += 1;
+            }
+        }
+    }
+
+    Some(result)
+}
+
+/// `var( <custom-property-name> , <declaration-value>? )`の引数を、名前とフォールバックに分ける
+fn parse_var_arguments(
+    arguments: &[ComponentValue],
+) -> Option<(String, Option<&[ComponentValue]>)> {
+    let arguments = trim_whitespace(arguments);
+
+    let name = match arguments.first()? {
+        ComponentValue::Ident(name) if is_custom_property(name) => name.to_string(),
+        _ => return None,
+    };
+
+    let rest = trim_whitespace(&arguments[1..]);
+    match rest.first() {
+        None => Some((name, None)),
+        Some(ComponentValue::Comma) => Some((name, Some(trim_whitespace(&rest[1..])))),
+        Some(_) => None,
+    }
+}
+
+/// https://www.w3.org/TR/css-variables-1/#cycles
+/// カスタムプロパティの値に含まれるvar()を置換する
+/// 循環参照しているカスタムプロパティと、無効な値を参照しているカスタムプロパティは
+/// 初期値（guaranteed-invalid value）になるので、結果から取り除く
+pub fn resolve_custom_properties(
+    specified: &BTreeMap<String, Vec<ComponentValue>>,
+) -> BTreeMap<String, Vec<ComponentValue>> {
+    let mut resolved = BTreeMap::new();
+    let mut stack = Vec::new();
+    for name in specified.keys() {
+        resolve_custom_property(name, specified, &mut resolved, &mut stack);
+    }
+
+    resolved
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .collect()
+}
+
+fn resolve_custom_property(
+    name: &str,
+    specified: &BTreeMap<String, Vec<ComponentValue>>,
+    resolved: &mut BTreeMap<String, Option<Vec<ComponentValue>>>,
+    stack: &mut Vec<String>,
+) -> Option<Vec<ComponentValue>> {
+    if let Some(value) = resolved.get(name) {
+        return value.clone();
+    }
+
+    // 解決中のプロパティを再び参照した場合、循環に含まれるすべてのプロパティを無効にする
+    if let Some(start) = stack.iter().position(|n| n == name) {
+        for n in &stack[start..] {
+            resolved.insert(n.clone(), None);
+        }
+        return None;
+    }
+
+    let tokens = specified.get(name)?;
+    stack.push(name.to_string());
+    let value = substitute_var(tokens, &mut |reference| {
+        resolve_custom_property(reference, specified, resolved, stack)
+    });
+    stack.pop();
+
+    // 置換の途中で循環が見つかった場合は、フォールバックで置換できていても無効のまま
+    if let Some(None) = resolved.get(name) {
+        return None;
+    }
+    resolved.insert(name.to_string(), value.clone());
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::token::CssTokenizer;
+
+    fn tokens(value: &str) -> Vec<ComponentValue> {
+        CssTokenizer::new(value.to_string()).collect()
+    }
+
+    fn properties(list: &[(&str, &str)]) -> BTreeMap<String, Vec<ComponentValue>> {
+        list.iter()
+            .map(|(name, value)| (name.to_string(), tokens(value)))
+            .collect()
+    }
+
+    #[test]
+    fn test_substitute() {
+        /* var()が値またはフォールバックで置換されることを確認する */
+        let variables = properties(&[("--brand", "#0af"), ("--size", "10px")]);
+        let mut lookup = |name: &str| variables.get(name).cloned();
+
+        assert_eq!(
+            substitute_var(&tokens("var(--brand)"), &mut lookup),
+            Some(tokens("#0af"))
+        );
+        assert_eq!(
+            substitute_var(&tokens("var(--missing, black)"), &mut lookup),
+            Some(tokens("black"))
+        );
+        assert_eq!(
+            substitute_var(
+                &tokens("var(--size) var(--missing, var(--size))"),
+                &mut lookup
+            ),
+            Some(tokens("10px 10px"))
+        );
+        assert_eq!(
+            substitute_var(&tokens("rgb(var(--missing,0) 0 0)"), &mut lookup),
+            Some(tokens("rgb(0 0 0)"))
+        );
+        assert_eq!(substitute_var(&tokens("var(--missing)"), &mut lookup), None);
+        assert_eq!(substitute_var(&tokens("var(brand)"), &mut lookup), None);
+    }
+
+    #[test]
+    fn test_resolve_custom_properties() {
+        /* カスタムプロパティ同士の参照が解決されることを確認する */
+        let resolved = resolve_custom_properties(&properties(&[
+            ("--a", "var(--b) var(--c)"),
+            ("--b", "1px"),
+            ("--c", "var(--b)"),
+            ("--d", "var(--missing)"),
+        ]));
+
+        assert_eq!(resolved.get("--a"), Some(&tokens("1px 1px")));
+        assert_eq!(resolved.get("--c"), Some(&tokens("1px")));
+        assert_eq!(resolved.get("--d"), None);
+    }
+
+    #[test]
+    fn test_cycle() {
+        /* 循環参照しているカスタムプロパティが無効になることを確認する */
+        let resolved = resolve_custom_properties(&properties(&[
+            ("--a", "var(--b, red)"),
+            ("--b", "var(--a)"),
+            ("--self", "var(--self, blue)"),
+            ("--c", "var(--a, green)"),
+        ]));
+
+        assert_eq!(resolved.get("--a"), None);
+        assert_eq!(resolved.get("--b"), None);
+        assert_eq!(resolved.get("--self"), None);
+        // 循環の外からの参照では、フォールバックが使われる
+        assert_eq!(resolved.get("--c"), Some(&tokens("green")));
+    }
+}
@@ -35,3 +35,64 @@ pub fn get_target_element_node(
         None => None,
     }
 }
+
+/// 与えた要素の種類と一致するノードを、ツリー順（深さ優先の先行順）ですべて返す
+pub fn get_target_element_nodes(
+    node: Option<Rc<RefCell<Node>>>,
+    element_kind: ElementKind,
+) -> Vec<Rc<RefCell<Node>>> {
+    let mut result = Vec::new();
+    collect_target_element_nodes(node, element_kind, &mut result);
+    result
+}
+
+fn collect_target_element_nodes(
+    node: Option<Rc<RefCell<Node>>>,
+    element_kind: ElementKind,
+    result: &mut Vec<Rc<RefCell<Node>>>,
+) {
+    let mut current = node;
+    while let Some(n) = current {
+        if n.borrow().element_kind() == Some(element_kind) {
+            result.push(n.clone());
+        }
+        collect_target_element_nodes(n.borrow().first_child(), element_kind, result);
+        current = n.borrow().next_sibling();
+    }
+}
+
+/// ノードのスタイルを再計算が必要な状態にし、祖先には子孫の再計算が必要なことを記録する
+/// 再計算のときは、記録をたどって必要な部分木だけを訪れる
+pub fn mark_style_dirty(node: &Rc<RefCell<Node>>) {
+    node.borrow_mut().set_style_dirty(true);
+
+    let mut parent = node.borrow().parent().upgrade();
+    while let Some(p) = parent {
+        // すでに記録されている祖先より上は、記録済み
+        if p.borrow().child_style_dirty() {
+            break;
+        }
+        p.borrow_mut().set_child_style_dirty(true);
+        parent = p.borrow().parent().upgrade();
+    }
+}
+
+/// https://html.spec.whatwg.org/multipage/semantics.html#update-a-style-block
+/// ドキュメント内のすべての<style>要素について、その子テキストをツリー順に返す
+pub fn get_style_contents(root: Rc<RefCell<Node>>) -> Vec<String> {
+    let mut contents = Vec::new();
+
+    for style in get_target_element_nodes(Some(root), ElementKind::Style) {
+        let mut content = String::new();
+        let mut child = style.borrow().first_child();
+        while let Some(c) = child {
+            if let NodeKind::Text(ref s) = c.borrow().kind {
+                content.push_str(s);
+            }
+            child = c.borrow().next_sibling();
+        }
+        contents.push(content);
+    }
+
+    contents
+}
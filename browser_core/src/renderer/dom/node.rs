@@ -1,8 +1,10 @@
 use crate::renderer::html::attribute::Attribute;
+use crate::renderer::layout::computed_style::ComputedStyle;
 use alloc::format;
 use alloc::rc::Rc;
 use alloc::rc::Weak;
 use alloc::string::String;
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use core::cell::RefCell;
 use core::fmt::Display;
@@ -41,6 +43,9 @@ pub struct Node {
     last_child: Weak<RefCell<Node>>, // 最後の子ノード
     previous_sibling: Weak<RefCell<Node>>, // ノードの前の兄弟ノード
     next_sibling: Option<Rc<RefCell<Node>>>, // ノードの次の兄弟ノード
+    computed_style: Option<ComputedStyle>, // 最後に再計算したときのスタイル
+    style_dirty: bool,             // このノードのスタイルの再計算が必要か
+    child_style_dirty: bool,       // 子孫にスタイルの再計算が必要なノードがあるか
 }
 
 /*
@@ -68,6 +73,10 @@ impl Node {
             last_child: Weak::new(),
             previous_sibling: Weak::new(),
             next_sibling: None,
+            // 作成されたばかりのノードはスタイルを持たないので、再計算が必要
+            computed_style: None,
+            style_dirty: true,
+            child_style_dirty: true,
         }
     }
 
@@ -111,6 +120,30 @@ impl Node {
         self.next_sibling.as_ref().cloned()
     }
 
+    pub fn set_computed_style(&mut self, style: Option<ComputedStyle>) {
+        self.computed_style = style;
+    }
+
+    pub fn computed_style(&self) -> Option<ComputedStyle> {
+        self.computed_style.clone()
+    }
+
+    pub fn set_style_dirty(&mut self, dirty: bool) {
+        self.style_dirty = dirty;
+    }
+
+    pub fn style_dirty(&self) -> bool {
+        self.style_dirty
+    }
+
+    pub fn set_child_style_dirty(&mut self, dirty: bool) {
+        self.child_style_dirty = dirty;
+    }
+
+    pub fn child_style_dirty(&self) -> bool {
+        self.child_style_dirty
+    }
+
     pub fn set_window(&mut self, window: Weak<RefCell<Window>>) {
         self.window = window;
     }
@@ -126,6 +159,13 @@ impl Node {
         }
     }
 
+    pub fn get_element_mut(&mut self) -> Option<&mut Element> {
+        match self.kind {
+            NodeKind::Document | NodeKind::Text(_) => None,
+            NodeKind::Element(ref mut e) => Some(e),
+        }
+    }
+
     pub fn element_kind(&self) -> Option<ElementKind> {
         match self.kind {
             NodeKind::Document | NodeKind::Text(_) => None,
@@ -183,15 +223,89 @@ impl Element {
     }
 
     pub fn is_block_element(&self) -> bool {
-        match self.kind {
-            ElementKind::Body | ElementKind::H1 | ElementKind::H2 | ElementKind::P => true,
-            _ => false,
-        }
+        matches!(
+            self.kind,
+            ElementKind::Body
+                | ElementKind::H1
+                | ElementKind::H2
+                | ElementKind::P
+                | ElementKind::Ul
+                | ElementKind::Ol
+                | ElementKind::Li
+        ) || self.is_table_element()
+    }
+
+    /// https://html.spec.whatwg.org/multipage/tables.html#tables
+    /// 表とその中の構造を表す要素か
+    pub fn is_table_element(&self) -> bool {
+        matches!(
+            self.kind,
+            ElementKind::Table
+                | ElementKind::Caption
+                | ElementKind::Colgroup
+                | ElementKind::Col
+                | ElementKind::Thead
+                | ElementKind::Tbody
+                | ElementKind::Tfoot
+                | ElementKind::Tr
+                | ElementKind::Td
+                | ElementKind::Th
+        )
     }
 
     pub fn kind(&self) -> ElementKind {
         self.kind
     }
+
+    pub fn attributes(&self) -> Vec<Attribute> {
+        self.attributes.clone()
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-element-getattribute
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name() == name)
+            .map(|attr| attr.value())
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-element-setattribute
+    /// 属性名はHTMLの要素なので小文字にする
+    pub fn set_attribute(&mut self, name: &str, value: &str) {
+        let name = name.to_ascii_lowercase();
+        match self.attributes.iter_mut().find(|attr| attr.name() == name) {
+            Some(attr) => attr.set_value(value.to_string()),
+            None => {
+                let mut attr = Attribute::new();
+                attr.set_name(name);
+                attr.set_value(value.to_string());
+                self.attributes.push(attr);
+            }
+        }
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-element-removeattribute
+    pub fn remove_attribute(&mut self, name: &str) {
+        let name = name.to_ascii_lowercase();
+        self.attributes.retain(|attr| attr.name() != name);
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-element-id
+    pub fn id(&self) -> Option<String> {
+        self.get_attribute("id")
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-element-classlist
+    /// class属性を空白で区切ったクラス名のリスト
+    pub fn class_list(&self) -> Vec<String> {
+        match self.get_attribute("class") {
+            Some(class) => class
+                .split_ascii_whitespace()
+                .map(|name| name.to_string())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -214,6 +328,32 @@ pub enum ElementKind {
     H2,
     /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-a-element
     A,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-table-element
+    Table,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-caption-element
+    Caption,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-colgroup-element
+    Colgroup,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-col-element
+    Col,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-thead-element
+    Thead,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-tbody-element
+    Tbody,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-tfoot-element
+    Tfoot,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-tr-element
+    Tr,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-td-element
+    Td,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-th-element
+    Th,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-ul-element
+    Ul,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-ol-element
+    Ol,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-li-element
+    Li,
 }
 
 impl FromStr for ElementKind {
@@ -230,6 +370,19 @@ impl FromStr for ElementKind {
             "h1" => Ok(ElementKind::H1),
             "h2" => Ok(ElementKind::H2),
             "a" => Ok(ElementKind::A),
+            "table" => Ok(ElementKind::Table),
+            "caption" => Ok(ElementKind::Caption),
+            "colgroup" => Ok(ElementKind::Colgroup),
+            "col" => Ok(ElementKind::Col),
+            "thead" => Ok(ElementKind::Thead),
+            "tbody" => Ok(ElementKind::Tbody),
+            "tfoot" => Ok(ElementKind::Tfoot),
+            "tr" => Ok(ElementKind::Tr),
+            "td" => Ok(ElementKind::Td),
+            "th" => Ok(ElementKind::Th),
+            "ul" => Ok(ElementKind::Ul),
+            "ol" => Ok(ElementKind::Ol),
+            "li" => Ok(ElementKind::Li),
             _ => Err(format!("unimplemented element name {:?}", s)),
         }
     }
@@ -248,6 +401,19 @@ impl Display for ElementKind {
             ElementKind::H2 => "h2",
             ElementKind::P => "p",
             ElementKind::A => "a",
+            ElementKind::Table => "table",
+            ElementKind::Caption => "caption",
+            ElementKind::Colgroup => "colgroup",
+            ElementKind::Col => "col",
+            ElementKind::Thead => "thead",
+            ElementKind::Tbody => "tbody",
+            ElementKind::Tfoot => "tfoot",
+            ElementKind::Tr => "tr",
+            ElementKind::Td => "td",
+            ElementKind::Th => "th",
+            ElementKind::Ul => "ul",
+            ElementKind::Ol => "ol",
+            ElementKind::Li => "li",
         };
         write!(f, "{}", s)
     }
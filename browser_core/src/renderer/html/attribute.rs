@@ -22,6 +22,14 @@ impl Attribute {
         }
     }
 
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn set_value(&mut self, value: String) {
+        self.value = value;
+    }
+
     pub fn name(&self) -> String {
         self.name.clone()
     }
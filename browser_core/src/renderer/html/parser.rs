@@ -23,6 +23,12 @@ pub enum InsertionMode {
     AfterHead,
     InBody,
     Text,
+    InTable,
+    InCaption,
+    InColumnGroup,
+    InTableBody,
+    InRow,
+    InCell,
     AfterBody,
     AfterAfterBody,
 }
@@ -36,6 +42,8 @@ pub struct HtmlParser {
     original_insertion_mode: InsertionMode, // 次の状態に遷移する際、以前の挿入モードを保持するために使用される
     /// https://html.spec.whatwg.org/multipage/parsing.html#the-stack-of-open-elements
     stack_of_open_elements: Vec<Rc<RefCell<Node>>>, // HTMLの構文解析中にブラウザが使用するスタック
+    /// https://html.spec.whatwg.org/multipage/parsing.html#foster-parent
+    foster_parenting: bool, // 表の中に置けないノードを、表の直前に挿入するか
     t: HtmlTokenizer,
 }
 
@@ -46,6 +54,7 @@ impl HtmlParser {
             mode: InsertionMode::Initial,
             original_insertion_mode: InsertionMode::Initial,
             stack_of_open_elements: Vec::new(),
+            foster_parenting: false,
             t,
         }
     }
@@ -59,51 +68,50 @@ impl HtmlParser {
     /// https://html.spec.whatwg.org/multipage/parsing.html#insert-a-foreign-element
     /// foreign element = HTML以外の要素. SVGやMathML要素などを指す
     fn insert_element(&mut self, tag: &str, attributes: Vec<Attribute>) {
-        let window = self.window.borrow();
+        let (parent, before) = self.appropriate_place_for_inserting();
+        let node = Rc::new(RefCell::new(self.create_element(tag, attributes)));
+        insert_node(&parent, before, &node);
 
-        // 現在のスタック末尾のノードを取得
-        let current = match self.stack_of_open_elements.last() {
+        self.stack_of_open_elements.push(node);
+    }
+
+    /// https://html.spec.whatwg.org/multipage/parsing.html#appropriate-place-for-inserting-a-node
+    /// ノードを挿入する親と、挿入したノードの直後になる兄弟（最後の子として挿入する場合はNone）を返す
+    fn appropriate_place_for_inserting(&self) -> (Rc<RefCell<Node>>, Option<Rc<RefCell<Node>>>) {
+        // 現在のスタック末尾のノードを取得. 空なら、ルート要素が現在参照中のノードになる
+        let target = match self.stack_of_open_elements.last() {
             Some(n) => n.clone(),
-            None => window.document(), // 空なら、ルート要素が現在参照中のノードになる
+            None => self.window.borrow().document(),
         };
-        let node = Rc::new(RefCell::new(self.create_element(tag, attributes)));
+        let is_table_structure = matches!(
+            target.borrow().element_kind(),
+            Some(ElementKind::Table)
+                | Some(ElementKind::Tbody)
+                | Some(ElementKind::Tfoot)
+                | Some(ElementKind::Thead)
+                | Some(ElementKind::Tr)
+        );
+        if !self.foster_parenting || !is_table_structure {
+            return (target, None);
+        }
 
-        // 現在参照中のノードに、子要素が既に存在する場合
-        if current.borrow().first_child().is_some() {
-            let mut last_sibiling = current.borrow().first_child();
-            loop {
-                last_sibiling = match last_sibiling {
-                    Some(ref node) => {
-                        if node.borrow().next_sibling().is_some() {
-                            node.borrow().next_sibling()
-                        } else {
-                            break;
-                        }
-                    }
-                    None => unimplemented!("last_sibiling should be Some"),
-                };
+        // https://html.spec.whatwg.org/multipage/parsing.html#foster-parent
+        // 表の構造を表す要素の中に置けないノードは、最後に開いた表の直前に挿入する
+        let index = self
+            .stack_of_open_elements
+            .iter()
+            .rposition(|n| n.borrow().element_kind() == Some(ElementKind::Table));
+        match index {
+            Some(index) => {
+                let table = self.stack_of_open_elements[index].clone();
+                let parent = table.borrow().parent().upgrade();
+                match parent {
+                    Some(parent) => (parent, Some(table)),
+                    None => (self.stack_of_open_elements[index - 1].clone(), None),
+                }
             }
-
-            // 新たなノードを挿入
-            last_sibiling
-                .unwrap()
-                .borrow_mut()
-                .set_next_sibling(Some(node.clone()));
-            node.borrow_mut().set_previous_sibling(Rc::downgrade(
-                &current
-                    .borrow()
-                    .first_child()
-                    .expect("failed to get a first child"),
-            ))
-        } else {
-            // 兄弟ノードが存在しない場合、新たなノードを現在参照中のノードの最初の子要素として設定
-            current.borrow_mut().set_first_child(Some(node.clone()));
+            None => (self.stack_of_open_elements[0].clone(), None),
         }
-
-        current.borrow_mut().set_last_child(Rc::downgrade(&node));
-        node.borrow_mut().set_parent(Rc::downgrade(&current));
-
-        self.stack_of_open_elements.push(node);
     }
 
     /// ノードをpopし、種類が一致するかどうかを返す
@@ -152,6 +160,153 @@ impl HtmlParser {
         false
     }
 
+    /// https://html.spec.whatwg.org/multipage/parsing.html#has-an-element-in-scope
+    /// 最後に開いた表やセルより後に、対象の種類の要素を保持しているかチェック
+    fn has_in_scope(&self, element_kind: ElementKind) -> bool {
+        for node in self.stack_of_open_elements.iter().rev() {
+            let kind = node.borrow().element_kind();
+            if kind == Some(element_kind) {
+                return true;
+            }
+            if matches!(
+                kind,
+                Some(ElementKind::Table)
+                    | Some(ElementKind::Td)
+                    | Some(ElementKind::Th)
+                    | Some(ElementKind::Caption)
+                    | Some(ElementKind::Html)
+            ) {
+                return false;
+            }
+        }
+
+        false
+    }
+
+    /// https://html.spec.whatwg.org/multipage/parsing.html#has-an-element-in-table-scope
+    /// 最後に開いた表より後に、対象の種類の要素を保持しているかチェック
+    fn has_in_table_scope(&self, element_kind: ElementKind) -> bool {
+        for node in self.stack_of_open_elements.iter().rev() {
+            let kind = node.borrow().element_kind();
+            if kind == Some(element_kind) {
+                return true;
+            }
+            if matches!(kind, Some(ElementKind::Table) | Some(ElementKind::Html)) {
+                return false;
+            }
+        }
+
+        false
+    }
+
+    /// https://html.spec.whatwg.org/multipage/parsing.html#has-an-element-in-list-item-scope
+    /// 最後に開いたリストや表より後に、対象の種類の要素を保持しているかチェック
+    fn has_in_list_item_scope(&self, element_kind: ElementKind) -> bool {
+        for node in self.stack_of_open_elements.iter().rev() {
+            let kind = node.borrow().element_kind();
+            if kind == Some(element_kind) {
+                return true;
+            }
+            if matches!(
+                kind,
+                Some(ElementKind::Ul)
+                    | Some(ElementKind::Ol)
+                    | Some(ElementKind::Table)
+                    | Some(ElementKind::Td)
+                    | Some(ElementKind::Th)
+                    | Some(ElementKind::Caption)
+                    | Some(ElementKind::Html)
+            ) {
+                return false;
+            }
+        }
+
+        false
+    }
+
+    /// https://html.spec.whatwg.org/multipage/parsing.html#clear-the-stack-back-to-a-table-context
+    /// 現在のノードが、指定した種類のどれかかhtml要素になるまでpopし続ける
+    fn clear_stack_back_to(&mut self, element_kinds: &[ElementKind]) {
+        while let Some(current) = self.stack_of_open_elements.last() {
+            match current.borrow().element_kind() {
+                Some(ElementKind::Html) => return,
+                Some(kind) if element_kinds.contains(&kind) => return,
+                _ => {}
+            }
+            self.stack_of_open_elements.pop();
+        }
+    }
+
+    /// 開いている行を閉じ、"in table body"挿入モードに戻る
+    /// 表の中に行が開いていない場合はfalseを返す
+    fn close_row(&mut self) -> bool {
+        if !self.has_in_table_scope(ElementKind::Tr) {
+            return false;
+        }
+        self.clear_stack_back_to(&[ElementKind::Tr]);
+        self.stack_of_open_elements.pop();
+        self.mode = InsertionMode::InTableBody;
+        true
+    }
+
+    /// 開いている行グループを閉じ、"in table"挿入モードに戻る
+    /// 表の中に行グループが開いていない場合はfalseを返す
+    fn close_table_body(&mut self) -> bool {
+        if !TABLE_BODY_CONTEXT
+            .iter()
+            .any(|kind| self.has_in_table_scope(*kind))
+        {
+            return false;
+        }
+        self.clear_stack_back_to(TABLE_BODY_CONTEXT);
+        self.stack_of_open_elements.pop();
+        self.mode = InsertionMode::InTable;
+        true
+    }
+
+    /// 現在のノードの要素の種類. スタックが空か、現在のノードが要素でない場合はNone
+    fn current_element_kind(&self) -> Option<ElementKind> {
+        self.stack_of_open_elements
+            .last()
+            .and_then(|n| n.borrow().element_kind())
+    }
+
+    /// https://html.spec.whatwg.org/multipage/parsing.html#close-the-cell
+    /// 開いているセルを閉じ、"in row"挿入モードに戻る
+    fn close_cell(&mut self) {
+        while let Some(current) = self.stack_of_open_elements.pop() {
+            if matches!(
+                current.borrow().element_kind(),
+                Some(ElementKind::Td) | Some(ElementKind::Th)
+            ) {
+                break;
+            }
+        }
+        self.mode = InsertionMode::InRow;
+    }
+
+    /// https://html.spec.whatwg.org/multipage/parsing.html#reset-the-insertion-mode-appropriately
+    /// スタックに残っている要素から、挿入モードを決め直す
+    fn reset_insertion_mode(&mut self) {
+        for node in self.stack_of_open_elements.iter().rev() {
+            let mode = match node.borrow().element_kind() {
+                Some(ElementKind::Td) | Some(ElementKind::Th) => InsertionMode::InCell,
+                Some(ElementKind::Tr) => InsertionMode::InRow,
+                Some(ElementKind::Tbody) | Some(ElementKind::Thead) | Some(ElementKind::Tfoot) => {
+                    InsertionMode::InTableBody
+                }
+                Some(ElementKind::Caption) => InsertionMode::InCaption,
+                Some(ElementKind::Colgroup) => InsertionMode::InColumnGroup,
+                Some(ElementKind::Table) => InsertionMode::InTable,
+                Some(ElementKind::Body) => InsertionMode::InBody,
+                _ => continue,
+            };
+            self.mode = mode;
+            return;
+        }
+        self.mode = InsertionMode::InBody;
+    }
+
     /// 文字の生成
     fn create_char(&self, c: char) -> Node {
         let mut s = String::new();
@@ -161,56 +316,54 @@ impl HtmlParser {
 
     /// 文字ノードを生成しDOMツリーに追加する、または現在のテキストノードに新たな文字を挿入する
     fn insert_char(&mut self, c: char) {
-        // 最後のノードを取得
-        let current = match self.stack_of_open_elements.last() {
-            Some(n) => n.clone(),
-            None => return,
-        };
-
-        // 現在参照中のノードがテキストノードであれば、そのノードに文字を追加する
-        if let NodeKind::Text(ref mut s) = current.borrow_mut().kind {
-            s.push(c);
+        if self.stack_of_open_elements.is_empty() {
             return;
         }
+        let (parent, before) = self.appropriate_place_for_inserting();
+
+        // https://html.spec.whatwg.org/multipage/parsing.html#insert-a-character
+        // 挿入位置の直前のノードがテキストノードであれば、そのノードに文字を追加する
+        let last_child = match &before {
+            Some(before) => before.borrow().previous_sibling().upgrade(),
+            None => parent.borrow().last_child().upgrade(),
+        };
+        if let Some(last) = &last_child {
+            if let NodeKind::Text(ref mut s) = last.borrow_mut().kind {
+                s.push(c);
+                return;
+            }
+        }
 
         // 改行or空白文字ならテキストノードを追加しない
-        if c == '\n' || c == ' ' {
+        // ただしインライン要素の直後の空白は、単語の区切りになるので残す
+        let follows_inline = last_child
+            .as_ref()
+            .and_then(|last| last.borrow().get_element())
+            .map_or(false, |element| !element.is_block_element());
+        if (c == '\n' || c == ' ') && !follows_inline {
             return;
         }
 
         let node = Rc::new(RefCell::new(self.create_char(c)));
-
-        // 参照中のノードに子要素がある場合、その直後に挿入
-        if current.borrow().first_child().is_some() {
-            current
-                .borrow()
-                .first_child()
-                .unwrap()
-                .borrow_mut()
-                .set_next_sibling(Some(node.clone()));
-            node.borrow_mut().set_previous_sibling(Rc::downgrade(
-                &current
-                    .borrow()
-                    .first_child()
-                    .expect("failed to get a first child"),
-            ));
-        } else {
-            // 最初の子要素として設定
-            current.borrow_mut().set_first_child(Some(node.clone()));
-        }
-
-        current.borrow_mut().set_last_child(Rc::downgrade(&node));
-        node.borrow_mut().set_parent(Rc::downgrade(&current));
-
-        self.stack_of_open_elements.push(node);
+        insert_node(&parent, before, &node);
     }
 
     /// ステートマシンの実装
     pub fn construct_tree(&mut self) -> Rc<RefCell<Window>> {
         let mut token = self.t.next();
+        // https://html.spec.whatwg.org/multipage/parsing.html#using-the-rules-for
+        // 現在のトークンを、現在の挿入モードとは別の挿入モードの規則で処理する場合の挿入モード
+        let mut rules: Option<InsertionMode> = None;
 
         while token.is_some() {
-            match self.mode {
+            let mode = match rules.take() {
+                Some(mode) => mode,
+                None => {
+                    self.foster_parenting = false;
+                    self.mode
+                }
+            };
+            match mode {
                 /*
                 書籍「ブラウザのしくみ」ではDOCTYPEトークンをサポートしていないため、
                 <!doctype html>のようなトークンは文字トークンとして表される.
@@ -404,6 +557,35 @@ impl HtmlParser {
                                 token = self.t.next();
                                 continue;
                             }
+                            "table" => {
+                                self.insert_element(tag, attributes.to_vec());
+                                self.mode = InsertionMode::InTable;
+                                token = self.t.next();
+                                continue;
+                            }
+                            "ul" | "ol" => {
+                                self.insert_element(tag, attributes.to_vec());
+                                token = self.t.next();
+                                continue;
+                            }
+                            "li" => {
+                                // 同じリストの中で開いているli要素は、新しいli要素の前で閉じる
+                                if self.has_in_list_item_scope(ElementKind::Li) {
+                                    self.pop_until(ElementKind::Li);
+                                }
+                                self.insert_element(tag, attributes.to_vec());
+                                token = self.t.next();
+                                continue;
+                            }
+                            // <body>内の<style>や<script>も、"in head"挿入モードと同じように処理する
+                            // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody
+                            "style" | "script" => {
+                                self.insert_element(tag, attributes.to_vec());
+                                self.original_insertion_mode = self.mode;
+                                self.mode = InsertionMode::Text;
+                                token = self.t.next();
+                                continue;
+                            }
                             _ => {
                                 token = self.t.next();
                             }
@@ -429,17 +611,34 @@ impl HtmlParser {
                                     }
                                     continue;
                                 }
-                                "p" => {
+                                "p" | "h1" | "h2" => {
                                     let element_kind = ElementKind::from_str(tag)
                                         .expect("failed to convert string to ElementKind");
                                     token = self.t.next();
+                                    if !self.has_in_scope(element_kind) {
+                                        // パースの失敗. トークンを無視する
+                                        continue;
+                                    }
                                     self.pop_until(element_kind);
                                     continue;
                                 }
-                                "h1" | "h2" => {
+                                "a" => {
+                                    token = self.t.next();
+                                    if !self.contain_in_stack(ElementKind::A) {
+                                        // パースの失敗. トークンを無視する
+                                        continue;
+                                    }
+                                    self.pop_until(ElementKind::A);
+                                    continue;
+                                }
+                                "ul" | "ol" | "li" => {
                                     let element_kind = ElementKind::from_str(tag)
                                         .expect("failed to convert string to ElementKind");
                                     token = self.t.next();
+                                    if !self.has_in_list_item_scope(element_kind) {
+                                        // パースの失敗. トークンを無視する
+                                        continue;
+                                    }
                                     self.pop_until(element_kind);
                                     continue;
                                 }
@@ -491,6 +690,377 @@ impl HtmlParser {
                     self.mode = self.original_insertion_mode;
                 }
 
+                // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intable
+                InsertionMode::InTable => {
+                    match token {
+                        Some(HtmlToken::Char(c)) => {
+                            // 表の構造を表す要素の中の空白は、そのまま挿入する
+                            let in_table_structure = matches!(
+                                self.current_element_kind(),
+                                Some(ElementKind::Table)
+                                    | Some(ElementKind::Tbody)
+                                    | Some(ElementKind::Tfoot)
+                                    | Some(ElementKind::Thead)
+                                    | Some(ElementKind::Tr)
+                            );
+                            if (c == ' ' || c == '\n') && in_table_structure {
+                                self.insert_char(c);
+                                token = self.t.next();
+                                continue;
+                            }
+                        }
+                        Some(HtmlToken::StartTag {
+                            ref tag,
+                            self_closing: _,
+                            ref attributes,
+                        }) => match tag.as_str() {
+                            "caption" => {
+                                self.clear_stack_back_to(&[ElementKind::Table]);
+                                self.insert_element(tag, attributes.to_vec());
+                                self.mode = InsertionMode::InCaption;
+                                token = self.t.next();
+                                continue;
+                            }
+                            "colgroup" => {
+                                self.clear_stack_back_to(&[ElementKind::Table]);
+                                self.insert_element(tag, attributes.to_vec());
+                                self.mode = InsertionMode::InColumnGroup;
+                                token = self.t.next();
+                                continue;
+                            }
+                            // 省略された<colgroup>を補い、トークンを処理し直す
+                            "col" => {
+                                self.clear_stack_back_to(&[ElementKind::Table]);
+                                self.insert_element("colgroup", Vec::new());
+                                self.mode = InsertionMode::InColumnGroup;
+                                continue;
+                            }
+                            "tbody" | "thead" | "tfoot" => {
+                                self.clear_stack_back_to(&[ElementKind::Table]);
+                                self.insert_element(tag, attributes.to_vec());
+                                self.mode = InsertionMode::InTableBody;
+                                token = self.t.next();
+                                continue;
+                            }
+                            // 省略された<tbody>を補い、トークンを処理し直す
+                            "tr" | "td" | "th" => {
+                                self.clear_stack_back_to(&[ElementKind::Table]);
+                                self.insert_element("tbody", Vec::new());
+                                self.mode = InsertionMode::InTableBody;
+                                continue;
+                            }
+                            // パースの失敗. 開いている表を閉じてから、トークンを処理し直す
+                            "table" => {
+                                if !self.has_in_table_scope(ElementKind::Table) {
+                                    token = self.t.next();
+                                    continue;
+                                }
+                                self.pop_until(ElementKind::Table);
+                                self.reset_insertion_mode();
+                                continue;
+                            }
+                            "style" | "script" => {
+                                self.insert_element(tag, attributes.to_vec());
+                                self.original_insertion_mode = self.mode;
+                                self.mode = InsertionMode::Text;
+                                token = self.t.next();
+                                continue;
+                            }
+                            _ => {}
+                        },
+                        Some(HtmlToken::EndTag { ref tag }) => match tag.as_str() {
+                            "table" => {
+                                token = self.t.next();
+                                if !self.has_in_table_scope(ElementKind::Table) {
+                                    // パースの失敗. トークンを無視する
+                                    continue;
+                                }
+                                self.pop_until(ElementKind::Table);
+                                self.reset_insertion_mode();
+                                continue;
+                            }
+                            // パースの失敗. トークンを無視する
+                            "body" | "caption" | "col" | "colgroup" | "html" | "tbody" | "td"
+                            | "tfoot" | "th" | "thead" | "tr" => {
+                                token = self.t.next();
+                                continue;
+                            }
+                            _ => {}
+                        },
+                        Some(HtmlToken::Eof) | None => {
+                            return self.window.clone();
+                        }
+                    }
+                    // それ以外はパースの失敗. 表の直前に挿入するようにして、"in body"挿入モードの規則で処理する
+                    self.foster_parenting = true;
+                    rules = Some(InsertionMode::InBody);
+                    continue;
+                }
+
+                // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-incaption
+                InsertionMode::InCaption => {
+                    match token {
+                        Some(HtmlToken::StartTag { ref tag, .. })
+                            if is_table_structure_tag(tag) =>
+                        {
+                            // キャプションを閉じてから、トークンを処理し直す
+                            if !self.has_in_table_scope(ElementKind::Caption) {
+                                token = self.t.next();
+                                continue;
+                            }
+                            self.pop_until(ElementKind::Caption);
+                            self.mode = InsertionMode::InTable;
+                            continue;
+                        }
+                        Some(HtmlToken::EndTag { ref tag }) => match tag.as_str() {
+                            "caption" => {
+                                token = self.t.next();
+                                if self.has_in_table_scope(ElementKind::Caption) {
+                                    self.pop_until(ElementKind::Caption);
+                                    self.mode = InsertionMode::InTable;
+                                }
+                                continue;
+                            }
+                            "table" => {
+                                if !self.has_in_table_scope(ElementKind::Caption) {
+                                    token = self.t.next();
+                                    continue;
+                                }
+                                self.pop_until(ElementKind::Caption);
+                                self.mode = InsertionMode::InTable;
+                                continue;
+                            }
+                            // パースの失敗. トークンを無視する
+                            "body" | "col" | "colgroup" | "html" | "tbody" | "td" | "tfoot"
+                            | "th" | "thead" | "tr" => {
+                                token = self.t.next();
+                                continue;
+                            }
+                            _ => {}
+                        },
+                        _ => {}
+                    }
+                    rules = Some(InsertionMode::InBody);
+                    continue;
+                }
+
+                // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-incolgroup
+                InsertionMode::InColumnGroup => {
+                    match token {
+                        Some(HtmlToken::Char(c)) => {
+                            if c == ' ' || c == '\n' {
+                                self.insert_char(c);
+                                token = self.t.next();
+                                continue;
+                            }
+                        }
+                        // <col>は空要素なので、挿入してすぐにpopする
+                        Some(HtmlToken::StartTag {
+                            ref tag,
+                            self_closing: _,
+                            ref attributes,
+                        }) if tag == "col" => {
+                            self.insert_element(tag, attributes.to_vec());
+                            self.stack_of_open_elements.pop();
+                            token = self.t.next();
+                            continue;
+                        }
+                        Some(HtmlToken::EndTag { ref tag })
+                            if tag == "colgroup" || tag == "col" =>
+                        {
+                            if tag == "colgroup" && self.pop_current_node(ElementKind::Colgroup) {
+                                self.mode = InsertionMode::InTable;
+                            }
+                            token = self.t.next();
+                            continue;
+                        }
+                        Some(HtmlToken::Eof) | None => {
+                            return self.window.clone();
+                        }
+                        _ => {}
+                    }
+                    // それ以外は列グループを閉じてから、トークンを処理し直す
+                    if !self.pop_current_node(ElementKind::Colgroup) {
+                        token = self.t.next();
+                        continue;
+                    }
+                    self.mode = InsertionMode::InTable;
+                    continue;
+                }
+
+                // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intbody
+                InsertionMode::InTableBody => {
+                    match token {
+                        Some(HtmlToken::StartTag {
+                            ref tag,
+                            self_closing: _,
+                            ref attributes,
+                        }) => match tag.as_str() {
+                            "tr" => {
+                                self.clear_stack_back_to(TABLE_BODY_CONTEXT);
+                                self.insert_element(tag, attributes.to_vec());
+                                self.mode = InsertionMode::InRow;
+                                token = self.t.next();
+                                continue;
+                            }
+                            // 省略された<tr>を補い、トークンを処理し直す
+                            "td" | "th" => {
+                                self.clear_stack_back_to(TABLE_BODY_CONTEXT);
+                                self.insert_element("tr", Vec::new());
+                                self.mode = InsertionMode::InRow;
+                                continue;
+                            }
+                            "caption" | "col" | "colgroup" | "tbody" | "tfoot" | "thead" => {
+                                if self.close_table_body() {
+                                    continue;
+                                }
+                                token = self.t.next();
+                                continue;
+                            }
+                            _ => {}
+                        },
+                        Some(HtmlToken::EndTag { ref tag }) => match tag.as_str() {
+                            "tbody" | "tfoot" | "thead" => {
+                                let element_kind = ElementKind::from_str(tag)
+                                    .expect("failed to convert string to ElementKind");
+                                token = self.t.next();
+                                if self.has_in_table_scope(element_kind) {
+                                    self.clear_stack_back_to(TABLE_BODY_CONTEXT);
+                                    self.stack_of_open_elements.pop();
+                                    self.mode = InsertionMode::InTable;
+                                }
+                                continue;
+                            }
+                            "table" => {
+                                if self.close_table_body() {
+                                    continue;
+                                }
+                                token = self.t.next();
+                                continue;
+                            }
+                            // パースの失敗. トークンを無視する
+                            "body" | "caption" | "col" | "colgroup" | "html" | "td" | "th"
+                            | "tr" => {
+                                token = self.t.next();
+                                continue;
+                            }
+                            _ => {}
+                        },
+                        _ => {}
+                    }
+                    rules = Some(InsertionMode::InTable);
+                    continue;
+                }
+
+                // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intr
+                InsertionMode::InRow => {
+                    match token {
+                        Some(HtmlToken::StartTag {
+                            ref tag,
+                            self_closing: _,
+                            ref attributes,
+                        }) => match tag.as_str() {
+                            "td" | "th" => {
+                                self.clear_stack_back_to(&[ElementKind::Tr]);
+                                self.insert_element(tag, attributes.to_vec());
+                                self.mode = InsertionMode::InCell;
+                                token = self.t.next();
+                                continue;
+                            }
+                            "caption" | "col" | "colgroup" | "tbody" | "tfoot" | "thead" | "tr" => {
+                                if self.close_row() {
+                                    continue;
+                                }
+                                token = self.t.next();
+                                continue;
+                            }
+                            _ => {}
+                        },
+                        Some(HtmlToken::EndTag { ref tag }) => match tag.as_str() {
+                            "tr" => {
+                                token = self.t.next();
+                                self.close_row();
+                                continue;
+                            }
+                            "table" => {
+                                if self.close_row() {
+                                    continue;
+                                }
+                                token = self.t.next();
+                                continue;
+                            }
+                            "tbody" | "tfoot" | "thead" => {
+                                let element_kind = ElementKind::from_str(tag)
+                                    .expect("failed to convert string to ElementKind");
+                                if self.has_in_table_scope(element_kind) && self.close_row() {
+                                    continue;
+                                }
+                                token = self.t.next();
+                                continue;
+                            }
+                            // パースの失敗. トークンを無視する
+                            "body" | "caption" | "col" | "colgroup" | "html" | "td" | "th" => {
+                                token = self.t.next();
+                                continue;
+                            }
+                            _ => {}
+                        },
+                        _ => {}
+                    }
+                    rules = Some(InsertionMode::InTable);
+                    continue;
+                }
+
+                // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intd
+                InsertionMode::InCell => {
+                    match token {
+                        Some(HtmlToken::StartTag { ref tag, .. })
+                            if is_table_structure_tag(tag) =>
+                        {
+                            // セルを閉じてから、トークンを処理し直す
+                            if !self.has_in_table_scope(ElementKind::Td)
+                                && !self.has_in_table_scope(ElementKind::Th)
+                            {
+                                token = self.t.next();
+                                continue;
+                            }
+                            self.close_cell();
+                            continue;
+                        }
+                        Some(HtmlToken::EndTag { ref tag }) => match tag.as_str() {
+                            "td" | "th" => {
+                                let element_kind = ElementKind::from_str(tag)
+                                    .expect("failed to convert string to ElementKind");
+                                token = self.t.next();
+                                if self.has_in_table_scope(element_kind) {
+                                    self.pop_until(element_kind);
+                                    self.mode = InsertionMode::InRow;
+                                }
+                                continue;
+                            }
+                            "table" | "tbody" | "tfoot" | "thead" | "tr" => {
+                                let element_kind = ElementKind::from_str(tag)
+                                    .expect("failed to convert string to ElementKind");
+                                if !self.has_in_table_scope(element_kind) {
+                                    token = self.t.next();
+                                    continue;
+                                }
+                                self.close_cell();
+                                continue;
+                            }
+                            // パースの失敗. トークンを無視する
+                            "body" | "caption" | "col" | "colgroup" | "html" => {
+                                token = self.t.next();
+                                continue;
+                            }
+                            _ => {}
+                        },
+                        _ => {}
+                    }
+                    rules = Some(InsertionMode::InBody);
+                    continue;
+                }
+
                 InsertionMode::AfterBody => {
                     match token {
                         // 文字トークンなら無視
@@ -536,8 +1106,53 @@ impl HtmlParser {
     }
 }
 
+/// https://html.spec.whatwg.org/multipage/parsing.html#clear-the-stack-back-to-a-table-body-context
+/// 行グループを表す要素. 行を挿入する前に、スタックをこれらの要素までpopする
+const TABLE_BODY_CONTEXT: &[ElementKind] =
+    &[ElementKind::Tbody, ElementKind::Tfoot, ElementKind::Thead];
+
+/// 開いているセルやキャプションを閉じる、表の構造を表す開始タグか
+fn is_table_structure_tag(tag: &str) -> bool {
+    matches!(
+        tag,
+        "caption" | "col" | "colgroup" | "tbody" | "td" | "tfoot" | "th" | "thead" | "tr"
+    )
+}
+
+/// `node`を`parent`の子として、`before`の直前に挿入する. `before`がNoneなら最後の子として挿入する
+fn insert_node(
+    parent: &Rc<RefCell<Node>>,
+    before: Option<Rc<RefCell<Node>>>,
+    node: &Rc<RefCell<Node>>,
+) {
+    let previous = match &before {
+        Some(before) => before.borrow().previous_sibling().upgrade(),
+        None => parent.borrow().last_child().upgrade(),
+    };
+    match &previous {
+        Some(previous) => {
+            previous.borrow_mut().set_next_sibling(Some(node.clone()));
+            node.borrow_mut()
+                .set_previous_sibling(Rc::downgrade(previous));
+        }
+        // 兄弟ノードが存在しない場合、最初の子要素として設定
+        None => parent.borrow_mut().set_first_child(Some(node.clone())),
+    }
+    match before {
+        Some(before) => {
+            before
+                .borrow_mut()
+                .set_previous_sibling(Rc::downgrade(node));
+            node.borrow_mut().set_next_sibling(Some(before));
+        }
+        None => parent.borrow_mut().set_last_child(Rc::downgrade(node)),
+    }
+    node.borrow_mut().set_parent(Rc::downgrade(parent));
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::format;
     use alloc::{string::ToString, vec};
 
     use super::*;
@@ -722,4 +1337,343 @@ mod tests {
             text
         );
     }
+
+    #[test]
+    fn test_style_in_body() {
+        /* <body>内の<style>要素が、テキストを子に持つ要素として処理されることを確認する */
+        let html =
+            "<html><head></head><body><style>p{}</style><p>text</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+
+        let style = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "style",
+                Vec::new()
+            ))))),
+            style
+        );
+
+        let text = style
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of style");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Text("p{}".to_string())))),
+            text
+        );
+
+        let p = style
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of style");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "p",
+                Vec::new()
+            ))))),
+            p
+        );
+    }
+
+    #[test]
+    fn test_text_and_inline_siblings() {
+        /* テキストとインライン要素が兄弟として並び、インライン要素の前後の空白が残ることを確認する */
+        let html = "<html><head></head><body><p>a <a>b</a> c</p><p>d</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+        let p = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+
+        let text1 = p
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of p");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Text("a ".to_string())))),
+            text1
+        );
+        let a = text1
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of text");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "a",
+                Vec::new()
+            ))))),
+            a
+        );
+        let text2 = a
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of a");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Text(" c".to_string())))),
+            text2
+        );
+        assert!(text2.borrow().next_sibling().is_none());
+        assert!(Rc::ptr_eq(
+            &p.borrow()
+                .last_child()
+                .upgrade()
+                .expect("failed to get a last child of p"),
+            &text2
+        ));
+        assert!(Rc::ptr_eq(
+            &text2
+                .borrow()
+                .previous_sibling()
+                .upgrade()
+                .expect("failed to get a previous sibling of text"),
+            &a
+        ));
+
+        let p2 = p
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of p");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "p",
+                Vec::new()
+            ))))),
+            p2
+        );
+    }
+
+    /// HTMLを構文解析し、<body>の子孫を1行に1つのノードを書いた文字列にする
+    /// 親と前の兄弟への参照が、ツリーの構造と一致していることも確認する
+    fn dump_body(html: &str) -> String {
+        let t = HtmlTokenizer::new(html.to_string());
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+        let mut result = String::new();
+        dump_children(&body, 0, &mut result);
+        result
+    }
+
+    fn dump_children(parent: &Rc<RefCell<Node>>, depth: usize, result: &mut String) {
+        let mut previous: Option<Rc<RefCell<Node>>> = None;
+        let mut current = parent.borrow().first_child();
+        while let Some(node) = current {
+            let parent_of_node = node.borrow().parent().upgrade();
+            assert!(parent_of_node.map_or(false, |p| Rc::ptr_eq(&p, parent)));
+            let previous_of_node = node.borrow().previous_sibling().upgrade();
+            assert_eq!(
+                previous_of_node.map(|n| Rc::as_ptr(&n)),
+                previous.as_ref().map(Rc::as_ptr)
+            );
+
+            let name = match node.borrow().kind() {
+                NodeKind::Element(element) => element.kind().to_string(),
+                NodeKind::Text(text) => format!("{:?}", text),
+                NodeKind::Document => "#document".to_string(),
+            };
+            result.push_str(&format!("{}{}\n", "  ".repeat(depth), name));
+            dump_children(&node, depth + 1, result);
+            current = node.borrow().next_sibling();
+            previous = Some(node);
+        }
+        let last_child = parent.borrow().last_child().upgrade();
+        assert_eq!(
+            last_child.map(|n| Rc::as_ptr(&n)),
+            previous.as_ref().map(Rc::as_ptr)
+        );
+    }
+
+    #[test]
+    fn test_table_implied_elements() {
+        /* 省略された<tbody>と<tr>が補われ、終了タグのないセルと行が次のセルと行で閉じられることを確認する */
+        let tree = dump_body(
+            "<html><head></head><body><table>\n<td>a<td>b\n<tr><th>c</table></body></html>",
+        );
+        assert_eq!(
+            tree,
+            "table\n\
+             \x20 tbody\n\
+             \x20   tr\n\
+             \x20     td\n\
+             \x20       \"a\"\n\
+             \x20     td\n\
+             \x20       \"b\\n\"\n\
+             \x20   tr\n\
+             \x20     th\n\
+             \x20       \"c\"\n"
+        );
+    }
+
+    #[test]
+    fn test_table_sections() {
+        /* キャプション、列グループ、行グループが表の子として処理され、表の後の要素が<body>に戻ることを確認する */
+        let tree = dump_body(
+            "<html><head></head><body><table><caption>cap</caption>\
+             <colgroup><col><col></colgroup><col>\
+             <thead><tr><th>h</th></tr></thead><tbody><tr><td>d</td></tr></tbody>\
+             <tfoot><tr><td>f</td></tr></tfoot></table><p>after</p></body></html>",
+        );
+        assert_eq!(
+            tree,
+            "table\n\
+             \x20 caption\n\
+             \x20   \"cap\"\n\
+             \x20 colgroup\n\
+             \x20   col\n\
+             \x20   col\n\
+             \x20 colgroup\n\
+             \x20   col\n\
+             \x20 thead\n\
+             \x20   tr\n\
+             \x20     th\n\
+             \x20       \"h\"\n\
+             \x20 tbody\n\
+             \x20   tr\n\
+             \x20     td\n\
+             \x20       \"d\"\n\
+             \x20 tfoot\n\
+             \x20   tr\n\
+             \x20     td\n\
+             \x20       \"f\"\n\
+             p\n\
+             \x20 \"after\"\n"
+        );
+    }
+
+    #[test]
+    fn test_foster_parenting() {
+        /* 表の構造を表す要素の中に置けないテキストと要素が、表の直前に挿入されることを確認する */
+        let tree = dump_body(
+            "<html><head></head><body><table>x<tr><td>a</td></tr>y<a>z</a></table></body></html>",
+        );
+        assert_eq!(
+            tree,
+            "\"xy\"\n\
+             a\n\
+             \x20 \"z\"\n\
+             table\n\
+             \x20 tbody\n\
+             \x20   tr\n\
+             \x20     td\n\
+             \x20       \"a\"\n"
+        );
+    }
+
+    #[test]
+    fn test_table_end_closes_cell() {
+        /* 表の終了タグで、開いているセルの中の要素とセル、行、行グループがまとめて閉じられることを確認する */
+        let tree = dump_body(
+            "<html><head></head><body><table><tr><td>a<p>b</table>c<table><tr><td><table>\
+             <tr><td>d</td></tr></table></td></tr></table></body></html>",
+        );
+        assert_eq!(
+            tree,
+            "table\n\
+             \x20 tbody\n\
+             \x20   tr\n\
+             \x20     td\n\
+             \x20       \"a\"\n\
+             \x20       p\n\
+             \x20         \"b\"\n\
+             \"c\"\n\
+             table\n\
+             \x20 tbody\n\
+             \x20   tr\n\
+             \x20     td\n\
+             \x20       table\n\
+             \x20         tbody\n\
+             \x20           tr\n\
+             \x20             td\n\
+             \x20               \"d\"\n"
+        );
+    }
+
+    #[test]
+    fn test_unmatched_end_tag_in_cell() {
+        /* セルの外で開いた要素や閉じた要素の終了タグが、セルの中では無視されることを確認する */
+        let tree = dump_body(
+            "<html><head></head><body><table><tr><td><h1>x</td><td>y</h1></td></tr></table>\
+             <h2><table><tr><td>z</h2></p></td></tr></table></h2></body></html>",
+        );
+        assert_eq!(
+            tree,
+            "table\n\
+             \x20 tbody\n\
+             \x20   tr\n\
+             \x20     td\n\
+             \x20       h1\n\
+             \x20         \"x\"\n\
+             \x20     td\n\
+             \x20       \"y\"\n\
+             h2\n\
+             \x20 table\n\
+             \x20   tbody\n\
+             \x20     tr\n\
+             \x20       td\n\
+             \x20         \"z\"\n"
+        );
+    }
+
+    #[test]
+    fn test_list_items() {
+        /* li開始タグで前のli要素が閉じられ、入れ子のリストの中では外側のli要素が閉じられないことを確認する */
+        let tree = dump_body(
+            "<html><head></head><body><ul><li>a<li>b<ol><li>c<li>d</ol>e</li></ul>f</li>\
+             </body></html>",
+        );
+        assert_eq!(
+            tree,
+            "ul\n\
+             \x20 li\n\
+             \x20   \"a\"\n\
+             \x20 li\n\
+             \x20   \"b\"\n\
+             \x20   ol\n\
+             \x20     li\n\
+             \x20       \"c\"\n\
+             \x20     li\n\
+             \x20       \"d\"\n\
+             \x20   \"e\"\n\
+             \"f\"\n"
+        );
+    }
 }
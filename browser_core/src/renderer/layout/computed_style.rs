@@ -1,248 +1,2046 @@
 use crate::error::Error;
+use crate::renderer::css::color::Color;
+use crate::renderer::css::cssom::ComponentValue;
+use crate::renderer::css::cssom::Declaration;
+use crate::renderer::css::property::lookup_property;
+use crate::renderer::css::property::properties;
+use crate::renderer::css::property::ComputeContext;
+use crate::renderer::css::serialize::serialize_tokens;
+use crate::renderer::css::shorthand::expand_declaration;
+use crate::renderer::css::shorthand::longhands_of;
+use crate::renderer::css::value::is_css_wide_keyword;
+use crate::renderer::css::value::ComputedLength;
+use crate::renderer::css::value::CssValue;
+use crate::renderer::css::value::Length;
+use crate::renderer::css::value::ResolutionContext;
+use crate::renderer::css::value::DEFAULT_FONT_SIZE;
+use crate::renderer::css::variable::contains_var;
+use crate::renderer::css::variable::is_custom_property;
+use crate::renderer::css::variable::resolve_custom_properties;
+use crate::renderer::css::variable::substitute_var;
 use crate::renderer::dom::node::ElementKind;
 use crate::renderer::dom::node::Node;
 use crate::renderer::dom::node::NodeKind;
+use alloc::collections::BTreeMap;
 use alloc::format;
 use alloc::rc::Rc;
 use alloc::string::String;
 use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
 use core::cell::RefCell;
+use core::str::FromStr;
 
+/// https://www.w3.org/TR/css-inline-3/#valdef-line-height-normal
+/// line-height: normalのときの、フォントサイズに対する行の高さの倍率
+const NORMAL_LINE_HEIGHT: f64 = 1.2;
+
+/// 要素のスタイル
+/// カスケードで選ばれた宣言を`cascade_declaration`で受け取り、`defaulting`で
+/// すべてのプロパティの計算値を求める. プロパティの定義は`css::property`の表にある
 #[derive(Debug, Clone, PartialEq)]
 pub struct ComputedStyle {
-    background_color: Option<Color>,
-    color: Option<Color>,
-    display: Option<DisplayType>,
-    font_size: Option<FontSize>,
-    text_decoration: Option<TextDecoration>,
-    height: Option<f64>,
-    width: Option<f64>,
+    /// https://www.w3.org/TR/css-cascade-4/#cascaded
+    /// 作成者のスタイルシートでカスケードに勝った値と、!importantかどうか
+    declared: BTreeMap<String, (DeclaredValue, bool)>,
+    /// https://www.w3.org/TR/css-variables-1/#defining-variables
+    /// カスタムプロパティの宣言. 値はトークン列のまま保持する
+    declared_custom_properties: BTreeMap<String, (Vec<ComponentValue>, bool)>,
+    /// https://www.w3.org/TR/css-cascade-4/#computed
+    /// プロパティ名ごとの計算値
+    values: BTreeMap<String, CssValue>,
+    /// カスタムプロパティの計算値. var()は置換済み
+    custom_properties: BTreeMap<String, Vec<ComponentValue>>,
+    /// remの基準になる、ルート要素のフォントサイズ
+    root_font_size: f64,
+    /// vwやvhの基準になる、ビューポートの幅と高さ. 疑似要素と匿名ボックスは元のスタイルから受け取る
+    viewport: (f64, f64),
+}
+
+impl ComputedStyle {
+    pub fn new() -> Self {
+        Self {
+            declared: BTreeMap::new(),
+            declared_custom_properties: BTreeMap::new(),
+            values: BTreeMap::new(),
+            custom_properties: BTreeMap::new(),
+            root_font_size: DEFAULT_FONT_SIZE,
+            viewport: {
+                let resolution = ResolutionContext::default();
+                (resolution.viewport_width(), resolution.viewport_height())
+            },
+        }
+    }
+
+    /// https://www.w3.org/TR/css-cascade-4/#cascading
+    /// 宣言をロングハンドに展開してカスケードに加える. 後から加えた宣言ほど優先されるが、
+    /// !importantな宣言は通常の宣言で上書きされない
+    /// サポートしていないプロパティや、文法に一致しない値の宣言は無視する
+    pub fn cascade_declaration(&mut self, declaration: &Declaration) {
+        // https://www.w3.org/TR/css-variables-1/#defining-variables
+        // カスタムプロパティの名前は大文字・小文字を区別し、値はどんなトークン列でもよい
+        if is_custom_property(&declaration.property) {
+            let name = declaration.property.clone();
+            if let Some((_, true)) = self.declared_custom_properties.get(&name) {
+                if !declaration.important {
+                    return;
+                }
+            }
+            self.declared_custom_properties
+                .insert(name, (declaration.value.clone(), declaration.important));
+            return;
+        }
+
+        // https://www.w3.org/TR/css-variables-1/#variables-in-shorthands
+        // var()を含む宣言は計算値の時点まで文法を検査できないので、トークン列のまま保持する
+        let longhands = if contains_var(&declaration.value) {
+            let property = declaration.property.to_ascii_lowercase();
+            let names = longhands_of(&property).unwrap_or_else(|| vec![property.clone()]);
+            names
+                .into_iter()
+                .map(|name| {
+                    let value = DeclaredValue::Unresolved {
+                        property: property.clone(),
+                        tokens: declaration.value.clone(),
+                    };
+                    (name, value)
+                })
+                .collect()
+        } else {
+            match expand_declaration(&declaration.property, &declaration.value) {
+                Ok(longhands) => longhands
+                    .into_iter()
+                    .map(|(name, value)| (name, DeclaredValue::Value(value)))
+                    .collect::<Vec<(String, DeclaredValue)>>(),
+                Err(_) => return,
+            }
+        };
+
+        for (name, value) in longhands {
+            let property = match lookup_property(&name) {
+                Some(property) => property,
+                None => continue,
+            };
+            let valid = match &value {
+                DeclaredValue::Value(CssValue::Keyword(keyword))
+                    if is_css_wide_keyword(keyword) =>
+                {
+                    true
+                }
+                DeclaredValue::Value(value) => property.parse(value),
+                DeclaredValue::Unresolved { .. } => true,
+            };
+            if !valid {
+                continue;
+            }
+            if let Some((_, true)) = self.declared.get(&name) {
+                if !declaration.important {
+                    continue;
+                }
+            }
+            self.declared.insert(name, (value, declaration.important));
+        }
+    }
+
+    /// https://www.w3.org/TR/css-variables-1/#substitute-a-var
+    /// var()をカスタムプロパティの計算値で置換して、ロングハンド`name`の値を求める
+    /// 置換できない場合や、置換した結果が文法に一致しない場合は、計算値の時点で無効なのでunsetになる
+    fn resolve_declared_value(&self, name: &str, value: &DeclaredValue) -> CssValue {
+        let (property, tokens) = match value {
+            DeclaredValue::Value(value) => return value.clone(),
+            DeclaredValue::Unresolved { property, tokens } => (property, tokens),
+        };
+        let unset = CssValue::Keyword("unset".to_string());
+
+        let mut lookup = |reference: &str| self.custom_properties.get(reference).cloned();
+        let substituted = match substitute_var(tokens, &mut lookup) {
+            Some(substituted) => substituted,
+            None => return unset,
+        };
+
+        let longhands = match expand_declaration(property, &substituted) {
+            Ok(longhands) => longhands,
+            Err(_) => return unset,
+        };
+        match longhands.into_iter().find(|(longhand, _)| longhand == name) {
+            Some((_, CssValue::Keyword(keyword))) if is_css_wide_keyword(&keyword) => {
+                CssValue::Keyword(keyword)
+            }
+            Some((_, value)) if lookup_property(name).map_or(false, |p| p.parse(&value)) => value,
+            _ => unset,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-variables-1/#defining-variables
+    /// カスタムプロパティはすべて継承する. initialは初期値（無効な値）になり、プロパティがないものとして扱う
+    fn compute_custom_properties(&mut self, parent_style: Option<&ComputedStyle>, is_text: bool) {
+        let mut specified =
+            parent_style.map_or(BTreeMap::new(), |parent| parent.custom_properties.clone());
+
+        if !is_text {
+            for (name, (tokens, _)) in &self.declared_custom_properties {
+                match trim_keyword(tokens) {
+                    Some(keyword) if keyword.eq_ignore_ascii_case("initial") => {
+                        specified.remove(name);
+                    }
+                    // inherit、unset、revertは親要素の値（すでにspecifiedに入っている）
+                    Some(keyword) if is_css_wide_keyword(keyword) => {}
+                    _ => {
+                        specified.insert(name.clone(), tokens.clone());
+                    }
+                }
+            }
+        }
+
+        self.custom_properties = resolve_custom_properties(&specified);
+    }
+
+    /// カスタムプロパティの計算値を返す
+    pub fn custom_property(&self, name: &str) -> Option<&Vec<ComponentValue>> {
+        self.custom_properties.get(name)
+    }
+
+    /// https://www.w3.org/TR/css-cascade-4/#defaulting
+    /// カスケードで値が決まらなかったプロパティに、継承する値または初期値を与え、
+    /// すべてのプロパティの計算値を求める
+    pub fn defaulting(&mut self, node: &Rc<RefCell<Node>>, parent_style: Option<ComputedStyle>) {
+        let user_agent = user_agent_values(node);
+        // テキストは宣言の対象にならず、親要素から値を受け取るだけ
+        let is_text = matches!(node.borrow().kind(), NodeKind::Text(_));
+        self.compute_values(&user_agent, is_text, parent_style);
+    }
+
+    /// https://www.w3.org/TR/css-pseudo-4/#treelike
+    /// 疑似要素のスタイルを求める. 疑似要素は元の要素から継承し、ユーザーエージェントのスタイルを持たない
+    pub fn defaulting_pseudo_element(&mut self, parent_style: ComputedStyle) {
+        self.viewport = parent_style.viewport;
+        self.compute_values(&BTreeMap::new(), false, Some(parent_style));
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#anonymous
+    /// 匿名ボックスのスタイルを求める. 継承するプロパティは囲んでいるボックスから受け取り、それ以外は初期値になる
+    pub fn defaulting_anonymous_box(&mut self, parent_style: ComputedStyle, display: DisplayType) {
+        self.viewport = parent_style.viewport;
+        self.compute_values(&BTreeMap::new(), false, Some(parent_style));
+        self.set_display(display);
+    }
+
+    fn compute_values(
+        &mut self,
+        user_agent: &BTreeMap<&'static str, CssValue>,
+        is_text: bool,
+        parent_style: Option<ComputedStyle>,
+    ) {
+        self.root_font_size = parent_style
+            .as_ref()
+            .map_or(DEFAULT_FONT_SIZE, |parent| parent.root_font_size);
+        self.values.clear();
+        self.compute_custom_properties(parent_style.as_ref(), is_text);
+
+        for property in properties() {
+            let name = property.name();
+            let declared = if is_text {
+                None
+            } else {
+                self.declared
+                    .get(name)
+                    .map(|(value, _)| self.resolve_declared_value(name, value))
+            };
+
+            // https://www.w3.org/TR/css-cascade-4/#valdef-all-revert
+            // revertは作成者のスタイルを取り消し、ユーザーエージェントのスタイルに戻す
+            let cascaded = match declared {
+                Some(CssValue::Keyword(keyword)) if keyword == "revert" => {
+                    user_agent.get(name).cloned()
+                }
+                Some(value) => Some(value),
+                None => user_agent.get(name).cloned(),
+            };
+
+            let inherit = match &cascaded {
+                Some(CssValue::Keyword(keyword)) => match keyword.as_str() {
+                    "inherit" => true,
+                    "initial" => false,
+                    "unset" | "revert" => property.inherited(),
+                    _ => false,
+                },
+                None => property.inherited(),
+                _ => false,
+            };
+            let parent_value = parent_style
+                .as_ref()
+                .and_then(|parent| parent.values.get(name).cloned());
+
+            // https://www.w3.org/TR/css-cascade-4/#inheriting
+            // 継承する値は親要素の計算値なので、計算し直さない
+            if inherit {
+                if let Some(parent_value) = parent_value {
+                    self.values.insert(name.to_string(), parent_value);
+                    continue;
+                }
+            }
+
+            let specified = match cascaded {
+                Some(CssValue::Keyword(keyword)) if is_css_wide_keyword(&keyword) => {
+                    property.initial_value()
+                }
+                Some(value) if !inherit => value,
+                _ => property.initial_value(),
+            };
+
+            let context = ComputeContext {
+                style: self,
+                parent: parent_style.as_ref(),
+                viewport: self.viewport,
+            };
+            // https://www.w3.org/TR/css-variables-1/#invalid-at-computed-value-time
+            // 計算できない値は、unsetが指定されたものとして扱う
+            let computed = match property.compute(&specified, &context) {
+                Some(computed) => computed,
+                None => match (property.inherited(), parent_value) {
+                    (true, Some(parent_value)) => parent_value,
+                    _ => property
+                        .compute(&property.initial_value(), &context)
+                        .expect("initial value should be computable"),
+                },
+            };
+            self.values.insert(name.to_string(), computed);
+        }
+
+        // https://www.w3.org/TR/css-display-3/#blockify
+        // フレックスコンテナとグリッドコンテナの子はアイテムになり、displayはブロックレベルの値になる
+        // https://www.w3.org/TR/CSS2/visuren.html#dis-pos-flo
+        // 絶対位置指定されたボックスとフロートも、displayはブロックレベルの値になる
+        let in_item_container = parent_style.as_ref().map_or(false, |parent| {
+            parent.display().is_flex_container() || parent.display().is_grid_container()
+        });
+        let is_out_of_flow = self.position().is_out_of_flow() || self.float() != Float::None;
+        if !is_text && (in_item_container || is_out_of_flow) {
+            self.set_display(self.display().blockified());
+        }
+
+        if parent_style.is_none() {
+            self.root_font_size = self.font_size();
+        }
+    }
+
+    /// プロパティの計算値を返す. 計算前またはサポートしていないプロパティの場合はNone
+    pub fn get_value(&self, property: &str) -> Option<&CssValue> {
+        self.values.get(property)
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-getpropertyvalue
+    /// プロパティの計算値を文字列で返す. サポートしていないプロパティの場合は空文字列
+    pub fn get_property_value(&self, property: &str) -> String {
+        if is_custom_property(property) {
+            return self
+                .custom_properties
+                .get(property)
+                .map(|tokens| serialize_tokens(tokens))
+                .unwrap_or_default();
+        }
+
+        let property = property.to_ascii_lowercase();
+        if lookup_property(&property).is_none() {
+            return String::new();
+        }
+        self.resolve(&property, |value| Some(value.css_text()))
+            .unwrap_or_default()
+    }
+
+    /// 計算値を`convert`で変換する. 計算前のプロパティや変換できない値の場合は、
+    /// 初期値の計算値を変換する（パニックしない）
+    fn resolve<T>(&self, property: &str, convert: impl Fn(&CssValue) -> Option<T>) -> Option<T> {
+        self.values
+            .get(property)
+            .and_then(&convert)
+            .or_else(|| convert(&self.initial_value(property)?))
+    }
+
+    /// https://www.w3.org/TR/css-cascade-4/#initial-value
+    fn initial_value(&self, property: &str) -> Option<CssValue> {
+        let property = lookup_property(property)?;
+        let context = ComputeContext {
+            style: self,
+            parent: None,
+            viewport: self.viewport,
+        };
+        property.compute(&property.initial_value(), &context)
+    }
+
+    fn keyword<T: FromStr>(&self, property: &str) -> Option<T> {
+        self.resolve(property, |value| match value {
+            CssValue::Keyword(keyword) => T::from_str(keyword).ok(),
+            _ => None,
+        })
+    }
+
+    fn length(&self, property: &str) -> ComputedLength {
+        self.resolve(property, |value| match value {
+            CssValue::Keyword(keyword) if keyword == "auto" => Some(ComputedLength::Auto),
+            CssValue::Length(length) => Some(ComputedLength::Px(length.value())),
+            CssValue::Percentage(percentage) => Some(ComputedLength::Percentage(*percentage)),
+            CssValue::Calc(node) => Some(ComputedLength::Calc(node.clone())),
+            _ => None,
+        })
+        .unwrap_or(ComputedLength::Auto)
+    }
+
+    fn px(&self, property: &str) -> f64 {
+        self.resolve(property, |value| match value {
+            CssValue::Length(length) => Some(length.value()),
+            _ => None,
+        })
+        .unwrap_or(0.0)
+    }
+
+    fn number(&self, property: &str) -> f64 {
+        self.resolve(property, |value| match value {
+            CssValue::Number(number) => Some(*number),
+            _ => None,
+        })
+        .unwrap_or(0.0)
+    }
+
+    fn color_of(&self, property: &str) -> Color {
+        self.resolve(property, |value| match value {
+            CssValue::Color(color) => Some(*color),
+            _ => None,
+        })
+        .unwrap_or(Color::black())
+    }
+
+    fn set_length(&mut self, property: &str, length: ComputedLength) {
+        let value = match length {
+            ComputedLength::Auto => CssValue::Keyword("auto".to_string()),
+            ComputedLength::Px(px) => CssValue::Length(Length::px(px)),
+            ComputedLength::Percentage(percentage) => CssValue::Percentage(percentage),
+            ComputedLength::Calc(node) => CssValue::Calc(node),
+        };
+        self.values.insert(property.to_string(), value);
+    }
+
+    /// remの基準になる、ルート要素のフォントサイズ
+    pub fn root_font_size(&self) -> f64 {
+        self.root_font_size
+    }
+
+    /// vwやvhの基準になる、ビューポートの幅と高さ
+    pub fn viewport(&self) -> (f64, f64) {
+        self.viewport
+    }
+
+    /// https://www.w3.org/TR/css-values-4/#viewport-relative-lengths
+    /// ビューポートの大きさを設定する. `defaulting`より前に設定する
+    pub fn set_viewport(&mut self, width: f64, height: f64) {
+        self.viewport = (width, height);
+    }
+
+    pub fn set_background_color(&mut self, color: Color) {
+        self.values
+            .insert("background-color".to_string(), CssValue::Color(color));
+    }
+
+    pub fn background_color(&self) -> Color {
+        self.color_of("background-color")
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.values
+            .insert("color".to_string(), CssValue::Color(color));
+    }
+
+    pub fn color(&self) -> Color {
+        self.color_of("color")
+    }
+
+    pub fn set_display(&mut self, display: DisplayType) {
+        self.values.insert(
+            "display".to_string(),
+            CssValue::Keyword(display.keyword().to_string()),
+        );
+    }
+
+    pub fn display(&self) -> DisplayType {
+        self.keyword("display").unwrap_or(DisplayType::Inline)
+    }
+
+    /// 計算値のフォントサイズ（ピクセル値）
+    pub fn font_size(&self) -> f64 {
+        self.px("font-size")
+    }
+
+    /// https://www.w3.org/TR/css-fonts-4/#font-style-prop
+    pub fn font_style(&self) -> FontStyle {
+        self.keyword("font-style").unwrap_or(FontStyle::Normal)
+    }
+
+    /// https://www.w3.org/TR/css-fonts-4/#font-weight-prop
+    /// 計算値は1から1000までの数値（normalは400、boldは700）
+    pub fn font_weight(&self) -> f64 {
+        self.resolve("font-weight", |value| match value {
+            CssValue::Number(weight) => Some(*weight),
+            _ => None,
+        })
+        .unwrap_or(400.0)
+    }
+
+    /// https://www.w3.org/TR/css-fonts-4/#font-family-prop
+    /// 優先する順のフォント名. 総称ファミリー（serifなど）はキーワードのまま返す
+    pub fn font_family(&self) -> Vec<String> {
+        self.resolve("font-family", |value| {
+            let families = match value {
+                CssValue::CommaList(list) => list.clone(),
+                family => vec![family.clone()],
+            };
+            families
+                .into_iter()
+                .map(|family| match family {
+                    CssValue::String(name) | CssValue::Keyword(name) => Some(name),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    /// https://www.w3.org/TR/css-inline-3/#line-height-property
+    pub fn line_height(&self) -> LineHeight {
+        self.resolve("line-height", |value| match value {
+            CssValue::Keyword(keyword) if keyword == "normal" => Some(LineHeight::Normal),
+            CssValue::Number(number) => Some(LineHeight::Number(*number)),
+            CssValue::Length(length) => Some(LineHeight::Px(length.value())),
+            _ => None,
+        })
+        .unwrap_or(LineHeight::Normal)
+    }
+
+    /// 行の高さの使用値（ピクセル値）. normalはフォントサイズの1.2倍とする
+    pub fn used_line_height(&self) -> f64 {
+        match self.line_height() {
+            LineHeight::Normal => self.font_size() * NORMAL_LINE_HEIGHT,
+            LineHeight::Number(number) => self.font_size() * number,
+            LineHeight::Px(px) => px,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-content-3/#content-property
+    /// 疑似要素の内容（文字列、attr()、カウンター）. normalとnoneの場合は内容を生成しないのでNone
+    pub fn content(&self) -> Option<Vec<CssValue>> {
+        match self.get_value("content")? {
+            CssValue::Keyword(_) => None,
+            CssValue::List(list) => Some(list.clone()),
+            value => Some(vec![value.clone()]),
+        }
+    }
+
+    /// contentがnoneか. ::markerは、normalの場合はマーカーを生成し、noneの場合は生成しない
+    pub fn is_content_none(&self) -> bool {
+        matches!(self.get_value("content"), Some(CssValue::Keyword(keyword)) if keyword == "none")
+    }
+
+    /// https://www.w3.org/TR/css-lists-3/#propdef-counter-reset
+    /// リセットするカウンターと値. 値を省略した場合は0
+    pub fn counter_reset(&self) -> Vec<(String, i64)> {
+        self.counters("counter-reset", 0)
+    }
+
+    /// https://www.w3.org/TR/css-lists-3/#propdef-counter-increment
+    /// 増やすカウンターと増分. 増分を省略した場合は1
+    pub fn counter_increment(&self) -> Vec<(String, i64)> {
+        self.counters("counter-increment", 1)
+    }
+
+    fn counters(&self, property: &str, default: i64) -> Vec<(String, i64)> {
+        let values = match self.get_value(property) {
+            Some(CssValue::List(list)) => list.clone(),
+            Some(value) => vec![value.clone()],
+            None => Vec::new(),
+        };
+
+        let mut counters: Vec<(String, i64)> = Vec::new();
+        for value in values {
+            match value {
+                CssValue::Keyword(name) if name != "none" => counters.push((name, default)),
+                CssValue::Number(n) => {
+                    if let Some(counter) = counters.last_mut() {
+                        counter.1 = n as i64;
+                    }
+                }
+                _ => {}
+            }
+        }
+        counters
+    }
+
+    pub fn text_decoration(&self) -> TextDecoration {
+        self.keyword("text-decoration")
+            .unwrap_or(TextDecoration::None)
+    }
+
+    pub fn text_align(&self) -> TextAlign {
+        self.keyword("text-align").unwrap_or(TextAlign::Left)
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#flex-direction-property
+    pub fn flex_direction(&self) -> FlexDirection {
+        self.keyword("flex-direction").unwrap_or(FlexDirection::Row)
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#flex-wrap-property
+    pub fn flex_wrap(&self) -> FlexWrap {
+        self.keyword("flex-wrap").unwrap_or(FlexWrap::NoWrap)
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#flex-grow-property
+    pub fn flex_grow(&self) -> f64 {
+        self.number("flex-grow")
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#flex-shrink-property
+    pub fn flex_shrink(&self) -> f64 {
+        self.number("flex-shrink")
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#flex-basis-property
+    /// autoは`FlexBasis::Size(ComputedLength::Auto)`になる
+    pub fn flex_basis(&self) -> FlexBasis {
+        match self.get_value("flex-basis") {
+            Some(CssValue::Keyword(keyword)) if keyword == "content" => FlexBasis::Content,
+            _ => FlexBasis::Size(self.length("flex-basis")),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#order-property
+    pub fn order(&self) -> i64 {
+        self.number("order") as i64
+    }
+
+    /// https://www.w3.org/TR/css-align-3/#propdef-justify-content
+    pub fn justify_content(&self) -> ContentAlignment {
+        self.keyword("justify-content")
+            .unwrap_or(ContentAlignment::Normal)
+    }
+
+    /// https://www.w3.org/TR/css-align-3/#propdef-align-content
+    pub fn align_content(&self) -> ContentAlignment {
+        self.keyword("align-content")
+            .unwrap_or(ContentAlignment::Normal)
+    }
+
+    /// https://www.w3.org/TR/css-align-3/#propdef-align-items
+    pub fn align_items(&self) -> ItemAlignment {
+        self.keyword("align-items").unwrap_or(ItemAlignment::Normal)
+    }
+
+    /// https://www.w3.org/TR/css-align-3/#propdef-align-self
+    /// autoの場合はNoneを返す. autoは親のalign-itemsの値を使う
+    pub fn align_self(&self) -> Option<ItemAlignment> {
+        self.keyword("align-self")
+    }
+
+    /// https://www.w3.org/TR/css-align-3/#column-row-gap
+    /// normalはフレックスコンテナとグリッドコンテナでは0になる
+    pub fn row_gap(&self) -> ComputedLength {
+        self.gap("row-gap")
+    }
+
+    pub fn column_gap(&self) -> ComputedLength {
+        self.gap("column-gap")
+    }
+
+    fn gap(&self, property: &str) -> ComputedLength {
+        match self.get_value(property) {
+            Some(CssValue::Keyword(keyword)) if keyword == "normal" => ComputedLength::Px(0.0),
+            _ => self.length(property),
+        }
+    }
+
+    /// https://www.w3.org/TR/css-align-3/#propdef-justify-items
+    /// legacyは、左から右に書く場合はnormalと同じになる
+    pub fn justify_items(&self) -> ItemAlignment {
+        self.keyword("justify-items")
+            .unwrap_or(ItemAlignment::Normal)
+    }
+
+    /// https://www.w3.org/TR/css-align-3/#propdef-justify-self
+    /// autoの場合はNoneを返す. autoは親のjustify-itemsの値を使う
+    pub fn justify_self(&self) -> Option<ItemAlignment> {
+        self.keyword("justify-self")
+    }
+
+    /// https://www.w3.org/TR/css-grid-2/#track-sizing
+    pub fn grid_template_columns(&self) -> TrackList {
+        self.track_list("grid-template-columns")
+    }
+
+    pub fn grid_template_rows(&self) -> TrackList {
+        self.track_list("grid-template-rows")
+    }
+
+    fn track_list(&self, property: &str) -> TrackList {
+        let entries = match self.get_value(property) {
+            Some(CssValue::List(list)) => list.clone(),
+            Some(CssValue::Keyword(keyword)) if keyword == "none" => Vec::new(),
+            Some(value) => vec![value.clone()],
+            None => Vec::new(),
+        };
+
+        let mut list = TrackList::default();
+        for entry in entries {
+            match entry {
+                CssValue::Repeat(count, tracks) => {
+                    let tracks = track_sizes(&tracks);
+                    match *count {
+                        CssValue::Number(n) => {
+                            for _ in 0..n as usize {
+                                list.tracks.extend(tracks.iter().cloned());
+                            }
+                        }
+                        _ => list.auto_repeat = Some((list.tracks.len(), tracks)),
+                    }
+                }
+                entry => list.tracks.extend(track_size(&entry)),
+            }
+        }
+        list
+    }
+
+    /// https://www.w3.org/TR/css-grid-2/#auto-tracks
+    /// 暗黙のトラックの大きさ. 複数の場合は、繰り返して使う
+    pub fn grid_auto_columns(&self) -> Vec<TrackSize> {
+        self.auto_tracks("grid-auto-columns")
+    }
+
+    pub fn grid_auto_rows(&self) -> Vec<TrackSize> {
+        self.auto_tracks("grid-auto-rows")
+    }
+
+    fn auto_tracks(&self, property: &str) -> Vec<TrackSize> {
+        let tracks = self.get_value(property).map_or(Vec::new(), track_sizes);
+        if tracks.is_empty() {
+            return vec![TrackSize::new(TrackBreadth::Auto, TrackBreadth::Auto)];
+        }
+        tracks
+    }
+
+    /// https://www.w3.org/TR/css-grid-2/#grid-auto-flow-property
+    pub fn grid_auto_flow(&self) -> GridAutoFlow {
+        let keywords = match self.get_value("grid-auto-flow") {
+            Some(CssValue::List(list)) => list.clone(),
+            Some(value) => vec![value.clone()],
+            None => Vec::new(),
+        };
+        let is = |name: &str| keywords.contains(&CssValue::Keyword(name.to_string()));
+        match (is("column"), is("dense")) {
+            (false, false) => GridAutoFlow::Row,
+            (false, true) => GridAutoFlow::RowDense,
+            (true, false) => GridAutoFlow::Column,
+            (true, true) => GridAutoFlow::ColumnDense,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-grid-2/#line-placement
+    pub fn grid_column_start(&self) -> GridLine {
+        self.grid_line("grid-column-start")
+    }
+
+    pub fn grid_column_end(&self) -> GridLine {
+        self.grid_line("grid-column-end")
+    }
+
+    pub fn grid_row_start(&self) -> GridLine {
+        self.grid_line("grid-row-start")
+    }
+
+    pub fn grid_row_end(&self) -> GridLine {
+        self.grid_line("grid-row-end")
+    }
+
+    fn grid_line(&self, property: &str) -> GridLine {
+        match self.get_value(property) {
+            Some(CssValue::Number(n)) => GridLine::Line(*n as i64),
+            Some(CssValue::List(list)) => list
+                .iter()
+                .find_map(|value| match value {
+                    CssValue::Number(n) => Some(GridLine::Span(*n as i64)),
+                    _ => None,
+                })
+                .unwrap_or(GridLine::Auto),
+            _ => GridLine::Auto,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-position-3/#position-property
+    pub fn position(&self) -> Position {
+        self.keyword("position").unwrap_or(Position::Static)
+    }
+
+    /// https://www.w3.org/TR/css-position-3/#insets
+    /// 計算値. パーセンテージはレイアウト時に包含ブロックの大きさから解決する
+    pub fn top(&self) -> ComputedLength {
+        self.length("top")
+    }
+
+    pub fn right(&self) -> ComputedLength {
+        self.length("right")
+    }
+
+    pub fn bottom(&self) -> ComputedLength {
+        self.length("bottom")
+    }
+
+    pub fn left(&self) -> ComputedLength {
+        self.length("left")
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#z-index
+    /// autoの場合はNoneを返す
+    pub fn z_index(&self) -> Option<i64> {
+        self.resolve("z-index", |value| match value {
+            CssValue::Number(number) => Some(*number as i64),
+            _ => None,
+        })
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#float-position
+    pub fn float(&self) -> Float {
+        self.keyword("float").unwrap_or(Float::None)
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#flow-control
+    pub fn clear(&self) -> Clear {
+        self.keyword("clear").unwrap_or(Clear::None)
+    }
+
+    /// https://www.w3.org/TR/css-overflow-3/#overflow-control
+    pub fn overflow_x(&self) -> Overflow {
+        self.keyword("overflow-x").unwrap_or(Overflow::Visible)
+    }
+
+    pub fn overflow_y(&self) -> Overflow {
+        self.keyword("overflow-y").unwrap_or(Overflow::Visible)
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#borders
+    pub fn border_collapse(&self) -> BorderCollapse {
+        self.keyword("border-collapse")
+            .unwrap_or(BorderCollapse::Separate)
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#separated-borders
+    /// 隣り合うセルのボーダーの間の、横と縦の間隔
+    pub fn border_spacing(&self) -> (f64, f64) {
+        self.resolve("border-spacing", |value| match value {
+            CssValue::List(list) => match list.as_slice() {
+                [CssValue::Length(horizontal), CssValue::Length(vertical)] => {
+                    Some((horizontal.value(), vertical.value()))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .unwrap_or((0.0, 0.0))
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#width-layout
+    pub fn table_layout(&self) -> TableLayout {
+        self.keyword("table-layout").unwrap_or(TableLayout::Auto)
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#caption-position
+    pub fn caption_side(&self) -> CaptionSide {
+        self.keyword("caption-side").unwrap_or(CaptionSide::Top)
+    }
+
+    /// https://www.w3.org/TR/CSS2/visudet.html#propdef-vertical-align
+    pub fn vertical_align(&self) -> VerticalAlign {
+        self.keyword("vertical-align")
+            .unwrap_or(VerticalAlign::Baseline)
+    }
+
+    /// https://www.w3.org/TR/css-lists-3/#text-markers
+    pub fn list_style_type(&self) -> ListStyleType {
+        self.keyword("list-style-type")
+            .unwrap_or(ListStyleType::Disc)
+    }
+
+    /// https://www.w3.org/TR/css-lists-3/#list-style-position-property
+    pub fn list_style_position(&self) -> ListStylePosition {
+        self.keyword("list-style-position")
+            .unwrap_or(ListStylePosition::Outside)
+    }
+
+    pub fn set_height(&mut self, height: ComputedLength) {
+        self.set_length("height", height);
+    }
+
+    /// 計算値. パーセンテージはレイアウト時に包含ブロックの大きさから解決する
+    pub fn height(&self) -> ComputedLength {
+        self.length("height")
+    }
+
+    pub fn set_width(&mut self, width: ComputedLength) {
+        self.set_length("width", width);
+    }
+
+    pub fn width(&self) -> ComputedLength {
+        self.length("width")
+    }
+
+    pub fn margin_top(&self) -> ComputedLength {
+        self.length("margin-top")
+    }
+
+    pub fn margin_right(&self) -> ComputedLength {
+        self.length("margin-right")
+    }
+
+    pub fn margin_bottom(&self) -> ComputedLength {
+        self.length("margin-bottom")
+    }
+
+    pub fn margin_left(&self) -> ComputedLength {
+        self.length("margin-left")
+    }
+
+    pub fn padding_top(&self) -> ComputedLength {
+        self.length("padding-top")
+    }
+
+    pub fn padding_right(&self) -> ComputedLength {
+        self.length("padding-right")
+    }
+
+    pub fn padding_bottom(&self) -> ComputedLength {
+        self.length("padding-bottom")
+    }
+
+    pub fn padding_left(&self) -> ComputedLength {
+        self.length("padding-left")
+    }
+
+    pub fn border_top_width(&self) -> f64 {
+        self.px("border-top-width")
+    }
+
+    pub fn border_right_width(&self) -> f64 {
+        self.px("border-right-width")
+    }
+
+    pub fn border_bottom_width(&self) -> f64 {
+        self.px("border-bottom-width")
+    }
+
+    pub fn border_left_width(&self) -> f64 {
+        self.px("border-left-width")
+    }
+
+    pub fn border_top_color(&self) -> Color {
+        self.color_of("border-top-color")
+    }
+
+    pub fn border_right_color(&self) -> Color {
+        self.color_of("border-right-color")
+    }
+
+    pub fn border_bottom_color(&self) -> Color {
+        self.color_of("border-bottom-color")
+    }
+
+    pub fn border_left_color(&self) -> Color {
+        self.color_of("border-left-color")
+    }
+
+    pub fn border_top_style(&self) -> BorderStyle {
+        self.keyword("border-top-style")
+            .unwrap_or(BorderStyle::None)
+    }
+
+    pub fn border_right_style(&self) -> BorderStyle {
+        self.keyword("border-right-style")
+            .unwrap_or(BorderStyle::None)
+    }
+
+    pub fn border_bottom_style(&self) -> BorderStyle {
+        self.keyword("border-bottom-style")
+            .unwrap_or(BorderStyle::None)
+    }
+
+    pub fn border_left_style(&self) -> BorderStyle {
+        self.keyword("border-left-style")
+            .unwrap_or(BorderStyle::None)
+    }
+}
+
+/// カスケードで選ばれた宣言の値
+#[derive(Debug, Clone, PartialEq)]
+enum DeclaredValue {
+    Value(CssValue),
+    /// var()を含む、置換前の宣言. ショートハンドの場合は`property`がショートハンドの名前になる
+    Unresolved {
+        property: String,
+        tokens: Vec<ComponentValue>,
+    },
+}
+
+/// 空白を除いて1つの識別子だけからなるトークン列なら、その識別子を返す
+fn trim_keyword(tokens: &[ComponentValue]) -> Option<&str> {
+    let tokens: Vec<&ComponentValue> = tokens
+        .iter()
+        .filter(|t| **t != ComponentValue::Whitespace)
+        .collect();
+    match tokens.as_slice() {
+        [ComponentValue::Ident(ident)] => Some(ident),
+        _ => None,
+    }
+}
+
+impl Default for ComputedStyle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// https://html.spec.whatwg.org/multipage/rendering.html#rendering
+/// ユーザーエージェントのスタイル. 作成者のスタイルシートで指定されなかった場合や
+/// revertが指定された場合に使われる
+fn user_agent_values(node: &Rc<RefCell<Node>>) -> BTreeMap<&'static str, CssValue> {
+    let mut values = BTreeMap::new();
+    if !matches!(node.borrow().kind(), NodeKind::Element(_)) {
+        return values;
+    }
+
+    values.insert(
+        "display",
+        CssValue::Keyword(DisplayType::default(node).keyword().to_string()),
+    );
+    let font_size = FontSize::default(node);
+    if font_size != FontSize::Medium {
+        values.insert(
+            "font-size",
+            CssValue::Keyword(font_size.keyword().to_string()),
+        );
+    }
+    // https://html.spec.whatwg.org/multipage/rendering.html#sections-and-headings
+    if matches!(
+        node.borrow().element_kind(),
+        Some(ElementKind::H1) | Some(ElementKind::H2)
+    ) {
+        values.insert("font-weight", CssValue::Keyword("bold".to_string()));
+    }
+    if TextDecoration::default(node) == TextDecoration::Underline {
+        values.insert(
+            "text-decoration",
+            CssValue::Keyword("underline".to_string()),
+        );
+    }
+
+    // https://html.spec.whatwg.org/multipage/rendering.html#tables-2
+    match node.borrow().element_kind() {
+        Some(ElementKind::Table) => {
+            let spacing = CssValue::Length(Length::px(2.0));
+            values.insert(
+                "border-spacing",
+                CssValue::List(vec![spacing.clone(), spacing]),
+            );
+        }
+        Some(ElementKind::Caption) => {
+            values.insert("text-align", CssValue::Keyword("center".to_string()));
+        }
+        Some(ElementKind::Thead)
+        | Some(ElementKind::Tbody)
+        | Some(ElementKind::Tfoot)
+        | Some(ElementKind::Tr) => {
+            values.insert("vertical-align", CssValue::Keyword("middle".to_string()));
+        }
+        Some(kind @ ElementKind::Td) | Some(kind @ ElementKind::Th) => {
+            values.insert("vertical-align", CssValue::Keyword("inherit".to_string()));
+            for side in [
+                "padding-top",
+                "padding-right",
+                "padding-bottom",
+                "padding-left",
+            ] {
+                values.insert(side, CssValue::Length(Length::px(1.0)));
+            }
+            if kind == ElementKind::Th {
+                values.insert("font-weight", CssValue::Keyword("bold".to_string()));
+                values.insert("text-align", CssValue::Keyword("center".to_string()));
+            }
+        }
+        _ => {}
+    }
+
+    // https://html.spec.whatwg.org/multipage/rendering.html#lists
+    if let Some(kind @ (ElementKind::Ul | ElementKind::Ol)) = node.borrow().element_kind() {
+        values.insert("padding-left", CssValue::Length(Length::px(40.0)));
+        let list_style_type = if kind == ElementKind::Ol {
+            // type属性の値でマーカーの種類を決める. ローマ数字はサポートしていないので10進数にする
+            match node_attribute(node, "type").as_deref() {
+                Some("a") => "lower-alpha",
+                Some("A") => "upper-alpha",
+                _ => "decimal",
+            }
+        } else {
+            // 入れ子になったulは、祖先のリストの数に応じてマーカーを変える
+            match list_nesting_depth(node) {
+                0 => "disc",
+                1 => "circle",
+                _ => "square",
+            }
+        };
+        values.insert(
+            "list-style-type",
+            CssValue::Keyword(list_style_type.to_string()),
+        );
+
+        // https://html.spec.whatwg.org/multipage/grouping-content.html#list-owner
+        // 最初のリスト項目の番号がstart属性の値になるよう、1つ前の値にリセットする
+        let start = match kind {
+            ElementKind::Ol => node_attribute(node, "start")
+                .and_then(|start| start.trim().parse::<i64>().ok())
+                .unwrap_or(1),
+            _ => 1,
+        };
+        values.insert(
+            "counter-reset",
+            CssValue::List(vec![
+                CssValue::Keyword("list-item".to_string()),
+                CssValue::Number((start - 1) as f64),
+            ]),
+        );
+    }
+
+    values
+}
+
+fn node_attribute(node: &Rc<RefCell<Node>>, name: &str) -> Option<String> {
+    node.borrow().get_element()?.get_attribute(name)
+}
+
+/// https://html.spec.whatwg.org/multipage/rendering.html#lists
+/// 祖先にあるulとolの数
+fn list_nesting_depth(node: &Rc<RefCell<Node>>) -> usize {
+    let mut depth = 0;
+    let mut current = node.borrow().parent().upgrade();
+    while let Some(ancestor) = current {
+        if matches!(
+            ancestor.borrow().element_kind(),
+            Some(ElementKind::Ul) | Some(ElementKind::Ol)
+        ) {
+            depth += 1;
+        }
+        current = ancestor.borrow().parent().upgrade();
+    }
+    depth
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#absolute-size-mapping
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FontSize {
+    XXSmall,
+    XSmall,
+    Small,
+    Medium,
+    Large,
+    XLarge,
+    XXLarge,
+    XXXLarge,
+}
+
+impl FontSize {
+    /// https://www.w3.org/TR/css-fonts-4/#absolute-size-mapping
+    /// mediumに対する倍率の表から求めたピクセル値
+    pub fn to_px(&self) -> f64 {
+        let scale = match self {
+            FontSize::XXSmall => 3.0 / 5.0,
+            FontSize::XSmall => 3.0 / 4.0,
+            FontSize::Small => 8.0 / 9.0,
+            FontSize::Medium => 1.0,
+            FontSize::Large => 6.0 / 5.0,
+            FontSize::XLarge => 3.0 / 2.0,
+            FontSize::XXLarge => 2.0,
+            FontSize::XXXLarge => 3.0,
+        };
+        DEFAULT_FONT_SIZE * scale
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            FontSize::XXSmall => "xx-small",
+            FontSize::XSmall => "x-small",
+            FontSize::Small => "small",
+            FontSize::Medium => "medium",
+            FontSize::Large => "large",
+            FontSize::XLarge => "x-large",
+            FontSize::XXLarge => "xx-large",
+            FontSize::XXXLarge => "xxx-large",
+        }
+    }
+
+    fn default(node: &Rc<RefCell<Node>>) -> Self {
+        match &node.borrow().kind() {
+            NodeKind::Element(element) => match element.kind() {
+                ElementKind::H1 => FontSize::XXLarge, // h1タグのデフォルトサイズ
+                ElementKind::H2 => FontSize::XLarge,  // h2タグのデフォルトサイズ
+                _ => FontSize::Medium,
+            },
+            _ => FontSize::Medium,
+        }
+    }
+}
+
+impl FromStr for FontSize {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "xx-small" => Ok(Self::XXSmall),
+            "x-small" => Ok(Self::XSmall),
+            "small" => Ok(Self::Small),
+            "medium" => Ok(Self::Medium),
+            "large" => Ok(Self::Large),
+            "x-large" => Ok(Self::XLarge),
+            "xx-large" => Ok(Self::XXLarge),
+            "xxx-large" => Ok(Self::XXXLarge),
+            _ => Err(Error::UnexpectedInput(format!(
+                "font-size {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-style-prop
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+impl FromStr for FontStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "italic" => Ok(Self::Italic),
+            "oblique" => Ok(Self::Oblique),
+            _ => Err(Error::UnexpectedInput(format!(
+                "font-style {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-inline-3/#line-height-property
+/// line-heightの計算値. 数値はそのまま継承され、子要素のフォントサイズに掛けられる
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LineHeight {
+    Normal,
+    Number(f64),
+    Px(f64),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DisplayType {
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-block
+    Block,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-inline
+    Inline,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-list-item
+    /// ブロックボックスに::markerのボックスを加えたもの
+    ListItem,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-flow-root
+    FlowRoot,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-flex
+    Flex,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-inline-flex
+    InlineFlex,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-grid
+    Grid,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-inline-grid
+    InlineGrid,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-table
+    Table,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-inline-table
+    InlineTable,
+    /// https://www.w3.org/TR/css-display-3/#layout-specific-display
+    /// 表の中の構造を表す値
+    TableRowGroup,
+    TableHeaderGroup,
+    TableFooterGroup,
+    TableRow,
+    TableCell,
+    TableColumnGroup,
+    TableColumn,
+    TableCaption,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-none
+    DisplayNone,
+}
+
+impl DisplayType {
+    fn default(node: &Rc<RefCell<Node>>) -> Self {
+        match &node.borrow().kind() {
+            NodeKind::Document => DisplayType::Block,
+            NodeKind::Element(e) => {
+                // https://html.spec.whatwg.org/multipage/rendering.html#hidden-elements
+                if matches!(
+                    e.kind(),
+                    ElementKind::Head | ElementKind::Style | ElementKind::Script
+                ) {
+                    DisplayType::DisplayNone
+                } else if e.is_table_element() {
+                    // https://html.spec.whatwg.org/multipage/rendering.html#tables-2
+                    match e.kind() {
+                        ElementKind::Caption => DisplayType::TableCaption,
+                        ElementKind::Colgroup => DisplayType::TableColumnGroup,
+                        ElementKind::Col => DisplayType::TableColumn,
+                        ElementKind::Thead => DisplayType::TableHeaderGroup,
+                        ElementKind::Tbody => DisplayType::TableRowGroup,
+                        ElementKind::Tfoot => DisplayType::TableFooterGroup,
+                        ElementKind::Tr => DisplayType::TableRow,
+                        ElementKind::Td | ElementKind::Th => DisplayType::TableCell,
+                        _ => DisplayType::Table,
+                    }
+                } else if e.kind() == ElementKind::Li {
+                    // https://html.spec.whatwg.org/multipage/rendering.html#lists
+                    DisplayType::ListItem
+                } else if e.is_block_element() {
+                    DisplayType::Block
+                } else {
+                    DisplayType::Inline
+                }
+            }
+            NodeKind::Text(_) => DisplayType::Inline,
+        }
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            DisplayType::Block => "block",
+            DisplayType::Inline => "inline",
+            DisplayType::ListItem => "list-item",
+            DisplayType::FlowRoot => "flow-root",
+            DisplayType::Flex => "flex",
+            DisplayType::InlineFlex => "inline-flex",
+            DisplayType::Grid => "grid",
+            DisplayType::InlineGrid => "inline-grid",
+            DisplayType::Table => "table",
+            DisplayType::InlineTable => "inline-table",
+            DisplayType::TableRowGroup => "table-row-group",
+            DisplayType::TableHeaderGroup => "table-header-group",
+            DisplayType::TableFooterGroup => "table-footer-group",
+            DisplayType::TableRow => "table-row",
+            DisplayType::TableCell => "table-cell",
+            DisplayType::TableColumnGroup => "table-column-group",
+            DisplayType::TableColumn => "table-column",
+            DisplayType::TableCaption => "table-caption",
+            DisplayType::DisplayNone => "none",
+        }
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#flex-container
+    /// 内容をフレックスレイアウトで並べるか
+    pub fn is_flex_container(&self) -> bool {
+        matches!(self, DisplayType::Flex | DisplayType::InlineFlex)
+    }
+
+    /// https://www.w3.org/TR/css-grid-2/#grid-container
+    /// 内容をグリッドレイアウトで並べるか
+    pub fn is_grid_container(&self) -> bool {
+        matches!(self, DisplayType::Grid | DisplayType::InlineGrid)
+    }
+
+    /// https://www.w3.org/TR/css-tables-3/#table-wrapper-box
+    /// 内容を表のレイアウトで並べるか
+    pub fn is_table(&self) -> bool {
+        matches!(self, DisplayType::Table | DisplayType::InlineTable)
+    }
+
+    /// https://www.w3.org/TR/css-tables-3/#row-group-box
+    pub fn is_table_row_group(&self) -> bool {
+        matches!(
+            self,
+            DisplayType::TableRowGroup
+                | DisplayType::TableHeaderGroup
+                | DisplayType::TableFooterGroup
+        )
+    }
+
+    /// https://www.w3.org/TR/css-tables-3/#table-internal-box
+    /// 表の中の構造を表す値か
+    pub fn is_table_internal(&self) -> bool {
+        self.is_table_row_group()
+            || matches!(
+                self,
+                DisplayType::TableRow
+                    | DisplayType::TableCell
+                    | DisplayType::TableColumnGroup
+                    | DisplayType::TableColumn
+                    | DisplayType::TableCaption
+            )
+    }
+
+    /// https://www.w3.org/TR/css-display-3/#blockify
+    /// インラインレベルの値を、対応するブロックレベルの値にする. 表の中の構造を表す値はblockになる
+    pub fn blockified(&self) -> Self {
+        match self {
+            DisplayType::Inline => DisplayType::Block,
+            DisplayType::InlineFlex => DisplayType::Flex,
+            DisplayType::InlineGrid => DisplayType::Grid,
+            DisplayType::InlineTable => DisplayType::Table,
+            display if display.is_table_internal() => DisplayType::Block,
+            display => *display,
+        }
+    }
+}
+
+impl FromStr for DisplayType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(Self::Block),
+            "inline" => Ok(Self::Inline),
+            "list-item" => Ok(Self::ListItem),
+            "flow-root" => Ok(Self::FlowRoot),
+            "flex" => Ok(Self::Flex),
+            "inline-flex" => Ok(Self::InlineFlex),
+            "grid" => Ok(Self::Grid),
+            "inline-grid" => Ok(Self::InlineGrid),
+            "table" => Ok(Self::Table),
+            "inline-table" => Ok(Self::InlineTable),
+            "table-row-group" => Ok(Self::TableRowGroup),
+            "table-header-group" => Ok(Self::TableHeaderGroup),
+            "table-footer-group" => Ok(Self::TableFooterGroup),
+            "table-row" => Ok(Self::TableRow),
+            "table-cell" => Ok(Self::TableCell),
+            "table-column-group" => Ok(Self::TableColumnGroup),
+            "table-column" => Ok(Self::TableColumn),
+            "table-caption" => Ok(Self::TableCaption),
+            "none" => Ok(Self::DisplayNone),
+            _ => Err(Error::UnexpectedInput(format!(
+                "display {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-direction-property
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FlexDirection {
+    Row,
+    RowReverse,
+    Column,
+    ColumnReverse,
+}
+
+impl FlexDirection {
+    /// 主軸が縦方向か
+    pub fn is_column(&self) -> bool {
+        matches!(self, FlexDirection::Column | FlexDirection::ColumnReverse)
+    }
+
+    /// 主軸の始まりと終わりが逆になるか
+    pub fn is_reverse(&self) -> bool {
+        matches!(
+            self,
+            FlexDirection::RowReverse | FlexDirection::ColumnReverse
+        )
+    }
+}
+
+impl FromStr for FlexDirection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "row" => Ok(Self::Row),
+            "row-reverse" => Ok(Self::RowReverse),
+            "column" => Ok(Self::Column),
+            "column-reverse" => Ok(Self::ColumnReverse),
+            _ => Err(Error::UnexpectedInput(format!(
+                "flex-direction {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-wrap-property
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FlexWrap {
+    NoWrap,
+    Wrap,
+    /// 折り返した行を、交差軸の終わりから始まりに向かって積む
+    WrapReverse,
+}
+
+impl FromStr for FlexWrap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nowrap" => Ok(Self::NoWrap),
+            "wrap" => Ok(Self::Wrap),
+            "wrap-reverse" => Ok(Self::WrapReverse),
+            _ => Err(Error::UnexpectedInput(format!(
+                "flex-wrap {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-basis-property
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlexBasis {
+    /// アイテムの内容から大きさを決める
+    Content,
+    /// 主軸方向の大きさ. autoの場合はwidthまたはheightを使う
+    Size(ComputedLength),
+}
+
+/// https://www.w3.org/TR/css-align-3/#propdef-justify-content
+/// justify-contentとalign-contentの値. 左から右、上から下に書く場合だけをサポートしているので、
+/// startとendは物理的な左（上）と右（下）を表す
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ContentAlignment {
+    Normal,
+    Start,
+    End,
+    FlexStart,
+    FlexEnd,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+    Stretch,
+}
+
+impl FromStr for ContentAlignment {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "start" => Ok(Self::Start),
+            "end" => Ok(Self::End),
+            "flex-start" => Ok(Self::FlexStart),
+            "flex-end" => Ok(Self::FlexEnd),
+            "center" => Ok(Self::Center),
+            "space-between" => Ok(Self::SpaceBetween),
+            "space-around" => Ok(Self::SpaceAround),
+            "space-evenly" => Ok(Self::SpaceEvenly),
+            "stretch" => Ok(Self::Stretch),
+            _ => Err(Error::UnexpectedInput(format!(
+                "content alignment {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-align-3/#propdef-align-items
+/// align-itemsとalign-selfの値. startとendは物理的な上（左）と下（右）を表す
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ItemAlignment {
+    /// フレックスアイテムではstretchと同じ
+    Normal,
+    Stretch,
+    Start,
+    End,
+    FlexStart,
+    FlexEnd,
+    Center,
+    Baseline,
+}
+
+impl FromStr for ItemAlignment {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "stretch" => Ok(Self::Stretch),
+            "start" => Ok(Self::Start),
+            "end" => Ok(Self::End),
+            "flex-start" => Ok(Self::FlexStart),
+            "flex-end" => Ok(Self::FlexEnd),
+            "center" => Ok(Self::Center),
+            "baseline" => Ok(Self::Baseline),
+            _ => Err(Error::UnexpectedInput(format!(
+                "item alignment {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#typedef-track-breadth
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackBreadth {
+    /// 長さまたはパーセンテージ
+    Length(ComputedLength),
+    /// frの値. 余白を比率で配分する
+    Flex(f64),
+    MinContent,
+    MaxContent,
+    Auto,
+}
+
+impl TrackBreadth {
+    fn from_value(value: &CssValue) -> Option<Self> {
+        match value {
+            CssValue::Flex(flex) => Some(TrackBreadth::Flex(*flex)),
+            CssValue::Keyword(keyword) => match keyword.as_str() {
+                "auto" => Some(TrackBreadth::Auto),
+                "min-content" => Some(TrackBreadth::MinContent),
+                "max-content" => Some(TrackBreadth::MaxContent),
+                _ => None,
+            },
+            CssValue::Length(length) => {
+                Some(TrackBreadth::Length(ComputedLength::Px(length.value())))
+            }
+            CssValue::Percentage(percentage) => Some(TrackBreadth::Length(
+                ComputedLength::Percentage(*percentage),
+            )),
+            CssValue::Calc(node) => Some(TrackBreadth::Length(ComputedLength::Calc(node.clone()))),
+            _ => None,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-grid-2/#intrinsic-sizing-function
+    /// 内容から大きさが決まるか
+    pub fn is_intrinsic(&self) -> bool {
+        matches!(
+            self,
+            TrackBreadth::MinContent | TrackBreadth::MaxContent | TrackBreadth::Auto
+        )
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#typedef-track-size
+/// トラックの大きさの最小値と最大値. minmax()でない場合は両方に同じ値を持つ
+/// ただしfrだけを指定した場合は、minmax(auto, <flex>)になる
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackSize {
+    pub min: TrackBreadth,
+    pub max: TrackBreadth,
+}
+
+impl TrackSize {
+    pub fn new(min: TrackBreadth, max: TrackBreadth) -> Self {
+        Self { min, max }
+    }
+}
+
+/// 値をトラックの大きさとして読む
+fn track_size(value: &CssValue) -> Option<TrackSize> {
+    match value {
+        CssValue::MinMax(min, max) => Some(TrackSize::new(
+            TrackBreadth::from_value(min)?,
+            TrackBreadth::from_value(max)?,
+        )),
+        CssValue::Flex(flex) => Some(TrackSize::new(
+            TrackBreadth::Auto,
+            TrackBreadth::Flex(*flex),
+        )),
+        value => {
+            let breadth = TrackBreadth::from_value(value)?;
+            Some(TrackSize::new(breadth.clone(), breadth))
+        }
+    }
+}
+
+/// 空白区切りの値を、トラックの大きさのリストとして読む
+fn track_sizes(value: &CssValue) -> Vec<TrackSize> {
+    match value {
+        CssValue::List(list) => list.iter().filter_map(track_size).collect(),
+        value => track_size(value).into_iter().collect(),
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#explicit-grids
+/// grid-template-columnsとgrid-template-rowsの計算値. noneは空のリストになる
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TrackList {
+    /// 回数を指定したrepeat()を展開したトラック
+    pub tracks: Vec<TrackSize>,
+    /// https://www.w3.org/TR/css-grid-2/#auto-repeat
+    /// auto-fillのrepeat()を挿入する位置（`tracks`の添字）と、繰り返すトラック
+    pub auto_repeat: Option<(usize, Vec<TrackSize>)>,
+}
+
+/// https://www.w3.org/TR/css-grid-2/#typedef-grid-row-start-grid-line
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GridLine {
+    Auto,
+    /// 線の番号. 負の場合は明示的なグリッドの終わりから数える
+    Line(i64),
+    /// 反対側の線から、指定した数のトラックにまたがる
+    Span(i64),
 }
 
-impl ComputedStyle {
-    pub fn new() -> Self {
-        Self {
-            background_color: None,
-            color: None,
-            display: None,
-            font_size: None,
-            text_decoration: None,
-            height: None,
-            width: None,
-        }
-    }
+/// https://www.w3.org/TR/css-grid-2/#grid-auto-flow-property
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GridAutoFlow {
+    Row,
+    Column,
+    /// 前のアイテムより前に空いているセルがあれば、そこに詰めて置く
+    RowDense,
+    ColumnDense,
+}
 
-    pub fn set_background_color(&mut self, color: Color) {
-        self.background_color = Some(color);
+impl GridAutoFlow {
+    /// アイテムを列ごとに（上から下に）置いていくか
+    pub fn is_column(&self) -> bool {
+        matches!(self, GridAutoFlow::Column | GridAutoFlow::ColumnDense)
     }
 
-    pub fn background_color(&self) -> Color {
-        self.background_color
-            .clone()
-            .expect("failed to access CSS property: background_color")
+    pub fn is_dense(&self) -> bool {
+        matches!(self, GridAutoFlow::RowDense | GridAutoFlow::ColumnDense)
     }
+}
 
-    pub fn set_color(&mut self, color: Color) {
-        self.color = Some(color);
-    }
+/// https://www.w3.org/TR/css-position-3/#position-property
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Position {
+    Static,
+    /// 通常フローで置いた位置から、insetの分だけずらす
+    Relative,
+    /// 通常フローから取り除き、最も近い位置指定された祖先のパディングボックスを基準に置く
+    Absolute,
+    /// 通常フローから取り除き、ビューポートを基準に置く
+    Fixed,
+    /// 通常フローで置いた位置から、スクロールしてもビューポートのinsetの内側に留まるようにずらす
+    Sticky,
+}
 
-    pub fn color(&self) -> Color {
-        self.color
-            .clone()
-            .expect("failed to access CSS property: color")
+impl Position {
+    /// https://www.w3.org/TR/css-position-3/#absolute-positioning-containing-block
+    /// 絶対位置指定された子孫の包含ブロックになるか
+    pub fn is_positioned(&self) -> bool {
+        *self != Position::Static
     }
 
-    pub fn set_display(&mut self, display: DisplayType) {
-        self.display = Some(display);
+    /// https://www.w3.org/TR/css-position-3/#absolute-pos
+    /// 通常フローから取り除かれるか
+    pub fn is_out_of_flow(&self) -> bool {
+        matches!(self, Position::Absolute | Position::Fixed)
     }
+}
 
-    pub fn display(&self) -> DisplayType {
-        self.display
-            .expect("failed to access CSS property: display")
-    }
+impl FromStr for Position {
+    type Err = Error;
 
-    pub fn font_size(&self) -> FontSize {
-        self.font_size
-            .expect("failed to access CSS property: font_size")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "static" => Ok(Self::Static),
+            "relative" => Ok(Self::Relative),
+            "absolute" => Ok(Self::Absolute),
+            "fixed" => Ok(Self::Fixed),
+            "sticky" => Ok(Self::Sticky),
+            _ => Err(Error::UnexpectedInput(format!(
+                "position {:?} is not supported yet",
+                s
+            ))),
+        }
     }
+}
 
-    pub fn text_decoration(&self) -> TextDecoration {
-        self.text_decoration
-            .expect("failed to access CSS property: text_decoration")
-    }
+/// https://www.w3.org/TR/CSS2/visuren.html#float-position
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Float {
+    None,
+    Left,
+    Right,
+}
+
+impl FromStr for Float {
+    type Err = Error;
 
-    pub fn set_height(&mut self, height: f64) {
-        self.height = Some(height);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            _ => Err(Error::UnexpectedInput(format!(
+                "float {:?} is not supported yet",
+                s
+            ))),
+        }
     }
+}
+
+/// https://www.w3.org/TR/CSS2/visuren.html#flow-control
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Clear {
+    None,
+    /// 前にある左のフロートの下に置く
+    Left,
+    /// 前にある右のフロートの下に置く
+    Right,
+    Both,
+}
 
-    pub fn height(&self) -> f64 {
-        self.height.expect("failed to access CSS property: height")
+impl Clear {
+    /// `float`の側のフロートの下に置くか
+    pub fn clears(&self, float: Float) -> bool {
+        match self {
+            Clear::None => false,
+            Clear::Left => float == Float::Left,
+            Clear::Right => float == Float::Right,
+            Clear::Both => float != Float::None,
+        }
     }
+}
 
-    pub fn set_width(&mut self, width: f64) {
-        self.width = Some(width);
+impl FromStr for Clear {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "both" => Ok(Self::Both),
+            _ => Err(Error::UnexpectedInput(format!(
+                "clear {:?} is not supported yet",
+                s
+            ))),
+        }
     }
+}
+
+/// https://www.w3.org/TR/css-overflow-3/#overflow-control
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Overflow {
+    Visible,
+    Hidden,
+    /// 内容を切り取るが、スクロールできるボックスにはならない
+    Clip,
+    Scroll,
+    Auto,
+}
 
-    pub fn width(&self) -> f64 {
-        self.width.expect("failed to access CSS property: width")
+impl Overflow {
+    /// https://www.w3.org/TR/css-overflow-3/#scroll-container
+    /// スクロールできるボックスになるか. スクロールできるボックスは、新しいブロック整形コンテキストを作る
+    pub fn is_scroll_container(&self) -> bool {
+        matches!(self, Overflow::Hidden | Overflow::Scroll | Overflow::Auto)
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Color {
-    name: Option<String>,
-    code: String,
-}
-
-impl Color {
-    pub fn from_name(name: &str) -> Result<Self, Error> {
-        let code = match name {
-            "black" => "#000000".to_string(),
-            "silver" => "#c0c0c0".to_string(),
-            "gray" => "#808080".to_string(),
-            "white" => "#ffffff".to_string(),
-            "maroon" => "#800000".to_string(),
-            "red" => "#ff0000".to_string(),
-            "purple" => "#800080".to_string(),
-            "fuchsia" => "#ff00ff".to_string(),
-            "green" => "#008000".to_string(),
-            "lime" => "#00ff00".to_string(),
-            "olive" => "#808000".to_string(),
-            "yellow" => "#ffff00".to_string(),
-            "navy" => "#000080".to_string(),
-            "blue" => "#0000ff".to_string(),
-            "teal" => "#008080".to_string(),
-            "aqua" => "#00ffff".to_string(),
-            "orange" => "#ffa500".to_string(),
-            "lightgray" => "#d3d3d3".to_string(),
-            _ => {
-                return Err(Error::UnexpectedInput(format!(
-                    "color name {:?} is not supported yet",
-                    name
-                )));
-            }
-        };
+impl FromStr for Overflow {
+    type Err = Error;
 
-        Ok(Self {
-            name: Some(name.to_string()),
-            code,
-        })
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "visible" => Ok(Self::Visible),
+            "hidden" => Ok(Self::Hidden),
+            "clip" => Ok(Self::Clip),
+            "scroll" => Ok(Self::Scroll),
+            "auto" => Ok(Self::Auto),
+            _ => Err(Error::UnexpectedInput(format!(
+                "overflow {:?} is not supported yet",
+                s
+            ))),
+        }
     }
+}
 
-    pub fn from_code(code: &str) -> Result<Self, Error> {
-        if code.chars().nth(0) != Some('#') || code.len() != 7 {
-            // #fff などの省略された形式のカラーコードは使用不可
-            return Err(Error::UnexpectedInput(format!(
-                "invalid color code {}",
-                code
-            )));
-        }
-
-        let name = match code {
-            "#000000" => "black".to_string(),
-            "#c0c0c0" => "silver".to_string(),
-            "#808080" => "gray".to_string(),
-            "#ffffff" => "white".to_string(),
-            "#800000" => "maroon".to_string(),
-            "#ff0000" => "red".to_string(),
-            "#800080" => "purple".to_string(),
-            "#ff00ff" => "fuchsia".to_string(),
-            "#008000" => "green".to_string(),
-            "#00ff00" => "lime".to_string(),
-            "#808000" => "olive".to_string(),
-            "#ffff00" => "yellow".to_string(),
-            "#000080" => "navy".to_string(),
-            "#0000ff" => "blue".to_string(),
-            "#008080" => "teal".to_string(),
-            "#00ffff" => "aqua".to_string(),
-            "#ffa500" => "orange".to_string(),
-            "#d3d3d3" => "lightgray".to_string(),
-            _ => {
-                return Err(Error::UnexpectedInput(format!(
-                    "color code {:?} is not supported yet",
-                    code
-                )));
-            }
-        };
+/// https://www.w3.org/TR/CSS2/tables.html#borders
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BorderCollapse {
+    /// セルごとにボーダーを持ち、セルの間をborder-spacingだけ空ける
+    Separate,
+    /// 隣り合うセルのボーダーを1本にまとめる
+    Collapse,
+}
 
-        Ok(Self {
-            name: Some(name),
-            code: code.to_string(),
-        })
+impl FromStr for BorderCollapse {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "separate" => Ok(Self::Separate),
+            "collapse" => Ok(Self::Collapse),
+            _ => Err(Error::UnexpectedInput(format!(
+                "border-collapse {:?} is not supported yet",
+                s
+            ))),
+        }
     }
+}
 
-    pub fn white() -> Self {
-        Self {
-            name: Some("white".to_string()),
-            code: "#ffffff".to_string(),
+/// https://www.w3.org/TR/CSS2/tables.html#width-layout
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TableLayout {
+    /// すべてのセルの内容から列の幅を決める
+    Auto,
+    /// 列の要素と最初の行のセルの幅だけから列の幅を決める
+    Fixed,
+}
+
+impl FromStr for TableLayout {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "fixed" => Ok(Self::Fixed),
+            _ => Err(Error::UnexpectedInput(format!(
+                "table-layout {:?} is not supported yet",
+                s
+            ))),
         }
     }
+}
 
-    pub fn black() -> Self {
-        Self {
-            name: Some("black".to_string()),
-            code: "#000000".to_string(),
+/// https://www.w3.org/TR/CSS2/tables.html#caption-position
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CaptionSide {
+    Top,
+    Bottom,
+}
+
+impl FromStr for CaptionSide {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top" => Ok(Self::Top),
+            "bottom" => Ok(Self::Bottom),
+            _ => Err(Error::UnexpectedInput(format!(
+                "caption-side {:?} is not supported yet",
+                s
+            ))),
         }
     }
+}
 
-    pub fn code_u32(&self) -> u32 {
-        u32::from_str_radix(self.code.trim_start_matches('#'), 16).unwrap()
+/// https://www.w3.org/TR/css-counter-styles-3/#predefined-counters
+/// lower-latinとupper-latinは、lower-alphaとupper-alphaと同じものとして扱う
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ListStyleType {
+    None,
+    Disc,
+    Circle,
+    Square,
+    Decimal,
+    LowerAlpha,
+    UpperAlpha,
+    CjkDecimal,
+    JapaneseInformal,
+}
+
+impl FromStr for ListStyleType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "disc" => Ok(Self::Disc),
+            "circle" => Ok(Self::Circle),
+            "square" => Ok(Self::Square),
+            "decimal" => Ok(Self::Decimal),
+            "lower-alpha" | "lower-latin" => Ok(Self::LowerAlpha),
+            "upper-alpha" | "upper-latin" => Ok(Self::UpperAlpha),
+            "cjk-decimal" => Ok(Self::CjkDecimal),
+            "japanese-informal" => Ok(Self::JapaneseInformal),
+            _ => Err(Error::UnexpectedInput(format!(
+                "list-style-type {:?} is not supported yet",
+                s
+            ))),
+        }
     }
 }
 
-/// https://www.w3.org/TR/css-fonts-4/#absolute-size-mapping
+/// https://www.w3.org/TR/css-lists-3/#list-style-position-property
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub enum FontSize {
-    Medium,
-    XLarge,
-    XXLarge,
+pub enum ListStylePosition {
+    Outside,
+    Inside,
 }
 
-impl FontSize {
-    fn default(node: &Rc<RefCell<Node>>) -> Self {
-        match &node.borrow().kind() {
-            NodeKind::Element(element) => match element.kind() {
-                ElementKind::H1 => FontSize::XXLarge, // h1タグのデフォルトサイズ
-                ElementKind::H2 => FontSize::XLarge,  // h2タグのデフォルトサイズ
-                _ => FontSize::Medium,
-            },
-            _ => FontSize::Medium,
+impl FromStr for ListStylePosition {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "outside" => Ok(Self::Outside),
+            "inside" => Ok(Self::Inside),
+            _ => Err(Error::UnexpectedInput(format!(
+                "list-style-position {:?} is not supported yet",
+                s
+            ))),
         }
     }
 }
 
+/// https://www.w3.org/TR/CSS2/visudet.html#propdef-vertical-align
+/// 表のセルの内容を、セルの中で縦に揃える位置にだけ使う
+/// セルでは、top、middle、bottom以外の値はbaselineとして扱う
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub enum DisplayType {
-    /// https://www.w3.org/TR/css-display-3/#valdef-display-block
-    Block,
-    /// https://www.w3.org/TR/css-display-3/#valdef-display-inline
-    Inline,
-    /// https://www.w3.org/TR/css-display-3/#valdef-display-none
-    DisplayNone,
+pub enum VerticalAlign {
+    Baseline,
+    Sub,
+    Super,
+    TextTop,
+    TextBottom,
+    Middle,
+    Top,
+    Bottom,
 }
 
-impl DisplayType {
-    fn default(node: &Rc<RefCell<Node>>) -> Self {
-        match &node.borrow().kind() {
-            NodeKind::Document => DisplayType::Block,
-            NodeKind::Element(e) => {
-                if e.is_block_element() {
-                    DisplayType::Block
-                } else {
-                    DisplayType::Inline
-                }
-            }
-            NodeKind::Text(_) => DisplayType::Inline,
+impl FromStr for VerticalAlign {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "baseline" => Ok(Self::Baseline),
+            "sub" => Ok(Self::Sub),
+            "super" => Ok(Self::Super),
+            "text-top" => Ok(Self::TextTop),
+            "text-bottom" => Ok(Self::TextBottom),
+            "middle" => Ok(Self::Middle),
+            "top" => Ok(Self::Top),
+            "bottom" => Ok(Self::Bottom),
+            _ => Err(Error::UnexpectedInput(format!(
+                "vertical-align {:?} is not supported yet",
+                s
+            ))),
         }
     }
+}
+
+/// https://www.w3.org/TR/css-text-3/#text-align-property
+/// 左から右に書く場合だけをサポートしているので、startはleft、endはrightとして扱う
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Right,
+    Center,
+    Justify,
+}
 
-    pub fn from_str(s: &str) -> Result<Self, Error> {
+impl FromStr for TextAlign {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "block" => Ok(Self::Block),
-            "inline" => Ok(Self::Inline),
-            "none" => Ok(Self::DisplayNone),
+            "start" | "left" => Ok(Self::Left),
+            "end" | "right" => Ok(Self::Right),
+            "center" => Ok(Self::Center),
+            "justify" => Ok(Self::Justify),
             _ => Err(Error::UnexpectedInput(format!(
-                "display {:?} is not supported yet",
+                "text-align {:?} is not supported yet",
                 s
             ))),
         }
@@ -267,3 +2065,625 @@ impl TextDecoration {
         }
     }
 }
+
+impl FromStr for TextDecoration {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "underline" => Ok(Self::Underline),
+            _ => Err(Error::UnexpectedInput(format!(
+                "text-decoration {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-backgrounds-3/#border-style
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BorderStyle {
+    None,
+    Hidden,
+    Dotted,
+    Dashed,
+    Solid,
+    Double,
+    Groove,
+    Ridge,
+    Inset,
+    Outset,
+}
+
+impl BorderStyle {
+    /// noneとhiddenの場合、ボーダーの幅は0になる
+    pub fn is_visible(&self) -> bool {
+        !matches!(self, BorderStyle::None | BorderStyle::Hidden)
+    }
+}
+
+impl FromStr for BorderStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "hidden" => Ok(Self::Hidden),
+            "dotted" => Ok(Self::Dotted),
+            "dashed" => Ok(Self::Dashed),
+            "solid" => Ok(Self::Solid),
+            "double" => Ok(Self::Double),
+            "groove" => Ok(Self::Groove),
+            "ridge" => Ok(Self::Ridge),
+            "inset" => Ok(Self::Inset),
+            "outset" => Ok(Self::Outset),
+            _ => Err(Error::UnexpectedInput(format!(
+                "border-style {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::token::CssTokenizer;
+    use crate::renderer::dom::node::Element;
+    use alloc::vec::Vec;
+
+    fn element(name: &str) -> Rc<RefCell<Node>> {
+        Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+            name,
+            Vec::new(),
+        )))))
+    }
+
+    fn declaration(property: &str, value: &str, important: bool) -> Declaration {
+        let mut declaration = Declaration::new();
+        declaration.set_property(property.to_string());
+        declaration.set_value(CssTokenizer::new(value.to_string()).collect());
+        declaration.set_important(important);
+        declaration
+    }
+
+    fn compute(
+        name: &str,
+        declarations: &[(&str, &str)],
+        parent_style: Option<ComputedStyle>,
+    ) -> ComputedStyle {
+        let mut style = ComputedStyle::new();
+        for (property, value) in declarations {
+            style.cascade_declaration(&declaration(property, value, false));
+        }
+        style.defaulting(&element(name), parent_style);
+        style
+    }
+
+    #[test]
+    fn test_initial_and_user_agent_values() {
+        /* 宣言がない場合、ユーザーエージェントのスタイルまたは初期値が使われることを確認する */
+        let style = compute("p", &[], None);
+        assert_eq!(style.display(), DisplayType::Block);
+        assert_eq!(style.color(), Color::black());
+        assert_eq!(style.background_color(), Color::transparent());
+        assert_eq!(style.font_size(), 16.0);
+        assert_eq!(style.width(), ComputedLength::Auto);
+        assert_eq!(style.margin_top(), ComputedLength::Px(0.0));
+        // ボーダーのスタイルがnoneなので、幅の初期値mediumは0になる
+        assert_eq!(style.border_top_style(), BorderStyle::None);
+        assert_eq!(style.border_top_width(), 0.0);
+
+        let h1 = compute("h1", &[], None);
+        assert_eq!(h1.font_size(), 32.0);
+        let a = compute("a", &[], None);
+        assert_eq!(a.display(), DisplayType::Inline);
+        assert_eq!(a.text_decoration(), TextDecoration::Underline);
+        let style = compute("style", &[], None);
+        assert_eq!(style.display(), DisplayType::DisplayNone);
+    }
+
+    #[test]
+    fn test_inheritance() {
+        /* 継承するプロパティだけが親要素から値を受け取ることを確認する */
+        let parent = compute(
+            "body",
+            &[
+                ("color", "red"),
+                ("background-color", "blue"),
+                ("font-size", "20px"),
+                ("text-align", "end"),
+                ("text-decoration", "underline"),
+            ],
+            None,
+        );
+        let child = compute("p", &[("width", "2em")], Some(parent));
+
+        assert_eq!(child.color(), Color::rgb(255, 0, 0));
+        assert_eq!(child.background_color(), Color::transparent());
+        assert_eq!(child.font_size(), 20.0);
+        assert_eq!(child.width(), ComputedLength::Px(40.0));
+        assert_eq!(child.text_align(), TextAlign::Right);
+        assert_eq!(child.text_decoration(), TextDecoration::None);
+    }
+
+    #[test]
+    fn test_flex_items_are_blockified() {
+        /* フレックスコンテナの子のdisplayがブロックレベルになり、フレックスのプロパティが計算されることを確認する */
+        let container = compute(
+            "p",
+            &[("display", "inline-flex"), ("flex-flow", "column wrap")],
+            None,
+        );
+        assert_eq!(container.flex_direction(), FlexDirection::Column);
+        assert_eq!(container.flex_wrap(), FlexWrap::Wrap);
+        assert_eq!(container.row_gap(), ComputedLength::Px(0.0));
+
+        let item = compute(
+            "a",
+            &[
+                ("flex", "2 0 10%"),
+                ("align-self", "center"),
+                ("order", "-1"),
+            ],
+            Some(container.clone()),
+        );
+        assert_eq!(item.display(), DisplayType::Block);
+        assert_eq!(item.flex_grow(), 2.0);
+        assert_eq!(item.flex_shrink(), 0.0);
+        assert_eq!(
+            item.flex_basis(),
+            FlexBasis::Size(ComputedLength::Percentage(10.0))
+        );
+        assert_eq!(item.align_self(), Some(ItemAlignment::Center));
+        assert_eq!(item.order(), -1);
+
+        let nested = compute("a", &[("display", "inline-flex")], Some(container));
+        assert_eq!(nested.display(), DisplayType::Flex);
+        assert_eq!(nested.align_self(), None);
+        assert_eq!(nested.flex_basis(), FlexBasis::Size(ComputedLength::Auto));
+    }
+
+    #[test]
+    fn test_grid_properties() {
+        /* グリッドのトラックと配置のプロパティが計算され、グリッドアイテムがブロック化されることを確認する */
+        let container = compute(
+            "p",
+            &[
+                ("display", "inline-grid"),
+                (
+                    "grid-template-columns",
+                    "2em repeat(2, 1fr) repeat(auto-fill, 10%)",
+                ),
+                ("grid-auto-rows", "minmax(10px, auto)"),
+                ("grid-auto-flow", "column dense"),
+            ],
+            None,
+        );
+        let fixed = |px: f64| TrackBreadth::Length(ComputedLength::Px(px));
+        let fr = TrackSize::new(TrackBreadth::Auto, TrackBreadth::Flex(1.0));
+        let percentage = TrackBreadth::Length(ComputedLength::Percentage(10.0));
+        assert_eq!(
+            container.grid_template_columns(),
+            TrackList {
+                tracks: vec![TrackSize::new(fixed(32.0), fixed(32.0)), fr.clone(), fr],
+                auto_repeat: Some((3, vec![TrackSize::new(percentage.clone(), percentage)])),
+            }
+        );
+        assert_eq!(container.grid_template_rows(), TrackList::default());
+        assert_eq!(
+            container.grid_auto_rows(),
+            vec![TrackSize::new(fixed(10.0), TrackBreadth::Auto)]
+        );
+        assert_eq!(
+            container.grid_auto_columns(),
+            vec![TrackSize::new(TrackBreadth::Auto, TrackBreadth::Auto)]
+        );
+        assert_eq!(container.grid_auto_flow(), GridAutoFlow::ColumnDense);
+
+        let item = compute(
+            "a",
+            &[("grid-column", "span 2 / -1"), ("justify-self", "end")],
+            Some(container),
+        );
+        assert_eq!(item.display(), DisplayType::Block);
+        assert_eq!(item.grid_column_start(), GridLine::Span(2));
+        assert_eq!(item.grid_column_end(), GridLine::Line(-1));
+        assert_eq!(item.grid_row_start(), GridLine::Auto);
+        assert_eq!(item.justify_self(), Some(ItemAlignment::End));
+        assert_eq!(item.justify_items(), ItemAlignment::Normal);
+    }
+
+    #[test]
+    fn test_position_properties() {
+        /* 位置指定のプロパティが計算され、絶対位置指定されたボックスがブロック化されることを確認する */
+        let parent = compute(
+            "p",
+            &[("position", "relative"), ("font-size", "10px")],
+            None,
+        );
+        assert_eq!(parent.position(), Position::Relative);
+        assert_eq!(parent.top(), ComputedLength::Auto);
+        assert_eq!(parent.z_index(), None);
+
+        let child = compute(
+            "a",
+            &[
+                ("position", "absolute"),
+                ("inset", "1em auto 10%"),
+                ("z-index", "-2"),
+            ],
+            Some(parent.clone()),
+        );
+        assert_eq!(child.display(), DisplayType::Block);
+        assert_eq!(child.top(), ComputedLength::Px(10.0));
+        assert_eq!(child.right(), ComputedLength::Auto);
+        assert_eq!(child.bottom(), ComputedLength::Percentage(10.0));
+        assert_eq!(child.left(), ComputedLength::Auto);
+        assert_eq!(child.z_index(), Some(-2));
+
+        let sticky = compute("a", &[("position", "sticky")], Some(parent));
+        assert_eq!(sticky.display(), DisplayType::Inline);
+    }
+
+    #[test]
+    fn test_float_properties() {
+        /* フロートがブロック化され、絶対位置指定されたボックスはフロートにならないことを確認する */
+        let float = compute(
+            "a",
+            &[
+                ("float", "right"),
+                ("clear", "left"),
+                ("overflow", "hidden"),
+            ],
+            None,
+        );
+        assert_eq!(float.display(), DisplayType::Block);
+        assert_eq!(float.float(), Float::Right);
+        assert_eq!(float.clear(), Clear::Left);
+        assert_eq!(float.overflow_y(), Overflow::Hidden);
+        assert!(Clear::Both.clears(Float::Left));
+        assert!(!Clear::Left.clears(Float::Right));
+
+        let absolute = compute("a", &[("float", "left"), ("position", "fixed")], None);
+        assert_eq!(absolute.float(), Float::None);
+
+        let flow_root = compute("a", &[("display", "flow-root")], None);
+        assert_eq!(flow_root.display(), DisplayType::FlowRoot);
+        assert_eq!(flow_root.overflow_x(), Overflow::Visible);
+    }
+
+    #[test]
+    fn test_table_properties() {
+        /* 表の要素のユーザーエージェントのスタイルと、表のプロパティの継承、表の中の構造のブロック化を確認する */
+        let table = compute(
+            "table",
+            &[("border-collapse", "collapse"), ("caption-side", "bottom")],
+            None,
+        );
+        assert_eq!(table.display(), DisplayType::Table);
+        assert_eq!(table.border_spacing(), (2.0, 2.0));
+        assert_eq!(table.table_layout(), TableLayout::Auto);
+
+        let row = compute("tr", &[("vertical-align", "top")], Some(table.clone()));
+        assert_eq!(row.display(), DisplayType::TableRow);
+        let cell = compute("th", &[("border-spacing", "1px 3px")], Some(row));
+        assert_eq!(cell.display(), DisplayType::TableCell);
+        assert_eq!(cell.border_collapse(), BorderCollapse::Collapse);
+        assert_eq!(cell.caption_side(), CaptionSide::Bottom);
+        assert_eq!(cell.border_spacing(), (1.0, 3.0));
+        assert_eq!(cell.vertical_align(), VerticalAlign::Top);
+        assert_eq!(cell.text_align(), TextAlign::Center);
+        assert_eq!(cell.padding_left(), ComputedLength::Px(1.0));
+
+        let inline_table = compute("a", &[("display", "inline-table"), ("float", "left")], None);
+        assert_eq!(inline_table.display(), DisplayType::Table);
+        let floated_cell = compute("td", &[("float", "left")], None);
+        assert_eq!(floated_cell.display(), DisplayType::Block);
+        assert_eq!(floated_cell.vertical_align(), VerticalAlign::Baseline);
+    }
+
+    #[test]
+    fn test_list_properties() {
+        /* リストの要素のユーザーエージェントのスタイルと、list-styleの展開と継承を確認する */
+        let ul = compute("ul", &[], None);
+        assert_eq!(ul.display(), DisplayType::Block);
+        assert_eq!(ul.padding_left(), ComputedLength::Px(40.0));
+        assert_eq!(ul.list_style_type(), ListStyleType::Disc);
+        assert_eq!(ul.counter_reset(), vec![("list-item".to_string(), 0)]);
+
+        let ol = compute("ol", &[("list-style", "inside upper-latin")], None);
+        assert_eq!(ol.list_style_type(), ListStyleType::UpperAlpha);
+        assert_eq!(ol.list_style_position(), ListStylePosition::Inside);
+
+        let li = compute("li", &[], Some(ol));
+        assert_eq!(li.display(), DisplayType::ListItem);
+        assert_eq!(li.list_style_type(), ListStyleType::UpperAlpha);
+        assert_eq!(li.list_style_position(), ListStylePosition::Inside);
+
+        let item = compute(
+            "a",
+            &[("display", "list-item"), ("list-style", "none")],
+            None,
+        );
+        assert_eq!(item.display(), DisplayType::ListItem);
+        assert_eq!(item.list_style_type(), ListStyleType::None);
+        assert_eq!(item.list_style_position(), ListStylePosition::Outside);
+        assert_eq!(
+            compute("ol", &[], None).list_style_type(),
+            ListStyleType::Decimal
+        );
+    }
+
+    #[test]
+    fn test_css_wide_keywords() {
+        /* inherit、initial、unset、revertが正しく解決されることを確認する */
+        let parent = compute(
+            "body",
+            &[("color", "red"), ("background-color", "blue")],
+            None,
+        );
+
+        let child = compute(
+            "h1",
+            &[
+                ("background-color", "inherit"),
+                ("color", "initial"),
+                ("font-size", "unset"),
+                ("display", "revert"),
+            ],
+            Some(parent.clone()),
+        );
+        assert_eq!(child.background_color(), Color::rgb(0, 0, 255));
+        assert_eq!(child.color(), Color::black());
+        // font-sizeは継承するプロパティなので、unsetはinheritになる
+        assert_eq!(child.font_size(), 16.0);
+        // revertはユーザーエージェントのスタイル（h1はブロック）に戻す
+        assert_eq!(child.display(), DisplayType::Block);
+
+        let child = compute("a", &[("text-decoration", "revert")], Some(parent));
+        assert_eq!(child.text_decoration(), TextDecoration::Underline);
+    }
+
+    #[test]
+    fn test_cascade_order() {
+        /* 後の宣言が優先され、!importantな宣言は通常の宣言で上書きされないことを確認する */
+        let mut style = ComputedStyle::new();
+        style.cascade_declaration(&declaration("color", "red", true));
+        style.cascade_declaration(&declaration("color", "blue", false));
+        style.cascade_declaration(&declaration("margin", "1px 2px", false));
+        style.cascade_declaration(&declaration("margin-left", "5px", false));
+        style.cascade_declaration(&declaration("width", "red", false));
+        style.defaulting(&element("p"), None);
+
+        assert_eq!(style.color(), Color::rgb(255, 0, 0));
+        assert_eq!(style.margin_top(), ComputedLength::Px(1.0));
+        assert_eq!(style.margin_right(), ComputedLength::Px(2.0));
+        assert_eq!(style.margin_left(), ComputedLength::Px(5.0));
+        // 文法に一致しない宣言は無視される
+        assert_eq!(style.width(), ComputedLength::Auto);
+    }
+
+    #[test]
+    fn test_current_color_and_border() {
+        /* currentcolorとボーダーの幅が計算値に解決されることを確認する */
+        let style = compute(
+            "p",
+            &[
+                ("color", "lime"),
+                ("border", "thick solid"),
+                ("border-left", "2px dotted red"),
+            ],
+            None,
+        );
+
+        assert_eq!(style.border_top_color(), Color::rgb(0, 255, 0));
+        assert_eq!(style.border_top_width(), 5.0);
+        assert_eq!(style.border_left_color(), Color::rgb(255, 0, 0));
+        assert_eq!(style.border_left_width(), 2.0);
+        assert_eq!(style.border_left_style(), BorderStyle::Dotted);
+    }
+
+    #[test]
+    fn test_custom_properties() {
+        /* カスタムプロパティが継承され、var()が置換されることを確認する */
+        let parent = compute("body", &[("--brand", "#0af"), ("--gap", "4px")], None);
+        let child = compute(
+            "p",
+            &[
+                ("--Brand", "red"),
+                ("color", "var(--brand, black)"),
+                ("background-color", "var(--Brand)"),
+                ("margin", "var(--gap) 0"),
+                ("width", "var(--missing, 10px)"),
+            ],
+            Some(parent),
+        );
+
+        assert_eq!(child.color(), Color::rgb(0, 0xaa, 0xff));
+        // カスタムプロパティの名前は大文字・小文字を区別する
+        assert_eq!(child.background_color(), Color::rgb(255, 0, 0));
+        assert_eq!(child.margin_top(), ComputedLength::Px(4.0));
+        assert_eq!(child.margin_right(), ComputedLength::Px(0.0));
+        assert_eq!(child.width(), ComputedLength::Px(10.0));
+        assert!(child.custom_property("--gap").is_some());
+    }
+
+    #[test]
+    fn test_invalid_var() {
+        /* 置換できないvar()を含むプロパティがunsetとして扱われることを確認する */
+        let parent = compute("body", &[("color", "red")], None);
+        let child = compute(
+            "p",
+            &[
+                ("--a", "var(--b)"),
+                ("--b", "var(--a)"),
+                ("--length", "10px"),
+                ("color", "var(--a)"),
+                ("background-color", "blue"),
+                ("background-color", "var(--length)"),
+                ("--reset", "initial"),
+                ("width", "var(--reset, 5px)"),
+            ],
+            Some(parent),
+        );
+
+        // 循環参照しているカスタムプロパティは初期値（無効な値）になる
+        assert!(child.custom_property("--a").is_none());
+        assert!(child.custom_property("--b").is_none());
+        // colorは継承するので親要素の値、background-colorは継承しないので初期値になる
+        assert_eq!(child.color(), Color::rgb(255, 0, 0));
+        assert_eq!(child.background_color(), Color::transparent());
+        assert_eq!(child.width(), ComputedLength::Px(5.0));
+    }
+
+    #[test]
+    fn test_getters_before_defaulting() {
+        /* 計算前のスタイルでも、ゲッターがパニックせず初期値を返すことを確認する */
+        let style = ComputedStyle::new();
+        assert_eq!(style.display(), DisplayType::Inline);
+        assert_eq!(style.color(), Color::black());
+        assert_eq!(style.background_color(), Color::transparent());
+        assert_eq!(style.font_size(), 16.0);
+        assert_eq!(style.width(), ComputedLength::Auto);
+        assert_eq!(style.margin_left(), ComputedLength::Px(0.0));
+        assert_eq!(style.border_top_style(), BorderStyle::None);
+        assert_eq!(style.border_top_width(), 0.0);
+        assert_eq!(style.get_property_value("display"), "inline");
+    }
+
+    #[test]
+    fn test_get_property_value() {
+        /* 計算値が文字列で取得でき、サポートしていないプロパティは空文字列になることを確認する */
+        let style = compute(
+            "p",
+            &[
+                ("--gap", "4px"),
+                ("color", "lime"),
+                ("margin", "1em 10% 0 calc(100% - 2em)"),
+            ],
+            None,
+        );
+
+        assert_eq!(style.get_property_value("COLOR"), "rgb(0, 255, 0)");
+        assert_eq!(style.get_property_value("margin-top"), "16px");
+        assert_eq!(style.get_property_value("margin-right"), "10%");
+        assert_eq!(style.get_property_value("margin-left"), "calc(100% - 32px)");
+        assert_eq!(style.get_property_value("--gap"), "4px");
+        assert_eq!(style.get_property_value("--missing"), "");
+        assert_eq!(style.get_property_value("grid-area"), "");
+    }
+
+    #[test]
+    fn test_font_size() {
+        /* 絶対的・相対的なキーワードと、長さやパーセンテージのフォントサイズを確認する */
+        let parent = compute("body", &[("font-size", "20px")], None);
+        for (value, expected) in [
+            ("xx-small", 9.6),
+            ("small", 16.0 * 8.0 / 9.0),
+            ("large", 19.2),
+            ("xxx-large", 48.0),
+            ("larger", 24.0),
+            ("smaller", 20.0 / 1.2),
+            ("1.5em", 30.0),
+            // remはルート要素（ここではbody）のフォントサイズを基準にする
+            ("2rem", 40.0),
+            ("50%", 10.0),
+        ] {
+            let style = compute("p", &[("font-size", value)], Some(parent.clone()));
+            assert_eq!(style.font_size(), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_font_properties() {
+        /* font-weight、font-style、font-family、line-heightの計算値と継承を確認する */
+        let parent = compute(
+            "body",
+            &[(
+                "font",
+                "italic bold 10px/150% \"Helvetica Neue\", Arial, sans-serif",
+            )],
+            None,
+        );
+        assert_eq!(parent.font_style(), FontStyle::Italic);
+        assert_eq!(parent.font_weight(), 700.0);
+        assert_eq!(
+            parent.font_family(),
+            ["Helvetica Neue", "Arial", "sans-serif"]
+        );
+        assert_eq!(parent.line_height(), LineHeight::Px(15.0));
+        assert_eq!(parent.get_property_value("font-stretch"), "100%");
+
+        // パーセンテージのline-heightは、計算値（ピクセル値）が継承される
+        let child = compute(
+            "p",
+            &[("font-size", "20px"), ("font-weight", "bolder")],
+            Some(parent.clone()),
+        );
+        assert_eq!(child.used_line_height(), 15.0);
+        assert_eq!(child.font_weight(), 900.0);
+        assert_eq!(child.font_style(), FontStyle::Italic);
+
+        // 数値のline-heightは数値のまま継承され、子要素のフォントサイズに掛けられる
+        let parent = compute(
+            "body",
+            &[("line-height", "1.5"), ("font-weight", "300")],
+            None,
+        );
+        let child = compute(
+            "p",
+            &[
+                ("font-size", "20px"),
+                ("font-weight", "lighter"),
+                ("font-family", "Times New  Roman"),
+            ],
+            Some(parent),
+        );
+        assert_eq!(child.line_height(), LineHeight::Number(1.5));
+        assert_eq!(child.used_line_height(), 30.0);
+        assert_eq!(child.font_weight(), 100.0);
+        assert_eq!(child.font_family(), ["Times New Roman"]);
+
+        // 見出しはユーザーエージェントのスタイルで太字になる
+        assert_eq!(compute("h1", &[], None).font_weight(), 700.0);
+        assert_eq!(compute("p", &[], None).used_line_height(), 19.2);
+    }
+
+    #[test]
+    fn test_root_font_size() {
+        /* remがルート要素のフォントサイズを基準にすることを確認する */
+        let root = compute("body", &[("font-size", "10px")], None);
+        let child = compute("p", &[("font-size", "2em"), ("width", "3rem")], Some(root));
+        assert_eq!(child.font_size(), 20.0);
+        assert_eq!(child.width(), ComputedLength::Px(30.0));
+    }
+
+    #[test]
+    fn test_calc() {
+        /* 計算式の値が計算値で簡約され、パーセンテージは使用値の計算時に解決されることを確認する */
+        let parent = compute("body", &[("font-size", "20px")], None);
+        let style = compute(
+            "p",
+            &[
+                ("font-size", "calc(50% + 2px)"),
+                ("width", "calc(100% - 2em)"),
+                ("margin-left", "min(10px, -5px)"),
+                ("padding-top", "calc(1px - 5px)"),
+                ("padding-left", "calc(10% - 100px)"),
+                ("border-top", "calc(0.5em) solid"),
+            ],
+            Some(parent),
+        );
+
+        assert_eq!(style.font_size(), 12.0);
+        assert_eq!(style.width().resolve(300.0), Some(276.0));
+        assert_eq!(style.margin_left(), ComputedLength::Px(-5.0));
+        // 負の値を取れないプロパティは0に丸められる
+        assert_eq!(style.padding_top(), ComputedLength::Px(0.0));
+        assert_eq!(style.padding_left().resolve(300.0), Some(0.0));
+        assert_eq!(style.padding_left().resolve(2000.0), Some(100.0));
+        assert_eq!(style.border_top_width(), 6.0);
+    }
+}
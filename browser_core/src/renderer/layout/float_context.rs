@@ -0,0 +1,168 @@
+use crate::renderer::layout::computed_style::Clear;
+use crate::renderer::layout::computed_style::Float;
+use crate::renderer::layout::layout_point::LayoutPoint;
+use crate::renderer::layout::layout_rect::LayoutRect;
+use crate::renderer::layout::layout_size::LayoutSize;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/CSS2/visuren.html#floats
+/// 1つのブロック整形コンテキストに置かれたフロートの、マージンボックスの矩形の集まり
+/// 位置はすべて、ブロック整形コンテキストを作るボックスを基準にした座標で表す
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FloatContext {
+    floats: Vec<(Float, LayoutRect)>,
+}
+
+impl FloatContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.floats.is_empty()
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#float-position
+    /// 左端`left`から右端`right`までの範囲に、マージンボックスの大きさが`size`のフロートを置き、その位置を返す
+    /// フロートの上端は`top`と、前に置いたフロートの上端より上にならない
+    /// 横に並べて収まらない場合は、収まるまで並んでいるフロートの下端に下げていく
+    pub fn place(
+        &mut self,
+        float: Float,
+        size: LayoutSize,
+        top: i64,
+        left: i64,
+        right: i64,
+    ) -> LayoutPoint {
+        let mut y = self
+            .floats
+            .last()
+            .map_or(top, |(_, rect)| top.max(rect.point().y()));
+        let (start, end) = loop {
+            let (start, end) = self.available(y, size.height(), left, right);
+            if end - start >= size.width() {
+                break (start, end);
+            }
+            // 並んでいるフロートがなくても収まらない場合は、はみ出させて置く
+            match self.next_bottom(y, size.height()) {
+                Some(bottom) => y = bottom,
+                None => break (start, end),
+            }
+        };
+
+        let x = match float {
+            Float::Right => end - size.width(),
+            _ => start,
+        };
+        let point = LayoutPoint::new(x, y);
+        self.floats.push((float, LayoutRect::new(point, size)));
+        point
+    }
+
+    /// 上端が`top`で高さが`height`の帯のうち、左端`left`から右端`right`までの範囲でフロートに重ならない部分の左端と右端
+    /// 高さが0の帯は、高さ1として扱う
+    pub fn available(&self, top: i64, height: i64, left: i64, right: i64) -> (i64, i64) {
+        let mut start = left;
+        let mut end = right;
+        for (float, rect) in self.overlapping(top, height) {
+            match float {
+                Float::Left => start = start.max(rect.right()),
+                Float::Right => end = end.min(rect.point().x()),
+                Float::None => {}
+            }
+        }
+        (start, end)
+    }
+
+    /// 上端が`top`で高さが`height`の帯に重なるフロートのうち、最も上にある下端
+    /// 帯に重なるフロートがない場合はNone
+    pub fn next_bottom(&self, top: i64, height: i64) -> Option<i64> {
+        self.overlapping(top, height)
+            .map(|(_, rect)| rect.bottom())
+            .min()
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#flow-control
+    /// `clear`で避けるフロートのうち、最も下にある下端. 避けるフロートがない場合はNone
+    pub fn clearance(&self, clear: Clear) -> Option<i64> {
+        self.floats
+            .iter()
+            .filter(|(float, _)| clear.clears(*float))
+            .map(|(_, rect)| rect.bottom())
+            .max()
+    }
+
+    /// すべてのフロートのうち、最も下にある下端. フロートがない場合はNone
+    pub fn bottom(&self) -> Option<i64> {
+        self.clearance(Clear::Both)
+    }
+
+    fn overlapping(&self, top: i64, height: i64) -> impl Iterator<Item = &(Float, LayoutRect)> {
+        let bottom = top + height.max(1);
+        self.floats
+            .iter()
+            .filter(move |(_, rect)| rect.point().y() < bottom && top < rect.bottom())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place_side_by_side() {
+        /* 左右のフロートが両端から並べられ、帯の空いている範囲が狭くなることを確認する */
+        let mut floats = FloatContext::new();
+        let size = LayoutSize::new(100, 50);
+        assert_eq!(
+            floats.place(Float::Left, size, 0, 0, 500),
+            LayoutPoint::new(0, 0)
+        );
+        assert_eq!(
+            floats.place(Float::Left, size, 0, 0, 500),
+            LayoutPoint::new(100, 0)
+        );
+        assert_eq!(
+            floats.place(Float::Right, size, 10, 0, 500),
+            LayoutPoint::new(400, 10)
+        );
+        assert_eq!(floats.available(0, 20, 0, 500), (200, 400));
+        assert_eq!(floats.available(50, 20, 0, 500), (0, 400));
+        assert_eq!(floats.available(60, 0, 0, 500), (0, 500));
+    }
+
+    #[test]
+    fn test_place_below() {
+        /* 収まらないフロートが、並んでいるフロートの下端まで下げられ、前のフロートより上には置かれないことを確認する */
+        let mut floats = FloatContext::new();
+        floats.place(Float::Left, LayoutSize::new(300, 50), 0, 0, 500);
+        floats.place(Float::Right, LayoutSize::new(100, 80), 0, 0, 500);
+        assert_eq!(
+            floats.place(Float::Left, LayoutSize::new(200, 10), 0, 0, 500),
+            LayoutPoint::new(0, 50)
+        );
+        assert_eq!(
+            floats.place(Float::Right, LayoutSize::new(50, 10), 0, 0, 500),
+            LayoutPoint::new(350, 50)
+        );
+        // どこにも収まらない場合は、フロートの下に置いてはみ出させる
+        assert_eq!(
+            floats.place(Float::Left, LayoutSize::new(600, 10), 0, 0, 500),
+            LayoutPoint::new(0, 80)
+        );
+    }
+
+    #[test]
+    fn test_clearance() {
+        /* clearで避けるフロートの下端が求められることを確認する */
+        let mut floats = FloatContext::new();
+        assert_eq!(floats.bottom(), None);
+        floats.place(Float::Left, LayoutSize::new(100, 30), 0, 0, 500);
+        floats.place(Float::Right, LayoutSize::new(100, 60), 0, 0, 500);
+        assert_eq!(floats.clearance(Clear::Left), Some(30));
+        assert_eq!(floats.clearance(Clear::Right), Some(60));
+        assert_eq!(floats.clearance(Clear::None), None);
+        assert_eq!(floats.bottom(), Some(60));
+        assert_eq!(floats.next_bottom(0, 10), Some(30));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::constants::CHAR_HEIGHT;
+use crate::constants::CHAR_WIDTH;
+use crate::renderer::layout::computed_style::ComputedStyle;
+
+/// 計算値のフォントからテキストの大きさを求める
+/// 描画できるのは等幅のビットマップフォントだけなので、font-sizeに比例して拡大した大きさを使う
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FontMetrics {
+    font_size: f64,
+    line_height: f64,
+}
+
+impl FontMetrics {
+    pub fn new(style: &ComputedStyle) -> Self {
+        Self {
+            font_size: style.font_size(),
+            line_height: style.used_line_height(),
+        }
+    }
+
+    pub fn font_size(&self) -> f64 {
+        self.font_size
+    }
+
+    /// 1文字の幅
+    pub fn char_width(&self) -> f64 {
+        CHAR_WIDTH as f64 * self.font_size / CHAR_HEIGHT as f64
+    }
+
+    /// テキストを1行に並べたときの幅
+    pub fn text_width(&self, text: &str) -> f64 {
+        text.chars().count() as f64 * self.char_width()
+    }
+
+    /// https://www.w3.org/TR/css-inline-3/#ascent-descent
+    /// ベースラインから文字の上端までの高さ. ビットマップフォントでは高さの4分の3の位置がベースラインになる
+    pub fn ascent(&self) -> f64 {
+        self.font_size * 3.0 / 4.0
+    }
+
+    /// ベースラインから文字の下端までの深さ
+    pub fn descent(&self) -> f64 {
+        self.font_size - self.ascent()
+    }
+
+    /// https://www.w3.org/TR/css-inline-3/#line-height-property
+    /// 行ボックスの高さ
+    pub fn line_height(&self) -> f64 {
+        self.line_height
+    }
+
+    /// https://www.w3.org/TR/css-inline-3/#half-leading
+    /// 行の高さとフォントサイズの差の半分. 文字の上下に均等に加える
+    pub fn half_leading(&self) -> f64 {
+        (self.line_height - self.font_size) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::cssom::Declaration;
+    use crate::renderer::css::token::CssTokenizer;
+    use crate::renderer::dom::node::Element;
+    use crate::renderer::dom::node::Node;
+    use crate::renderer::dom::node::NodeKind;
+    use alloc::rc::Rc;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+
+    fn metrics(declarations: &[(&str, &str)]) -> FontMetrics {
+        let mut style = ComputedStyle::new();
+        for (property, value) in declarations {
+            let mut declaration = Declaration::new();
+            declaration.set_property(property.to_string());
+            declaration.set_value(CssTokenizer::new(value.to_string()).collect());
+            style.cascade_declaration(&declaration);
+        }
+        let node = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+            "p",
+            Vec::new(),
+        )))));
+        style.defaulting(&node, None);
+        FontMetrics::new(&style)
+    }
+
+    #[test]
+    fn test_text_width() {
+        /* テキストの幅がフォントサイズに比例することを確認する */
+        assert_eq!(metrics(&[]).text_width("hello"), 40.0);
+        assert_eq!(metrics(&[("font-size", "32px")]).text_width("hello"), 80.0);
+        assert_eq!(metrics(&[("font-size", "small")]).char_width(), 64.0 / 9.0);
+    }
+
+    #[test]
+    fn test_line_height() {
+        /* 行の高さとハーフレディングが計算値から求まることを確認する */
+        let normal = metrics(&[("font-size", "20px")]);
+        assert_eq!(normal.line_height(), 24.0);
+        assert_eq!(normal.half_leading(), 2.0);
+        assert_eq!(normal.ascent(), 15.0);
+        assert_eq!(normal.descent(), 5.0);
+
+        let number = metrics(&[("font", "10px/2 sans-serif")]);
+        assert_eq!(number.line_height(), 20.0);
+        assert_eq!(number.half_leading(), 5.0);
+    }
+}
@@ -0,0 +1,429 @@
+use crate::renderer::css::value::CssValue;
+use crate::renderer::dom::node::Element;
+use crate::renderer::dom::node::Node;
+use crate::renderer::layout::computed_style::ComputedStyle;
+use crate::renderer::layout::computed_style::DisplayType;
+use crate::renderer::layout::computed_style::ListStyleType;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::str::FromStr;
+
+/// https://www.w3.org/TR/css-lists-3/#creating-a-counter
+/// 要素が作ったカウンター
+#[derive(Debug, Clone)]
+struct CounterInstance {
+    name: String,
+    value: i64,
+    /// カウンターを作った要素. スコープはこの要素と後続の兄弟要素、およびそれらの子孫
+    creator: Rc<RefCell<Node>>,
+}
+
+impl CounterInstance {
+    /// `element`がこのカウンターのスコープに含まれるか
+    /// 要素は文書順に処理されるので、作った要素の親要素の子孫であればスコープに含まれる
+    fn is_in_scope(&self, element: &Rc<RefCell<Node>>) -> bool {
+        if Rc::ptr_eq(&self.creator, element) {
+            return true;
+        }
+        match self.creator.borrow().parent().upgrade() {
+            Some(parent) => is_ancestor(&parent, element),
+            None => true,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-lists-3/#auto-numbering
+/// 文書順に要素を処理しながら、counter-resetとcounter-incrementでカウンターの値を求める
+#[derive(Debug, Clone, Default)]
+pub struct CounterState {
+    /// 外側のスコープのカウンターほど前に並ぶ
+    counters: Vec<CounterInstance>,
+}
+
+impl CounterState {
+    pub fn new() -> Self {
+        Self {
+            counters: Vec::new(),
+        }
+    }
+
+    /// 要素の処理を始める前に、要素がスコープに含まれないカウンターを取り除く
+    /// 子孫を処理した後に::afterの内容を求める場合にも、要素に対してもう一度呼び出す
+    pub fn enter(&mut self, element: &Rc<RefCell<Node>>) {
+        self.counters.retain(|counter| counter.is_in_scope(element));
+    }
+
+    /// 要素のcounter-reset、counter-incrementの順にカウンターを更新する
+    pub fn apply(&mut self, element: &Rc<RefCell<Node>>, style: &ComputedStyle) {
+        self.enter(element);
+
+        for (name, value) in style.counter_reset() {
+            // 兄弟要素が作った同じ名前のカウンターは、新しいカウンターで置き換える
+            let parent = element.borrow().parent().upgrade();
+            if let Some(index) = self.counters.iter().rposition(|c| c.name == name) {
+                let sibling_parent = self.counters[index].creator.borrow().parent().upgrade();
+                let is_sibling = match (&parent, &sibling_parent) {
+                    (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+                    _ => false,
+                };
+                if is_sibling {
+                    self.counters.remove(index);
+                }
+            }
+            self.counters.push(CounterInstance {
+                name,
+                value,
+                creator: element.clone(),
+            });
+        }
+
+        // https://www.w3.org/TR/css-lists-3/#declaring-a-list-item
+        // リスト項目は、counter-incrementでlist-itemを指定していなければlist-itemカウンターを1増やす
+        let mut increments = style.counter_increment();
+        if style.display() == DisplayType::ListItem
+            && !increments.iter().any(|(name, _)| name == "list-item")
+        {
+            increments.push(("list-item".to_string(), 1));
+        }
+
+        for (name, increment) in increments {
+            // カウンターが存在しない場合は、値が0のカウンターを要素に作ってから増やす
+            if !self.counters.iter().any(|c| c.name == name) {
+                self.counters.push(CounterInstance {
+                    name: name.clone(),
+                    value: 0,
+                    creator: element.clone(),
+                });
+            }
+            if let Some(counter) = self.counters.iter_mut().rev().find(|c| c.name == name) {
+                counter.value = counter.value.saturating_add(increment);
+            }
+        }
+    }
+
+    /// https://www.w3.org/TR/css-lists-3/#funcdef-counter
+    /// 最も内側のカウンターの値. カウンターが存在しない場合は0
+    pub fn value(&self, name: &str) -> i64 {
+        self.counters
+            .iter()
+            .rev()
+            .find(|c| c.name == name)
+            .map_or(0, |c| c.value)
+    }
+
+    /// https://www.w3.org/TR/css-lists-3/#funcdef-counters
+    /// 外側から順に並べた、入れ子のカウンターの値
+    pub fn values(&self, name: &str) -> Vec<i64> {
+        let values: Vec<i64> = self
+            .counters
+            .iter()
+            .filter(|c| c.name == name)
+            .map(|c| c.value)
+            .collect();
+        if values.is_empty() {
+            vec![0]
+        } else {
+            values
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-content-3/#content-property
+/// contentプロパティの計算値から、疑似要素に表示するテキストを求める
+/// `element`は疑似要素の元になる要素で、attr()の値の参照に使う
+pub fn generated_text(content: &[CssValue], element: &Element, counters: &CounterState) -> String {
+    let mut text = String::new();
+    for value in content {
+        match value {
+            CssValue::String(s) => text.push_str(s),
+            // 属性が存在しない場合は空文字列になる
+            CssValue::Attr(name) => text.push_str(&element.get_attribute(name).unwrap_or_default()),
+            CssValue::Counter {
+                name,
+                separator: None,
+                style,
+            } => text.push_str(&format_counter(counters.value(name), style.as_deref())),
+            CssValue::Counter {
+                name,
+                separator: Some(separator),
+                style,
+            } => {
+                let values: Vec<String> = counters
+                    .values(name)
+                    .into_iter()
+                    .map(|value| format_counter(value, style.as_deref()))
+                    .collect();
+                text.push_str(&values.join(separator));
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+/// https://www.w3.org/TR/css-counter-styles-3/#generate-a-counter
+/// カウンターの値を文字列にする. サポートしていないカウンタースタイルは10進数で表す
+fn format_counter(value: i64, style: Option<&str>) -> String {
+    let style = style
+        .and_then(|style| ListStyleType::from_str(style).ok())
+        .unwrap_or(ListStyleType::Decimal);
+    counter_representation(value, style)
+}
+
+/// https://www.w3.org/TR/css-lists-3/#content-property
+/// ::markerのcontentがnormalの場合に表示するテキスト. カウンターの表現の後に接尾辞を付ける
+/// list-style-typeがnoneの場合はマーカーを生成しないのでNone
+pub fn marker_text(value: i64, style: ListStyleType) -> Option<String> {
+    // https://www.w3.org/TR/css-counter-styles-3/#counter-style-suffix
+    let suffix = match style {
+        ListStyleType::None => return None,
+        ListStyleType::Disc | ListStyleType::Circle | ListStyleType::Square => " ",
+        ListStyleType::Decimal | ListStyleType::LowerAlpha | ListStyleType::UpperAlpha => ". ",
+        ListStyleType::CjkDecimal | ListStyleType::JapaneseInformal => "、",
+    };
+    Some(counter_representation(value, style) + suffix)
+}
+
+/// https://www.w3.org/TR/css-counter-styles-3/#predefined-counters
+/// 定義済みのカウンタースタイルで、カウンターの値を表す文字列
+fn counter_representation(value: i64, style: ListStyleType) -> String {
+    match style {
+        ListStyleType::None => String::new(),
+        // https://www.w3.org/TR/css-counter-styles-3/#simple-symbolic
+        ListStyleType::Disc => "•".to_string(),
+        ListStyleType::Circle => "◦".to_string(),
+        ListStyleType::Square => "▪".to_string(),
+        ListStyleType::Decimal => value.to_string(),
+        ListStyleType::LowerAlpha => alphabetic(value, 'a'),
+        ListStyleType::UpperAlpha => alphabetic(value, 'A'),
+        ListStyleType::CjkDecimal => cjk_decimal(value),
+        ListStyleType::JapaneseInformal => japanese_informal(value),
+    }
+}
+
+/// https://www.w3.org/TR/css-counter-styles-3/#alphabetic-system
+/// a, b, ..., z, aa, ab, ...と数える. 1未満の値は表せないので10進数で表す
+fn alphabetic(value: i64, first: char) -> String {
+    if value < 1 {
+        return value.to_string();
+    }
+    let mut letters = Vec::new();
+    let mut rest = value;
+    while rest > 0 {
+        rest -= 1;
+        letters.push((first as u8 + (rest % 26) as u8) as char);
+        rest /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// https://www.w3.org/TR/css-counter-styles-3/#cjk-decimal
+/// 10進数の各桁を漢数字に置き換える
+fn cjk_decimal(value: i64) -> String {
+    const DIGITS: [char; 10] = ['〇', '一', '二', '三', '四', '五', '六', '七', '八', '九'];
+    let digits: String = value
+        .unsigned_abs()
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| DIGITS[d as usize])
+        .collect();
+    if value < 0 {
+        format!("-{}", digits)
+    } else {
+        digits
+    }
+}
+
+/// https://www.w3.org/TR/css-counter-styles-3/#japanese-informal
+/// 千・百・十の位を使う漢数字. 一千・一百・一十の「一」は省略する
+/// 表せる範囲は-9999から9999までで、範囲外の値はcjk-decimalで表す
+fn japanese_informal(value: i64) -> String {
+    const DIGITS: [&str; 10] = ["〇", "一", "二", "三", "四", "五", "六", "七", "八", "九"];
+    if !(-9999..=9999).contains(&value) {
+        return cjk_decimal(value);
+    }
+    if value == 0 {
+        return DIGITS[0].to_string();
+    }
+
+    let mut text = String::new();
+    if value < 0 {
+        text.push_str("マイナス");
+    }
+    let mut rest = value.unsigned_abs() as usize;
+    for (unit, symbol) in [(1000, "千"), (100, "百"), (10, "十")] {
+        let digit = rest / unit;
+        if digit > 1 {
+            text.push_str(DIGITS[digit]);
+        }
+        if digit > 0 {
+            text.push_str(symbol);
+        }
+        rest %= unit;
+    }
+    if rest > 0 {
+        text.push_str(DIGITS[rest]);
+    }
+    text
+}
+
+fn is_ancestor(ancestor: &Rc<RefCell<Node>>, node: &Rc<RefCell<Node>>) -> bool {
+    let mut current = node.borrow().parent().upgrade();
+    while let Some(n) = current {
+        if Rc::ptr_eq(&n, ancestor) {
+            return true;
+        }
+        current = n.borrow().parent().upgrade();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::cssom::CssParser;
+    use crate::renderer::css::cssom::PseudoElement;
+    use crate::renderer::css::media::MediaEnvironment;
+    use crate::renderer::css::token::CssTokenizer;
+    use crate::renderer::html::parser::HtmlParser;
+    use crate::renderer::html::token::HtmlTokenizer;
+    use crate::renderer::layout::style_engine::StyleEngine;
+
+    /// 文書順に要素を処理し、::beforeと::afterのテキストを集める
+    fn collect_generated_text(
+        engine: &StyleEngine,
+        node: &Rc<RefCell<Node>>,
+        counters: &mut CounterState,
+        texts: &mut Vec<String>,
+    ) {
+        let element = node.borrow().get_element();
+        if let Some(element) = &element {
+            counters.apply(node, &engine.get_computed_style(node));
+            if let Some(style) = engine.get_pseudo_element_style(node, PseudoElement::Before) {
+                let content = style.content().expect("content should exist");
+                texts.push(generated_text(&content, element, counters));
+            }
+        }
+
+        let mut child = node.borrow().first_child();
+        while let Some(c) = child {
+            collect_generated_text(engine, &c, counters, texts);
+            child = c.borrow().next_sibling();
+        }
+
+        if let Some(element) = &element {
+            counters.enter(node);
+            if let Some(style) = engine.get_pseudo_element_style(node, PseudoElement::After) {
+                let content = style.content().expect("content should exist");
+                texts.push(generated_text(&content, element, counters));
+            }
+        }
+    }
+
+    fn generate(html: &str, style: &str) -> Vec<String> {
+        let window = HtmlParser::new(HtmlTokenizer::new(html.to_string())).construct_tree();
+        let document = window.borrow().document();
+        let sheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+        let engine = StyleEngine::new(sheet, MediaEnvironment::default());
+
+        let mut texts = Vec::new();
+        collect_generated_text(&engine, &document, &mut CounterState::new(), &mut texts);
+        texts
+    }
+
+    #[test]
+    fn test_strings_and_attr() {
+        /* 文字列とattr()を連結したテキストが生成されることを確認する */
+        let texts = generate(
+            "<html><head></head><body><a href=\"/top\">top</a><p>x</p></body></html>",
+            "a::before { content: \"→ \" } a::after { content: \" (\" attr(href) \")\" attr(title) } p::before { content: none }",
+        );
+        assert_eq!(texts, ["→ ", " (/top)"]);
+    }
+
+    #[test]
+    fn test_counters() {
+        /* counter-resetとcounter-incrementの値が、文書順とスコープに従って求められることを確認する */
+        let texts = generate(
+            "<html><head></head><body><h1>a</h1><h2>b</h2><h2>c</h2><h1>d</h1><h2>e</h2></body></html>",
+            "body { counter-reset: chapter } h1 { counter-increment: chapter; counter-reset: section } \
+             h1::before { content: counter(chapter) \". \" } \
+             h2 { counter-increment: section 2 } h2::before { content: counters(chapter, \"-\") \"-\" counter(section) }",
+        );
+        assert_eq!(texts, ["1. ", "1-2", "1-4", "2. ", "2-2"]);
+    }
+
+    #[test]
+    fn test_nested_counters() {
+        /* 入れ子の要素が作ったカウンターは、counters()で外側から順に連結されることを確認する */
+        let texts = generate(
+            "<html><head></head><body><p class=\"o\"><a class=\"i\"></a></p><a class=\"z\"></a></body></html>",
+            ".o { counter-reset: item 1 } .i { counter-reset: item 1 } \
+             .o::before { content: counters(item, \".\") } .i::before { content: counters(item, \".\") } \
+             .z::after { content: counter(item, none) \"/\" counter(missing) }",
+        );
+        assert_eq!(texts, ["1", "1.1", "/0"]);
+    }
+
+    #[test]
+    fn test_list_item_counter() {
+        /* リスト項目がlist-itemカウンターを暗黙に増やし、olのstart属性から数え始めることを確認する */
+        let texts = generate(
+            "<html><head></head><body><ol start=\"3\"><li>a</li><li class=\"skip\">b</li><li>c</li></ol><ul><li>d</li></ul></body></html>",
+            "li::before { content: counter(list-item) } .skip { counter-increment: list-item 0 }",
+        );
+        assert_eq!(texts, ["3", "3", "4", "1"]);
+    }
+
+    #[test]
+    fn test_counter_styles() {
+        /* 定義済みのカウンタースタイルと接尾辞で、マーカーのテキストが求められることを確認する */
+        assert_eq!(marker_text(3, ListStyleType::Disc).as_deref(), Some("• "));
+        assert_eq!(marker_text(3, ListStyleType::Square).as_deref(), Some("▪ "));
+        assert_eq!(
+            marker_text(-2, ListStyleType::Decimal).as_deref(),
+            Some("-2. ")
+        );
+        assert_eq!(
+            marker_text(28, ListStyleType::LowerAlpha).as_deref(),
+            Some("ab. ")
+        );
+        assert_eq!(
+            marker_text(26, ListStyleType::UpperAlpha).as_deref(),
+            Some("Z. ")
+        );
+        assert_eq!(
+            marker_text(0, ListStyleType::LowerAlpha).as_deref(),
+            Some("0. ")
+        );
+        assert_eq!(
+            marker_text(2024, ListStyleType::CjkDecimal).as_deref(),
+            Some("二〇二四、")
+        );
+        assert_eq!(
+            marker_text(1110, ListStyleType::JapaneseInformal).as_deref(),
+            Some("千百十、")
+        );
+        assert_eq!(
+            marker_text(3405, ListStyleType::JapaneseInformal).as_deref(),
+            Some("三千四百五、")
+        );
+        assert_eq!(
+            marker_text(-12, ListStyleType::JapaneseInformal).as_deref(),
+            Some("マイナス十二、")
+        );
+        assert_eq!(
+            marker_text(10000, ListStyleType::JapaneseInformal).as_deref(),
+            Some("一〇〇〇〇、")
+        );
+        assert_eq!(marker_text(1, ListStyleType::None), None);
+        assert_eq!(format_counter(5, Some("lower-roman")), "5");
+        assert_eq!(format_counter(5, Some("none")), "");
+    }
+}
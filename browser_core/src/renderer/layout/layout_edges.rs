@@ -0,0 +1,62 @@
+/// https://www.w3.org/TR/css-box-3/#box-model
+/// マージン・ボーダー・パディングの上下左右の幅（使用値のピクセル値）
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Default)]
+pub struct LayoutEdges {
+    top: i64,
+    right: i64,
+    bottom: i64,
+    left: i64,
+}
+
+impl LayoutEdges {
+    pub fn new(top: i64, right: i64, bottom: i64, left: i64) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    pub fn top(&self) -> i64 {
+        self.top
+    }
+
+    pub fn right(&self) -> i64 {
+        self.right
+    }
+
+    pub fn bottom(&self) -> i64 {
+        self.bottom
+    }
+
+    pub fn left(&self) -> i64 {
+        self.left
+    }
+
+    pub fn set_top(&mut self, top: i64) {
+        self.top = top;
+    }
+
+    pub fn set_right(&mut self, right: i64) {
+        self.right = right;
+    }
+
+    pub fn set_bottom(&mut self, bottom: i64) {
+        self.bottom = bottom;
+    }
+
+    pub fn set_left(&mut self, left: i64) {
+        self.left = left;
+    }
+
+    /// 左右の幅の合計
+    pub fn horizontal(&self) -> i64 {
+        self.left + self.right
+    }
+
+    /// 上下の幅の合計
+    pub fn vertical(&self) -> i64 {
+        self.top + self.bottom
+    }
+}
@@ -1,176 +1,4497 @@
-use crate::renderer::css::cssom::StyleSheet;
+use crate::renderer::css::cssom::Declaration;
+use crate::renderer::css::cssom::PseudoElement;
+use crate::renderer::css::cssom::Selector;
+use crate::renderer::css::value::ComputedLength;
 use crate::renderer::dom::node::Node;
+use crate::renderer::dom::node::NodeKind;
+use crate::renderer::layout::computed_style::BorderCollapse;
+use crate::renderer::layout::computed_style::BorderStyle;
+use crate::renderer::layout::computed_style::CaptionSide;
 use crate::renderer::layout::computed_style::ComputedStyle;
+use crate::renderer::layout::computed_style::ContentAlignment;
+use crate::renderer::layout::computed_style::DisplayType;
+use crate::renderer::layout::computed_style::FlexBasis;
+use crate::renderer::layout::computed_style::FlexWrap;
+use crate::renderer::layout::computed_style::Float;
+use crate::renderer::layout::computed_style::GridAutoFlow;
+use crate::renderer::layout::computed_style::GridLine;
+use crate::renderer::layout::computed_style::ItemAlignment;
+use crate::renderer::layout::computed_style::ListStylePosition;
+use crate::renderer::layout::computed_style::TableLayout;
+use crate::renderer::layout::computed_style::TextAlign;
+use crate::renderer::layout::computed_style::TextDecoration;
+use crate::renderer::layout::computed_style::TrackBreadth;
+use crate::renderer::layout::computed_style::TrackList;
+use crate::renderer::layout::computed_style::TrackSize;
+use crate::renderer::layout::computed_style::VerticalAlign;
+use crate::renderer::layout::float_context::FloatContext;
+use crate::renderer::layout::font_metrics::FontMetrics;
+use crate::renderer::layout::generated_content::generated_text;
+use crate::renderer::layout::generated_content::marker_text;
+use crate::renderer::layout::generated_content::CounterState;
+use crate::renderer::layout::layout_edges::LayoutEdges;
+use crate::renderer::layout::layout_point::LayoutPoint;
+use crate::renderer::layout::layout_rect::LayoutRect;
+use crate::renderer::layout::layout_size::LayoutSize;
+use crate::renderer::layout::line_box::LineBox;
+use crate::renderer::layout::line_box::LineFragment;
+use crate::renderer::layout::line_break::soft_wrap_opportunities;
+use crate::renderer::layout::style_engine::StyleEngine;
 use alloc::rc::Rc;
 use alloc::rc::Weak;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 use core::cell::RefCell;
+use core::ops::Range;
 
-use super::computed_style::DisplayType;
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutObjectKind {
+    Block,
+    Inline,
+    /// https://www.w3.org/TR/css-display-3/#atomic-inline
+    /// インラインレベルのフレックスコンテナやグリッドコンテナのように、分割できない1つの矩形として行に並べるボックス
+    AtomicInline,
+    Text,
+}
+
+impl LayoutObjectKind {
+    /// displayプロパティの計算値から、要素のボックスの種類を決める
+    fn of_display(display: DisplayType) -> Self {
+        match display {
+            DisplayType::Block
+            | DisplayType::ListItem
+            | DisplayType::FlowRoot
+            | DisplayType::Flex
+            | DisplayType::Grid
+            | DisplayType::Table => LayoutObjectKind::Block,
+            // 表の内部のボックスは、表のレイアウトで置くブロックとして扱う
+            DisplayType::TableRowGroup
+            | DisplayType::TableHeaderGroup
+            | DisplayType::TableFooterGroup
+            | DisplayType::TableRow
+            | DisplayType::TableCell
+            | DisplayType::TableColumnGroup
+            | DisplayType::TableColumn
+            | DisplayType::TableCaption => LayoutObjectKind::Block,
+            DisplayType::Inline => LayoutObjectKind::Inline,
+            DisplayType::InlineFlex | DisplayType::InlineGrid | DisplayType::InlineTable => {
+                LayoutObjectKind::AtomicInline
+            }
+            DisplayType::DisplayNone => {
+                panic!("should not create a layout object for display:none")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LayoutObject {
+    kind: LayoutObjectKind,
+    node: Rc<RefCell<Node>>,
+    first_child: Option<Rc<RefCell<LayoutObject>>>,
+    next_sibling: Option<Rc<RefCell<LayoutObject>>>,
+    parent: Weak<RefCell<LayoutObject>>,
+    style: ComputedStyle,
+    /// ボーダーボックスの左上の位置
+    point: LayoutPoint,
+    /// ボーダーボックスの大きさ
+    size: LayoutSize,
+    margin: LayoutEdges,
+    border: LayoutEdges,
+    padding: LayoutEdges,
+    /// インラインボックスとテキストの場合、行ごとに分割された断片
+    fragments: Vec<LineFragment>,
+    /// ブロックの場合、インラインレベルの子を並べた行ボックス
+    line_boxes: Vec<LineBox>,
+    /// 疑似要素のボックスの場合、その種類. `node`は疑似要素の元になる要素を指す
+    pseudo: Option<PseudoElement>,
+    /// 匿名ボックスか. 匿名ボックスの`node`は、囲んでいるボックスのノードを指す
+    anonymous: bool,
+    /// https://www.w3.org/TR/css-break-3/#break-decoration
+    /// ブロックレベルのボックスの前後で分割されたインラインボックスでは、分割された側の辺に
+    /// マージン・ボーダー・パディングを置かない. 始まり（左）と終わり（右）の辺を持つか
+    has_start_edge: bool,
+    has_end_edge: bool,
+    /// フレックスアイテムのように、親のレイアウトで決まったボーダーボックスの幅と高さ
+    /// 指定されている場合は、widthとheightプロパティの代わりに使う
+    override_width: Option<i64>,
+    override_height: Option<i64>,
+    /// https://www.w3.org/TR/CSS2/tables.html#collapsing-borders
+    /// ボーダーを重ねる表のセルの場合、隣のセルや表と共有して決めたボーダーの幅
+    collapsed_border: Option<LayoutEdges>,
+    /// https://www.w3.org/TR/css-text-decor-3/#line-decoration
+    /// 祖先から伝わったものを含む、このボックスの中のテキストに描く装飾
+    text_decoration: TextDecoration,
+}
+
+impl LayoutObject {
+    pub fn new(node: Rc<RefCell<Node>>, parent_obj: &Option<Rc<RefCell<LayoutObject>>>) -> Self {
+        let parent = match parent_obj {
+            Some(p) => Rc::downgrade(p),
+            None => Weak::new(),
+        };
+
+        Self {
+            kind: LayoutObjectKind::Block,
+            node: node.clone(),
+            first_child: None,
+            next_sibling: None,
+            parent,
+            style: ComputedStyle::new(),
+            point: LayoutPoint::new(0, 0),
+            size: LayoutSize::new(0, 0),
+            margin: LayoutEdges::default(),
+            border: LayoutEdges::default(),
+            padding: LayoutEdges::default(),
+            fragments: Vec::new(),
+            line_boxes: Vec::new(),
+            pseudo: None,
+            anonymous: false,
+            has_start_edge: true,
+            has_end_edge: true,
+            override_width: None,
+            override_height: None,
+            collapsed_border: None,
+            text_decoration: TextDecoration::None,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-pseudo-4/#generated-content
+    /// 要素の::before、::afterまたは::markerのボックスを生成する
+    /// 疑似要素はDOMノードを持たないので、生成したテキストを持つテキストノードを作り、
+    /// そのレイアウトオブジェクトを疑似要素のボックスの子にする
+    pub fn create_pseudo_element_object(
+        element_obj: &Rc<RefCell<LayoutObject>>,
+        pseudo: PseudoElement,
+        engine: &StyleEngine,
+        counters: &CounterState,
+    ) -> Option<Rc<RefCell<LayoutObject>>> {
+        let node = element_obj.borrow().node.clone();
+        let element = node.borrow().get_element()?;
+        let style = engine.get_pseudo_element_style(&node, pseudo)?;
+
+        let text = if pseudo == PseudoElement::Marker {
+            // https://www.w3.org/TR/css-lists-3/#marker-pseudo
+            // ::markerはリスト項目にだけ生成する. contentがnormalの場合は、list-itemカウンターの値を
+            // list-style-typeで表したテキストを内容にし、list-style-typeがnoneの場合は生成しない
+            if element_obj.borrow().style.display() != DisplayType::ListItem {
+                return None;
+            }
+            match style.content() {
+                Some(content) => generated_text(&content, &element, counters),
+                None if style.is_content_none() => return None,
+                None => marker_text(counters.value("list-item"), style.list_style_type())?,
+            }
+        } else {
+            // contentがnormalまたはnoneの場合はボックスを生成しない
+            generated_text(&style.content()?, &element, counters)
+        };
+        if style.display() == DisplayType::DisplayNone {
+            return None;
+        }
+
+        let pseudo_obj = Rc::new(RefCell::new(LayoutObject::new(
+            node,
+            &Some(element_obj.clone()),
+        )));
+        {
+            let mut obj = pseudo_obj.borrow_mut();
+            // ::markerにはdisplayが適用されず、常にインラインボックスになる
+            obj.kind = if pseudo == PseudoElement::Marker {
+                LayoutObjectKind::Inline
+            } else {
+                LayoutObjectKind::of_display(style.display())
+            };
+            obj.style = style.clone();
+            obj.pseudo = Some(pseudo);
+        }
+
+        if !text.is_empty() {
+            let text_node = Rc::new(RefCell::new(Node::new(NodeKind::Text(text))));
+            let mut text_style = ComputedStyle::new();
+            text_style.defaulting(&text_node, Some(style));
+
+            let text_obj = Rc::new(RefCell::new(LayoutObject::new(
+                text_node,
+                &Some(pseudo_obj.clone()),
+            )));
+            text_obj.borrow_mut().kind = LayoutObjectKind::Text;
+            text_obj.borrow_mut().style = text_style;
+            pseudo_obj.borrow_mut().set_first_child(Some(text_obj));
+        }
+
+        Some(pseudo_obj)
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#anonymous-block-level
+    /// `parent`の子として匿名ブロックボックスを作る
+    pub fn create_anonymous_block(parent: &Rc<RefCell<LayoutObject>>) -> Rc<RefCell<LayoutObject>> {
+        let mut anonymous = LayoutObject::new(parent.borrow().node(), &Some(parent.clone()));
+        anonymous
+            .style
+            .defaulting_anonymous_box(parent.borrow().style(), DisplayType::Block);
+        anonymous.kind = LayoutObjectKind::Block;
+        anonymous.anonymous = true;
+        Rc::new(RefCell::new(anonymous))
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#anonymous-block-level
+    /// ブロックレベルのボックスの前後で分割したインラインボックスの、2つ目以降の部分を作る
+    /// 子は持たず、始まりの辺を持たない. この部分の前までのボックスは、終わりの辺を持たなくなる
+    pub fn split_inline(&mut self) -> Rc<RefCell<LayoutObject>> {
+        let mut continuation = self.clone();
+        continuation.first_child = None;
+        continuation.next_sibling = None;
+        continuation.fragments = Vec::new();
+        continuation.has_start_edge = false;
+        self.has_end_edge = false;
+        Rc::new(RefCell::new(continuation))
+    }
+
+    /// https://www.w3.org/TR/selectors-4/#match-a-selector-against-an-element
+    /// セレクタがこのレイアウトオブジェクトのノードに一致するか. 要素以外のノードには一致しない
+    pub fn is_node_selected(&self, selector: &Selector) -> bool {
+        match self.node.borrow().get_element() {
+            Some(element) => selector.matches(&element),
+            None => false,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-cascade-4/#cascading
+    /// カスケードの順に並べた宣言を、順にスタイルへカスケードする. 後の宣言ほど優先される
+    pub fn cascading_style(&mut self, declarations: Vec<Declaration>) {
+        for declaration in &declarations {
+            self.style.cascade_declaration(declaration);
+        }
+    }
+
+    /// https://www.w3.org/TR/css-cascade-4/#defaulting
+    /// カスケードで値が決まらなかったプロパティを、親のスタイルからの継承または初期値で埋める
+    pub fn defaulting_style(
+        &mut self,
+        node: &Rc<RefCell<Node>>,
+        parent_style: Option<ComputedStyle>,
+    ) {
+        self.style.defaulting(node, parent_style);
+    }
+
+    /// displayプロパティの計算値から、レイアウトオブジェクトの種類を決める
+    /// テキストノードはdisplayによらず、常にテキストになる
+    pub fn update_kind(&mut self) {
+        match self.node_kind() {
+            NodeKind::Document => panic!("should not create a layout object for a Document node"),
+            NodeKind::Element(_) => self.kind = LayoutObjectKind::of_display(self.style.display()),
+            NodeKind::Text(_) => self.kind = LayoutObjectKind::Text,
+        }
+    }
+
+    /// レイアウトツリーのルートとして、ビューポートを包含ブロックにしてレイアウトする
+    /// すべてのレイアウトオブジェクトの位置は、コンテンツ領域の左上を原点とする座標になる
+    pub fn layout_root(&mut self, viewport: LayoutSize) {
+        // https://www.w3.org/TR/css-display-3/#transformations
+        // ルート要素のdisplayはブロック化されるので、インラインであってもブロックとしてレイアウトする
+        // ルートはブロック整形コンテキストを作るので、マージンは子孫のマージンと折り畳まれない
+        self.layout_block(viewport.width(), Some(viewport.height()), true);
+
+        self.point = LayoutPoint::new(self.margin.left(), self.margin.top());
+        self.update_absolute_point();
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#block-formatting
+    /// ブロックの幅と高さ、子の位置を求める. 子の位置は、このブロックのボーダーボックスの左上からの相対位置になる
+    /// 子孫のフロートは、このブロックの中だけで避ける. 親のマージンと折り畳むための、マージンの情報を返す
+    fn layout_block(
+        &mut self,
+        containing_width: i64,
+        containing_height: Option<i64>,
+        is_root: bool,
+    ) -> BlockMargins {
+        self.layout_block_in_context(
+            containing_width,
+            containing_height,
+            is_root,
+            &mut FloatContext::new(),
+            LayoutPoint::new(0, 0),
+        )
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#block-formatting
+    /// 親と同じブロック整形コンテキストに置かれたブロックとしてレイアウトする
+    /// `floats`はそのブロック整形コンテキストのフロートで、`origin`は包含ブロックのコンテンツ領域の左端と、
+    /// このブロックのボーダーボックスの上端の、ブロック整形コンテキストの中での位置
+    fn layout_block_in_context(
+        &mut self,
+        containing_width: i64,
+        containing_height: Option<i64>,
+        is_root: bool,
+        floats: &mut FloatContext,
+        origin: LayoutPoint,
+    ) -> BlockMargins {
+        let base = containing_width as f64;
+        let style = self.style.clone();
+
+        self.resolve_border_and_padding(containing_width);
+
+        let (width, margin_left, margin_right) =
+            match self.override_width {
+                // 親のレイアウトで幅が決まっている場合、autoのマージンは0になる
+                Some(override_width) => (
+                    (override_width - self.border.horizontal() - self.padding.horizontal()).max(0),
+                    resolve_px(&style.margin_left(), base),
+                    resolve_px(&style.margin_right(), base),
+                ),
+                None => resolve_block_width(
+                    containing_width,
+                    if style.display().is_table() {
+                        Some(self.table_width(
+                            containing_width,
+                            style.width().resolve(base).map(round_px),
+                        ))
+                    } else {
+                        style.width().resolve(base).map(round_px)
+                    },
+                    style.margin_left().resolve(base).map(round_px),
+                    style.margin_right().resolve(base).map(round_px),
+                    self.border.horizontal() + self.padding.horizontal(),
+                ),
+            };
+        // 通常フローのブロックでは、上下のautoのマージンは0になる
+        self.margin = LayoutEdges::new(
+            resolve_px(&style.margin_top(), base),
+            margin_right,
+            resolve_px(&style.margin_bottom(), base),
+            margin_left,
+        );
+        // このブロックのボーダーボックスの、ブロック整形コンテキストの中での位置
+        let offset = LayoutPoint::new(origin.x() + margin_left, origin.y());
+
+        // https://www.w3.org/TR/CSS2/visudet.html#the-height-property
+        // パーセンテージの高さは、包含ブロックの高さが決まっている場合だけ解決し、そうでなければautoとして扱う
+        let height = match (style.height(), containing_height) {
+            _ if self.override_height.is_some() => self
+                .override_height
+                .map(|h| (h - self.border.vertical() - self.padding.vertical()).max(0)),
+            (ComputedLength::Auto, _) => None,
+            (ComputedLength::Px(px), _) => Some(round_px(px).max(0)),
+            (_, None) => None,
+            (height, Some(containing_height)) => height
+                .resolve(containing_height as f64)
+                .map(|h| round_px(h).max(0)),
+        };
+
+        if style.display().is_flex_container()
+            || style.display().is_grid_container()
+            || style.display().is_table()
+        {
+            // https://www.w3.org/TR/css-flexbox-1/#flex-containers
+            // https://www.w3.org/TR/css-grid-2/#grid-containers
+            // https://www.w3.org/TR/CSS2/tables.html#model
+            // フレックスコンテナとグリッドコンテナと表は新しい整形コンテキストを作るので、
+            // マージンは子のマージンと折り畳まれない
+            self.line_boxes.clear();
+            let content_height = if style.display().is_table() {
+                self.layout_table_container(width, height)
+            } else if style.display().is_grid_container() {
+                self.layout_grid_container(width, height)
+            } else {
+                self.layout_flex_container(width, height)
+            };
+            self.size = LayoutSize::new(
+                width + self.border.horizontal() + self.padding.horizontal(),
+                height.unwrap_or(content_height) + self.border.vertical() + self.padding.vertical(),
+            );
+            return BlockMargins {
+                top: MarginStrut::new(self.margin.top()),
+                bottom: MarginStrut::new(self.margin.bottom()),
+                self_collapsing: false,
+            };
+        }
+
+        // https://www.w3.org/TR/CSS2/box.html#collapsing-margins
+        // ボーダーとパディングがなければ、親の上（下）マージンと最初（最後）の子の上（下）マージンは隣接する
+        let top_collapses = !is_root && self.border.top() == 0 && self.padding.top() == 0;
+        let bottom_collapses =
+            !is_root && self.border.bottom() == 0 && self.padding.bottom() == 0 && height.is_none();
+
+        let content_x = self.border.left() + self.padding.left();
+        let content_y = self.border.top() + self.padding.top();
+        self.line_boxes.clear();
+        let mut top = MarginStrut::new(self.margin.top());
+        // 直前の兄弟（または内容の上端）との間にある、まだ位置に反映していないマージン
+        let mut strut = MarginStrut::default();
+        // 内容の上端からの、次の子を置く位置
+        let mut cursor = 0;
+        let mut has_content = false;
+
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            if !c.borrow().is_in_flow_block() {
+                // インラインレベルの子は行ボックスに並べる. 行ボックスはマージンの折り畳みを妨げる
+                // 絶対位置指定された子とフロートも、行ボックスと一緒に静的位置と置く位置を決める
+                let line_top = if !has_content && top_collapses {
+                    cursor
+                } else {
+                    cursor + strut.collapsed()
+                };
+                let (next, lines) = layout_inline_formatting_context(
+                    &c,
+                    &style,
+                    width,
+                    LayoutPoint::new(content_x, content_y + line_top),
+                    floats,
+                    offset,
+                );
+                child = next;
+
+                // 空のインラインボックスしかない高さ0の行は、マージンの折り畳みでは存在しないものとして扱う
+                let height: i64 = lines.iter().map(|line| line.size().height()).sum();
+                self.line_boxes.extend(lines);
+                if height == 0 {
+                    continue;
+                }
+                if !has_content && top_collapses {
+                    top.merge(strut);
+                }
+                strut = MarginStrut::default();
+                cursor = line_top + height;
+                has_content = true;
+                continue;
+            }
+
+            let (child_margin_top, clear, establishes_context) = {
+                let c = c.borrow();
+                (
+                    resolve_px(&c.style.margin_top(), width as f64),
+                    c.style.clear(),
+                    c.establishes_block_formatting_context(),
+                )
+            };
+            // 子の上マージンを折り畳んだ後の、子のボーダーボックスの上端を見積もる
+            let mut estimate = strut;
+            estimate.merge(MarginStrut::new(child_margin_top));
+            let estimated_y = if !has_content && top_collapses {
+                cursor
+            } else {
+                cursor + estimate.collapsed()
+            };
+
+            // https://www.w3.org/TR/CSS2/visuren.html#flow-control
+            // clearで避けるフロートの下端より上に置かれる場合は、間隔（clearance）を空けて下端まで下げる
+            // 間隔を空けた子の上マージンは、前のマージンと折り畳まれない
+            let clearance = floats
+                .clearance(clear)
+                .map(|bottom| bottom - offset.y() - content_y)
+                .filter(|&bottom| bottom > estimated_y);
+
+            // https://www.w3.org/TR/CSS2/visuren.html#bfc-next-to-float
+            // 新しいブロック整形コンテキストを作る子は、フロートに重ならないように幅を狭めて横に置く
+            let (child_x, child_width) = if establishes_context {
+                let left = offset.x() + content_x;
+                let top = offset.y() + content_y + clearance.unwrap_or(estimated_y);
+                let (start, end) = floats.available(top, 0, left, left + width);
+                (start - offset.x(), (end - start).max(0))
+            } else {
+                (content_x, width)
+            };
+            let layout_child = |floats: &mut FloatContext, y: i64| {
+                if establishes_context {
+                    c.borrow_mut().layout_block(child_width, height, true)
+                } else {
+                    let origin =
+                        LayoutPoint::new(offset.x() + content_x, offset.y() + content_y + y);
+                    c.borrow_mut()
+                        .layout_block_in_context(width, height, false, floats, origin)
+                }
+            };
+
+            let snapshot = floats.clone();
+            let mut margins = layout_child(floats, clearance.unwrap_or(estimated_y));
+            // 子孫のマージンが折り畳まれて見積もりと位置が変わった場合は、フロートを置き直すためにレイアウトし直す
+            let y = {
+                let mut strut = strut;
+                strut.merge(margins.top);
+                if margins.self_collapsing {
+                    strut.merge(margins.bottom);
+                }
+                if !has_content && top_collapses {
+                    cursor
+                } else {
+                    cursor + strut.collapsed()
+                }
+            };
+            if clearance.is_none() && y != estimated_y && !floats.is_empty() {
+                *floats = snapshot;
+                margins = layout_child(floats, y);
+            }
+
+            let child_margin_left = c.borrow().margin.left();
+            if let Some(y) = clearance {
+                if !has_content && top_collapses {
+                    top.merge(strut);
+                }
+                c.borrow_mut().point = LayoutPoint::new(child_x + child_margin_left, content_y + y);
+                cursor = y + c.borrow().size.height();
+                strut = margins.bottom;
+                has_content = true;
+            } else if margins.self_collapsing {
+                // 高さも内容もないブロックは、前後のマージンをそのまま通す
+                strut.merge(margins.top);
+                strut.merge(margins.bottom);
+                let y = if !has_content && top_collapses {
+                    0
+                } else {
+                    cursor + strut.collapsed()
+                };
+                c.borrow_mut().point = LayoutPoint::new(child_x + child_margin_left, content_y + y);
+            } else {
+                strut.merge(margins.top);
+                if !has_content && top_collapses {
+                    // 最初の子の上マージンは、このブロックの上マージンと折り畳まれてブロックの外に出る
+                    top.merge(strut);
+                } else {
+                    cursor += strut.collapsed();
+                }
+                c.borrow_mut().point =
+                    LayoutPoint::new(child_x + child_margin_left, content_y + cursor);
+                cursor += c.borrow().size.height();
+                strut = margins.bottom;
+                has_content = true;
+            }
+            child = c.borrow().next_sibling();
+        }
+
+        let mut bottom = MarginStrut::new(self.margin.bottom());
+        let self_collapsing = !has_content && top_collapses && bottom_collapses;
+        if self_collapsing || (!has_content && top_collapses) {
+            top.merge(strut);
+        } else if bottom_collapses {
+            // 最後の子の下マージンは、このブロックの下マージンと折り畳まれてブロックの外に出る
+            bottom.merge(strut);
+        } else {
+            cursor += strut.collapsed();
+        }
+
+        // https://www.w3.org/TR/CSS2/visudet.html#root-height
+        // ブロック整形コンテキストを作るブロックの高さがautoの場合は、子孫のフロートの下端までを含める
+        if is_root {
+            if let Some(bottom) = floats.bottom() {
+                cursor = cursor.max(bottom - offset.y() - content_y);
+            }
+        }
+
+        let content_height = height.unwrap_or(cursor.max(0));
+        self.size = LayoutSize::new(
+            width + self.border.horizontal() + self.padding.horizontal(),
+            content_height + self.border.vertical() + self.padding.vertical(),
+        );
+        self.place_outside_marker();
+
+        BlockMargins {
+            top,
+            bottom,
+            self_collapsing,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#layout-algorithm
+    /// 子をフレックスアイテムとして並べ、内容の高さを返す. 子の位置は、このボックスのボーダーボックスの左上からの相対位置になる
+    /// `width`と`height`はコンテンツ領域の大きさで、高さのNoneはautoを表す
+    fn layout_flex_container(&mut self, width: i64, height: Option<i64>) -> i64 {
+        let style = self.style.clone();
+        let direction = style.flex_direction();
+        let is_row = !direction.is_column();
+        let wrap = style.flex_wrap();
+        let wrap_reverse = wrap == FlexWrap::WrapReverse;
+        // 主軸と交差軸の方向の、コンテンツ領域の大きさ. Noneは内容から決まることを表す
+        let (main_size, cross_size) = if is_row {
+            (Some(width), height)
+        } else {
+            (height, Some(width))
+        };
+        let column_gap = resolve_px(&style.column_gap(), width as f64);
+        let row_gap = resolve_px(&style.row_gap(), height.unwrap_or(0) as f64);
+        let (main_gap, cross_gap) = if is_row {
+            (column_gap, row_gap)
+        } else {
+            (row_gap, column_gap)
+        };
+
+        // https://www.w3.org/TR/css-flexbox-1/#algo-main-item
+        // orderの小さい順に並べる. 同じorderのアイテムは文書順のまま
+        let mut items = Vec::new();
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            child = c.borrow().next_sibling();
+            if c.borrow().is_absolutely_positioned() {
+                self.set_static_position_at_content_origin(&c);
+                continue;
+            }
+            items.push(FlexItem::new(c, is_row, width, height, style.align_items()));
+        }
+        items.sort_by_key(|item| item.object.borrow().style.order());
+        if items.is_empty() {
+            return 0;
+        }
+
+        // https://www.w3.org/TR/css-flexbox-1/#algo-line-break
+        // 主軸方向の大きさが決まっている場合だけ、行に収まらないアイテムを次の行に送る
+        let lines = match main_size {
+            Some(main_size) if wrap != FlexWrap::NoWrap => {
+                collect_flex_lines(&items, main_size, main_gap)
+            }
+            _ => collect_flex_lines(&items, i64::MAX, main_gap),
+        };
+
+        // https://www.w3.org/TR/css-flexbox-1/#algo-flex
+        // 主軸方向の大きさが決まらない場合は、アイテムの仮の大きさの合計がコンテナの大きさになる
+        let mut main_extent = 0;
+        for range in &lines {
+            let line = &mut items[range.clone()];
+            let available = main_size.unwrap_or_else(|| {
+                line.iter()
+                    .map(|item| item.hypothetical_main_size + item.main_margin_sum())
+                    .sum::<i64>()
+                    + main_gap * (line.len() as i64 - 1)
+            });
+            resolve_flexible_lengths(line, available, main_gap);
+            main_extent = main_extent.max(available);
+        }
+
+        // https://www.w3.org/TR/css-flexbox-1/#algo-cross-item
+        // 主軸方向の大きさを決めてアイテムをレイアウトし、交差軸方向の仮の大きさを求める
+        for item in &mut items {
+            let cross_size = if is_row { None } else { Some(item.cross_size) };
+            item.layout(is_row, cross_size, width, height);
+        }
+
+        // https://www.w3.org/TR/css-flexbox-1/#algo-cross-line
+        // 1行のコンテナで交差軸方向の大きさが決まっている場合は、行の大きさはコンテナの大きさになる
+        let mut line_cross_sizes: Vec<i64> = lines
+            .iter()
+            .map(|range| match cross_size {
+                Some(cross_size) if wrap == FlexWrap::NoWrap => cross_size,
+                _ => {
+                    let line = &items[range.clone()];
+                    let (above, below) = baseline_extents(line, is_row);
+                    line.iter()
+                        .map(|item| item.cross_size + item.cross_margin_sum())
+                        .fold(above + below, i64::max)
+                }
+            })
+            .collect();
+        let lines_cross =
+            |sizes: &[i64]| sizes.iter().sum::<i64>() + cross_gap * (sizes.len() as i64 - 1);
+
+        // https://www.w3.org/TR/css-flexbox-1/#algo-line-stretch
+        // align-contentがnormalまたはstretchの場合、余った交差軸方向の大きさを行に均等に配分する
+        let align_content = style.align_content();
+        if let Some(cross_size) = cross_size {
+            let free = cross_size - lines_cross(&line_cross_sizes);
+            let count = line_cross_sizes.len() as i64;
+            if matches!(
+                align_content,
+                ContentAlignment::Normal | ContentAlignment::Stretch
+            ) && free > 0
+            {
+                for (i, size) in line_cross_sizes.iter_mut().enumerate() {
+                    *size += free / count + if (i as i64) < free % count { 1 } else { 0 };
+                }
+            }
+        }
+
+        // https://www.w3.org/TR/css-flexbox-1/#algo-stretch
+        // 交差軸方向の大きさがautoのアイテムは、行の大きさに合わせて伸ばし、その大きさでレイアウトし直す
+        for (range, &line_cross) in lines.iter().zip(&line_cross_sizes) {
+            for item in &mut items[range.clone()] {
+                if item.is_stretched() {
+                    item.cross_size = (line_cross - item.cross_margin_sum()).max(item.cross_edges);
+                    item.layout(is_row, Some(item.cross_size), width, height);
+                }
+            }
+        }
+
+        let cross_extent = cross_size.unwrap_or_else(|| lines_cross(&line_cross_sizes));
+        let content_x = self.border.left() + self.padding.left();
+        let content_y = self.border.top() + self.padding.top();
+
+        // https://www.w3.org/TR/css-flexbox-1/#algo-main-align
+        // 位置は主軸と交差軸の始まりからの距離で求め、逆向きの場合は物理的な位置に反転する
+        let reverse = direction.is_reverse();
+        let justify_content = style.justify_content();
+        for range in &lines {
+            let line = &mut items[range.clone()];
+            let used: i64 = line
+                .iter()
+                .map(|item| item.main_size + item.main_margin_sum())
+                .sum::<i64>()
+                + main_gap * (line.len() as i64 - 1);
+            let mut free = main_extent - used;
+
+            // https://www.w3.org/TR/css-flexbox-1/#auto-margins
+            // 主軸方向のautoのマージンがある場合は、余白をautoのマージンに配分し、justify-contentは使わない
+            let auto_margins = line
+                .iter()
+                .map(|item| item.main_auto_margin_count())
+                .sum::<i64>();
+            let auto_margin = if auto_margins > 0 && free > 0 {
+                let share = free as f64 / auto_margins as f64;
+                free = 0;
+                share
+            } else {
+                0.0
+            };
+
+            let (offset, between) =
+                distribute_free_space(justify_content, free, line.len(), reverse);
+            let mut position = offset;
+            for item in line.iter_mut() {
+                let start_margin = item.main_margins.0.map_or(auto_margin, |m| m as f64);
+                let end_margin = item.main_margins.1.map_or(auto_margin, |m| m as f64);
+                let start = round_px(position);
+                position += start_margin + item.main_size as f64 + end_margin;
+                let end = round_px(position);
+                let margin_start = round_px(start as f64 + start_margin) - start;
+                let margin_end = end - start - margin_start - item.main_size;
+
+                let slot = if reverse { main_extent - end } else { start };
+                item.main_position = slot + margin_start;
+                item.main_margins = (Some(margin_start), Some(margin_end));
+                position += main_gap as f64 + between;
+            }
+        }
+
+        // https://www.w3.org/TR/css-flexbox-1/#algo-cross-align
+        // https://www.w3.org/TR/css-flexbox-1/#algo-line-align
+        let free = cross_extent - lines_cross(&line_cross_sizes);
+        let (offset, between) = match wrap {
+            FlexWrap::NoWrap => (0.0, 0.0),
+            _ => distribute_free_space(align_content, free, lines.len(), wrap_reverse),
+        };
+        let mut line_position = offset;
+        for (range, &line_cross) in lines.iter().zip(&line_cross_sizes) {
+            let line_start = round_px(line_position);
+            let line = &mut items[range.clone()];
+            let (max_above, _) = baseline_extents(line, is_row);
+            for item in line.iter_mut() {
+                let free = line_cross - item.cross_size - item.cross_margin_sum();
+                let (margin_start, margin_end, offset) = match item.cross_margins {
+                    // 交差軸方向のautoのマージンは、余白をすべて受け取る
+                    (None, None) => (free.max(0) / 2, free.max(0) - free.max(0) / 2, 0),
+                    (None, Some(end)) => (free.max(0), end, 0),
+                    (Some(start), None) => (start, free.max(0), 0),
+                    (Some(start), Some(end)) => {
+                        let offset = match item.alignment {
+                            ItemAlignment::Normal
+                            | ItemAlignment::Stretch
+                            | ItemAlignment::FlexStart => 0,
+                            ItemAlignment::FlexEnd => free,
+                            ItemAlignment::Center => free / 2,
+                            ItemAlignment::Start if wrap_reverse => free,
+                            ItemAlignment::Start => 0,
+                            ItemAlignment::End if wrap_reverse => 0,
+                            ItemAlignment::End => free,
+                            ItemAlignment::Baseline => item
+                                .baseline_offset(is_row)
+                                .map_or(0, |above| max_above - above),
+                        };
+                        (start, end, offset)
+                    }
+                };
+
+                let start = line_start + offset;
+                let outer = margin_start + item.cross_size + margin_end;
+                let slot = if wrap_reverse {
+                    cross_extent - start - outer
+                } else {
+                    start
+                };
+                item.cross_position = slot + margin_start;
+                item.cross_margins = (Some(margin_start), Some(margin_end));
+            }
+            line_position += (line_cross + cross_gap) as f64 + between;
+        }
+
+        for item in &items {
+            let mut object = item.object.borrow_mut();
+            let (main_start, main_end) = (
+                item.main_margins.0.unwrap_or(0),
+                item.main_margins.1.unwrap_or(0),
+            );
+            let (cross_start, cross_end) = (
+                item.cross_margins.0.unwrap_or(0),
+                item.cross_margins.1.unwrap_or(0),
+            );
+            if is_row {
+                object.point = LayoutPoint::new(
+                    content_x + item.main_position,
+                    content_y + item.cross_position,
+                );
+                object.margin = LayoutEdges::new(cross_start, main_end, cross_end, main_start);
+            } else {
+                object.point = LayoutPoint::new(
+                    content_x + item.cross_position,
+                    content_y + item.main_position,
+                );
+                object.margin = LayoutEdges::new(main_start, cross_end, main_end, cross_start);
+            }
+        }
+
+        if is_row {
+            cross_extent
+        } else {
+            main_extent
+        }
+    }
+
+    /// https://www.w3.org/TR/css-grid-2/#layout-algorithm
+    /// 子をグリッドアイテムとしてグリッドに置き、内容の高さを返す. 子の位置は、このボックスのボーダーボックスの左上からの相対位置になる
+    /// `width`と`height`はコンテンツ領域の大きさで、高さのNoneはautoを表す
+    fn layout_grid_container(&mut self, width: i64, height: Option<i64>) -> i64 {
+        let style = self.style.clone();
+        let column_gap = resolve_px(&style.column_gap(), width as f64);
+        let row_gap = resolve_px(&style.row_gap(), height.unwrap_or(0) as f64);
+        let grid = self.place_grid_items(Some(width), height);
+        let mut items = grid.items;
+
+        // https://www.w3.org/TR/css-grid-2/#algo-track-sizing
+        // 列の大きさを先に決め、行の大きさは、決まった列の幅でアイテムをレイアウトした高さから決める
+        let justify_content = style.justify_content();
+        let column_sizes = size_grid_tracks(
+            &grid.columns,
+            &grid_column_contributions(&items),
+            GridAvailableSpace::Definite(width),
+            column_gap,
+            is_stretch(justify_content),
+        );
+        let columns = position_grid_tracks(&column_sizes, Some(width), column_gap, justify_content);
+
+        let mut contributions = Vec::new();
+        for item in &mut items {
+            let (_, area_width) = track_area(&columns, &item.columns);
+            item.layout_in_area(area_width, None);
+            let outer_height = item.height
+                + item.vertical_margins.0.unwrap_or(0)
+                + item.vertical_margins.1.unwrap_or(0);
+            contributions.push((item.rows.clone(), outer_height, outer_height));
+        }
+
+        // 高さがautoの場合は、最大コンテンツの制約の下で行の大きさを決める
+        let align_content = style.align_content();
+        let row_sizes = size_grid_tracks(
+            &grid.rows,
+            &contributions,
+            height.map_or(GridAvailableSpace::MaxContent, GridAvailableSpace::Definite),
+            row_gap,
+            is_stretch(align_content),
+        );
+        let rows = position_grid_tracks(&row_sizes, height, row_gap, align_content);
+
+        // https://www.w3.org/TR/css-grid-2/#alignment
+        // グリッド領域の大きさでレイアウトし直し、領域の中で揃える
+        let content_x = self.border.left() + self.padding.left();
+        let content_y = self.border.top() + self.padding.top();
+        for item in &mut items {
+            let (area_x, area_width) = track_area(&columns, &item.columns);
+            let (area_y, area_height) = track_area(&rows, &item.rows);
+            item.layout_in_area(area_width, Some(area_height));
+            let (left, right, x) = align_in_area(
+                item.justify,
+                item.horizontal_margins,
+                item.width,
+                area_width,
+            );
+            let (top, bottom, y) =
+                align_in_area(item.align, item.vertical_margins, item.height, area_height);
+
+            let mut object = item.object.borrow_mut();
+            object.point =
+                LayoutPoint::new(content_x + area_x + x + left, content_y + area_y + y + top);
+            object.margin = LayoutEdges::new(top, right, bottom, left);
+        }
+
+        height.unwrap_or_else(|| rows.last().map_or(0, |row| row.end))
+    }
+
+    /// https://www.w3.org/TR/css-grid-2/#grid-item-placement-algorithm
+    /// 子をorderの順に並べてグリッドに置き、暗黙のトラックを含むグリッドを作る
+    /// `width`と`height`はコンテンツ領域の大きさで、auto-fillの繰り返しの回数を決めるのに使う
+    fn place_grid_items(&self, width: Option<i64>, height: Option<i64>) -> GridPlacement {
+        let style = self.style.clone();
+        let column_gap = resolve_px(&style.column_gap(), width.unwrap_or(0) as f64);
+        let row_gap = resolve_px(&style.row_gap(), height.unwrap_or(0) as f64);
+        let explicit_columns = expand_track_list(&style.grid_template_columns(), width, column_gap);
+        let explicit_rows = expand_track_list(&style.grid_template_rows(), height, row_gap);
+
+        // https://www.w3.org/TR/css-grid-2/#order-property
+        let mut objects = Vec::new();
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            child = c.borrow().next_sibling();
+            if c.borrow().is_absolutely_positioned() {
+                self.set_static_position_at_content_origin(&c);
+                continue;
+            }
+            objects.push(c);
+        }
+        objects.sort_by_key(|object| object.borrow().style.order());
+
+        let placements: Vec<(AxisPlacement, AxisPlacement)> = objects
+            .iter()
+            .map(|object| {
+                let style = object.borrow().style();
+                (
+                    AxisPlacement::new(
+                        style.grid_column_start(),
+                        style.grid_column_end(),
+                        explicit_columns.len() as i64,
+                    ),
+                    AxisPlacement::new(
+                        style.grid_row_start(),
+                        style.grid_row_end(),
+                        explicit_rows.len() as i64,
+                    ),
+                )
+            })
+            .collect();
+        let areas = place_grid_areas(
+            &placements,
+            explicit_columns.len() as i64,
+            explicit_rows.len() as i64,
+            style.grid_auto_flow(),
+        );
+
+        // 明示的なグリッドより前に置かれたアイテムがある場合は、その分だけ暗黙のトラックを前に加える
+        let column_offset = areas.iter().map(|(c, _)| c.start).fold(0, i64::min);
+        let row_offset = areas.iter().map(|(_, r)| r.start).fold(0, i64::min);
+        let column_count = areas
+            .iter()
+            .map(|(c, _)| c.end)
+            .fold(explicit_columns.len() as i64, i64::max)
+            - column_offset;
+        let row_count = areas
+            .iter()
+            .map(|(_, r)| r.end)
+            .fold(explicit_rows.len() as i64, i64::max)
+            - row_offset;
+
+        let items = objects
+            .into_iter()
+            .zip(areas)
+            .map(|(object, (columns, rows))| {
+                GridItem::new(
+                    object,
+                    (columns.start - column_offset) as usize
+                        ..(columns.end - column_offset) as usize,
+                    (rows.start - row_offset) as usize..(rows.end - row_offset) as usize,
+                    &style,
+                )
+            })
+            .collect();
+
+        GridPlacement {
+            items,
+            columns: grid_axis_tracks(
+                &explicit_columns,
+                &style.grid_auto_columns(),
+                -column_offset as usize,
+                column_count as usize,
+            ),
+            rows: grid_axis_tracks(
+                &explicit_rows,
+                &style.grid_auto_rows(),
+                -row_offset as usize,
+                row_count as usize,
+            ),
+        }
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#width-layout
+    /// 子を表の行と列に並べ、内容の高さを返す. 子の位置は、親（表・行グループ・行）のボーダーボックスの左上からの相対位置になる
+    /// `width`と`height`はコンテンツ領域の大きさで、高さのNoneはautoを表す. キャプションもコンテンツ領域の中に置く
+    fn layout_table_container(&mut self, width: i64, height: Option<i64>) -> i64 {
+        let style = self.style.clone();
+        let grid = self.build_table_grid();
+        self.prepare_table_cells(&grid);
+        let (h_spacing, v_spacing) = self.table_spacing();
+        let content_x = self.border.left() + self.padding.left();
+        let content_y = self.border.top() + self.padding.top();
+
+        let columns = if style.table_layout() == TableLayout::Fixed && !style.width().is_auto() {
+            fixed_table_columns(&grid, width, h_spacing)
+        } else {
+            auto_table_columns(&grid, width, h_spacing)
+        };
+        // 列の左端の、コンテンツ領域の左端からの位置
+        let mut xs = Vec::new();
+        let mut x = h_spacing;
+        for column in &columns {
+            xs.push(x);
+            x += column + h_spacing;
+        }
+        let grid_width = if columns.is_empty() { 0 } else { x };
+        let span_width = |cell: &TableGridCell| {
+            xs[cell.column + cell.columns - 1] + columns[cell.column + cell.columns - 1]
+                - xs[cell.column]
+        };
+
+        // https://www.w3.org/TR/CSS2/tables.html#caption-position
+        // キャプションは表の幅でレイアウトし、caption-sideに従って行の上か下に置く
+        let mut grid_top = 0;
+        let mut bottom_captions_height = 0;
+        for caption in &grid.captions {
+            let mut caption = caption.borrow_mut();
+            caption.layout_block(width, None, true);
+            let outer_height = caption.size.height() + caption.margin.vertical();
+            if caption.style.caption_side() == CaptionSide::Top {
+                caption.point = LayoutPoint::new(
+                    content_x + caption.margin.left(),
+                    content_y + grid_top + caption.margin.top(),
+                );
+                grid_top += outer_height;
+            } else {
+                bottom_captions_height += outer_height;
+            }
+        }
+
+        // https://www.w3.org/TR/CSS2/tables.html#height-layout
+        // 列の幅でセルをレイアウトし、行の高さはセルの高さとベースラインから決める
+        let mut heights: Vec<i64> = grid
+            .rows
+            .iter()
+            .map(|row| {
+                row.object
+                    .as_ref()
+                    .map_or(0, |object| match object.borrow().style.height() {
+                        ComputedLength::Px(height) => round_px(height).max(0),
+                        _ => 0,
+                    })
+            })
+            .collect();
+        let mut extents = Vec::new();
+        for cell in &grid.cells {
+            let cell_width = span_width(cell);
+            let mut object = cell.object.borrow_mut();
+            object.override_width = Some(cell_width);
+            object.layout_block(cell_width, None, true);
+            let height = object.size.height();
+            // 行を持たないセルのベースラインは、コンテンツ領域の下端にする
+            let baseline = object
+                .first_baseline()
+                .unwrap_or(height - object.border.bottom() - object.padding.bottom());
+            // https://www.w3.org/TR/CSS2/tables.html#height-layout
+            // vertical-alignはセルの領域の中で内容を揃えるので、指定された高さを除いた内容の高さも測る
+            let content_height = if object.style.height().is_auto() {
+                height
+            } else {
+                let style = object.style.clone();
+                object.style.set_height(ComputedLength::Auto);
+                object.layout_block(cell_width, None, true);
+                object.style = style;
+                object.size.height()
+            };
+            object.override_width = None;
+            extents.push((height, baseline, content_height));
+        }
+        let mut baselines = vec![0; grid.rows.len()];
+        for (cell, &(_, baseline, _)) in grid.cells.iter().zip(&extents) {
+            if cell.is_baseline_aligned() {
+                baselines[cell.row] = baselines[cell.row].max(baseline);
+            }
+        }
+        let required_height = |cell: &TableGridCell, (height, baseline, _): (i64, i64, i64)| {
+            if cell.is_baseline_aligned() {
+                height + baselines[cell.row] - baseline
+            } else {
+                height
+            }
+        };
+        for (cell, &extent) in grid.cells.iter().zip(&extents) {
+            if cell.rows == 1 {
+                heights[cell.row] = heights[cell.row].max(required_height(cell, extent));
+            }
+        }
+        // 複数の行にまたがるセルが行の高さの合計より高い場合は、超えた分を行に配る
+        for (cell, &extent) in grid.cells.iter().zip(&extents) {
+            let rows = cell.row..cell.row + cell.rows;
+            if cell.rows > 1 {
+                let total =
+                    heights[rows.clone()].iter().sum::<i64>() + v_spacing * (cell.rows as i64 - 1);
+                let weights = heights[rows.clone()].to_vec();
+                distribute_excess(
+                    &mut heights[rows],
+                    &weights,
+                    required_height(cell, extent) - total,
+                );
+            }
+        }
+
+        // 高さが指定されている場合は、余る高さを行に配る
+        let rows_height = heights.iter().sum::<i64>()
+            + if heights.is_empty() {
+                0
+            } else {
+                v_spacing * (heights.len() as i64 + 1)
+            };
+        if let Some(height) = height {
+            let weights = heights.clone();
+            distribute_excess(
+                &mut heights,
+                &weights,
+                height - grid_top - rows_height - bottom_captions_height,
+            );
+        }
+
+        // 行の上端の、コンテンツ領域の上端からの位置
+        let mut ys = Vec::new();
+        let mut y = grid_top + v_spacing;
+        for height in &heights {
+            ys.push(y);
+            y += height + v_spacing;
+        }
+        if heights.is_empty() {
+            y = grid_top;
+        }
+        let row_width = (grid_width - 2 * h_spacing).max(0);
+        let row_rect = |rows: Range<usize>| {
+            if rows.is_empty() {
+                return LayoutRect::new(
+                    LayoutPoint::new(content_x + h_spacing, content_y + grid_top),
+                    LayoutSize::new(row_width, 0),
+                );
+            }
+            let top = ys[rows.start];
+            let bottom = ys[rows.end - 1] + heights[rows.end - 1];
+            LayoutRect::new(
+                LayoutPoint::new(content_x + h_spacing, content_y + top),
+                LayoutSize::new(row_width, bottom - top),
+            )
+        };
+
+        // 行グループと行は、行の領域を覆う大きさにする. 位置は表のボーダーボックスからの位置から、親からの相対位置にする
+        let group_rects: Vec<LayoutRect> = grid
+            .groups
+            .iter()
+            .map(|(group, rows)| {
+                let rect = row_rect(rows.clone());
+                let mut group = group.borrow_mut();
+                group.point = rect.point();
+                group.size = rect.size();
+                rect
+            })
+            .collect();
+        let parent_origin = |row: &TableGridRow| {
+            row.group
+                .map_or(LayoutPoint::new(0, 0), |group| group_rects[group].point())
+        };
+        for (r, row) in grid.rows.iter().enumerate() {
+            if let Some(object) = &row.object {
+                let rect = row_rect(r..r + 1);
+                let origin = parent_origin(row);
+                let mut object = object.borrow_mut();
+                object.point =
+                    LayoutPoint::new(rect.point().x() - origin.x(), rect.point().y() - origin.y());
+                object.size = rect.size();
+            }
+        }
+
+        // セルを行の領域の高さでレイアウトし直し、vertical-alignに従って内容を縦に揃える
+        for (cell, &(_, baseline, content_height)) in grid.cells.iter().zip(&extents) {
+            let cell_width = span_width(cell);
+            let last = cell.row + cell.rows - 1;
+            let area_height = ys[last] + heights[last] - ys[cell.row];
+            let row = &grid.rows[cell.row];
+            let origin = match &row.object {
+                Some(_) => row_rect(cell.row..cell.row + 1).point(),
+                None => parent_origin(row),
+            };
+            let mut object = cell.object.borrow_mut();
+            object.override_width = Some(cell_width);
+            object.override_height = Some(area_height);
+            object.layout_block(cell_width, Some(area_height), true);
+            object.override_width = None;
+            object.override_height = None;
+            let shift = match object.style.vertical_align() {
+                VerticalAlign::Top => 0,
+                VerticalAlign::Middle => (area_height - content_height) / 2,
+                VerticalAlign::Bottom => area_height - content_height,
+                _ => baselines[cell.row] - baseline,
+            };
+            object.shift_content(shift.max(0));
+            object.point = LayoutPoint::new(
+                content_x + xs[cell.column] - origin.x(),
+                content_y + ys[cell.row] - origin.y(),
+            );
+        }
+
+        // 列グループと列は大きさを持たない. 列グループの中の列は、列グループと同じ位置に置く
+        for column in &grid.columns {
+            let mut column = column.borrow_mut();
+            column.point = LayoutPoint::new(content_x, content_y + grid_top);
+            column.size = LayoutSize::new(0, 0);
+            let mut child = column.first_child();
+            while let Some(c) = child {
+                c.borrow_mut().point = LayoutPoint::new(0, 0);
+                c.borrow_mut().size = LayoutSize::new(0, 0);
+                child = c.borrow().next_sibling();
+            }
+        }
+        for caption in &grid.captions {
+            let mut caption = caption.borrow_mut();
+            if caption.style.caption_side() == CaptionSide::Bottom {
+                caption.point = LayoutPoint::new(
+                    content_x + caption.margin.left(),
+                    content_y + y + caption.margin.top(),
+                );
+                y += caption.size.height() + caption.margin.vertical();
+            }
+        }
+
+        height.map_or(y, |height| height.max(y))
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#table-layout
+    /// 子を行グループ・行・セルに分け、セルを行と列のスロットに置く
+    /// 最初のヘッダーの行グループは先頭に、最初のフッターの行グループは末尾に置く
+    fn build_table_grid(&self) -> TableGrid {
+        let mut grid = TableGrid::default();
+        let mut header = None;
+        let mut footer = None;
+        let mut groups: Vec<TableGroupContent> = Vec::new();
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            child = c.borrow().next_sibling();
+            if c.borrow().is_absolutely_positioned() {
+                self.set_static_position_at_content_origin(&c);
+                continue;
+            }
+            let display = c.borrow().style.display();
+            match display {
+                DisplayType::TableCaption => grid.captions.push(c),
+                DisplayType::TableColumnGroup | DisplayType::TableColumn => {
+                    collect_table_columns(&mut grid, &c, None);
+                    grid.columns.push(c);
+                }
+                DisplayType::TableHeaderGroup if header.is_none() => {
+                    header = Some((Some(c.clone()), table_rows(&c)))
+                }
+                DisplayType::TableFooterGroup if footer.is_none() => {
+                    footer = Some((Some(c.clone()), table_rows(&c)))
+                }
+                _ if display.is_table_row_group() => groups.push((Some(c.clone()), table_rows(&c))),
+                _ => {
+                    // 行グループに入っていない行とセルは、匿名の行グループにまとめる
+                    if !matches!(groups.last(), Some((None, _))) {
+                        groups.push((None, Vec::new()));
+                    }
+                    if let Some((_, rows)) = groups.last_mut() {
+                        push_table_row_content(rows, c);
+                    }
+                }
+            }
+        }
+
+        for (group, rows) in header.into_iter().chain(groups).chain(footer) {
+            let first = grid.rows.len();
+            let count = rows.len();
+            let group_index = group.as_ref().map(|_| grid.groups.len());
+            grid.slots.resize(first + count, Vec::new());
+            for (i, (row, cells)) in rows.into_iter().enumerate() {
+                let r = first + i;
+                grid.rows.push(TableGridRow {
+                    object: row,
+                    group: group_index,
+                });
+                let mut column = 0;
+                for cell in cells {
+                    while matches!(grid.slots[r].get(column), Some(Some(_))) {
+                        column += 1;
+                    }
+                    // https://html.spec.whatwg.org/multipage/tables.html#attr-tdth-rowspan
+                    // rowspanが0のセルと行グループの外まで伸びるセルは、行グループの最後の行までにする
+                    let remaining = first + count - r;
+                    let columns = table_span(&cell, "colspan")
+                        .filter(|&span| span > 0)
+                        .map_or(1, |span| span.min(1000));
+                    let rows = match table_span(&cell, "rowspan") {
+                        Some(0) => remaining,
+                        Some(span) => span.min(remaining),
+                        None => 1,
+                    };
+                    let index = grid.cells.len();
+                    for slots in &mut grid.slots[r..r + rows] {
+                        if slots.len() < column + columns {
+                            slots.resize(column + columns, None);
+                        }
+                        for slot in &mut slots[column..column + columns] {
+                            *slot = Some(index);
+                        }
+                    }
+                    grid.cells.push(TableGridCell {
+                        object: cell,
+                        row: r,
+                        column,
+                        rows,
+                        columns,
+                    });
+                    column += columns;
+                }
+            }
+            if let Some(group) = group {
+                grid.groups.push((group, first..first + count));
+            }
+        }
+
+        grid.column_count = grid
+            .slots
+            .iter()
+            .map(Vec::len)
+            .fold(grid.column_widths.len(), usize::max);
+        let column_count = grid.column_count;
+        for slots in &mut grid.slots {
+            slots.resize(column_count, None);
+        }
+        grid
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#collapsing-borders
+    /// ボーダーを重ねる場合は、セルのボーダーを隣のセルや表と共有するボーダーにする
+    fn prepare_table_cells(&self, grid: &TableGrid) {
+        let borders = if self.style.border_collapse() == BorderCollapse::Collapse {
+            Some(self.collapsed_borders(grid).1)
+        } else {
+            None
+        };
+        for (i, cell) in grid.cells.iter().enumerate() {
+            cell.object.borrow_mut().collapsed_border = borders.as_ref().map(|borders| borders[i]);
+        }
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#collapsing-borders
+    /// 表とセルが使うボーダーの幅を返す. 境界のボーダーはその両側で半分ずつ分け合い、
+    /// 表は最初の行の左右と、最初と最後の行の上下の境界のうち、外側の半分の最大を使う
+    fn collapsed_borders(&self, grid: &TableGrid) -> (LayoutEdges, Vec<LayoutEdges>) {
+        let style = |index: usize| grid.cells[index].object.borrow().style();
+        let row_count = grid.rows.len();
+        let mut table = LayoutEdges::default();
+        let mut cells = Vec::new();
+        for cell in &grid.cells {
+            let own = cell.object.borrow().style();
+            let mut top = vec![(own.border_top_style(), own.border_top_width())];
+            let mut right = vec![(own.border_right_style(), own.border_right_width())];
+            let mut bottom = vec![(own.border_bottom_style(), own.border_bottom_width())];
+            let mut left = vec![(own.border_left_style(), own.border_left_width())];
+            let columns = cell.column..cell.column + cell.columns;
+            let rows = cell.row..cell.row + cell.rows;
+            if cell.row == 0 {
+                top.push((self.style.border_top_style(), self.style.border_top_width()));
+            } else {
+                for index in columns.clone().filter_map(|c| grid.slots[cell.row - 1][c]) {
+                    let other = style(index);
+                    top.push((other.border_bottom_style(), other.border_bottom_width()));
+                }
+            }
+            if rows.end == row_count {
+                bottom.push((
+                    self.style.border_bottom_style(),
+                    self.style.border_bottom_width(),
+                ));
+            } else {
+                for index in columns.filter_map(|c| grid.slots[rows.end][c]) {
+                    let other = style(index);
+                    bottom.push((other.border_top_style(), other.border_top_width()));
+                }
+            }
+            if cell.column == 0 {
+                left.push((
+                    self.style.border_left_style(),
+                    self.style.border_left_width(),
+                ));
+            } else {
+                for index in rows.clone().filter_map(|r| grid.slots[r][cell.column - 1]) {
+                    let other = style(index);
+                    left.push((other.border_right_style(), other.border_right_width()));
+                }
+            }
+            let last_column = cell.column + cell.columns;
+            if last_column == grid.column_count {
+                right.push((
+                    self.style.border_right_style(),
+                    self.style.border_right_width(),
+                ));
+            } else {
+                for index in rows.filter_map(|r| grid.slots[r][last_column]) {
+                    let other = style(index);
+                    right.push((other.border_left_style(), other.border_left_width()));
+                }
+            }
+
+            let (top, right, bottom, left) = (
+                resolve_border_conflict(&top),
+                resolve_border_conflict(&right),
+                resolve_border_conflict(&bottom),
+                resolve_border_conflict(&left),
+            );
+            if cell.row == 0 {
+                table.set_top(table.top().max(top / 2));
+                if cell.column == 0 {
+                    table.set_left(left / 2);
+                }
+                if last_column == grid.column_count {
+                    table.set_right(right - right / 2);
+                }
+            }
+            if cell.row + cell.rows == row_count {
+                table.set_bottom(table.bottom().max(bottom - bottom / 2));
+            }
+            cells.push(LayoutEdges::new(
+                top - top / 2,
+                right / 2,
+                bottom / 2,
+                left - left / 2,
+            ));
+        }
+        (table, cells)
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#separated-borders
+    /// 列と列、行と行の間隔. ボーダーを重ねる場合は間隔を空けない
+    fn table_spacing(&self) -> (i64, i64) {
+        if self.style.border_collapse() == BorderCollapse::Collapse {
+            return (0, 0);
+        }
+        let (horizontal, vertical) = self.style.border_spacing();
+        (round_px(horizontal).max(0), round_px(vertical).max(0))
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#auto-table-layout
+    /// 表のコンテンツ領域の最小幅と最大幅. 列の最小幅と最大幅の合計に間隔を加え、キャプションの最小幅より狭くしない
+    fn table_content_widths(&self) -> (i64, i64) {
+        let grid = self.build_table_grid();
+        self.prepare_table_cells(&grid);
+        let (h_spacing, _) = self.table_spacing();
+        let extents = table_column_extents(&grid, h_spacing);
+        let spacing = if extents.is_empty() {
+            0
+        } else {
+            h_spacing * (extents.len() as i64 + 1)
+        };
+        let mut min = extents.iter().map(|&(min, _)| min).sum::<i64>() + spacing;
+        let max = extents.iter().map(|&(_, max)| max).sum::<i64>() + spacing;
+        for caption in &grid.captions {
+            let (caption_min, _) = caption.borrow_mut().intrinsic_widths();
+            min = min.max(caption_min + caption.borrow().horizontal_margins(0));
+        }
+        (min, max.max(min))
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#width-layout
+    /// 通常フローに置かれた表のコンテンツ領域の幅を求める. 幅がautoの場合は最大幅を包含ブロックに収まる範囲で使う
+    /// 自動レイアウトの表は、指定された幅でも最小幅より狭くしない
+    fn table_width(&self, containing_width: i64, specified: Option<i64>) -> i64 {
+        if let (Some(width), TableLayout::Fixed) = (specified, self.style.table_layout()) {
+            return width;
+        }
+        let (min, max) = self.table_content_widths();
+        match specified {
+            Some(width) => width.max(min),
+            None => {
+                let available = containing_width
+                    - self.horizontal_margins(containing_width)
+                    - self.border.horizontal()
+                    - self.padding.horizontal();
+                max.min(available).max(min)
+            }
+        }
+    }
+
+    /// https://www.w3.org/TR/CSS2/tables.html#height-layout
+    /// セルの内容を`dy`だけ下にずらす. 行ボックスと子の位置は、セルのボーダーボックスの左上からの相対位置のまま
+    fn shift_content(&mut self, dy: i64) {
+        if dy == 0 {
+            return;
+        }
+        let delta = LayoutPoint::new(0, dy);
+        for line in &mut self.line_boxes {
+            line.set_point(translate(line.point(), delta));
+        }
+        self.shift_children(delta);
+    }
+
+    /// 子の位置と断片の位置に`delta`を加える. インラインボックスの子も行ボックスを持つブロックからの相対位置なので、同じだけずらす
+    fn shift_children(&self, delta: LayoutPoint) {
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            {
+                let mut c = c.borrow_mut();
+                c.point = translate(c.point, delta);
+                for fragment in &mut c.fragments {
+                    fragment.set_point(translate(fragment.point(), delta));
+                }
+                if c.kind == LayoutObjectKind::Inline {
+                    c.shift_children(delta);
+                }
+            }
+            child = c.borrow().next_sibling();
+        }
+    }
+
+    /// https://www.w3.org/TR/CSS2/visudet.html#inlineblock-width
+    /// インラインレベルのフレックスコンテナのような、分割できないインラインレベルのボックスをレイアウトする
+    /// フロートも同じようにレイアウトする
+    /// 幅がautoの場合は、内容に合わせた幅（shrink-to-fit）になる. 左右のマージンは包含ブロックの幅を埋めない
+    fn layout_atomic_inline(&mut self, containing_width: i64) {
+        let width = self.style.width().resolve(containing_width as f64);
+        self.override_width = Some(match width {
+            Some(width) => {
+                self.resolve_border_and_padding(containing_width);
+                round_px(width).max(0) + self.border.horizontal() + self.padding.horizontal()
+            }
+            None => {
+                let (min, max) = self.intrinsic_widths();
+                let available = containing_width - self.horizontal_margins(containing_width);
+                max.min(available.max(min))
+            }
+        });
+        self.layout_block(containing_width, None, true);
+        self.override_width = None;
+    }
+
+    /// https://www.w3.org/TR/css-sizing-3/#intrinsic-sizes
+    /// ボーダーボックスの最小コンテンツ幅と最大コンテンツ幅を返す. 幅が長さで指定されている場合は、その幅を使う
+    /// パーセンテージは、包含ブロックの幅が決まっていないものとして0に解決する
+    fn intrinsic_widths(&mut self) -> (i64, i64) {
+        self.resolve_border_and_padding(0);
+        let edges = self.border.horizontal() + self.padding.horizontal();
+        if let ComputedLength::Px(width) = self.style.width() {
+            let width = round_px(width).max(0) + edges;
+            return (width, width);
+        }
+
+        let style = self.style.clone();
+        let mut min = 0;
+        let mut max = 0;
+        if style.display().is_flex_container() {
+            // https://www.w3.org/TR/css-flexbox-1/#intrinsic-main-sizes
+            // アイテムを伸縮させずに並べた大きさとして求める
+            let mut count = 0;
+            let mut child = self.first_child();
+            while let Some(c) = child {
+                if c.borrow().is_absolutely_positioned() {
+                    child = c.borrow().next_sibling();
+                    continue;
+                }
+                let (child_min, child_max) = c.borrow_mut().intrinsic_widths();
+                let margins = c.borrow().horizontal_margins(0);
+                if style.flex_direction().is_column() {
+                    min = min.max(child_min + margins);
+                    max = max.max(child_max + margins);
+                } else {
+                    min = if style.flex_wrap() == FlexWrap::NoWrap {
+                        min + child_min + margins
+                    } else {
+                        min.max(child_min + margins)
+                    };
+                    max += child_max + margins;
+                }
+                count += 1;
+                child = c.borrow().next_sibling();
+            }
+            if !style.flex_direction().is_column() && count > 1 {
+                let gaps = resolve_px(&style.column_gap(), 0.0) * (count - 1);
+                max += gaps;
+                if style.flex_wrap() == FlexWrap::NoWrap {
+                    min += gaps;
+                }
+            }
+        } else if style.display().is_grid_container() {
+            // https://www.w3.org/TR/css-grid-2/#intrinsic-sizes
+            // 最小コンテンツと最大コンテンツの制約の下で決めた、列の大きさの合計として求める
+            let grid = self.place_grid_items(None, None);
+            let gap = resolve_px(&style.column_gap(), 0.0);
+            let contributions = grid_column_contributions(&grid.items);
+            let extent = |space: GridAvailableSpace| {
+                let sizes = size_grid_tracks(&grid.columns, &contributions, space, gap, false);
+                round_px(sizes.iter().sum::<f64>()) + gap * (sizes.len() as i64 - 1).max(0)
+            };
+            min = extent(GridAvailableSpace::MinContent);
+            max = extent(GridAvailableSpace::MaxContent);
+        } else if style.display().is_table() {
+            (min, max) = self.table_content_widths();
+        } else {
+            let mut child = self.first_child();
+            while let Some(c) = child {
+                if !c.borrow().is_in_flow_block() {
+                    let (next, inline_min, inline_max) = inline_intrinsic_widths(&c);
+                    min = min.max(inline_min);
+                    max = max.max(inline_max);
+                    child = next;
+                    continue;
+                }
+                let (child_min, child_max) = c.borrow_mut().intrinsic_widths();
+                let margins = c.borrow().horizontal_margins(0);
+                min = min.max(child_min + margins);
+                max = max.max(child_max + margins);
+                child = c.borrow().next_sibling();
+            }
+        }
+
+        (min + edges, max + edges)
+    }
+
+    /// https://www.w3.org/TR/css-position-3/#absolute-pos
+    /// 絶対位置指定されたボックスか. 親のレイアウトでは、静的位置だけを決める
+    pub fn is_absolutely_positioned(&self) -> bool {
+        self.style.position().is_out_of_flow()
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#floats
+    pub fn is_float(&self) -> bool {
+        self.style.float() != Float::None
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#positioning-scheme
+    /// 絶対位置指定されたボックスかフロートで、通常フローから取り除かれるか
+    pub fn is_out_of_flow(&self) -> bool {
+        self.is_absolutely_positioned() || self.is_float()
+    }
+
+    /// https://www.w3.org/TR/CSS2/visuren.html#block-formatting
+    /// 新しいブロック整形コンテキストを作るか. 中のフロートは外に影響せず、外のフロートは中に影響しない
+    fn establishes_block_formatting_context(&self) -> bool {
+        let display = self.style.display();
+        display == DisplayType::FlowRoot
+            || display.is_flex_container()
+            || display.is_grid_container()
+            || display.is_table()
+            || self.style.overflow_x().is_scroll_container()
+            || self.style.overflow_y().is_scroll_container()
+            || self.kind == LayoutObjectKind::AtomicInline
+            || self.is_out_of_flow()
+    }
+
+    /// https://www.w3.org/TR/css-text-decor-3/#line-decoration
+    /// text-decorationは継承せず、装飾を指定したボックスから通常フローの子孫のテキストに伝える
+    /// フロート、絶対位置指定されたボックスと分割できないインラインレベルのボックスには、外側の装飾を伝えない
+    pub fn propagate_text_decoration(&mut self, outer: TextDecoration) {
+        let outer = if self.is_out_of_flow() || self.kind == LayoutObjectKind::AtomicInline {
+            TextDecoration::None
+        } else {
+            outer
+        };
+        self.text_decoration = match self.style.text_decoration() {
+            TextDecoration::None => outer,
+            decoration => decoration,
+        };
+
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            c.borrow_mut()
+                .propagate_text_decoration(self.text_decoration);
+            child = c.borrow().next_sibling();
+        }
+    }
+
+    /// https://www.w3.org/TR/css-lists-3/#list-style-position-property
+    /// リスト項目の外側に置くマーカーか. 行には並べず、リスト項目のレイアウトの後で置く
+    pub fn is_outside_marker(&self) -> bool {
+        self.pseudo == Some(PseudoElement::Marker)
+            && self.style.list_style_position() == ListStylePosition::Outside
+    }
+
+    /// https://www.w3.org/TR/css-lists-3/#list-style-position-property
+    /// 外側のマーカーを、右端がコンテンツ領域の左端に接し、ベースラインが最初の行のベースラインに揃うように置く
+    /// 行を持たない場合は、コンテンツ領域の上端に揃える
+    fn place_outside_marker(&self) {
+        let marker = match self.first_child() {
+            Some(marker) if marker.borrow().is_outside_marker() => marker,
+            _ => return,
+        };
+        let content_x = self.border.left() + self.padding.left();
+        let baseline = self.first_baseline().unwrap_or_else(|| {
+            self.border.top()
+                + self.padding.top()
+                + VerticalMetrics::new(&marker.borrow().style).above
+        });
+
+        let mut left = content_x;
+        let text_obj = marker.borrow().first_child();
+        if let Some(text_obj) = text_obj {
+            let text = match text_obj.borrow().node_kind() {
+                NodeKind::Text(text) => text,
+                _ => String::new(),
+            };
+            let width = text_width(&text_obj, &text);
+            left -= width;
+            let metrics = VerticalMetrics::new(&text_obj.borrow().style);
+            let mut text_obj = text_obj.borrow_mut();
+            text_obj.fragments = vec![LineFragment::new(
+                LayoutPoint::new(left, baseline - metrics.above),
+                LayoutSize::new(width, metrics.above + metrics.below),
+                text,
+            )];
+            text_obj.update_fragments_rect();
+        }
+
+        let fragment = box_fragment(&marker, left, content_x, baseline);
+        let mut marker = marker.borrow_mut();
+        marker.fragments = vec![fragment];
+        marker.update_fragments_rect();
+    }
+
+    /// 通常フローに置かれるブロックレベルのボックスか
+    pub fn is_in_flow_block(&self) -> bool {
+        self.kind == LayoutObjectKind::Block && !self.is_out_of_flow()
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#abspos-items
+    /// フレックスコンテナとグリッドコンテナの絶対位置指定された子の静的位置は、コンテンツ領域の左上にする
+    fn set_static_position_at_content_origin(&self, child: &Rc<RefCell<LayoutObject>>) {
+        child.borrow_mut().point = LayoutPoint::new(
+            self.border.left() + self.padding.left(),
+            self.border.top() + self.padding.top(),
+        );
+    }
+
+    /// 左右のマージンの合計. autoは0として扱う
+    fn horizontal_margins(&self, containing_width: i64) -> i64 {
+        let base = containing_width as f64;
+        resolve_px(&self.style.margin_left(), base) + resolve_px(&self.style.margin_right(), base)
+    }
+
+    /// https://www.w3.org/TR/CSS2/visudet.html#leading
+    /// 行に並べる分割できないボックスの、上マージンの端からベースラインまでの距離
+    /// インラインレベルのフレックスコンテナは最初の行のベースラインを、それ以外は下マージンの端をベースラインにする
+    fn atomic_baseline(&self) -> i64 {
+        let bottom = self.size.height() + self.margin.vertical();
+        if self.kind != LayoutObjectKind::AtomicInline {
+            return bottom;
+        }
+        self.first_baseline()
+            .map_or(bottom, |baseline| self.margin.top() + baseline)
+    }
+
+    /// https://www.w3.org/TR/css-align-3/#first-last-baselines
+    /// ボーダーボックスの上端から、最初の行のベースラインまでの距離. 行を持たない場合はNone
+    fn first_baseline(&self) -> Option<i64> {
+        if let Some(line) = self.line_boxes.first() {
+            return Some(line.point().y() + line.baseline());
+        }
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            let c = c.borrow();
+            if c.kind != LayoutObjectKind::Inline && !c.is_out_of_flow() {
+                if let Some(baseline) = c.first_baseline() {
+                    return Some(c.point.y() + baseline);
+                }
+            }
+            child = c.next_sibling();
+        }
+        None
+    }
+
+    /// https://www.w3.org/TR/CSS2/box.html#padding-properties
+    /// ボーダーとパディングの使用値を求める
+    /// パディングとマージンのパーセンテージは、上下も含めて包含ブロックの幅を基準にする
+    /// https://www.w3.org/TR/CSS2/tables.html#collapsing-borders
+    /// ボーダーを重ねる表はセルと共有するボーダーの外側の半分を使い、パディングを持たない
+    fn resolve_border_and_padding(&mut self, containing_width: i64) {
+        let base = containing_width as f64;
+        if self.style.display().is_table()
+            && self.style.border_collapse() == BorderCollapse::Collapse
+        {
+            self.border = self.collapsed_borders(&self.build_table_grid()).0;
+            self.padding = LayoutEdges::default();
+            return;
+        }
+        self.border = self.collapsed_border.unwrap_or_else(|| {
+            LayoutEdges::new(
+                round_px(self.style.border_top_width()),
+                round_px(self.style.border_right_width()),
+                round_px(self.style.border_bottom_width()),
+                round_px(self.style.border_left_width()),
+            )
+        });
+        self.padding = LayoutEdges::new(
+            resolve_px(&self.style.padding_top(), base),
+            resolve_px(&self.style.padding_right(), base),
+            resolve_px(&self.style.padding_bottom(), base),
+            resolve_px(&self.style.padding_left(), base),
+        );
+    }
+
+    /// https://www.w3.org/TR/CSS2/visudet.html#inline-width
+    /// インラインボックスのマージン・ボーダー・パディングを求め、行に並べるときの左と右の幅を返す
+    /// autoのマージンは0になる. 上下のマージンは行の高さに影響しない
+    fn resolve_inline_edges(&mut self, containing_width: i64) -> (i64, i64) {
+        let base = containing_width as f64;
+        self.resolve_border_and_padding(containing_width);
+        self.margin = LayoutEdges::new(
+            resolve_px(&self.style.margin_top(), base),
+            resolve_px(&self.style.margin_right(), base),
+            resolve_px(&self.style.margin_bottom(), base),
+            resolve_px(&self.style.margin_left(), base),
+        );
+        if !self.has_start_edge {
+            self.margin.set_left(0);
+            self.border.set_left(0);
+            self.padding.set_left(0);
+        }
+        if !self.has_end_edge {
+            self.margin.set_right(0);
+            self.border.set_right(0);
+            self.padding.set_right(0);
+        }
+        (
+            self.margin.left() + self.border.left() + self.padding.left(),
+            self.margin.right() + self.border.right() + self.padding.right(),
+        )
+    }
+
+    /// 行ごとの断片を囲む矩形を、インラインボックスやテキストの位置と大きさにする
+    fn update_fragments_rect(&mut self) {
+        let mut fragments = self.fragments.iter();
+        let first = match fragments.next() {
+            Some(first) => first,
+            None => return,
+        };
+        let mut left = first.point().x();
+        let mut top = first.point().y();
+        let mut right = left + first.size().width();
+        let mut bottom = top + first.size().height();
+        for fragment in fragments {
+            left = left.min(fragment.point().x());
+            top = top.min(fragment.point().y());
+            right = right.max(fragment.point().x() + fragment.size().width());
+            bottom = bottom.max(fragment.point().y() + fragment.size().height());
+        }
+        self.point = LayoutPoint::new(left, top);
+        self.size = LayoutSize::new(right - left, bottom - top);
+    }
+
+    /// レイアウトで求めた相対位置を、コンテンツ領域の左上を原点とする位置にする
+    /// ルートの位置を決めた後に、ルートに対して呼び出す
+    fn update_absolute_point(&mut self) {
+        let origin = self.point;
+        for line in &mut self.line_boxes {
+            line.set_point(translate(line.point(), origin));
+        }
+        self.translate_descendants(origin);
+    }
+
+    /// 子孫の位置に`origin`を加える
+    /// ブロックの子はそのブロックのボーダーボックスの左上からの相対位置、インラインボックスの子は
+    /// インラインボックスと同じく、行ボックスを持つブロックからの相対位置になっている
+    fn translate_descendants(&self, origin: LayoutPoint) {
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            {
+                let mut c = c.borrow_mut();
+                c.point = translate(c.point, origin);
+                for fragment in &mut c.fragments {
+                    fragment.set_point(translate(fragment.point(), origin));
+                }
+                if c.kind == LayoutObjectKind::Inline {
+                    c.translate_descendants(origin);
+                } else {
+                    c.update_absolute_point();
+                }
+            }
+            child = c.borrow().next_sibling();
+        }
+    }
+
+    /// ボックスと子孫の位置、行ボックスと断片の位置に`delta`を加える
+    /// すべての位置がコンテンツ領域の左上を原点とする位置になった後に使う
+    fn translate_subtree(&mut self, delta: LayoutPoint) {
+        self.point = translate(self.point, delta);
+        for line in &mut self.line_boxes {
+            line.set_point(translate(line.point(), delta));
+        }
+        for fragment in &mut self.fragments {
+            fragment.set_point(translate(fragment.point(), delta));
+        }
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            c.borrow_mut().translate_subtree(delta);
+            child = c.borrow().next_sibling();
+        }
+    }
+
+    /// https://www.w3.org/TR/css-position-3/#relpos-insets
+    /// 相対位置指定されたボックスを、通常フローで置いた位置からinsetの分だけずらす
+    /// 左右（上下）の両方が指定されている場合は、右（下）を無視する
+    pub fn apply_relative_offset(&mut self, containing_block: LayoutSize) {
+        let width = containing_block.width() as f64;
+        let height = containing_block.height() as f64;
+        let dx = match (
+            self.style.left().resolve(width),
+            self.style.right().resolve(width),
+        ) {
+            (Some(left), _) => round_px(left),
+            (None, Some(right)) => -round_px(right),
+            (None, None) => 0,
+        };
+        let dy = match (
+            self.style.top().resolve(height),
+            self.style.bottom().resolve(height),
+        ) {
+            (Some(top), _) => round_px(top),
+            (None, Some(bottom)) => -round_px(bottom),
+            (None, None) => 0,
+        };
+        self.translate_subtree(LayoutPoint::new(dx, dy));
+    }
+
+    /// https://www.w3.org/TR/css-position-3/#stickypos-insets
+    /// 粘着位置指定されたボックスを、スクロールポートからinsetだけ内側の矩形に収まるようにずらす
+    /// ずらしたボックスのマージンボックスは、包含ブロック`containing_block`からははみ出さない
+    pub fn apply_sticky_offset(&mut self, containing_block: LayoutRect, scrollport: LayoutRect) {
+        let width = scrollport.size().width() as f64;
+        let height = scrollport.size().height() as f64;
+        let inset = |length: ComputedLength, base: f64| length.resolve(base).map(round_px);
+        let dx = sticky_offset(
+            (
+                inset(self.style.left(), width),
+                inset(self.style.right(), width),
+            ),
+            self.point.x()..self.point.x() + self.size.width(),
+            (self.margin.left(), self.margin.right()),
+            scrollport.point().x()..scrollport.right(),
+            containing_block.point().x()..containing_block.right(),
+        );
+        let dy = sticky_offset(
+            (
+                inset(self.style.top(), height),
+                inset(self.style.bottom(), height),
+            ),
+            self.point.y()..self.point.y() + self.size.height(),
+            (self.margin.top(), self.margin.bottom()),
+            scrollport.point().y()..scrollport.bottom(),
+            containing_block.point().y()..containing_block.bottom(),
+        );
+        self.translate_subtree(LayoutPoint::new(dx, dy));
+    }
+
+    /// https://www.w3.org/TR/CSS2/visudet.html#abs-non-replaced-width
+    /// https://www.w3.org/TR/CSS2/visudet.html#abs-non-replaced-height
+    /// 絶対位置指定されたボックスを、包含ブロック`containing_block`を基準にレイアウトする
+    /// insetがautoの辺は、親のレイアウトで決めた静的位置（マージンボックスの左上）に置く
+    pub fn layout_absolute(&mut self, containing_block: LayoutRect) {
+        let style = self.style.clone();
+        let static_point = self.point;
+        let width = containing_block.size().width();
+        let height = containing_block.size().height();
+        let resolve = |length: ComputedLength, base: i64| length.resolve(base as f64).map(round_px);
+
+        // 幅がautoの場合は、内容に合わせた幅（shrink-to-fit）になる
+        let (min, max) = if style.width().is_auto() {
+            self.intrinsic_widths()
+        } else {
+            (0, 0)
+        };
+        // 絶対位置指定されたボックスは、ブロック整形コンテキストを作る
+        self.resolve_border_and_padding(width);
+        let (left, border_width, margin_left, margin_right) = resolve_absolute_axis(
+            width,
+            (resolve(style.left(), width), resolve(style.right(), width)),
+            (
+                resolve(style.margin_left(), width),
+                resolve(style.margin_right(), width),
+            ),
+            resolve(style.width(), width)
+                .map(|w| w.max(0) + self.border.horizontal() + self.padding.horizontal()),
+            static_point.x() - containing_block.point().x(),
+            |available| max.min(available.max(min)),
+        );
+        self.override_width = Some(border_width);
+        self.layout_block(width, Some(height), true);
+
+        // 高さがautoの場合は、内容の高さになる. 上下のinsetから高さが決まる場合は、その高さでレイアウトし直す
+        // 上下のマージンのパーセンテージも、包含ブロックの幅を基準にする
+        let content_height = self.size.height();
+        let (top, border_height, margin_top, margin_bottom) = resolve_absolute_axis(
+            height,
+            (
+                resolve(style.top(), height),
+                resolve(style.bottom(), height),
+            ),
+            (
+                resolve(style.margin_top(), width),
+                resolve(style.margin_bottom(), width),
+            ),
+            style
+                .height()
+                .resolve(height as f64)
+                .map(|h| round_px(h).max(0) + self.border.vertical() + self.padding.vertical()),
+            static_point.y() - containing_block.point().y(),
+            |_| content_height,
+        );
+        if border_height != content_height {
+            self.override_height = Some(border_height);
+            self.layout_block(width, Some(height), true);
+        }
+        self.override_width = None;
+        self.override_height = None;
+
+        self.margin = LayoutEdges::new(margin_top, margin_right, margin_bottom, margin_left);
+        self.point = LayoutPoint::new(
+            containing_block.point().x() + left + margin_left,
+            containing_block.point().y() + top + margin_top,
+        );
+        self.update_absolute_point();
+    }
+
+    /// https://www.w3.org/TR/css-position-3/#def-cb
+    /// 絶対位置指定された子孫の包含ブロックになる、パディングボックスの矩形
+    /// インラインボックスの場合は、断片を囲む矩形のボーダーの内側になる
+    pub fn padding_box(&self) -> LayoutRect {
+        LayoutRect::new(
+            LayoutPoint::new(
+                self.point.x() + self.border.left(),
+                self.point.y() + self.border.top(),
+            ),
+            LayoutSize::new(
+                self.size.width() - self.border.horizontal(),
+                self.size.height() - self.border.vertical(),
+            ),
+        )
+    }
+
+    /// 子の通常フローの包含ブロックになる、コンテンツボックスの矩形
+    pub fn content_box(&self) -> LayoutRect {
+        let padding_box = self.padding_box();
+        LayoutRect::new(
+            LayoutPoint::new(
+                padding_box.point().x() + self.padding.left(),
+                padding_box.point().y() + self.padding.top(),
+            ),
+            LayoutSize::new(
+                padding_box.size().width() - self.padding.horizontal(),
+                padding_box.size().height() - self.padding.vertical(),
+            ),
+        )
+    }
+
+    pub fn kind(&self) -> LayoutObjectKind {
+        self.kind
+    }
+
+    pub fn node(&self) -> Rc<RefCell<Node>> {
+        self.node.clone()
+    }
+
+    pub fn node_kind(&self) -> NodeKind {
+        self.node.borrow().kind().clone()
+    }
+
+    pub fn set_first_child(&mut self, first_child: Option<Rc<RefCell<LayoutObject>>>) {
+        self.first_child = first_child;
+    }
+
+    pub fn first_child(&self) -> Option<Rc<RefCell<LayoutObject>>> {
+        self.first_child.as_ref().cloned()
+    }
+
+    pub fn set_next_sibling(&mut self, next_sibling: Option<Rc<RefCell<LayoutObject>>>) {
+        self.next_sibling = next_sibling;
+    }
+
+    pub fn next_sibling(&self) -> Option<Rc<RefCell<LayoutObject>>> {
+        self.next_sibling.as_ref().cloned()
+    }
+
+    pub fn set_parent(&mut self, parent: Weak<RefCell<Self>>) {
+        self.parent = parent;
+    }
+
+    pub fn parent(&self) -> Weak<RefCell<Self>> {
+        self.parent.clone()
+    }
+
+    pub fn style(&self) -> ComputedStyle {
+        self.style.clone()
+    }
+
+    /// 疑似要素のボックスの場合、その種類を返す
+    pub fn pseudo(&self) -> Option<PseudoElement> {
+        self.pseudo
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        self.anonymous
+    }
+
+    pub fn point(&self) -> LayoutPoint {
+        self.point
+    }
+
+    pub fn size(&self) -> LayoutSize {
+        self.size
+    }
+
+    /// マージンの使用値. 折り畳む前の値
+    pub fn margin(&self) -> LayoutEdges {
+        self.margin
+    }
+
+    pub fn border(&self) -> LayoutEdges {
+        self.border
+    }
+
+    pub fn padding(&self) -> LayoutEdges {
+        self.padding
+    }
+
+    /// インラインボックスとテキストの、行ごとの断片
+    pub fn fragments(&self) -> Vec<LineFragment> {
+        self.fragments.clone()
+    }
+
+    /// ブロックの中の行ボックス
+    pub fn line_boxes(&self) -> Vec<LineBox> {
+        self.line_boxes.clone()
+    }
+}
+
+/// DOMノードに対応するレイアウトオブジェクトを生成する
+/// スタイルのdisplayがnoneの場合や、ノードがない場合はNoneを返す
+pub fn create_layout_object(
+    node: &Option<Rc<RefCell<Node>>>,
+    parent_obj: &Option<Rc<RefCell<LayoutObject>>>,
+    engine: &StyleEngine,
+) -> Option<Rc<RefCell<LayoutObject>>> {
+    if let Some(n) = node {
+        let layout_object = Rc::new(RefCell::new(LayoutObject::new(n.clone(), parent_obj)));
+
+        // スタイルエンジンがノードに保持している計算値を使う
+        // 再計算が必要な印がついている場合だけ、カスケードを行って求める
+        let cached = n.borrow().computed_style();
+        let style = match cached {
+            Some(style) if !n.borrow().style_dirty() => style,
+            _ => engine.get_computed_style(n),
+        };
+        layout_object.borrow_mut().style = style;
+
+        // displayプロパティがnoneの場合、ノードを生成しない
+        if layout_object.borrow().style().display() == DisplayType::DisplayNone {
+            return None;
+        }
+
+        // displayプロパティの最終的な値を用いてノードの種類を決定
+        layout_object.borrow_mut().update_kind();
+        return Some(layout_object);
+    }
+    None
+}
+
+/// https://www.w3.org/TR/CSS2/box.html#collapsing-margins
+/// 隣接して折り畳まれるマージンの集まり. 正のマージンの最大値と負のマージンの最小値の和が、
+/// 折り畳んだ後のマージンになる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct MarginStrut {
+    positive: i64,
+    negative: i64,
+}
+
+impl MarginStrut {
+    fn new(margin: i64) -> Self {
+        Self {
+            positive: margin.max(0),
+            negative: margin.min(0),
+        }
+    }
+
+    fn merge(&mut self, other: MarginStrut) {
+        self.positive = self.positive.max(other.positive);
+        self.negative = self.negative.min(other.negative);
+    }
+
+    fn collapsed(&self) -> i64 {
+        self.positive + self.negative
+    }
+}
+
+/// ブロックをレイアウトした結果のうち、親のマージンとの折り畳みに使う情報
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BlockMargins {
+    /// 上マージンと、それに隣接する子孫の上マージン
+    top: MarginStrut,
+    /// 下マージンと、それに隣接する子孫の下マージン
+    bottom: MarginStrut,
+    /// 高さも内容もないため、上下のマージンが隣接しているか
+    self_collapsing: bool,
+}
+
+/// https://www.w3.org/TR/CSS2/visudet.html#blockwidth
+/// 通常フローのブロックの幅と左右のマージンを、包含ブロックの幅に収まるように決める
+/// `width`とマージンのNoneはautoを表し、`edges`は左右のボーダーとパディングの合計
+fn resolve_block_width(
+    containing_width: i64,
+    width: Option<i64>,
+    margin_left: Option<i64>,
+    margin_right: Option<i64>,
+    edges: i64,
+) -> (i64, i64, i64) {
+    let width = match width {
+        Some(width) => width.max(0),
+        None => {
+            // 幅がautoの場合、autoのマージンは0になり、残りがすべて幅になる
+            let margin_left = margin_left.unwrap_or(0);
+            let margin_right = margin_right.unwrap_or(0);
+            let width = containing_width - margin_left - margin_right - edges;
+            if width >= 0 {
+                return (width, margin_left, margin_right);
+            }
+            // 幅は負にならないので、はみ出した分は右マージンで調整する
+            return (0, margin_left, containing_width - margin_left - edges);
+        }
+    };
+
+    // 包含ブロックからはみ出す場合、autoのマージンは0として扱う
+    let remaining = containing_width - width - edges;
+    let (margin_left, margin_right) =
+        if remaining - margin_left.unwrap_or(0) - margin_right.unwrap_or(0) < 0 {
+            (margin_left.or(Some(0)), margin_right.or(Some(0)))
+        } else {
+            (margin_left, margin_right)
+        };
+
+    match (margin_left, margin_right) {
+        // 左右のマージンがautoの場合は、中央に寄せる
+        (None, None) => (width, remaining / 2, remaining - remaining / 2),
+        (None, Some(margin_right)) => (width, remaining - margin_right, margin_right),
+        // 過剰に制約されている場合は、右マージンを計算し直す（左から右に書く場合）
+        (Some(margin_left), _) => (width, margin_left, remaining - margin_left),
+    }
+}
+
+/// https://www.w3.org/TR/CSS2/visudet.html#abs-non-replaced-width
+/// 絶対位置指定されたボックスの1つの軸で、始まりのinset、ボーダーボックスの大きさ、始まりと終わりのマージンを、
+/// 包含ブロックの大きさ`available`に収まるように決める. inset・マージン・大きさのNoneはautoを表す
+/// 大きさがautoの場合は、insetとマージンを除いた残りの大きさを`fit`に渡し、内容から大きさを決める
+fn resolve_absolute_axis(
+    available: i64,
+    insets: (Option<i64>, Option<i64>),
+    margins: (Option<i64>, Option<i64>),
+    size: Option<i64>,
+    static_start: i64,
+    fit: impl FnOnce(i64) -> i64,
+) -> (i64, i64, i64, i64) {
+    // insetと大きさのいずれかがautoの場合、autoのマージンは0になる
+    let margin_start = margins.0.unwrap_or(0);
+    let margin_end = margins.1.unwrap_or(0);
+    let margin_sum = margin_start + margin_end;
+
+    match (insets, size) {
+        ((Some(start), Some(end)), Some(size)) => {
+            // 左右のマージンがautoの場合は、中央に寄せる. はみ出す場合は、始まりのマージンを0にする
+            // 過剰に制約されている場合は、終わりのinsetを無視する（左から右に書く場合）
+            let remaining = available - start - end - size;
+            let (margin_start, margin_end) = match margins {
+                (None, None) if remaining < 0 => (0, remaining),
+                (None, None) => (remaining / 2, remaining - remaining / 2),
+                (None, Some(margin_end)) => (remaining - margin_end, margin_end),
+                (Some(margin_start), None) => (margin_start, remaining - margin_start),
+                (Some(margin_start), Some(margin_end)) => (margin_start, margin_end),
+            };
+            (start, size, margin_start, margin_end)
+        }
+        // 両方のinsetがautoの場合は、静的位置に置く
+        ((None, None), None) => {
+            let size = fit(available - static_start - margin_sum);
+            (static_start, size, margin_start, margin_end)
+        }
+        ((None, None), Some(size)) => (static_start, size, margin_start, margin_end),
+        // 始まりのinsetがautoの場合は、終わりのinsetから位置を決める
+        ((None, Some(end)), size) => {
+            let size = size.unwrap_or_else(|| fit(available - end - margin_sum));
+            let start = available - end - margin_sum - size;
+            (start, size, margin_start, margin_end)
+        }
+        ((Some(start), None), size) => {
+            let size = size.unwrap_or_else(|| fit(available - start - margin_sum));
+            (start, size, margin_start, margin_end)
+        }
+        // 両方のinsetが指定されている場合は、残りがすべて大きさになる
+        ((Some(start), Some(end)), None) => {
+            let size = (available - start - end - margin_sum).max(0);
+            (start, size, margin_start, margin_end)
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-position-3/#stickypos-insets
+/// 1つの軸で、粘着位置指定されたボックスをずらす大きさを求める
+/// `border`はボーダーボックスの範囲、`margins`は始まりと終わりのマージン、`view`はスクロールポートの範囲
+/// 両方のinsetが指定されていて収まらない場合は、始まりのinsetを優先する
+fn sticky_offset(
+    insets: (Option<i64>, Option<i64>),
+    border: Range<i64>,
+    margins: (i64, i64),
+    view: Range<i64>,
+    containing: Range<i64>,
+) -> i64 {
+    let mut offset = 0;
+    if let Some(end) = insets.1 {
+        // 終わりの辺がスクロールポートの終わりからinsetより外に出る場合は、始まりの方向にずらす
+        let limit = (containing.start - (border.start - margins.0)).min(0);
+        offset = (view.end - end - border.end).min(0).max(limit);
+    }
+    if let Some(start) = insets.0 {
+        // 始まりの辺がスクロールポートの始まりからinsetより内側に入らない場合は、終わりの方向にずらす
+        let limit = (containing.end - (border.end + margins.1)).max(0);
+        offset = offset.max((view.start + start - border.start).min(limit));
+    }
+    offset
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-items
+/// フレックスレイアウトの途中の、アイテムごとの情報. 大きさはすべてボーダーボックスの大きさ
+/// マージンは物理的な左（上）と右（下）の順に持ち、Noneはautoを表す
+#[derive(Debug, Clone)]
+struct FlexItem {
+    object: Rc<RefCell<LayoutObject>>,
+    main_margins: (Option<i64>, Option<i64>),
+    cross_margins: (Option<i64>, Option<i64>),
+    /// 主軸方向と交差軸方向の、ボーダーとパディングの合計
+    main_edges: i64,
+    cross_edges: i64,
+    /// https://www.w3.org/TR/css-flexbox-1/#flex-base-size
+    flex_base_size: i64,
+    hypothetical_main_size: i64,
+    /// https://www.w3.org/TR/css-flexbox-1/#min-size-auto
+    /// 内容に基づく最小の大きさ. アイテムはこれより小さく縮まない
+    min_main_size: i64,
+    grow: f64,
+    shrink: f64,
+    /// align-selfの使用値. autoはコンテナのalign-itemsになる
+    alignment: ItemAlignment,
+    /// 交差軸方向の大きさのプロパティがautoか
+    cross_size_is_auto: bool,
+    main_size: i64,
+    cross_size: i64,
+    /// コンテナのコンテンツ領域の左上からの、ボーダーボックスの位置
+    main_position: i64,
+    cross_position: i64,
+    /// ボーダーボックスの上端から最初の行のベースラインまでの距離
+    baseline: Option<i64>,
+}
+
+impl FlexItem {
+    /// https://www.w3.org/TR/css-flexbox-1/#algo-main-item
+    /// アイテムのマージンと、フレックスベースサイズと仮の主軸方向の大きさを求める
+    fn new(
+        object: Rc<RefCell<LayoutObject>>,
+        is_row: bool,
+        container_width: i64,
+        container_height: Option<i64>,
+        align_items: ItemAlignment,
+    ) -> Self {
+        let style = object.borrow().style();
+        object
+            .borrow_mut()
+            .resolve_border_and_padding(container_width);
+        let (horizontal_edges, vertical_edges) = {
+            let object = object.borrow();
+            (
+                object.border.horizontal() + object.padding.horizontal(),
+                object.border.vertical() + object.padding.vertical(),
+            )
+        };
+        // マージンのパーセンテージは、上下も含めてコンテナの幅を基準にする
+        let base = container_width as f64;
+        let margin = |length: ComputedLength| length.resolve(base).map(round_px);
+        let horizontal_margins = (margin(style.margin_left()), margin(style.margin_right()));
+        let vertical_margins = (margin(style.margin_top()), margin(style.margin_bottom()));
+        let width =
+            definite_size(&style.width(), Some(container_width)).map(|w| w + horizontal_edges);
+        let height = definite_size(&style.height(), container_height).map(|h| h + vertical_edges);
+
+        let mut item = if is_row {
+            Self::with_axes(
+                &object,
+                horizontal_margins,
+                vertical_margins,
+                horizontal_edges,
+                vertical_edges,
+                height.is_none(),
+            )
+        } else {
+            Self::with_axes(
+                &object,
+                vertical_margins,
+                horizontal_margins,
+                vertical_edges,
+                horizontal_edges,
+                width.is_none(),
+            )
+        };
+        item.alignment = style.align_self().unwrap_or(align_items);
+        item.grow = style.flex_grow();
+        item.shrink = style.flex_shrink();
+
+        // 内容から決まる主軸方向の大きさ（最大）と、最小の大きさ
+        let (content_size, content_min) = if is_row {
+            let (min, max) = object.borrow_mut().intrinsic_widths();
+            (max, min)
+        } else {
+            // 縦に並べる場合は、幅を決めてレイアウトした高さが内容の大きさになる
+            let available = container_width - item.cross_margin_sum();
+            item.cross_size = match width {
+                Some(width) => width,
+                None if item.is_stretched() => available.max(horizontal_edges),
+                None => {
+                    let (min, max) = object.borrow_mut().intrinsic_widths();
+                    max.min(available.max(min))
+                }
+            };
+            let mut object = object.borrow_mut();
+            object.override_width = Some(item.cross_size);
+            object.layout_block(container_width, container_height, true);
+            object.override_width = None;
+            (object.size.height(), object.size.height())
+        };
+
+        let main_property = if is_row { width } else { height };
+        let basis = match style.flex_basis() {
+            FlexBasis::Content => None,
+            FlexBasis::Size(ComputedLength::Auto) => main_property,
+            FlexBasis::Size(length) => {
+                let container_main = if is_row {
+                    Some(container_width)
+                } else {
+                    container_height
+                };
+                definite_size(&length, container_main).map(|b| b + item.main_edges)
+            }
+        };
+        item.flex_base_size = basis.unwrap_or(content_size).max(item.main_edges);
+        item.min_main_size = main_property
+            .map_or(content_min, |size| size.min(content_min))
+            .max(item.main_edges);
+        item.hypothetical_main_size = item.flex_base_size.max(item.min_main_size);
+        item
+    }
+
+    fn with_axes(
+        object: &Rc<RefCell<LayoutObject>>,
+        main_margins: (Option<i64>, Option<i64>),
+        cross_margins: (Option<i64>, Option<i64>),
+        main_edges: i64,
+        cross_edges: i64,
+        cross_size_is_auto: bool,
+    ) -> Self {
+        Self {
+            object: object.clone(),
+            main_margins,
+            cross_margins,
+            main_edges,
+            cross_edges,
+            flex_base_size: 0,
+            hypothetical_main_size: 0,
+            min_main_size: 0,
+            grow: 0.0,
+            shrink: 1.0,
+            alignment: ItemAlignment::Normal,
+            cross_size_is_auto,
+            main_size: 0,
+            cross_size: 0,
+            main_position: 0,
+            cross_position: 0,
+            baseline: None,
+        }
+    }
+
+    /// 主軸方向の大きさと、決まっていれば交差軸方向の大きさ`cross_size`を与えてアイテムをレイアウトし、
+    /// 交差軸方向の大きさとベースラインを求める
+    fn layout(
+        &mut self,
+        is_row: bool,
+        cross_size: Option<i64>,
+        container_width: i64,
+        container_height: Option<i64>,
+    ) {
+        {
+            let mut object = self.object.borrow_mut();
+            if is_row {
+                object.override_width = Some(self.main_size);
+                object.override_height = cross_size;
+            } else {
+                object.override_width = cross_size;
+                object.override_height = Some(self.main_size);
+            }
+            object.layout_block(container_width, container_height, true);
+            object.override_width = None;
+            object.override_height = None;
+        }
+        let object = self.object.borrow();
+        self.cross_size = if is_row {
+            object.size.height()
+        } else {
+            object.size.width()
+        };
+        self.baseline = object.first_baseline();
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#algo-stretch
+    /// 行の交差軸方向の大きさに合わせて伸ばすか
+    fn is_stretched(&self) -> bool {
+        matches!(
+            self.alignment,
+            ItemAlignment::Normal | ItemAlignment::Stretch
+        ) && self.cross_size_is_auto
+            && self.cross_margins.0.is_some()
+            && self.cross_margins.1.is_some()
+    }
+
+    /// https://www.w3.org/TR/css-flexbox-1/#baseline-participation
+    /// ベースラインで揃える場合、交差軸方向のマージンの端からベースラインまでの距離
+    /// ベースラインがない場合は、ボーダーボックスの下端をベースラインとする
+    fn baseline_offset(&self, is_row: bool) -> Option<i64> {
+        match self.cross_margins {
+            (Some(margin), Some(_)) if is_row && self.alignment == ItemAlignment::Baseline => {
+                Some(margin + self.baseline.unwrap_or(self.cross_size))
+            }
+            _ => None,
+        }
+    }
+
+    fn main_margin_sum(&self) -> i64 {
+        self.main_margins.0.unwrap_or(0) + self.main_margins.1.unwrap_or(0)
+    }
+
+    fn cross_margin_sum(&self) -> i64 {
+        self.cross_margins.0.unwrap_or(0) + self.cross_margins.1.unwrap_or(0)
+    }
+
+    fn main_auto_margin_count(&self) -> i64 {
+        self.main_margins.0.is_none() as i64 + self.main_margins.1.is_none() as i64
+    }
+}
+
+/// 長さを、`base`を基準にしたピクセル値にする. autoの場合と、基準が決まらないパーセンテージの場合はNone
+fn definite_size(length: &ComputedLength, base: Option<i64>) -> Option<i64> {
+    match (length, base) {
+        (ComputedLength::Auto, _) => None,
+        (ComputedLength::Px(px), _) => Some(round_px(*px).max(0)),
+        (_, None) => None,
+        (length, Some(base)) => length.resolve(base as f64).map(|v| round_px(v).max(0)),
+    }
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#algo-line-break
+/// アイテムを、仮の大きさの合計が`available`に収まる限り同じ行に集める. 各行のアイテムの範囲を返す
+fn collect_flex_lines(items: &[FlexItem], available: i64, gap: i64) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    let mut line_size = 0;
+    for (i, item) in items.iter().enumerate() {
+        let outer = item.hypothetical_main_size + item.main_margin_sum();
+        if i > line_start && line_size + gap + outer > available {
+            lines.push(line_start..i);
+            line_start = i;
+        }
+        line_size = if i == line_start {
+            outer
+        } else {
+            line_size + gap + outer
+        };
+    }
+    lines.push(line_start..items.len());
+    lines
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#resolve-flexible-lengths
+/// 1つの行のアイテムを、行の大きさ`available`に合わせて伸縮させ、主軸方向の大きさを決める
+fn resolve_flexible_lengths(items: &mut [FlexItem], available: i64, gap: i64) {
+    let fixed = items.iter().map(|item| item.main_margin_sum()).sum::<i64>()
+        + gap * (items.len() as i64 - 1);
+    let hypothetical: i64 = items.iter().map(|item| item.hypothetical_main_size).sum();
+    let growing = fixed + hypothetical < available;
+    let factor = |item: &FlexItem| if growing { item.grow } else { item.shrink };
+
+    // 伸縮しないアイテムは、仮の大きさで凍結する
+    let mut frozen: Vec<bool> = items
+        .iter()
+        .map(|item| {
+            factor(item) == 0.0
+                || (growing && item.flex_base_size > item.hypothetical_main_size)
+                || (!growing && item.flex_base_size < item.hypothetical_main_size)
+        })
+        .collect();
+    let mut targets: Vec<f64> = items
+        .iter()
+        .zip(&frozen)
+        .map(|(item, frozen)| {
+            if *frozen {
+                item.hypothetical_main_size as f64
+            } else {
+                item.flex_base_size as f64
+            }
+        })
+        .collect();
+    let free_space = |targets: &[f64], frozen: &[bool]| {
+        let used: f64 = items
+            .iter()
+            .zip(targets.iter().zip(frozen))
+            .map(|(item, (target, frozen))| {
+                if *frozen {
+                    *target
+                } else {
+                    item.flex_base_size as f64
+                }
+            })
+            .sum();
+        (available - fixed) as f64 - used
+    };
+    let initial_free_space = free_space(&targets, &frozen);
+
+    while frozen.iter().any(|frozen| !frozen) {
+        let mut free = free_space(&targets, &frozen);
+        // 縮める場合は、フレックスベースサイズが大きいアイテムほど大きく縮める
+        let scaled_shrink =
+            |item: &FlexItem| item.shrink * (item.flex_base_size - item.main_edges) as f64;
+        let mut factors = 0.0;
+        let mut total_scaled_shrink = 0.0;
+        for (item, frozen) in items.iter().zip(&frozen) {
+            if !frozen {
+                factors += factor(item);
+                total_scaled_shrink += scaled_shrink(item);
+            }
+        }
+
+        // 伸縮係数の合計が1未満の場合は、余白の一部だけを配分する
+        if factors < 1.0 {
+            let scaled = initial_free_space * factors;
+            // 伸ばす場合は余白が正、縮める場合は負なので、絶対値が小さい方を使う
+            if (growing && scaled < free) || (!growing && scaled > free) {
+                free = scaled;
+            }
+        }
+
+        let mut violation = Vec::new();
+        for (i, item) in items.iter().enumerate() {
+            if frozen[i] {
+                violation.push(0.0);
+                continue;
+            }
+            let base = item.flex_base_size as f64;
+            let target = if growing && factors > 0.0 {
+                base + free * item.grow / factors
+            } else if !growing && total_scaled_shrink > 0.0 {
+                base + free * scaled_shrink(item) / total_scaled_shrink
+            } else {
+                base
+            };
+            // 最小の大きさより小さくならないようにする
+            let clamped = target.max(item.min_main_size as f64);
+            violation.push(clamped - target);
+            targets[i] = clamped;
+        }
+
+        let total_violation: f64 = violation.iter().sum();
+        for (frozen, violation) in frozen.iter_mut().zip(violation) {
+            if total_violation == 0.0 || violation > 0.0 {
+                *frozen = true;
+            }
+        }
+    }
+
+    // 小数の大きさを丸めても行の大きさが変わらないように、累積した位置を丸める
+    let mut position = 0.0;
+    for (item, target) in items.iter_mut().zip(targets) {
+        let start = round_px(position);
+        position += target;
+        item.main_size = round_px(position) - start;
+    }
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#baseline-participation
+/// 行の中でベースラインで揃えるアイテムの、ベースラインより上と下の大きさの最大値
+fn baseline_extents(line: &[FlexItem], is_row: bool) -> (i64, i64) {
+    let mut above = 0;
+    let mut below = 0;
+    for item in line {
+        if let Some(offset) = item.baseline_offset(is_row) {
+            above = above.max(offset);
+            below = below.max(item.cross_size + item.cross_margin_sum() - offset);
+        }
+    }
+    (above, below)
+}
+
+/// https://www.w3.org/TR/css-align-3/#distribution-values
+/// 余白`free`を`count`個の並びに配分し、最初の並びの前の余白と、並びの間に加える余白を返す
+/// `reverse`は、始まりが物理的な右（下）にあるかで、startとendの向きを決めるのに使う
+fn distribute_free_space(
+    alignment: ContentAlignment,
+    free: i64,
+    count: usize,
+    reverse: bool,
+) -> (f64, f64) {
+    let free = free as f64;
+    let count = count as f64;
+    match alignment {
+        ContentAlignment::Normal | ContentAlignment::Stretch | ContentAlignment::FlexStart => {
+            (0.0, 0.0)
+        }
+        ContentAlignment::FlexEnd => (free, 0.0),
+        ContentAlignment::Start if reverse => (free, 0.0),
+        ContentAlignment::Start => (0.0, 0.0),
+        ContentAlignment::End if reverse => (0.0, 0.0),
+        ContentAlignment::End => (free, 0.0),
+        ContentAlignment::Center => (free / 2.0, 0.0),
+        // 余白が負の場合と並びが1つの場合は、始まりに揃える
+        ContentAlignment::SpaceBetween if free > 0.0 && count > 1.0 => (0.0, free / (count - 1.0)),
+        ContentAlignment::SpaceBetween => (0.0, 0.0),
+        // 余白が負の場合は、中央に揃える
+        ContentAlignment::SpaceAround if free > 0.0 => (free / count / 2.0, free / count),
+        ContentAlignment::SpaceEvenly if free > 0.0 => (free / (count + 1.0), free / (count + 1.0)),
+        ContentAlignment::SpaceAround | ContentAlignment::SpaceEvenly => (free / 2.0, 0.0),
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#grid-items
+/// グリッドレイアウトの途中の、アイテムごとの情報
+#[derive(Debug, Clone)]
+struct GridItem {
+    object: Rc<RefCell<LayoutObject>>,
+    /// アイテムを置いた列と行の範囲. 暗黙のトラックを含む、グリッドの始まりからのトラックの添字
+    columns: Range<usize>,
+    rows: Range<usize>,
+    /// justify-selfとalign-selfの使用値. autoはコンテナのjustify-itemsとalign-itemsになる
+    justify: ItemAlignment,
+    align: ItemAlignment,
+    /// 左右と上下のマージン. Noneはautoを表す
+    horizontal_margins: (Option<i64>, Option<i64>),
+    vertical_margins: (Option<i64>, Option<i64>),
+    /// ボーダーボックスの幅と高さ
+    width: i64,
+    height: i64,
+}
+
+impl GridItem {
+    fn new(
+        object: Rc<RefCell<LayoutObject>>,
+        columns: Range<usize>,
+        rows: Range<usize>,
+        container_style: &ComputedStyle,
+    ) -> Self {
+        let style = object.borrow().style();
+        Self {
+            object,
+            columns,
+            rows,
+            justify: style
+                .justify_self()
+                .unwrap_or(container_style.justify_items()),
+            align: style.align_self().unwrap_or(container_style.align_items()),
+            horizontal_margins: (Some(0), Some(0)),
+            vertical_margins: (Some(0), Some(0)),
+            width: 0,
+            height: 0,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-grid-2/#grid-item-sizing
+    /// グリッド領域を包含ブロックとしてアイテムの幅を決め、レイアウトする. `area_height`のNoneは行の大きさが未定であることを表す
+    /// 幅と高さがautoの場合、揃え方がnormalかstretchなら領域に合わせて伸ばし、そうでなければ内容に合わせる
+    fn layout_in_area(&mut self, area_width: i64, area_height: Option<i64>) {
+        let style = self.object.borrow().style();
+        // マージンのパーセンテージは、上下も含めてグリッド領域の幅を基準にする
+        let base = area_width as f64;
+        let margin = |length: ComputedLength| length.resolve(base).map(round_px);
+        self.horizontal_margins = (margin(style.margin_left()), margin(style.margin_right()));
+        self.vertical_margins = (margin(style.margin_top()), margin(style.margin_bottom()));
+
+        let mut object = self.object.borrow_mut();
+        object.resolve_border_and_padding(area_width);
+        let horizontal_edges = object.border.horizontal() + object.padding.horizontal();
+        let vertical_edges = object.border.vertical() + object.padding.vertical();
+        let available = area_width
+            - self.horizontal_margins.0.unwrap_or(0)
+            - self.horizontal_margins.1.unwrap_or(0);
+        self.width = match definite_size(&style.width(), Some(area_width)) {
+            Some(width) => width + horizontal_edges,
+            None if is_stretched_in_area(self.justify, self.horizontal_margins) => {
+                available.max(horizontal_edges)
+            }
+            None => {
+                let (min, max) = object.intrinsic_widths();
+                max.min(available.max(min))
+            }
+        };
+        let stretched_height = match area_height {
+            Some(area_height)
+                if style.height().is_auto()
+                    && is_stretched_in_area(self.align, self.vertical_margins) =>
+            {
+                let margins =
+                    self.vertical_margins.0.unwrap_or(0) + self.vertical_margins.1.unwrap_or(0);
+                Some((area_height - margins).max(vertical_edges))
+            }
+            _ => None,
+        };
+
+        object.override_width = Some(self.width);
+        object.override_height = stretched_height;
+        object.layout_block(area_width, area_height, true);
+        object.override_width = None;
+        object.override_height = None;
+        self.height = object.size.height();
+    }
+}
+
+/// グリッドにアイテムを置いた結果
+struct GridPlacement {
+    items: Vec<GridItem>,
+    /// 暗黙のトラックを含む、列と行のトラックの大きさの指定
+    columns: Vec<TrackSize>,
+    rows: Vec<TrackSize>,
+}
+
+/// https://www.w3.org/TR/css-grid-2/#line-placement
+/// 1つの軸のアイテムの位置. 線の番号は明示的なグリッドの始まりを0とし、負の値は明示的なグリッドより前を表す
+#[derive(Debug, Clone, PartialEq)]
+enum AxisPlacement {
+    /// 始まりと終わりの線が決まっている
+    Definite(Range<i64>),
+    /// 自動配置で決める. またがるトラックの数を持つ
+    Auto(i64),
+}
+
+impl AxisPlacement {
+    /// https://www.w3.org/TR/css-grid-2/#line-placement
+    /// https://www.w3.org/TR/css-grid-2/#grid-placement-errors
+    /// 始まりと終わりの線の指定から位置を決める. `explicit`は明示的なグリッドのトラックの数
+    fn new(start: GridLine, end: GridLine, explicit: i64) -> Self {
+        // 正の番号は始まりの線から、負の番号は明示的なグリッドの終わりの線から数える
+        let line = |n: i64| if n > 0 { n - 1 } else { explicit + 1 + n };
+        match (start, end) {
+            (GridLine::Line(start), GridLine::Line(end)) => {
+                // 終わりが始まりより前の場合は入れ替え、同じ場合は終わりをautoとして扱う
+                let (start, end) = (line(start), line(end));
+                if start == end {
+                    Self::Definite(start..start + 1)
+                } else {
+                    Self::Definite(start.min(end)..start.max(end))
+                }
+            }
+            (GridLine::Line(start), GridLine::Span(span)) => {
+                Self::Definite(line(start)..line(start) + span)
+            }
+            (GridLine::Line(start), GridLine::Auto) => Self::Definite(line(start)..line(start) + 1),
+            (GridLine::Span(span), GridLine::Line(end)) => {
+                Self::Definite(line(end) - span..line(end))
+            }
+            (GridLine::Auto, GridLine::Line(end)) => Self::Definite(line(end) - 1..line(end)),
+            // 両方がspanの場合は、終わりのspanを無視する
+            (GridLine::Span(span), _) | (GridLine::Auto, GridLine::Span(span)) => Self::Auto(span),
+            (GridLine::Auto, GridLine::Auto) => Self::Auto(1),
+        }
+    }
+
+    fn span(&self) -> i64 {
+        match self {
+            Self::Definite(range) => range.end - range.start,
+            Self::Auto(span) => *span,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#auto-placement-algo
+/// アイテムの列と行の位置`placements`から、自動配置も含めてグリッド領域を決め、列と行の線の範囲を返す
+/// 行方向に置く場合は明示的なグリッドの列の数`explicit_columns`だけ1行に並べ、
+/// 列方向に置く場合は、行と列を入れ替えて同じ手順で置く
+fn place_grid_areas(
+    placements: &[(AxisPlacement, AxisPlacement)],
+    explicit_columns: i64,
+    explicit_rows: i64,
+    flow: GridAutoFlow,
+) -> Vec<(Range<i64>, Range<i64>)> {
+    let is_column = flow.is_column();
+    let dense = flow.is_dense();
+    // カーソルを進める軸（primary）と、あふれたときに次に進める軸（secondary）の順にする
+    let oriented: Vec<(AxisPlacement, AxisPlacement)> = placements
+        .iter()
+        .map(|(column, row)| {
+            if is_column {
+                (row.clone(), column.clone())
+            } else {
+                (column.clone(), row.clone())
+            }
+        })
+        .collect();
+    let explicit_primary = if is_column {
+        explicit_rows
+    } else {
+        explicit_columns
+    };
+    let mut areas: Vec<Option<(Range<i64>, Range<i64>)>> = vec![None; oriented.len()];
+    let overlaps = |areas: &[Option<(Range<i64>, Range<i64>)>],
+                    primary: &Range<i64>,
+                    secondary: &Range<i64>| {
+        areas.iter().flatten().any(|(p, s)| {
+            p.start < primary.end
+                && primary.start < p.end
+                && s.start < secondary.end
+                && secondary.start < s.end
+        })
+    };
+
+    // 1. 位置が決まっているアイテムを置く
+    for (area, placement) in areas.iter_mut().zip(&oriented) {
+        if let (AxisPlacement::Definite(primary), AxisPlacement::Definite(secondary)) = placement {
+            *area = Some((primary.clone(), secondary.clone()));
+        }
+    }
+    let min_primary = oriented
+        .iter()
+        .filter_map(|(primary, _)| match primary {
+            AxisPlacement::Definite(range) => Some(range.start),
+            AxisPlacement::Auto(_) => None,
+        })
+        .fold(0, i64::min);
+    let min_secondary = oriented
+        .iter()
+        .filter_map(|(_, secondary)| match secondary {
+            AxisPlacement::Definite(range) => Some(range.start),
+            AxisPlacement::Auto(_) => None,
+        })
+        .fold(0, i64::min);
+
+    // 2. secondaryの軸だけが決まっているアイテムを、その行（列）の空いている位置に置く
+    // 詰めて置かない場合は、同じ行に前に置いたアイテムより後ろに置く
+    let mut cursors: Vec<(i64, i64)> = Vec::new();
+    for i in 0..oriented.len() {
+        let (span, secondary) = match &oriented[i] {
+            (AxisPlacement::Auto(span), AxisPlacement::Definite(secondary)) => (*span, secondary),
+            _ => continue,
+        };
+        let cursor = cursors.iter().find(|(start, _)| *start == secondary.start);
+        let mut primary = match cursor {
+            Some((_, next)) if !dense => *next,
+            _ => min_primary,
+        };
+        while overlaps(&areas, &(primary..primary + span), secondary) {
+            primary += 1;
+        }
+        cursors.retain(|(start, _)| *start != secondary.start);
+        cursors.push((secondary.start, primary + span));
+        areas[i] = Some((primary..primary + span, secondary.clone()));
+    }
+
+    // 3. 暗黙のグリッドのprimaryの軸のトラックの数を決める
+    let primary_end = oriented
+        .iter()
+        .zip(&areas)
+        .map(|(placement, area)| match (placement, area) {
+            (_, Some((primary, _))) => primary.end,
+            ((AxisPlacement::Definite(primary), _), None) => primary.end,
+            ((AxisPlacement::Auto(span), _), None) => min_primary + span,
+        })
+        .fold(explicit_primary, i64::max);
+
+    // 4. 残りのアイテムを、カーソルを進めながら空いている位置に置く
+    let mut cursor = (min_primary, min_secondary);
+    for i in 0..oriented.len() {
+        if areas[i].is_some() {
+            continue;
+        }
+        if dense {
+            cursor = (min_primary, min_secondary);
+        }
+        let secondary_span = oriented[i].1.span();
+        match &oriented[i].0 {
+            AxisPlacement::Definite(primary) => {
+                if !dense && primary.start < cursor.0 {
+                    cursor.1 += 1;
+                }
+                cursor.0 = primary.start;
+                while overlaps(&areas, primary, &(cursor.1..cursor.1 + secondary_span)) {
+                    cursor.1 += 1;
+                }
+                areas[i] = Some((primary.clone(), cursor.1..cursor.1 + secondary_span));
+            }
+            AxisPlacement::Auto(span) => loop {
+                if cursor.0 + span > primary_end {
+                    cursor = (min_primary, cursor.1 + 1);
+                    continue;
+                }
+                let primary = cursor.0..cursor.0 + span;
+                let secondary = cursor.1..cursor.1 + secondary_span;
+                if overlaps(&areas, &primary, &secondary) {
+                    cursor.0 += 1;
+                    continue;
+                }
+                areas[i] = Some((primary, secondary));
+                break;
+            },
+        }
+    }
+
+    areas
+        .into_iter()
+        .flatten()
+        .map(|(primary, secondary)| {
+            if is_column {
+                (secondary, primary)
+            } else {
+                (primary, secondary)
+            }
+        })
+        .collect()
+}
+
+/// https://www.w3.org/TR/css-grid-2/#auto-repeat
+/// auto-fillのrepeat()を、コンテナの大きさ`available`に収まる最大の回数だけ繰り返して、明示的なグリッドのトラックにする
+/// 大きさが決まらない場合は1回だけ繰り返す
+fn expand_track_list(list: &TrackList, available: Option<i64>, gap: i64) -> Vec<TrackSize> {
+    let (index, repeated) = match &list.auto_repeat {
+        Some(auto_repeat) => auto_repeat,
+        None => return list.tracks.clone(),
+    };
+
+    // トラックの大きさは、最大値が決まっていれば最大値を、そうでなければ最小値を使う
+    let fixed = |track: &TrackSize| {
+        let resolve = |breadth: &TrackBreadth| match (breadth, available) {
+            (TrackBreadth::Length(ComputedLength::Px(px)), _) => Some(*px),
+            (TrackBreadth::Length(length), Some(available)) => length.resolve(available as f64),
+            _ => None,
+        };
+        resolve(&track.max)
+            .or_else(|| resolve(&track.min))
+            .unwrap_or(0.0)
+    };
+    let mut count = 1;
+    if let Some(available) = available {
+        let others: f64 = list.tracks.iter().map(fixed).sum();
+        let repeat: f64 = repeated.iter().map(fixed).sum();
+        let extent = |count: usize| {
+            let tracks = list.tracks.len() + repeated.len() * count;
+            others + repeat * count as f64 + (gap * (tracks as i64 - 1)) as f64
+        };
+        if repeat + (gap * repeated.len() as i64) as f64 > 0.0 {
+            while extent(count + 1) <= available as f64 {
+                count += 1;
+            }
+        }
+    }
+
+    let mut tracks = list.tracks[..*index].to_vec();
+    for _ in 0..count {
+        tracks.extend(repeated.iter().cloned());
+    }
+    tracks.extend(list.tracks[*index..].iter().cloned());
+    tracks
+}
+
+/// https://www.w3.org/TR/css-grid-2/#implicit-grids
+/// 明示的なグリッドのトラックの前後に暗黙のトラックを加え、`count`個のトラックにする. `offset`は前に加える数
+/// 暗黙のトラックは、明示的なグリッドの後ろにはgrid-auto-*の順に、前には逆順に繰り返す
+fn grid_axis_tracks(
+    explicit: &[TrackSize],
+    auto: &[TrackSize],
+    offset: usize,
+    count: usize,
+) -> Vec<TrackSize> {
+    (0..count)
+        .map(|i| {
+            if i < offset {
+                auto[(auto.len() - (offset - i) % auto.len()) % auto.len()].clone()
+            } else if i - offset < explicit.len() {
+                explicit[i - offset].clone()
+            } else {
+                auto[(i - offset - explicit.len()) % auto.len()].clone()
+            }
+        })
+        .collect()
+}
+
+/// グリッドアイテムの、列の大きさを決めるための寄与. またがる列の範囲と、最小コンテンツと最大コンテンツの寄与を返す
+/// 寄与はマージンボックスの幅で、パーセンテージのマージンは0として扱う
+fn grid_column_contributions(items: &[GridItem]) -> Vec<(Range<usize>, i64, i64)> {
+    items
+        .iter()
+        .map(|item| {
+            let (min, max) = item.object.borrow_mut().intrinsic_widths();
+            let margins = item.object.borrow().horizontal_margins(0);
+            (item.columns.clone(), min + margins, max + margins)
+        })
+        .collect()
+}
+
+/// https://www.w3.org/TR/css-grid-2/#algo-overview
+/// トラックの大きさを決めるときの、利用できる大きさ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GridAvailableSpace {
+    Definite(i64),
+    /// https://www.w3.org/TR/css-sizing-3/#min-content-constraint
+    MinContent,
+    /// https://www.w3.org/TR/css-sizing-3/#max-content-constraint
+    MaxContent,
+}
+
+/// frの値を持つトラックの場合、その値
+fn track_flex(track: &TrackSize) -> Option<f64> {
+    match track.max {
+        TrackBreadth::Flex(flex) => Some(flex),
+        _ => None,
+    }
+}
+
+/// https://www.w3.org/TR/css-grid-2/#algo-track-sizing
+/// 1つの軸のトラックの大きさを決める. `contributions`は、アイテムがまたがるトラックの範囲と、
+/// 最小コンテンツと最大コンテンツの寄与. `stretch`は、余白をautoのトラックに配分するか
+fn size_grid_tracks(
+    tracks: &[TrackSize],
+    contributions: &[(Range<usize>, i64, i64)],
+    available: GridAvailableSpace,
+    gap: i64,
+    stretch: bool,
+) -> Vec<f64> {
+    let definite = match available {
+        GridAvailableSpace::Definite(size) => Some(size as f64),
+        _ => None,
+    };
+    // パーセンテージは、利用できる大きさが決まらない場合はautoとして扱う
+    let resolve = |breadth: &TrackBreadth| match breadth {
+        TrackBreadth::Length(ComputedLength::Px(px)) => Some(px.max(0.0)),
+        TrackBreadth::Length(length) => length.resolve(definite?).map(|size| size.max(0.0)),
+        _ => None,
+    };
+    let gaps = |count: usize| (gap * (count as i64 - 1).max(0)) as f64;
+
+    // https://www.w3.org/TR/css-grid-2/#algo-init
+    // 基本の大きさと上限. 上限のNoneは無限大を表す
+    let mut bases: Vec<f64> = tracks
+        .iter()
+        .map(|track| resolve(&track.min).unwrap_or(0.0))
+        .collect();
+    let mut limits: Vec<Option<f64>> = tracks
+        .iter()
+        .zip(&bases)
+        .map(|(track, base)| match &track.max {
+            TrackBreadth::Flex(_) => Some(*base),
+            max => resolve(max).map(|limit| limit.max(*base)),
+        })
+        .collect();
+    let has_intrinsic_min = |t: usize| resolve(&tracks[t].min).is_none();
+    let has_intrinsic_max =
+        |t: usize| track_flex(&tracks[t]).is_none() && resolve(&tracks[t].max).is_none();
+    let crosses_flex =
+        |range: &Range<usize>| range.clone().any(|t| track_flex(&tracks[t]).is_some());
+
+    // https://www.w3.org/TR/css-grid-2/#algo-content
+    // frのトラックをまたがないアイテムを、またがるトラックの数が少ない順に、内容に合わせて大きさを広げる
+    let mut order: Vec<usize> = (0..contributions.len())
+        .filter(|&i| !crosses_flex(&contributions[i].0))
+        .collect();
+    order.sort_by_key(|&i| contributions[i].0.len());
+    for i in order {
+        let (range, min_contribution, max_contribution) = &contributions[i];
+        let spanned_gaps = gaps(range.len());
 
-#[derive(Debug, Clone, PartialEq, Copy)]
-pub struct LayoutPoint {
-    x: i64,
-    y: i64
-}
+        let targets: Vec<usize> = range.clone().filter(|&t| has_intrinsic_min(t)).collect();
+        let min_content_min = targets
+            .iter()
+            .all(|&t| tracks[t].min != TrackBreadth::MaxContent);
+        let contribution = if min_content_min {
+            *min_contribution
+        } else {
+            *max_contribution
+        };
+        let current: f64 = range.clone().map(|t| bases[t]).sum();
+        let extra = contribution as f64 - spanned_gaps - current;
+        if extra > 0.0 && !targets.is_empty() {
+            for &t in &targets {
+                bases[t] += extra / targets.len() as f64;
+            }
+        }
 
-impl LayoutPoint {
-    pub fn new(x: i64, y: i64) -> Self {
-        Self {x, y}
+        let targets: Vec<usize> = range.clone().filter(|&t| has_intrinsic_max(t)).collect();
+        let min_content_max = targets
+            .iter()
+            .all(|&t| tracks[t].max == TrackBreadth::MinContent);
+        let contribution = if min_content_max {
+            *min_contribution
+        } else {
+            *max_contribution
+        };
+        // 無限大の上限は、基本の大きさとして合計する
+        let current: f64 = range.clone().map(|t| limits[t].unwrap_or(bases[t])).sum();
+        let extra = (contribution as f64 - spanned_gaps - current).max(0.0);
+        for &t in &targets {
+            limits[t] = Some(limits[t].unwrap_or(bases[t]) + extra / targets.len() as f64);
+        }
     }
 
-    pub fn x(&self) -> i64 {
-        self.x
+    // https://www.w3.org/TR/css-grid-2/#algo-spanning-flex-items
+    // frのトラックをまたぐアイテムは、最小コンテンツの寄与に足りない分をfrの値の比率で配分する
+    for (range, min_contribution, _) in contributions {
+        if !crosses_flex(range) {
+            continue;
+        }
+        let targets: Vec<usize> = range
+            .clone()
+            .filter(|&t| track_flex(&tracks[t]).is_some() && has_intrinsic_min(t))
+            .collect();
+        let current: f64 = range.clone().map(|t| bases[t]).sum();
+        let extra = *min_contribution as f64 - gaps(range.len()) - current;
+        if extra <= 0.0 || targets.is_empty() {
+            continue;
+        }
+        let total_flex: f64 = targets.iter().filter_map(|&t| track_flex(&tracks[t])).sum();
+        for &t in &targets {
+            let flex = track_flex(&tracks[t]).unwrap_or(0.0);
+            bases[t] += if total_flex > 0.0 {
+                extra * flex / total_flex
+            } else {
+                extra / targets.len() as f64
+            };
+        }
     }
+    // 上限が無限大のままのトラックは、上限を基本の大きさにする
+    let mut limits: Vec<f64> = limits
+        .iter()
+        .zip(&bases)
+        .map(|(limit, base)| limit.unwrap_or(*base).max(*base))
+        .collect();
 
-    pub fn y(&self) -> i64 {
-        self.y
+    // https://www.w3.org/TR/css-grid-2/#algo-grow-tracks
+    // 余白を上限に達していないトラックに均等に配分する. 最大コンテンツの制約の下では上限まで広げる
+    match available {
+        GridAvailableSpace::Definite(size) => {
+            let mut free = size as f64 - bases.iter().sum::<f64>() - gaps(tracks.len());
+            loop {
+                let targets: Vec<usize> = (0..tracks.len())
+                    .filter(|&t| bases[t] < limits[t])
+                    .collect();
+                if free <= 0.0 || targets.is_empty() {
+                    break;
+                }
+                let share = free / targets.len() as f64;
+                let mut frozen = false;
+                for t in targets {
+                    let growth = share.min(limits[t] - bases[t]);
+                    frozen |= growth < share;
+                    bases[t] += growth;
+                    free -= growth;
+                }
+                if !frozen {
+                    break;
+                }
+            }
+        }
+        GridAvailableSpace::MaxContent => bases.clone_from(&limits),
+        GridAvailableSpace::MinContent => {}
     }
 
-    pub fn set_x(&mut self, x: i64) {
-        self.x = x;
+    // https://www.w3.org/TR/css-grid-2/#algo-flex-tracks
+    // 1frあたりの大きさを求めて、frのトラックを広げる. 最小コンテンツの制約の下では広げない
+    let flex_tracks: Vec<usize> = (0..tracks.len())
+        .filter(|&t| track_flex(&tracks[t]).is_some())
+        .collect();
+    if !flex_tracks.is_empty() {
+        let all: Vec<usize> = (0..tracks.len()).collect();
+        let fr = match available {
+            GridAvailableSpace::Definite(size) => {
+                find_fr_size(tracks, &bases, &all, size as f64 - gaps(tracks.len()))
+            }
+            GridAvailableSpace::MinContent => 0.0,
+            GridAvailableSpace::MaxContent => {
+                let mut fr: f64 = 0.0;
+                for &t in &flex_tracks {
+                    let flex = track_flex(&tracks[t]).unwrap_or(0.0);
+                    fr = fr.max(if flex > 1.0 {
+                        bases[t] / flex
+                    } else {
+                        bases[t]
+                    });
+                }
+                for (range, _, max_contribution) in contributions {
+                    if crosses_flex(range) {
+                        let spanned: Vec<usize> = range.clone().collect();
+                        let space = *max_contribution as f64 - gaps(range.len());
+                        fr = fr.max(find_fr_size(tracks, &bases, &spanned, space));
+                    }
+                }
+                fr
+            }
+        };
+        for t in flex_tracks {
+            let flex = track_flex(&tracks[t]).unwrap_or(0.0);
+            bases[t] = bases[t].max(fr * flex);
+            limits[t] = bases[t];
+        }
     }
 
-    pub fn set_y(&mut self, y: i64) {
-        self.y = y;
+    // https://www.w3.org/TR/css-grid-2/#algo-stretch
+    // 残った余白を、最大値がautoのトラックに均等に配分する
+    if let (true, GridAvailableSpace::Definite(size)) = (stretch, available) {
+        let free = size as f64 - bases.iter().sum::<f64>() - gaps(tracks.len());
+        let targets: Vec<usize> = (0..tracks.len())
+            .filter(|&t| tracks[t].max == TrackBreadth::Auto)
+            .collect();
+        if free > 0.0 && !targets.is_empty() {
+            for t in &targets {
+                bases[*t] += free / targets.len() as f64;
+            }
+        }
     }
+
+    bases
 }
 
-#[derive(Debug, Clone, PartialEq, Copy)]
-pub struct LayoutSize {
-    width: i64,
-    height: i64
+/// https://www.w3.org/TR/css-grid-2/#algo-find-fr-size
+/// `indices`のトラックで大きさ`space`を埋めるときの、1frあたりの大きさ
+/// frのトラックのうち、基本の大きさの方が大きいものはfrでないトラックとして扱い、求め直す
+fn find_fr_size(tracks: &[TrackSize], bases: &[f64], indices: &[usize], space: f64) -> f64 {
+    let mut inflexible: Vec<bool> = indices
+        .iter()
+        .map(|&t| track_flex(&tracks[t]).is_none())
+        .collect();
+    loop {
+        let mut leftover = space;
+        let mut flex_sum = 0.0;
+        for (&t, &inflexible) in indices.iter().zip(&inflexible) {
+            if inflexible {
+                leftover -= bases[t];
+            } else {
+                flex_sum += track_flex(&tracks[t]).unwrap_or(0.0);
+            }
+        }
+        // frの合計が1未満の場合は、余白の一部だけを配分する
+        let fr = leftover.max(0.0) / if flex_sum < 1.0 { 1.0 } else { flex_sum };
+
+        let mut changed = false;
+        for (&t, inflexible) in indices.iter().zip(inflexible.iter_mut()) {
+            if !*inflexible && fr * track_flex(&tracks[t]).unwrap_or(0.0) < bases[t] {
+                *inflexible = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            return fr;
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-align-3/#distribution-values
+/// justify-content（align-content）が、余白をautoのトラックに配分するか
+fn is_stretch(alignment: ContentAlignment) -> bool {
+    matches!(
+        alignment,
+        ContentAlignment::Normal | ContentAlignment::Stretch
+    )
 }
 
-impl LayoutSize {
-    pub fn new(width: i64, height: i64) -> Self {
-        Self {width, height}
+/// https://www.w3.org/TR/css-grid-2/#grid-align
+/// トラックの大きさ`sizes`と、justify-content（align-content）による余白の配分から、
+/// コンテンツ領域の始まりからの各トラックの位置を求める. `available`のNoneは大きさが内容から決まることを表す
+fn position_grid_tracks(
+    sizes: &[f64],
+    available: Option<i64>,
+    gap: i64,
+    alignment: ContentAlignment,
+) -> Vec<Range<i64>> {
+    let used = sizes.iter().sum::<f64>() + (gap * (sizes.len() as i64 - 1).max(0)) as f64;
+    let free = available.map_or(0, |available| round_px(available as f64 - used));
+    let (offset, between) = distribute_free_space(alignment, free, sizes.len(), false);
+
+    // 小数の大きさを丸めてもグリッドの大きさが変わらないように、累積した位置を丸める
+    let mut position = offset;
+    let mut tracks = Vec::new();
+    for size in sizes {
+        let start = round_px(position);
+        position += size;
+        tracks.push(start..round_px(position));
+        position += gap as f64 + between;
     }
+    tracks
+}
+
+/// `span`のトラックにまたがるグリッド領域の、始まりの位置と大きさ
+fn track_area(tracks: &[Range<i64>], span: &Range<usize>) -> (i64, i64) {
+    let start = tracks[span.start].start;
+    (start, tracks[span.end - 1].end - start)
+}
 
-    pub fn width(&self)-> i64 {
-        self.width
+/// https://www.w3.org/TR/css-align-3/#valdef-justify-self-stretch
+/// 大きさがautoのアイテムを、グリッド領域に合わせて伸ばすか. autoのマージンがある場合は伸ばさない
+fn is_stretched_in_area(alignment: ItemAlignment, margins: (Option<i64>, Option<i64>)) -> bool {
+    matches!(alignment, ItemAlignment::Normal | ItemAlignment::Stretch)
+        && margins.0.is_some()
+        && margins.1.is_some()
+}
+
+/// https://www.w3.org/TR/css-grid-2/#auto-margins
+/// https://www.w3.org/TR/css-align-3/#justify-self-property
+/// グリッド領域の中の1つの軸の位置を決め、始まりと終わりのマージンと、領域の始まりからマージンの始まりまでの距離を返す
+/// autoのマージンは余白を受け取り、なければ揃え方に従う. ベースラインでの整列はサポートしていないので、始まりに揃える
+fn align_in_area(
+    alignment: ItemAlignment,
+    margins: (Option<i64>, Option<i64>),
+    size: i64,
+    area: i64,
+) -> (i64, i64, i64) {
+    let free = area - size - margins.0.unwrap_or(0) - margins.1.unwrap_or(0);
+    match margins {
+        (None, None) => (free.max(0) / 2, free.max(0) - free.max(0) / 2, 0),
+        (None, Some(end)) => (free.max(0), end, 0),
+        (Some(start), None) => (start, free.max(0), 0),
+        (Some(start), Some(end)) => {
+            let offset = match alignment {
+                ItemAlignment::End | ItemAlignment::FlexEnd => free,
+                ItemAlignment::Center => free / 2,
+                _ => 0,
+            };
+            (start, end, offset)
+        }
     }
+}
+
+/// 行の要素（なければNone）と、その行に並ぶセル
+type TableRowContent = (
+    Option<Rc<RefCell<LayoutObject>>>,
+    Vec<Rc<RefCell<LayoutObject>>>,
+);
+
+/// 行グループの要素（なければNone）と、その行グループの行
+type TableGroupContent = (Option<Rc<RefCell<LayoutObject>>>, Vec<TableRowContent>);
+
+/// https://www.w3.org/TR/CSS2/tables.html#table-layout
+/// 表のキャプション・列・行グループ・行・セルを、行と列のスロットに置いたもの
+#[derive(Default)]
+struct TableGrid {
+    captions: Vec<Rc<RefCell<LayoutObject>>>,
+    /// 表の子の列グループと列の要素
+    columns: Vec<Rc<RefCell<LayoutObject>>>,
+    /// 列の要素で指定された、列ごとの幅
+    column_widths: Vec<Option<i64>>,
+    column_count: usize,
+    rows: Vec<TableGridRow>,
+    /// 行グループの要素と、その行の範囲
+    groups: Vec<(Rc<RefCell<LayoutObject>>, Range<usize>)>,
+    cells: Vec<TableGridCell>,
+    /// 行と列のスロットごとの、そこを占めるセルの番号
+    slots: Vec<Vec<Option<usize>>>,
+}
+
+struct TableGridRow {
+    /// 行の要素. 行に入っていないセルの場合はNone
+    object: Option<Rc<RefCell<LayoutObject>>>,
+    /// 行が入っている行グループの番号
+    group: Option<usize>,
+}
+
+struct TableGridCell {
+    object: Rc<RefCell<LayoutObject>>,
+    /// 最初の行と列の番号、またがる行と列の数
+    row: usize,
+    column: usize,
+    rows: usize,
+    columns: usize,
+}
 
-    pub fn height(&self) -> i64 {
-        self.height
+impl TableGridCell {
+    /// https://www.w3.org/TR/CSS2/tables.html#height-layout
+    /// 行のベースラインに揃えるセルか. top・middle・bottom以外の値はbaselineとして扱う
+    fn is_baseline_aligned(&self) -> bool {
+        !matches!(
+            self.object.borrow().style.vertical_align(),
+            VerticalAlign::Top | VerticalAlign::Middle | VerticalAlign::Bottom
+        )
     }
+}
 
-    pub fn set_width(&mut self, width: i64) {
-        self.width = width;
+/// 行グループの子を、行ごとのセルに分ける
+fn table_rows(group: &Rc<RefCell<LayoutObject>>) -> Vec<TableRowContent> {
+    let mut rows = Vec::new();
+    let mut child = group.borrow().first_child();
+    while let Some(c) = child {
+        child = c.borrow().next_sibling();
+        push_table_row_content(&mut rows, c);
     }
+    rows
+}
 
-    pub fn set_height(&mut self, height: i64) {
-        self.height = height;
+/// https://www.w3.org/TR/CSS2/tables.html#anonymous-boxes
+/// 行はそのセルと一緒に`rows`に加え、行に入っていないセルは匿名の行にまとめる
+/// 絶対位置指定されたボックスと、セルにならないインラインレベルの内容は表に置かない
+fn push_table_row_content(rows: &mut Vec<TableRowContent>, object: Rc<RefCell<LayoutObject>>) {
+    if !is_table_box(&object) {
+        return;
+    }
+    if object.borrow().style.display() == DisplayType::TableRow {
+        let mut cells = Vec::new();
+        let mut child = object.borrow().first_child();
+        while let Some(c) = child {
+            child = c.borrow().next_sibling();
+            if is_table_box(&c) {
+                cells.push(c);
+            }
+        }
+        rows.push((Some(object), cells));
+        return;
+    }
+    match rows.last_mut() {
+        Some((None, cells)) => cells.push(object),
+        _ => rows.push((None, vec![object])),
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum LayoutObjectKind {
-    Block,
-    Inline,
-    Text
+/// 表の行やセルとして置くボックスか
+fn is_table_box(object: &Rc<RefCell<LayoutObject>>) -> bool {
+    let object = object.borrow();
+    !object.is_absolutely_positioned()
+        && matches!(
+            object.kind,
+            LayoutObjectKind::Block | LayoutObjectKind::AtomicInline
+        )
 }
 
-#[derive(Debug, Clone)]
-pub struct LayoutObject {
-    kind: LayoutObjectKind,
-    node: Rc<RefCell<Node>>,
-    first_child: Option<Rc<RefCell<LayoutObject>>>,
-    next_sibling: Option<Rc<RefCell<LayoutObject>>>,
-    parent: Weak<RefCell<LayoutObject>,
-    style: ComputedStyle,
-    point: LayoutPoint,
-    size: LayoutSize,
+/// https://www.w3.org/TR/CSS2/tables.html#columns
+/// 列グループと列の要素から、列ごとの幅を集める. 列を含まない列グループと列は、span属性の数の列を表す
+/// 幅が指定されていない列は、列グループの幅`group_width`を使う
+fn collect_table_columns(
+    grid: &mut TableGrid,
+    object: &Rc<RefCell<LayoutObject>>,
+    group_width: Option<i64>,
+) {
+    let (display, width) = {
+        let object = object.borrow();
+        let width = match object.style.width() {
+            ComputedLength::Px(width) => Some(round_px(width).max(0)),
+            _ => group_width,
+        };
+        (object.style.display(), width)
+    };
+    if display == DisplayType::TableColumnGroup {
+        let mut has_columns = false;
+        let mut child = object.borrow().first_child();
+        while let Some(c) = child {
+            child = c.borrow().next_sibling();
+            if c.borrow().style.display() == DisplayType::TableColumn {
+                collect_table_columns(grid, &c, width);
+                has_columns = true;
+            }
+        }
+        if has_columns {
+            return;
+        }
+    }
+    let span = table_span(object, "span")
+        .filter(|&span| span > 0)
+        .map_or(1, |span| span.min(1000));
+    let count = grid.column_widths.len() + span;
+    grid.column_widths.resize(count, width);
 }
 
-impl LayoutObject {
-    pub fn new(
-        node: Rc<RefCell<Node>>,parent_obj: &Option<Rc<RefCell<LayoutObject>>>
-    ) -> Self {
-        let parent = match parent_obj {
-            Some(p) => Rc::downgrade(p),
-            None => Weak::new()
+/// https://html.spec.whatwg.org/multipage/tables.html#attributes-common-to-td-and-th-elements
+/// colspan・rowspan・span属性で指定された、またがる数. 属性がないか数でない場合はNone
+fn table_span(object: &Rc<RefCell<LayoutObject>>, name: &str) -> Option<usize> {
+    let object = object.borrow();
+    if object.anonymous || object.pseudo.is_some() {
+        return None;
+    }
+    let element = object.node.borrow().get_element()?;
+    element.get_attribute(name)?.trim().parse().ok()
+}
+
+/// https://www.w3.org/TR/CSS2/tables.html#auto-table-layout
+/// 列ごとの最小幅と最大幅を、列の要素の幅とセルのボーダーボックスの幅から求める
+/// 複数の列にまたがるセルは、またがる列の幅の合計が足りない場合に、超えた分を列の最大幅に比例して配る
+fn table_column_extents(grid: &TableGrid, h_spacing: i64) -> Vec<(i64, i64)> {
+    let mut mins = vec![0; grid.column_count];
+    let mut maxes = vec![0; grid.column_count];
+    for (i, width) in grid.column_widths.iter().enumerate() {
+        if let Some(width) = *width {
+            mins[i] = width;
+            maxes[i] = width;
+        }
+    }
+
+    let mut cells: Vec<&TableGridCell> = grid.cells.iter().collect();
+    cells.sort_by_key(|cell| cell.columns);
+    for cell in cells {
+        let (min, max) = cell.object.borrow_mut().intrinsic_widths();
+        let columns = cell.column..cell.column + cell.columns;
+        let spacing = h_spacing * (cell.columns as i64 - 1);
+        let weights = maxes[columns.clone()].to_vec();
+        let total_min = mins[columns.clone()].iter().sum::<i64>() + spacing;
+        distribute_excess(&mut mins[columns.clone()], &weights, min - total_min);
+        let total_max = maxes[columns.clone()].iter().sum::<i64>() + spacing;
+        distribute_excess(&mut maxes[columns], &weights, max - total_max);
+    }
+
+    mins.into_iter()
+        .zip(maxes)
+        .map(|(min, max)| (min, max.max(min)))
+        .collect()
+}
+
+/// https://www.w3.org/TR/CSS2/tables.html#auto-table-layout
+/// 自動レイアウトで、コンテンツ領域の幅`width`に列を並べるときの列の幅を求める
+/// 最大幅で収まる場合は余る幅を最大幅に比例して配り、収まらない場合は最小幅と最大幅の間で幅を決める
+fn auto_table_columns(grid: &TableGrid, width: i64, h_spacing: i64) -> Vec<i64> {
+    let extents = table_column_extents(grid, h_spacing);
+    let available = width - h_spacing * (extents.len() as i64 + 1);
+    let min_sum: i64 = extents.iter().map(|&(min, _)| min).sum();
+    let max_sum: i64 = extents.iter().map(|&(_, max)| max).sum();
+    if available >= max_sum {
+        let mut columns: Vec<i64> = extents.iter().map(|&(_, max)| max).collect();
+        let weights = columns.clone();
+        distribute_excess(&mut columns, &weights, available - max_sum);
+        columns
+    } else {
+        let mut columns: Vec<i64> = extents.iter().map(|&(min, _)| min).collect();
+        let weights: Vec<i64> = extents.iter().map(|&(min, max)| max - min).collect();
+        distribute_excess(&mut columns, &weights, available - min_sum);
+        columns
+    }
+}
+
+/// https://www.w3.org/TR/CSS2/tables.html#fixed-table-layout
+/// 固定レイアウトで、列の幅を列の要素と最初の行のセルの幅だけから求める
+/// 幅が決まらない列は残りの幅を等しく分け、すべての列の幅が決まっている場合は余る幅を幅に比例して配る
+fn fixed_table_columns(grid: &TableGrid, width: i64, h_spacing: i64) -> Vec<i64> {
+    let mut columns = grid.column_widths.clone();
+    columns.resize(grid.column_count, None);
+    for cell in grid.cells.iter().filter(|cell| cell.row == 0) {
+        let mut object = cell.object.borrow_mut();
+        if !matches!(object.style.width(), ComputedLength::Px(_)) {
+            continue;
+        }
+        let (cell_width, _) = object.intrinsic_widths();
+        let spans = &mut columns[cell.column..cell.column + cell.columns];
+        let count = spans.len() as i64;
+        let share = (cell_width - h_spacing * (count - 1)).max(0) / count;
+        for column in spans.iter_mut().filter(|column| column.is_none()) {
+            *column = Some(share);
+        }
+    }
+
+    let available = width - h_spacing * (columns.len() as i64 + 1);
+    let fixed: i64 = columns.iter().flatten().sum();
+    let auto_count = columns.iter().filter(|column| column.is_none()).count() as i64;
+    let remaining = (available - fixed).max(0);
+    let mut given = 0;
+    let mut index = 0;
+    let mut result: Vec<i64> = columns
+        .iter()
+        .map(|column| match column {
+            Some(width) => *width,
+            None => {
+                index += 1;
+                let share = if index == auto_count {
+                    remaining - given
+                } else {
+                    remaining / auto_count
+                };
+                given += share;
+                share
+            }
+        })
+        .collect();
+    if auto_count == 0 {
+        let weights = result.clone();
+        distribute_excess(&mut result, &weights, available - fixed);
+    }
+    result
+}
+
+/// `excess`が正の場合に、`values`に`weights`に比例して配る. 重みがすべて0の場合は等しく配る
+/// 端数は最後の値に加える
+fn distribute_excess(values: &mut [i64], weights: &[i64], excess: i64) {
+    if excess <= 0 || values.is_empty() {
+        return;
+    }
+    let total: i64 = weights.iter().sum();
+    let count = values.len();
+    let mut given = 0;
+    for (i, value) in values.iter_mut().enumerate() {
+        let share = if i == count - 1 {
+            excess - given
+        } else if total > 0 {
+            excess * weights[i] / total
+        } else {
+            excess / count as i64
         };
+        *value += share;
+        given += share;
+    }
+}
 
-        Self {
-            kind: LayoutObjectKind::Block,
-            node: node.clone(),
-            first_child: None,
-            next_sibling: None,
-            parent,
-            style: ComputedStyle::new(),
-            point: LayoutPoint::new(0,0),
-            size: LayoutSize::new(0,0)
+/// https://www.w3.org/TR/CSS2/tables.html#border-conflict-resolution
+/// 境界を共有するボーダーのうち、使うボーダーの幅を決める
+/// hiddenのボーダーがあれば0、それ以外は最も太いボーダーの幅にする. noneのボーダーの幅は0になっている
+fn resolve_border_conflict(borders: &[(BorderStyle, f64)]) -> i64 {
+    if borders
+        .iter()
+        .any(|(style, _)| *style == BorderStyle::Hidden)
+    {
+        return 0;
+    }
+    borders
+        .iter()
+        .map(|&(_, width)| round_px(width))
+        .max()
+        .unwrap_or(0)
+}
+
+/// https://www.w3.org/TR/css-inline-3/#inline-formatting-context
+/// ブロックの子のうち、`first`から続くインラインレベルの子を行ボックスに並べる
+/// 行は`origin`を左上として幅`available_width`で並べ、位置はブロックのボーダーボックスの左上からの相対位置になる
+/// `floats`はブロック整形コンテキストのフロートで、`offset`はブロックのボーダーボックスの、その中での位置
+/// 次のブロックの子と、作った行ボックスを返す
+fn layout_inline_formatting_context(
+    first: &Rc<RefCell<LayoutObject>>,
+    container_style: &ComputedStyle,
+    available_width: i64,
+    origin: LayoutPoint,
+    floats: &mut FloatContext,
+    offset: LayoutPoint,
+) -> (Option<Rc<RefCell<LayoutObject>>>, Vec<LineBox>) {
+    let mut collector = InlineItemCollector::new(available_width, origin, AtomicSizing::Layout);
+    let mut next_block = None;
+    let mut child = Some(first.clone());
+    while let Some(c) = child {
+        if c.borrow().is_in_flow_block() {
+            next_block = Some(c);
+            break;
         }
+        collector.collect(&c);
+        child = c.borrow().next_sibling();
     }
+    let items = collector.into_items();
 
-    pub fn create_layout_object(node: &Option<Rc<RefCell<Node>>>, parent_obj: &Option<Rc<RefCell<LayoutObject>>>, cssom: &StyleSheet) -> Option<Rc<RefCell<LayoutObject>>> {
-        if let Some(n) = node {
-            let layout_object = Rc::new(RefCell::new(LayoutObject::new(n.clone(), parent_obj)));
+    // 絶対位置指定された子とフロートしかない場合は、行ボックスを作らず、行の始点を静的位置にする
+    if items.iter().all(|item| {
+        matches!(
+            item.kind,
+            InlineItemKind::Placeholder | InlineItemKind::Float
+        )
+    }) {
+        for item in &items {
+            if item.kind == InlineItemKind::Float {
+                place_float(&item.object, origin, available_width, floats, offset);
+            } else {
+                item.object.borrow_mut().point = origin;
+            }
+        }
+        return (next_block, Vec::new());
+    }
 
-            // CSSのルールを、セレクタで選択されたノードに適用
-            for rule in &cssom.rules {
-                if layout_object.borrow().is_node_selected(&rule.selector) {
-                    layout_object.borrow_mut().cascading_style(rule.declarations.clone());
-                }
+    let strut = VerticalMetrics::new(container_style);
+    let align = container_style.text_align();
+    let mut fragments: Vec<(Rc<RefCell<LayoutObject>>, LineFragment)> = Vec::new();
+    // 前の行から続いているインラインボックスと、この行でのボーダーボックスの左端
+    let mut open: Vec<(Rc<RefCell<LayoutObject>>, i64)> = Vec::new();
+    let mut line_boxes = Vec::new();
+    let mut line_top = origin.y();
+    let mut start = 0;
+
+    while start < items.len() {
+        // 行頭のフロートは、行の上端に置く
+        if items[start].kind == InlineItemKind::Float {
+            let point = LayoutPoint::new(origin.x(), line_top);
+            place_float(&items[start].object, point, available_width, floats, offset);
+            start += 1;
+            continue;
+        }
+
+        // https://www.w3.org/TR/CSS2/visuren.html#floats
+        // 行ボックスは、フロートに重ならないように短くなる
+        let band = |floats: &FloatContext| {
+            let left = offset.x() + origin.x();
+            let (start, end) = floats.available(
+                offset.y() + line_top,
+                strut.above + strut.below,
+                left,
+                left + available_width,
+            );
+            (start - offset.x(), (end - start).max(0))
+        };
+        let (mut line_left, mut line_width) = band(floats);
+        let end = next_line_end(&items, start, line_width);
+        let mut line = items[start..end].to_vec();
+        trim_hanging_spaces(&mut line);
+        let content_width: i64 = line.iter().map(|item| item.width).sum();
+
+        // 短くなった行に収まらない場合は、フロートの下端まで行を下げる
+        if content_width > line_width && line_width < available_width {
+            if let Some(bottom) =
+                floats.next_bottom(offset.y() + line_top, strut.above + strut.below)
+            {
+                line_top = bottom - offset.y();
+                continue;
             }
+        }
 
-            // CSSでスタイルが指定されていない場合、デフォルト値または親ノードから継承した値を使用
-            let parent_style = if let Some(parent) = parent_obj {
-                Some(parent.borrow().style())
+        // 行の途中のフロートは、行の内容と並べて収まる場合は行の上端に、収まらない場合は行の下に置く
+        let mut used_width = content_width;
+        let mut deferred = Vec::new();
+        for item in line
+            .iter()
+            .filter(|item| item.kind == InlineItemKind::Float)
+        {
+            let float_width = {
+                let object = item.object.borrow();
+                object.size.width() + object.margin.horizontal()
+            };
+            if used_width + float_width <= line_width {
+                let point = LayoutPoint::new(origin.x(), line_top);
+                place_float(&item.object, point, available_width, floats, offset);
+                used_width += float_width;
             } else {
-                None
+                deferred.push(item.object.clone());
+            }
+        }
+        if used_width != content_width {
+            (line_left, line_width) = band(floats);
+        }
+
+        // https://www.w3.org/TR/css-text-3/#text-align-property
+        let free = line_width - content_width;
+        let is_last_line = end == items.len();
+        let extras = if align == TextAlign::Justify && !is_last_line && free > 0 {
+            justify(&line, free)
+        } else {
+            vec![0; line.len()]
+        };
+        let mut x = line_left
+            + match align {
+                TextAlign::Right => free.max(0),
+                TextAlign::Center => (free / 2).max(0),
+                TextAlign::Left | TextAlign::Justify => 0,
             };
-            layout_object.borrow_mut().defaulting_style(n, parent_style);
 
-            // displayプロパティがnoneの場合、ノードを生成しない
-            if layout_object.borrow().style().display() == DisplayType::DisplayNone {
-                return None;
+        // https://www.w3.org/TR/css-inline-3/#line-height
+        // ブロックのストラットと、行に置かれたテキストとインラインボックスの、ベースラインの上下の高さの最大値で行の高さが決まる
+        let mut above = strut.above;
+        let mut below = strut.below;
+        let styles = open.iter().map(|(object, _)| object).chain(
+            line.iter()
+                .filter(|item| {
+                    !matches!(
+                        item.kind,
+                        InlineItemKind::Placeholder | InlineItemKind::Float
+                    )
+                })
+                .map(|item| &item.object),
+        );
+        for object in styles {
+            let object = object.borrow();
+            if matches!(
+                object.kind,
+                LayoutObjectKind::Block | LayoutObjectKind::AtomicInline
+            ) {
+                let baseline = object.atomic_baseline();
+                above = above.max(baseline);
+                below = below.max(object.size.height() + object.margin.vertical() - baseline);
+            } else {
+                let metrics = VerticalMetrics::new(&object.style);
+                above = above.max(metrics.above);
+                below = below.max(metrics.below);
             }
+        }
+        // テキストも幅のあるインラインボックスもない行は、高さが0になる
+        if content_width == 0 && !line.iter().any(|item| item.kind == InlineItemKind::Atomic) {
+            above = 0;
+            below = 0;
+        }
+        let baseline = line_top + above;
 
-            // displayプロパティの最終的な値を用いてノードの種類を決定
-            layout_object.borrow_mut().update_kind();
-            return Some(layout_object);
+        // 前の行から続くインラインボックスは、行の左端から始まる
+        for (_, left) in &mut open {
+            *left = x;
+        }
+        for (item, extra) in line.iter().zip(extras) {
+            let object = &item.object;
+            match &item.kind {
+                InlineItemKind::Start => {
+                    open.push((object.clone(), x + object.borrow().margin.left()));
+                    x += item.width;
+                }
+                InlineItemKind::End => {
+                    x += item.width;
+                    if let Some((object, left)) = open.pop() {
+                        let right = x - object.borrow().margin.right();
+                        let fragment = box_fragment(&object, left, right, baseline);
+                        fragments.push((object, fragment));
+                    }
+                }
+                InlineItemKind::Text(text) => {
+                    let width = item.width + extra;
+                    if text.is_empty() {
+                        continue;
+                    }
+                    let metrics = VerticalMetrics::new(&object.borrow().style);
+                    let point = LayoutPoint::new(x, baseline - metrics.above);
+                    x += width;
+
+                    // 同じテキストの続きの部分は、1つの断片にまとめる
+                    if let Some((last, fragment)) = fragments.last_mut() {
+                        if Rc::ptr_eq(last, object)
+                            && fragment.point().y() == point.y()
+                            && fragment.point().x() + fragment.size().width() == point.x()
+                        {
+                            let size = fragment.size();
+                            *fragment = LineFragment::new(
+                                fragment.point(),
+                                LayoutSize::new(size.width() + width, size.height()),
+                                fragment.text() + text,
+                            );
+                            continue;
+                        }
+                    }
+                    fragments.push((
+                        object.clone(),
+                        LineFragment::new(
+                            point,
+                            LayoutSize::new(width, metrics.above + metrics.below),
+                            text.clone(),
+                        ),
+                    ));
+                }
+                InlineItemKind::Atomic => {
+                    let mut object = object.borrow_mut();
+                    let above = object.atomic_baseline();
+                    object.point = LayoutPoint::new(
+                        x + object.margin.left(),
+                        baseline - above + object.margin.top(),
+                    );
+                    x += item.width;
+                }
+                InlineItemKind::Placeholder => {
+                    object.borrow_mut().point = LayoutPoint::new(x, line_top);
+                }
+                InlineItemKind::Float => {}
+            }
+        }
+
+        // 次の行に続くインラインボックスは、行の右端で分割する
+        // https://www.w3.org/TR/css-break-3/#break-decoration
+        // 分割された位置には、左右のマージン・ボーダー・パディングを置かない
+        for (object, left) in &open {
+            fragments.push((object.clone(), box_fragment(object, *left, x, baseline)));
+        }
+
+        line_boxes.push(LineBox::new(
+            LayoutPoint::new(line_left, line_top),
+            LayoutSize::new(line_width, above + below),
+            above,
+        ));
+        line_top += above + below;
+        start = end;
+
+        for object in deferred {
+            let point = LayoutPoint::new(origin.x(), line_top);
+            place_float(&object, point, available_width, floats, offset);
         }
-        None
     }
 
-    pub fn kind(&self) -> LayoutObjectKind {
-        self.kind
+    // 断片をレイアウトオブジェクトに設定し、断片を囲む矩形をレイアウトオブジェクトの位置と大きさにする
+    for (object, mut fragment) in fragments {
+        if object.borrow().kind == LayoutObjectKind::Text {
+            let decoration = object.borrow().text_decoration;
+            fragment.set_decoration(decoration);
+        }
+        object.borrow_mut().fragments.push(fragment);
+    }
+    for item in &items {
+        if item.kind != InlineItemKind::Atomic {
+            item.object.borrow_mut().update_fragments_rect();
+        }
     }
 
-    pub fn node_kind(&self)-> NodeKind {
-        self.node.borrow().kind().clone()
+    (next_block, line_boxes)
+}
+
+/// インライン整形コンテキストに並べる要素の種類
+#[derive(Debug, Clone, PartialEq)]
+enum InlineItemKind {
+    /// インラインボックスの始まり. 幅は左のマージン・ボーダー・パディングの合計
+    Start,
+    /// インラインボックスの終わり. 幅は右のマージン・ボーダー・パディングの合計
+    End,
+    /// テキストのうち、改行できる位置で区切った部分
+    Text(String),
+    /// インラインレベルのフレックスコンテナや、インラインボックスの中のブロック. 分割できない1つの矩形として並べる
+    Atomic,
+    /// https://www.w3.org/TR/css-position-3/#staticpos-rect
+    /// 絶対位置指定されたボックス. 幅0で並べ、置かれた位置を静的位置にする
+    Placeholder,
+    /// https://www.w3.org/TR/CSS2/visuren.html#floats
+    /// レイアウト済みのフロート. 幅0で並べ、置かれた行をもとにフロートを置く位置を決める
+    Float,
+}
+
+#[derive(Debug, Clone)]
+struct InlineItem {
+    kind: InlineItemKind,
+    object: Rc<RefCell<LayoutObject>>,
+    width: i64,
+    /// 直後で改行できるか
+    break_after: bool,
+}
+
+impl InlineItem {
+    fn new(kind: InlineItemKind, object: &Rc<RefCell<LayoutObject>>, width: i64) -> Self {
+        Self {
+            kind,
+            object: object.clone(),
+            width,
+            break_after: false,
+        }
     }
 
-    pub fn set_first_child(&mut self, first_child: Option<Rc<RefCell<LayoutObject>>>) {
-        self.first_child = first_child;
+    /// https://www.w3.org/TR/css-text-3/#hanging
+    /// テキストの末尾の空白の幅. 行末の空白はぶら下がり、行に収まるかの判定に含めない
+    fn hanging_width(&self) -> i64 {
+        match &self.kind {
+            InlineItemKind::Text(text) => {
+                self.width - text_width(&self.object, text.trim_end_matches(' '))
+            }
+            _ => 0,
+        }
     }
+}
 
-    pub fn first_child(&self) -> Option<Rc<RefCell<LayoutObject>>> {
-        self.first_child.as_ref().cloned()
+/// 分割できないインラインレベルのボックスの幅の決め方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AtomicSizing {
+    /// レイアウトして決まった幅を使う
+    Layout,
+    /// https://www.w3.org/TR/css-sizing-3/#min-content
+    MinContent,
+    /// https://www.w3.org/TR/css-sizing-3/#max-content
+    MaxContent,
+}
+
+/// インラインレベルのレイアウトオブジェクトを文書順に辿り、インライン整形コンテキストに並べる要素を集める
+struct InlineItemCollector {
+    items: Vec<InlineItem>,
+    /// テキストの要素を連結した文字列. 要素の境界をまたいで改行できる位置を求めるのに使う
+    text: String,
+    /// 直前に追加した文字が空白か. 行頭の空白を取り除くため、最初はtrueにする
+    after_space: bool,
+    containing_width: i64,
+    origin: LayoutPoint,
+    atomic_sizing: AtomicSizing,
+}
+
+impl InlineItemCollector {
+    fn new(containing_width: i64, origin: LayoutPoint, atomic_sizing: AtomicSizing) -> Self {
+        Self {
+            items: Vec::new(),
+            text: String::new(),
+            after_space: true,
+            containing_width,
+            origin,
+            atomic_sizing,
+        }
     }
 
-    pub fn set_next_sibling(&mut self, next_sibling: Option<Rc<RefCell<LayoutObject>>>) {
-        self.next_sibling = next_sibling;
+    fn collect(&mut self, object: &Rc<RefCell<LayoutObject>>) {
+        // 外側のマーカーは行に並べず、リスト項目のレイアウトの後で置く
+        if object.borrow().is_outside_marker() {
+            return;
+        }
+        {
+            // 内容のないテキストやインラインボックスは、行の始点に大きさ0で置く
+            let mut object = object.borrow_mut();
+            object.fragments.clear();
+            object.point = self.origin;
+            object.size = LayoutSize::new(0, 0);
+        }
+
+        if object.borrow().is_absolutely_positioned() {
+            self.items
+                .push(InlineItem::new(InlineItemKind::Placeholder, object, 0));
+            return;
+        }
+        // 内在的な幅を求める場合は、フロートを分割できない矩形として行に並べる
+        if object.borrow().is_float() && self.atomic_sizing == AtomicSizing::Layout {
+            object
+                .borrow_mut()
+                .layout_atomic_inline(self.containing_width);
+            self.items
+                .push(InlineItem::new(InlineItemKind::Float, object, 0));
+            return;
+        }
+
+        let kind = object.borrow().kind();
+        match kind {
+            LayoutObjectKind::Text => {
+                let text = match object.borrow().node_kind() {
+                    NodeKind::Text(text) => text,
+                    _ => String::new(),
+                };
+                // https://www.w3.org/TR/css-text-3/#white-space-phase-1
+                // 改行とタブを含む連続した空白を、1つの空白にまとめる
+                let mut collapsed = String::new();
+                for c in text.chars() {
+                    if matches!(c, ' ' | '\n' | '\t' | '\r' | '\u{0C}') {
+                        if !self.after_space {
+                            collapsed.push(' ');
+                        }
+                        self.after_space = true;
+                    } else {
+                        collapsed.push(c);
+                        self.after_space = false;
+                    }
+                }
+                if !collapsed.is_empty() {
+                    self.text.push_str(&collapsed);
+                    self.items
+                        .push(InlineItem::new(InlineItemKind::Text(collapsed), object, 0));
+                }
+            }
+            LayoutObjectKind::Inline => {
+                let (left, right) = object
+                    .borrow_mut()
+                    .resolve_inline_edges(self.containing_width);
+                self.items
+                    .push(InlineItem::new(InlineItemKind::Start, object, left));
+                let mut child = object.borrow().first_child();
+                while let Some(c) = child {
+                    self.collect(&c);
+                    child = c.borrow().next_sibling();
+                }
+                self.items
+                    .push(InlineItem::new(InlineItemKind::End, object, right));
+            }
+            LayoutObjectKind::Block | LayoutObjectKind::AtomicInline => {
+                let width = match self.atomic_sizing {
+                    AtomicSizing::Layout => {
+                        let mut object = object.borrow_mut();
+                        if kind == LayoutObjectKind::Block {
+                            object.layout_block(self.containing_width, None, false);
+                        } else {
+                            object.layout_atomic_inline(self.containing_width);
+                        }
+                        object.size.width() + object.margin.horizontal()
+                    }
+                    AtomicSizing::MinContent | AtomicSizing::MaxContent => {
+                        let (min, max) = object.borrow_mut().intrinsic_widths();
+                        let margins = object.borrow().horizontal_margins(self.containing_width);
+                        if self.atomic_sizing == AtomicSizing::MinContent {
+                            min + margins
+                        } else {
+                            max + margins
+                        }
+                    }
+                };
+                self.items
+                    .push(InlineItem::new(InlineItemKind::Atomic, object, width));
+                self.after_space = false;
+            }
+        }
     }
 
-    pub fn next_sibling(&self) -> Option<Rc<RefCell<LayoutObject>>> {
-        self.next_sibling.as_ref().cloned()
+    /// テキストを改行できる位置で分割し、それぞれの要素の直後で改行できるかを決める
+    fn into_items(self) -> Vec<InlineItem> {
+        let opportunities = soft_wrap_opportunities(&self.text);
+        let mut items: Vec<InlineItem> = Vec::new();
+        // テキストの要素の、連結した文字列の中での開始位置（文字数）
+        let mut offset = 0;
+
+        for mut item in self.items {
+            let text = match &item.kind {
+                InlineItemKind::Text(text) => text.clone(),
+                InlineItemKind::Start | InlineItemKind::Placeholder | InlineItemKind::Float => {
+                    items.push(item);
+                    continue;
+                }
+                InlineItemKind::End => {
+                    // インラインボックスの末尾で改行できる場合は、ボックスの終わりの直後で改行する
+                    if let Some(last) = items.last_mut() {
+                        item.break_after = last.break_after;
+                        last.break_after = false;
+                    }
+                    items.push(item);
+                    continue;
+                }
+                InlineItemKind::Atomic => {
+                    // 分割できない矩形の前後では改行できる
+                    if let Some(last) = items
+                        .iter_mut()
+                        .rev()
+                        .find(|item| item.kind != InlineItemKind::Start)
+                    {
+                        last.break_after = true;
+                    }
+                    item.break_after = true;
+                    items.push(item);
+                    continue;
+                }
+            };
+
+            let chars: Vec<char> = text.chars().collect();
+            let end = offset + chars.len();
+            let mut start = offset;
+            let mut push = |start: usize, end: usize, break_after: bool| {
+                let piece: String = chars[start - offset..end - offset].iter().collect();
+                let mut piece_item = InlineItem::new(
+                    InlineItemKind::Text(piece.clone()),
+                    &item.object,
+                    text_width(&item.object, &piece),
+                );
+                piece_item.break_after = break_after;
+                items.push(piece_item);
+            };
+            for &opportunity in opportunities.iter().filter(|&&o| o > offset && o <= end) {
+                push(start, opportunity, true);
+                start = opportunity;
+            }
+            if start < end {
+                push(start, end, false);
+            }
+            offset = end;
+        }
+
+        items
     }
+}
 
-    pub fn parent(&self) -> Weak<RefCell<Self>> {
-        self.parent.clone()
+/// https://www.w3.org/TR/css-sizing-3/#intrinsic-sizes
+/// ブロックの子のうち、`first`から続くインラインレベルの子の最小コンテンツ幅と最大コンテンツ幅を求める
+/// 次のブロックの子と、2つの幅を返す
+fn inline_intrinsic_widths(
+    first: &Rc<RefCell<LayoutObject>>,
+) -> (Option<Rc<RefCell<LayoutObject>>>, i64, i64) {
+    let collect = |atomic_sizing: AtomicSizing| {
+        let mut collector = InlineItemCollector::new(0, LayoutPoint::new(0, 0), atomic_sizing);
+        let mut child = Some(first.clone());
+        while let Some(c) = child {
+            if c.borrow().is_in_flow_block() {
+                return (Some(c), collector.into_items());
+            }
+            collector.collect(&c);
+            child = c.borrow().next_sibling();
+        }
+        (None, collector.into_items())
+    };
+    // 最小コンテンツ幅は、改行できるすべての位置で改行した場合の最も長い行の幅になる
+    let (next_block, items) = collect(AtomicSizing::MinContent);
+    let min = widest_line(&items, 0);
+    // 最大コンテンツ幅は、まったく改行しない場合の行の幅になる
+    let (_, items) = collect(AtomicSizing::MaxContent);
+    let max = widest_line(&items, i64::MAX);
+    (next_block, min, max)
+}
+
+/// 幅`available_width`で改行した場合の、行末の空白を除いた最も長い行の幅
+fn widest_line(items: &[InlineItem], available_width: i64) -> i64 {
+    break_lines(items, available_width)
+        .into_iter()
+        .map(|range| {
+            let mut line = items[range].to_vec();
+            trim_hanging_spaces(&mut line);
+            line.iter().map(|item| item.width).sum::<i64>()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// https://www.w3.org/TR/css-text-3/#line-breaking
+/// 改行できる位置で区切った塊を、行の幅に収まる限り同じ行に並べる
+/// 1つの塊が行の幅より長い場合は、その塊だけを1行に置いてはみ出させる. 各行の要素の範囲を返す
+fn break_lines(items: &[InlineItem], available_width: i64) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    while start < items.len() {
+        let end = next_line_end(items, start, available_width);
+        lines.push(start..end);
+        start = end;
     }
+    lines
+}
 
-    pub fn style(&self) -> ComputedStyle {
-        self.style.clone()
+/// `line_start`の要素から始まる行に、幅`available_width`で並べられる要素の終わりの位置
+fn next_line_end(items: &[InlineItem], line_start: usize, available_width: i64) -> usize {
+    let mut line_width = 0;
+    let mut start = line_start;
+
+    while start < items.len() {
+        let mut end = start;
+        while !items[end].break_after && end + 1 < items.len() {
+            end += 1;
+        }
+        let chunk = &items[start..=end];
+        let width: i64 = chunk.iter().map(|item| item.width).sum();
+        let hanging = chunk
+            .iter()
+            .rev()
+            .find(|item| {
+                !matches!(
+                    item.kind,
+                    InlineItemKind::End | InlineItemKind::Placeholder | InlineItemKind::Float
+                )
+            })
+            .map_or(0, |item| item.hanging_width());
+
+        if start > line_start && line_width + width - hanging > available_width {
+            return start;
+        }
+        line_width += width;
+        start = end + 1;
     }
 
-    pub fn point(&self) -> LayoutPoint {
-        self.point
+    items.len()
+}
+
+/// https://www.w3.org/TR/CSS2/visuren.html#float-position
+/// レイアウト済みのフロートを、`point`を上端として幅`available_width`の範囲に置く
+/// 位置はブロックのボーダーボックスの左上からの相対位置で、`offset`はブロックのブロック整形コンテキストの中での位置
+fn place_float(
+    object: &Rc<RefCell<LayoutObject>>,
+    point: LayoutPoint,
+    available_width: i64,
+    floats: &mut FloatContext,
+    offset: LayoutPoint,
+) {
+    let mut object = object.borrow_mut();
+    let mut top = offset.y() + point.y();
+    // clearを指定されたフロートは、避けるフロートの下端より上には置かない
+    if let Some(bottom) = floats.clearance(object.style.clear()) {
+        top = top.max(bottom);
     }
+    let size = LayoutSize::new(
+        object.size.width() + object.margin.horizontal(),
+        object.size.height() + object.margin.vertical(),
+    );
+    let left = offset.x() + point.x();
+    let placed = floats.place(
+        object.style.float(),
+        size,
+        top,
+        left,
+        left + available_width,
+    );
+    object.point = LayoutPoint::new(
+        placed.x() - offset.x() + object.margin.left(),
+        placed.y() - offset.y() + object.margin.top(),
+    );
+}
 
-    pub fn size(&self) -> LayoutSize {
-        self.size
+/// https://www.w3.org/TR/css-text-3/#white-space-phase-2
+/// 行末の空白を取り除く
+fn trim_hanging_spaces(line: &mut [InlineItem]) {
+    if let Some(item) = line.iter_mut().rev().find(|item| {
+        !matches!(
+            item.kind,
+            InlineItemKind::End | InlineItemKind::Placeholder | InlineItemKind::Float
+        )
+    }) {
+        let hanging = item.hanging_width();
+        if let InlineItemKind::Text(text) = &mut item.kind {
+            let trimmed_len = text.trim_end_matches(' ').len();
+            text.truncate(trimmed_len);
+            item.width -= hanging;
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-text-3/#justify-algorithm
+/// 行の余白を単語の間の空白に均等に配分し、それぞれの要素の幅に加える大きさを返す
+fn justify(line: &[InlineItem], free: i64) -> Vec<i64> {
+    let spaces = |item: &InlineItem| match &item.kind {
+        InlineItemKind::Text(text) => text.matches(' ').count() as i64,
+        _ => 0,
+    };
+    let count: i64 = line.iter().map(spaces).sum();
+    if count == 0 {
+        return vec![0; line.len()];
+    }
+
+    // 割り切れない余りは、前の空白から1pxずつ配分する
+    let mut index = 0;
+    line.iter()
+        .map(|item| {
+            let mut extra = 0;
+            for _ in 0..spaces(item) {
+                extra += free / count + if index < free % count { 1 } else { 0 };
+                index += 1;
+            }
+            extra
+        })
+        .collect()
+}
+
+/// インラインボックスの、1つの行に置かれた部分のボーダーボックス
+/// 高さはフォントの高さに上下のパディングとボーダーを加えたもので、行の高さには影響しない
+fn box_fragment(
+    object: &Rc<RefCell<LayoutObject>>,
+    left: i64,
+    right: i64,
+    baseline: i64,
+) -> LineFragment {
+    let object = object.borrow();
+    let metrics = VerticalMetrics::new(&object.style);
+    LineFragment::new(
+        LayoutPoint::new(
+            left,
+            baseline - metrics.ascent - object.padding.top() - object.border.top(),
+        ),
+        LayoutSize::new(
+            (right - left).max(0),
+            metrics.ascent + metrics.descent + object.padding.vertical() + object.border.vertical(),
+        ),
+        String::new(),
+    )
+}
+
+/// https://www.w3.org/TR/css-inline-3/#inline-height
+/// 行の高さを求めるための、フォントの縦方向の大きさのピクセル値
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct VerticalMetrics {
+    ascent: i64,
+    descent: i64,
+    /// ハーフレディングを加えた、ベースラインより上の高さ
+    above: i64,
+    /// ハーフレディングを加えた、ベースラインより下の深さ
+    below: i64,
+}
+
+impl VerticalMetrics {
+    fn new(style: &ComputedStyle) -> Self {
+        let metrics = FontMetrics::new(style);
+        let ascent = round_px(metrics.ascent());
+        let descent = round_px(metrics.font_size()) - ascent;
+        // レディングが奇数の場合、余りは下に加える
+        let leading = round_px(metrics.line_height()) - ascent - descent;
+        let half_leading = leading.div_euclid(2);
+        Self {
+            ascent,
+            descent,
+            above: ascent + half_leading,
+            below: descent + leading - half_leading,
+        }
+    }
+}
+
+fn text_width(object: &Rc<RefCell<LayoutObject>>, text: &str) -> i64 {
+    round_px(FontMetrics::new(&object.borrow().style).text_width(text))
+}
+
+/// 長さの使用値をピクセル値にする. autoは0として扱う
+fn resolve_px(length: &ComputedLength, base: f64) -> i64 {
+    round_px(length.resolve(base).unwrap_or(0.0))
+}
+
+fn translate(point: LayoutPoint, origin: LayoutPoint) -> LayoutPoint {
+    LayoutPoint::new(point.x() + origin.x(), point.y() + origin.y())
+}
+
+/// 小数のピクセル値を、最も近い整数のピクセル値にする
+fn round_px(value: f64) -> i64 {
+    if value >= 0.0 {
+        (value + 0.5) as i64
+    } else {
+        (value - 0.5) as i64
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,31 @@
+use crate::renderer::layout::layout_point::LayoutPoint;
+use crate::renderer::layout::layout_size::LayoutSize;
+
+/// 位置と大きさで表す矩形. 包含ブロックやビューポートのような、ボックスを置く基準になる領域に使う
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub struct LayoutRect {
+    point: LayoutPoint,
+    size: LayoutSize,
+}
+
+impl LayoutRect {
+    pub fn new(point: LayoutPoint, size: LayoutSize) -> Self {
+        Self { point, size }
+    }
+
+    pub fn point(&self) -> LayoutPoint {
+        self.point
+    }
+
+    pub fn size(&self) -> LayoutSize {
+        self.size
+    }
+
+    pub fn right(&self) -> i64 {
+        self.point.x() + self.size.width()
+    }
+
+    pub fn bottom(&self) -> i64 {
+        self.point.y() + self.size.height()
+    }
+}
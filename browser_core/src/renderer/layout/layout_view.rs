@@ -1,10 +1,24 @@
-use crate::renderer::css::cssom::StyleSheet;
+use crate::constants::CONTENT_AREA_HEIGHT;
+use crate::constants::CONTENT_AREA_WIDTH;
+use crate::renderer::css::cssom::PseudoElement;
 use crate::renderer::dom::api::get_target_element_node;
 use crate::renderer::dom::node::ElementKind;
 use crate::renderer::dom::node::Node;
+use crate::renderer::dom::node::NodeKind;
+use crate::renderer::layout::computed_style::DisplayType;
+use crate::renderer::layout::computed_style::Position;
+use crate::renderer::layout::computed_style::TextDecoration;
+use crate::renderer::layout::generated_content::CounterState;
 use crate::renderer::layout::layout_object::create_layout_object;
 use crate::renderer::layout::layout_object::LayoutObject;
+use crate::renderer::layout::layout_object::LayoutObjectKind;
+use crate::renderer::layout::layout_point::LayoutPoint;
+use crate::renderer::layout::layout_rect::LayoutRect;
+use crate::renderer::layout::layout_size::LayoutSize;
+use crate::renderer::layout::style_engine::StyleEngine;
 use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
 use core::cell::RefCell;
 
 #[derive(Debug, Clone)]
@@ -13,15 +27,30 @@ pub struct LayoutView {
 }
 
 impl LayoutView {
-    pub fn new(root: Rc<RefCell<Node>>, cssom: &StyleSheet) -> Self {
+    /// `engine`が保持しているスタイルから、レイアウトツリーを構築する
+    /// 再計算が必要な印のついたノードのスタイルだけを計算し直し、他のノードは保持している計算値を使う
+    pub fn new(root: Rc<RefCell<Node>>, engine: &StyleEngine) -> Self {
+        engine.restyle(&root);
+
         // レイアウトツリーは描画される要素のみを持つツリーなので、<body>タグを取得し
         // その子要素以下をレイアウトツリーのノードに変換する
+        // ルート要素（<html>）のボックスは作らないが、<body>の計算値はルート要素から継承している
+        // ルート要素がdisplay: noneの場合は、何も描画しない
+        if let Some(html) = get_target_element_node(Some(root.clone()), ElementKind::Html) {
+            if engine.get_computed_style(&html).display() == DisplayType::DisplayNone {
+                return Self { root: None };
+            }
+        }
         let body_root = get_target_element_node(Some(root), ElementKind::Body);
 
         let mut tree = Self {
-            root: build_layout_tree(&body_root, &None, cssom),
+            root: build_layout_tree(&body_root, &None, engine, &mut CounterState::new()),
         };
 
+        if let Some(root) = &tree.root {
+            root.borrow_mut()
+                .propagate_text_decoration(TextDecoration::None);
+        }
         tree.update_layout();
 
         tree
@@ -30,25 +59,87 @@ impl LayoutView {
     pub fn root(&self) -> Option<Rc<RefCell<LayoutObject>>> {
         self.root.clone()
     }
+
+    /// レイアウトツリーのすべてのレイアウトオブジェクトの大きさと位置を求める
+    /// ビューポート（コンテンツ領域）が、ルートの包含ブロックになる
+    /// https://www.w3.org/TR/css-position-3/#positioning-scheme
+    /// 位置指定されたボックスは、通常フローのレイアウトが終わった後に置く
+    fn update_layout(&mut self) {
+        if let Some(root) = &self.root {
+            let viewport = LayoutRect::new(
+                LayoutPoint::new(0, 0),
+                LayoutSize::new(CONTENT_AREA_WIDTH, CONTENT_AREA_HEIGHT),
+            );
+            root.borrow_mut().layout_root(viewport.size());
+
+            // ルートは通常フローに置いたままにし、位置指定されている場合は子孫の包含ブロックにだけなる
+            let containing_block = if root.borrow().style().position().is_positioned() {
+                root.borrow().padding_box()
+            } else {
+                viewport
+            };
+            let flow_box = root.borrow().content_box();
+            layout_positioned_descendants(root, flow_box, containing_block, viewport);
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-position-3/#positioning-scheme
+/// `object`の子孫のうち、位置指定されたボックスを文書順に置く
+/// 祖先を先に置くので、子孫は祖先を動かした後の位置を基準にする
+/// `flow_box`は通常フローの包含ブロック（最も近いブロックコンテナのコンテンツボックス）、`containing_block`は
+/// 絶対位置指定されたボックスの包含ブロック（最も近い位置指定された祖先のパディングボックス）
+/// 固定位置指定されたボックスと粘着位置指定されたボックスは、ビューポート`viewport`を基準にする
+fn layout_positioned_descendants(
+    object: &Rc<RefCell<LayoutObject>>,
+    flow_box: LayoutRect,
+    containing_block: LayoutRect,
+    viewport: LayoutRect,
+) {
+    let mut child = object.borrow().first_child();
+    while let Some(c) = child {
+        let position = c.borrow().style().position();
+        match position {
+            Position::Static => {}
+            Position::Relative => c.borrow_mut().apply_relative_offset(flow_box.size()),
+            Position::Sticky => c.borrow_mut().apply_sticky_offset(flow_box, viewport),
+            Position::Absolute => c.borrow_mut().layout_absolute(containing_block),
+            Position::Fixed => c.borrow_mut().layout_absolute(viewport),
+        }
+
+        let child_flow_box = match c.borrow().kind() {
+            LayoutObjectKind::Inline | LayoutObjectKind::Text => flow_box,
+            _ => c.borrow().content_box(),
+        };
+        let child_containing_block = if position.is_positioned() {
+            c.borrow().padding_box()
+        } else {
+            containing_block
+        };
+        layout_positioned_descendants(&c, child_flow_box, child_containing_block, viewport);
+        child = c.borrow().next_sibling();
+    }
 }
 
 /// 再帰的に呼び出しレイアウトツリーを構築していく
 /// DOMツリーをルートノードから走査しながらDOMノードからレイアウトオブジェクトを作成する
+/// DOMツリーを文書順に辿るので、その順でカウンターを更新し、疑似要素の内容に使う
 fn build_layout_tree(
     node: &Option<Rc<RefCell<Node>>>,
     parent_obj: &Option<Rc<RefCell<LayoutObject>>>,
-    cssom: &StyleSheet,
+    engine: &StyleEngine,
+    counters: &mut CounterState,
 ) -> Option<Rc<RefCell<LayoutObject>>> {
     // DOMノードに対応するレイアウトオブジェクトを生成
     // ただし"display:none"が指定されていた場合、ノードは生成しない
     let mut target_node = node.clone();
-    let mut layout_object = create_layout_object(node, parent_obj, cssom);
+    let mut layout_object = create_layout_object(node, parent_obj, engine);
 
     // ノードが生成されなかった場合、兄弟ノードを使用してレイアウトオブジェクトの生成を試みる（生成されるまで兄弟ノードを辿り続ける）
     while layout_object.is_none() {
         if let Some(n) = target_node {
             target_node = n.borrow().next_sibling().clone();
-            layout_object = create_layout_object(&target_node, parent_obj, cssom);
+            layout_object = create_layout_object(&target_node, parent_obj, engine);
         } else {
             // 兄弟ノードがなくなれば、処理すべきDOMツリーは終了したのでこれまでのレイアウトツリーを返す
             return layout_object;
@@ -56,11 +147,31 @@ fn build_layout_tree(
     }
 
     if let Some(n) = target_node {
+        let obj = match layout_object {
+            Some(ref obj) => obj.clone(),
+            None => panic!("render object should exist here"),
+        };
+        if n.borrow().get_element().is_some() {
+            counters.apply(&n, &obj.borrow().style());
+        }
+        let marker = LayoutObject::create_pseudo_element_object(
+            &obj,
+            PseudoElement::Marker,
+            engine,
+            counters,
+        );
+        let before = LayoutObject::create_pseudo_element_object(
+            &obj,
+            PseudoElement::Before,
+            engine,
+            counters,
+        );
+
         let original_first_child = n.borrow().first_child();
         let original_next_sibling = n.borrow().next_sibling();
         // 子ノード・兄弟ノードに対して再帰的にレイアウトオブジェクトを生成
-        let mut first_child = build_layout_tree(&original_first_child, layout_object, cssom);
-        let mut next_sibling = build_layout_tree(&original_next_sibling, &None, cssom);
+        let mut first_child =
+            build_layout_tree(&original_first_child, &layout_object, engine, counters);
 
         // ノードが生成されなかった場合、兄弟ノードを使用してレイアウトオブジェクトの生成を試みる（生成されるまで兄弟ノードを辿り続ける）
         if first_child.is_none() && original_first_child.is_some() {
@@ -70,7 +181,8 @@ fn build_layout_tree(
                 .next_sibling();
 
             loop {
-                first_child = build_layout_tree(&original_dom_node, &layout_object, cssom);
+                first_child =
+                    build_layout_tree(&original_dom_node, &layout_object, engine, counters);
 
                 if first_child.is_none() && original_dom_node.is_some() {
                     original_dom_node = original_dom_node
@@ -83,6 +195,19 @@ fn build_layout_tree(
                 break;
             }
         }
+
+        // ::afterは子孫の後に生成されるので、子孫が作ったカウンターはスコープから外す
+        counters.enter(&n);
+        let after = LayoutObject::create_pseudo_element_object(
+            &obj,
+            PseudoElement::After,
+            engine,
+            counters,
+        );
+        let first_child = link_pseudo_elements(marker, before, first_child, after);
+
+        let mut next_sibling =
+            build_layout_tree(&original_next_sibling, parent_obj, engine, counters);
         if next_sibling.is_none() && n.borrow().next_sibling().is_some() {
             let mut original_dom_node = original_next_sibling
                 .expect("first child should exist")
@@ -90,7 +215,7 @@ fn build_layout_tree(
                 .next_sibling();
 
             loop {
-                next_sibling = build_layout_tree(&original_dom_node, &None, cssom);
+                next_sibling = build_layout_tree(&original_dom_node, parent_obj, engine, counters);
 
                 if next_sibling.is_none() && original_dom_node.is_some() {
                     original_dom_node = original_dom_node
@@ -104,13 +229,1539 @@ fn build_layout_tree(
             }
         }
 
-        let obj = match layout_object {
-            Some(ref obj) => obj,
-            None => panic!("render object should exist here"),
-        };
         obj.borrow_mut().set_first_child(first_child);
         obj.borrow_mut().set_next_sibling(next_sibling);
+        if obj.borrow().kind() != LayoutObjectKind::Inline {
+            generate_anonymous_blocks(&obj);
+        }
     }
 
     layout_object
 }
+
+/// ::markerのボックスを最初の子、::beforeのボックスをその次の子、::afterのボックスを最後の子として、子のリストにつなぐ
+fn link_pseudo_elements(
+    marker: Option<Rc<RefCell<LayoutObject>>>,
+    before: Option<Rc<RefCell<LayoutObject>>>,
+    first_child: Option<Rc<RefCell<LayoutObject>>>,
+    after: Option<Rc<RefCell<LayoutObject>>>,
+) -> Option<Rc<RefCell<LayoutObject>>> {
+    let mut first = first_child;
+
+    if let Some(after) = after {
+        let mut last = first.clone();
+        while let Some(next) = last.as_ref().and_then(|l| l.borrow().next_sibling()) {
+            last = Some(next);
+        }
+        match last {
+            Some(last) => last.borrow_mut().set_next_sibling(Some(after)),
+            None => first = Some(after),
+        }
+    }
+
+    if let Some(before) = before {
+        before.borrow_mut().set_next_sibling(first);
+        first = Some(before);
+    }
+
+    if let Some(marker) = marker {
+        marker.borrow_mut().set_next_sibling(first);
+        first = Some(marker);
+    }
+
+    first
+}
+
+/// https://www.w3.org/TR/CSS2/visuren.html#anonymous-block-level
+/// ブロックコンテナがブロックレベルの子を持つ場合、連続するインラインレベルの子を匿名ブロックボックスで包み、
+/// 子をすべてブロックレベルにする. ブロックレベルのボックスを含むインラインボックスは、その前後で分割する
+/// https://www.w3.org/TR/css-flexbox-1/#flex-items
+/// フレックスコンテナの子のうち、連続するテキストも匿名ブロックボックスで包んでフレックスアイテムにする
+/// https://www.w3.org/TR/css-grid-2/#grid-items
+/// グリッドコンテナの場合も同じく、連続するテキストをグリッドアイテムにする
+/// https://www.w3.org/TR/CSS2/tables.html#anonymous-boxes
+/// 表・行グループ・行の中の連続するテキストは、匿名のセルになるように包む
+fn generate_anonymous_blocks(container: &Rc<RefCell<LayoutObject>>) {
+    let children: Vec<Rc<RefCell<LayoutObject>>> = children(container)
+        .iter()
+        .flat_map(split_inline_around_blocks)
+        .collect();
+    let display = container.borrow().style().display();
+    let is_item_container = display.is_flex_container()
+        || display.is_grid_container()
+        || display.is_table()
+        || display.is_table_row_group()
+        || display == DisplayType::TableRow;
+    if !is_item_container && !children.iter().any(is_block_level) {
+        link_children(container, children);
+        return;
+    }
+
+    let mut blocks = Vec::new();
+    let mut inlines = Vec::new();
+    for child in children {
+        // https://www.w3.org/TR/css-flexbox-1/#abspos-items
+        // 絶対位置指定された子は、インラインレベルの兄弟の途中にある場合だけ一緒に包む
+        // フレックスコンテナとグリッドコンテナでは、アイテムにならないので包まない
+        let is_out_of_flow =
+            child.borrow().is_out_of_flow() && (is_item_container || is_collapsible_run(&inlines));
+        // https://www.w3.org/TR/css-lists-3/#list-style-position-property
+        // 外側のマーカーは行に並べないので、匿名ブロックボックスで包まない
+        let is_out_of_flow = is_out_of_flow || child.borrow().is_outside_marker();
+        if is_block_level(&child) || is_out_of_flow {
+            wrap_in_anonymous_block(container, &mut inlines, &mut blocks);
+            blocks.push(child);
+        } else {
+            inlines.push(child);
+        }
+    }
+    wrap_in_anonymous_block(container, &mut inlines, &mut blocks);
+    link_children(container, blocks);
+}
+
+/// ブロックレベルの子孫を含むインラインボックスを、ブロックレベルのボックスの前後で分割する
+/// 分割したインラインボックスの部分とブロックレベルのボックスを、文書順に並べて返す
+fn split_inline_around_blocks(
+    object: &Rc<RefCell<LayoutObject>>,
+) -> Vec<Rc<RefCell<LayoutObject>>> {
+    if object.borrow().kind() != LayoutObjectKind::Inline {
+        return vec![object.clone()];
+    }
+
+    let children: Vec<Rc<RefCell<LayoutObject>>> = children(object)
+        .iter()
+        .flat_map(split_inline_around_blocks)
+        .collect();
+    if !children.iter().any(is_block_level) {
+        link_children(object, children);
+        return vec![object.clone()];
+    }
+
+    // ブロックレベルのボックスの前後には、内容がなくてもインラインボックスの部分を置く
+    let mut result = Vec::new();
+    let mut piece = object.clone();
+    let mut piece_children = Vec::new();
+    for child in children {
+        if is_block_level(&child) {
+            let continuation = piece.borrow_mut().split_inline();
+            link_children(&piece, core::mem::take(&mut piece_children));
+            result.push(piece);
+            result.push(child);
+            piece = continuation;
+        } else {
+            piece_children.push(child);
+        }
+    }
+    link_children(&piece, piece_children);
+    result.push(piece);
+    result
+}
+
+/// 連続するインラインレベルのボックスを匿名ブロックボックスで包み、`blocks`に加える
+/// https://www.w3.org/TR/CSS2/visuren.html#anonymous
+/// 空白の並びだけで、空白の処理で取り除かれるテキストは、ボックスを作らない
+fn wrap_in_anonymous_block(
+    container: &Rc<RefCell<LayoutObject>>,
+    inlines: &mut Vec<Rc<RefCell<LayoutObject>>>,
+    blocks: &mut Vec<Rc<RefCell<LayoutObject>>>,
+) {
+    let inlines = core::mem::take(inlines);
+    if is_collapsible_run(&inlines) {
+        return;
+    }
+
+    let anonymous = LayoutObject::create_anonymous_block(container);
+    link_children(&anonymous, inlines);
+    blocks.push(anonymous);
+}
+
+/// 連続するインラインレベルのボックスが、空白の処理で取り除かれるテキストだけか
+fn is_collapsible_run(inlines: &[Rc<RefCell<LayoutObject>>]) -> bool {
+    inlines
+        .iter()
+        .all(|object| match object.borrow().node_kind() {
+            NodeKind::Text(text) if object.borrow().kind() == LayoutObjectKind::Text => text
+                .chars()
+                .all(|c| matches!(c, ' ' | '\n' | '\t' | '\r' | '\u{0C}')),
+            _ => false,
+        })
+}
+
+/// 通常フローに置かれるブロックレベルのボックスか
+fn is_block_level(object: &Rc<RefCell<LayoutObject>>) -> bool {
+    object.borrow().is_in_flow_block()
+}
+
+fn children(object: &Rc<RefCell<LayoutObject>>) -> Vec<Rc<RefCell<LayoutObject>>> {
+    let mut children = Vec::new();
+    let mut child = object.borrow().first_child();
+    while let Some(c) = child {
+        child = c.borrow().next_sibling();
+        children.push(c);
+    }
+    children
+}
+
+/// `children`を順に兄弟としてつなぎ、`parent`の子にする
+fn link_children(parent: &Rc<RefCell<LayoutObject>>, children: Vec<Rc<RefCell<LayoutObject>>>) {
+    let mut next = None;
+    for child in children.into_iter().rev() {
+        child.borrow_mut().set_next_sibling(next);
+        child.borrow_mut().set_parent(Rc::downgrade(parent));
+        next = Some(child);
+    }
+    parent.borrow_mut().set_first_child(next);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::cssom::CssParser;
+    use crate::renderer::css::media::MediaEnvironment;
+    use crate::renderer::css::token::CssTokenizer;
+    use crate::renderer::dom::api::get_style_contents;
+    use crate::renderer::dom::api::get_target_element_nodes;
+    use crate::renderer::html::parser::HtmlParser;
+    use crate::renderer::html::token::HtmlTokenizer;
+    use crate::renderer::layout::layout_edges::LayoutEdges;
+    use crate::renderer::layout::layout_point::LayoutPoint;
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::string::ToString;
+
+    fn create_layout_view(html: &str) -> LayoutView {
+        let window = HtmlParser::new(HtmlTokenizer::new(html.to_string())).construct_tree();
+        let dom = window.borrow().document();
+        let style = get_style_contents(dom.clone()).concat();
+        let cssom = CssParser::new(CssTokenizer::new(style)).parse_stylesheet();
+        LayoutView::new(dom, &StyleEngine::new(cssom, MediaEnvironment::default()))
+    }
+
+    /// レイアウトツリーを、1行に1つのレイアウトオブジェクトを書いた文字列にする
+    fn dump(view: &LayoutView) -> String {
+        let mut result = String::new();
+        dump_internal(&view.root(), 0, &mut result);
+        result
+    }
+
+    fn dump_internal(node: &Option<Rc<RefCell<LayoutObject>>>, depth: usize, result: &mut String) {
+        let mut current = node.clone();
+        while let Some(n) = current {
+            let name = match (n.borrow().pseudo(), n.borrow().node_kind()) {
+                _ if n.borrow().is_anonymous() => "(anonymous)".to_string(),
+                (Some(pseudo), _) => format!("::{}", pseudo.name()),
+                (None, NodeKind::Element(element)) => element.kind().to_string(),
+                (None, NodeKind::Text(text)) => format!("{:?}", text),
+                (None, NodeKind::Document) => "#document".to_string(),
+            };
+            result.push_str(&format!(
+                "{}{:?} {}\n",
+                "  ".repeat(depth),
+                n.borrow().kind(),
+                name
+            ));
+            dump_internal(&n.borrow().first_child(), depth + 1, result);
+            current = n.borrow().next_sibling();
+        }
+    }
+
+    /// レイアウトツリーを、レイアウトオブジェクトごとにボーダーボックスの位置と大きさを書いた文字列にする
+    fn dump_geometry(view: &LayoutView) -> String {
+        let mut result = String::new();
+        dump_geometry_internal(&view.root(), 0, &mut result);
+        result
+    }
+
+    fn dump_geometry_internal(
+        node: &Option<Rc<RefCell<LayoutObject>>>,
+        depth: usize,
+        result: &mut String,
+    ) {
+        let mut current = node.clone();
+        while let Some(n) = current {
+            let name = match (n.borrow().pseudo(), n.borrow().node_kind()) {
+                _ if n.borrow().is_anonymous() => "(anonymous)".to_string(),
+                (Some(pseudo), _) => format!("::{}", pseudo.name()),
+                (None, NodeKind::Element(element)) => element.kind().to_string(),
+                (None, _) => "#text".to_string(),
+            };
+            let point = n.borrow().point();
+            let size = n.borrow().size();
+            result.push_str(&format!(
+                "{}{} ({}, {}) {}x{}\n",
+                "  ".repeat(depth),
+                name,
+                point.x(),
+                point.y(),
+                size.width(),
+                size.height()
+            ));
+            dump_geometry_internal(&n.borrow().first_child(), depth + 1, result);
+            current = n.borrow().next_sibling();
+        }
+    }
+
+    /// ブロックの行ボックスと、インラインボックスとテキストの行ごとの断片を、1行に1つずつ書いた文字列にする
+    /// 行ボックスは`line (x, y) 幅x高さ ベースライン`の形式で書く
+    fn dump_fragments(view: &LayoutView) -> String {
+        let mut result = String::new();
+        dump_fragments_internal(&view.root(), &mut result);
+        result
+    }
+
+    fn dump_fragments_internal(node: &Option<Rc<RefCell<LayoutObject>>>, result: &mut String) {
+        let mut current = node.clone();
+        while let Some(n) = current {
+            for line in n.borrow().line_boxes() {
+                result.push_str(&format!(
+                    "line ({}, {}) {}x{} {}\n",
+                    line.point().x(),
+                    line.point().y(),
+                    line.size().width(),
+                    line.size().height(),
+                    line.baseline()
+                ));
+            }
+            for fragment in n.borrow().fragments() {
+                let name = match (n.borrow().pseudo(), n.borrow().node_kind()) {
+                    _ if n.borrow().is_anonymous() => "(anonymous)".to_string(),
+                    (Some(pseudo), _) => format!("::{}", pseudo.name()),
+                    (None, NodeKind::Element(element)) => element.kind().to_string(),
+                    (None, _) => format!("{:?}", fragment.text()),
+                };
+                result.push_str(&format!(
+                    "{} ({}, {}) {}x{}\n",
+                    name,
+                    fragment.point().x(),
+                    fragment.point().y(),
+                    fragment.size().width(),
+                    fragment.size().height()
+                ));
+            }
+            dump_fragments_internal(&n.borrow().first_child(), result);
+            current = n.borrow().next_sibling();
+        }
+    }
+
+    #[test]
+    fn test_body() {
+        /* <body>要素がブロックのルートになることを確認する */
+        let view = create_layout_view("<html><head></head><body></body></html>");
+        assert_eq!(dump(&view), "Block body\n");
+    }
+
+    #[test]
+    fn test_block_and_inline() {
+        /* displayの値によって、ブロック・インライン・テキストのレイアウトオブジェクトが作られることを確認する */
+        let view =
+            create_layout_view("<html><head></head><body><p><a>b</a></p><h1>c</h1></body></html>");
+        assert_eq!(
+            dump(&view),
+            "Block body\n\
+             \x20 Block p\n\
+             \x20   Inline a\n\
+             \x20     Text \"b\"\n\
+             \x20 Block h1\n\
+             \x20   Text \"c\"\n"
+        );
+    }
+
+    #[test]
+    fn test_style_rules() {
+        /* タイプ・クラス・idセレクタで指定したdisplayが、レイアウトツリーに反映されることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>a { display: block } .hidden { display: none } #inline { display: inline }</style></head>\
+             <body><p class=\"hidden\">x</p><p><a>y</a></p><h1 id=\"inline\">z</h1><p>w</p></body></html>",
+        );
+        assert_eq!(
+            dump(&view),
+            "Block body\n\
+             \x20 Block p\n\
+             \x20   Block a\n\
+             \x20     Text \"y\"\n\
+             \x20 Block (anonymous)\n\
+             \x20   Inline h1\n\
+             \x20     Text \"z\"\n\
+             \x20 Block p\n\
+             \x20   Text \"w\"\n"
+        );
+    }
+
+    #[test]
+    fn test_display_none() {
+        /* display: noneの要素は、子孫とともにレイアウトツリーから除かれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: none }</style></head>\
+             <body><p>x<a>y</a></p><p>z</p><a>w</a><p>v</p></body></html>",
+        );
+        assert_eq!(dump(&view), "Block body\n  Inline a\n    Text \"w\"\n");
+
+        let view = create_layout_view(
+            "<html><head><style>body { display: none }</style></head><body><p>x</p></body></html>",
+        );
+        assert!(view.root().is_none());
+    }
+
+    #[test]
+    fn test_inherited_style() {
+        /* 最初の子だけでなく、後続の兄弟要素も親要素のスタイルを継承することを確認する */
+        let view = create_layout_view(
+            "<html><head><style>body { font-size: 32px }</style></head>\
+             <body><p>a</p><p>b</p><a>c</a></body></html>",
+        );
+        let body = view.root().expect("root should exist");
+        let p1 = body.borrow().first_child().expect("p should exist");
+        let p2 = p1.borrow().next_sibling().expect("p should exist");
+        let a = p2.borrow().next_sibling().expect("a should exist");
+
+        assert_eq!(p1.borrow().style().font_size(), 32.0);
+        assert_eq!(p2.borrow().style().font_size(), 32.0);
+        assert_eq!(a.borrow().style().font_size(), 32.0);
+    }
+
+    #[test]
+    fn test_cascade_specificity() {
+        /* 詳細度の高いセレクタの宣言が、出現順によらず優先されることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>#x { font-size: 32px } .c { font-size: 24px } p { font-size: 8px } \
+             #x::before { content: \"id\" } p::before { content: \"type\" }</style></head>\
+             <body><p id=\"x\" class=\"c\">a</p><p class=\"c\">b</p><p>c</p></body></html>",
+        );
+        let body = view.root().expect("root should exist");
+        let p1 = body.borrow().first_child().expect("p should exist");
+        let p2 = p1.borrow().next_sibling().expect("p should exist");
+        let p3 = p2.borrow().next_sibling().expect("p should exist");
+
+        assert_eq!(p1.borrow().style().font_size(), 32.0);
+        assert_eq!(p2.borrow().style().font_size(), 24.0);
+        assert_eq!(p3.borrow().style().font_size(), 8.0);
+        assert_eq!(
+            dump(&view),
+            "Block body\n\
+             \x20 Block p\n\
+             \x20   Inline ::before\n\
+             \x20     Text \"id\"\n\
+             \x20   Text \"a\"\n\
+             \x20 Block p\n\
+             \x20   Inline ::before\n\
+             \x20     Text \"type\"\n\
+             \x20   Text \"b\"\n\
+             \x20 Block p\n\
+             \x20   Inline ::before\n\
+             \x20     Text \"type\"\n\
+             \x20   Text \"c\"\n"
+        );
+    }
+
+    #[test]
+    fn test_stored_styles() {
+        /* レイアウトツリーの構築が、スタイルエンジンが保持している計算値を使い、印のついたノードだけを再計算することを確認する */
+        let window = HtmlParser::new(HtmlTokenizer::new(
+            "<html><head></head><body><p>a</p><p>b</p></body></html>".to_string(),
+        ))
+        .construct_tree();
+        let document = window.borrow().document();
+        let sheet = CssParser::new(CssTokenizer::new(".big { font-size: 32px }".to_string()))
+            .parse_stylesheet();
+        let engine = StyleEngine::new(sheet, MediaEnvironment::default());
+        LayoutView::new(document.clone(), &engine);
+        // 構築の前に再計算するので、印のついたノードは残らない
+        assert_eq!(engine.restyle(&document), 0);
+
+        let paragraphs = get_target_element_nodes(Some(document.clone()), ElementKind::P);
+        engine.set_attribute(&paragraphs[0], "class", "big");
+        // 印のついていないノードは、保持している計算値がそのまま使われる
+        let mut hidden = paragraphs[1]
+            .borrow()
+            .computed_style()
+            .expect("style should be computed");
+        hidden.set_display(DisplayType::DisplayNone);
+        paragraphs[1].borrow_mut().set_computed_style(Some(hidden));
+
+        let view = LayoutView::new(document.clone(), &engine);
+        assert_eq!(engine.restyle(&document), 0);
+        assert_eq!(dump(&view), "Block body\n  Block p\n    Text \"a\"\n");
+        let p = view
+            .root()
+            .and_then(|body| body.borrow().first_child())
+            .expect("p should exist");
+        assert_eq!(p.borrow().style().font_size(), 32.0);
+    }
+
+    #[test]
+    fn test_pseudo_elements() {
+        /* ::beforeと::afterのボックスが、生成されたテキストとともに最初と最後の子になることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>body { counter-reset: n } p { counter-increment: n } \
+             p::before { content: counter(n) \". \" } a::after { content: \" (\" attr(href) \")\" } \
+             h1::before { content: none } h1::after { content: \"!\"; display: block }</style></head>\
+             <body><p>a</p><p><a href=\"/x\">b</a></p><h1></h1></body></html>",
+        );
+        assert_eq!(
+            dump(&view),
+            "Block body\n\
+             \x20 Block p\n\
+             \x20   Inline ::before\n\
+             \x20     Text \"1. \"\n\
+             \x20   Text \"a\"\n\
+             \x20 Block p\n\
+             \x20   Inline ::before\n\
+             \x20     Text \"2. \"\n\
+             \x20   Inline a\n\
+             \x20     Text \"b\"\n\
+             \x20     Inline ::after\n\
+             \x20       Text \" (/x)\"\n\
+             \x20 Block h1\n\
+             \x20   Block ::after\n\
+             \x20     Text \"!\"\n"
+        );
+    }
+
+    #[test]
+    fn test_size_and_position() {
+        /* ブロックは縦に、インラインとテキストは横に並ぶことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { line-height: 20px } p::before { content: \"ab\" }</style></head>\
+             <body><p>cde</p><p>f</p></body></html>",
+        );
+        let body = view.root().expect("root should exist");
+        let p1 = body.borrow().first_child().expect("p should exist");
+        let before = p1.borrow().first_child().expect("::before should exist");
+        let text = before.borrow().next_sibling().expect("text should exist");
+        let p2 = p1.borrow().next_sibling().expect("p should exist");
+
+        assert_eq!(
+            body.borrow().size(),
+            LayoutSize::new(CONTENT_AREA_WIDTH, 40)
+        );
+        assert_eq!(p1.borrow().size(), LayoutSize::new(CONTENT_AREA_WIDTH, 20));
+        // インラインボックスの高さはフォントの高さで、行の高さの中でベースラインに揃えて置かれる
+        assert_eq!(before.borrow().size(), LayoutSize::new(16, 16));
+        assert_eq!(before.borrow().point(), LayoutPoint::new(0, 2));
+        assert_eq!(text.borrow().size(), LayoutSize::new(24, 20));
+        assert_eq!(text.borrow().point(), LayoutPoint::new(16, 0));
+        assert_eq!(p2.borrow().point(), LayoutPoint::new(0, 20));
+    }
+
+    #[test]
+    fn test_box_model() {
+        /* 幅がautoのブロックが、マージン・ボーダー・パディングを除いた包含ブロックの幅に広がることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>body { margin: 10px } \
+             p { margin: 5px 20px; padding: 2px 10%; border: 3px solid black; line-height: 16px }</style></head>\
+             <body><p>a</p></body></html>",
+        );
+        // ボディの幅は590 - 20 = 570px、pの内容の幅は570 - 40 - 6 - 114 = 410px
+        assert_eq!(
+            dump_geometry(&view),
+            "body (10, 10) 570x36\n\
+             \x20 p (30, 15) 530x26\n\
+             \x20   #text (90, 20) 8x16\n"
+        );
+        let p = view
+            .root()
+            .and_then(|body| body.borrow().first_child())
+            .expect("p should exist");
+        assert_eq!(p.borrow().padding(), LayoutEdges::new(2, 57, 2, 57));
+        assert_eq!(p.borrow().border(), LayoutEdges::new(3, 3, 3, 3));
+    }
+
+    #[test]
+    fn test_width_and_auto_margins() {
+        /* 幅を指定したブロックの左右のマージンが、autoの指定と過剰な制約に従って決まることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { height: 10px } .center { width: 190px; margin: 0 auto } \
+             .right { width: 50%; margin-left: auto } .over { width: 700px; margin: 0 auto } \
+             .fixed { width: 100px; margin: 0 10px }</style></head>\
+             <body><p class=\"center\"></p><p class=\"right\"></p><p class=\"over\"></p><p class=\"fixed\"></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x40\n\
+             \x20 p (200, 0) 190x10\n\
+             \x20 p (295, 10) 295x10\n\
+             \x20 p (0, 20) 700x10\n\
+             \x20 p (10, 30) 100x10\n"
+        );
+        let fixed = view
+            .root()
+            .and_then(|body| body.borrow().first_child())
+            .and_then(|p| p.borrow().next_sibling())
+            .and_then(|p| p.borrow().next_sibling())
+            .and_then(|p| p.borrow().next_sibling())
+            .expect("p should exist");
+        assert_eq!(fixed.borrow().margin(), LayoutEdges::new(0, 480, 0, 10));
+    }
+
+    #[test]
+    fn test_height() {
+        /* 高さの指定と、包含ブロックの高さが決まっている場合だけパーセンテージが解決されることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>body { height: 50% } p { height: 20% } h1 { height: 30px } \
+             h2 { height: 50% }</style></head>\
+             <body><p></p><h1><h2></h2></h1><h2></h2></body></html>",
+        );
+        // ビューポートの高さは364pxなので、ボディは182px、pは36px
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x182\n\
+             \x20 p (0, 0) 590x36\n\
+             \x20 h1 (0, 36) 590x30\n\
+             \x20   h2 (0, 36) 590x15\n\
+             \x20 h2 (0, 66) 590x91\n"
+        );
+    }
+
+    #[test]
+    fn test_sibling_margin_collapsing() {
+        /* 隣接する兄弟の上下のマージンが、正の最大値と負の最小値の和に折り畳まれることを確認する */
+        // ルートのボディはマージンを折り畳まないので、最後の子の下マージンはボディの内側に残る
+        let view = create_layout_view(
+            "<html><head><style>p { height: 10px; margin: 20px 0 } .small { margin-top: 5px } \
+             .negative { margin-top: -30px; margin-bottom: -5px }</style></head>\
+             <body><p></p><p class=\"small\"></p><p class=\"negative\"></p><p></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x105\n\
+             \x20 p (0, 20) 590x10\n\
+             \x20 p (0, 50) 590x10\n\
+             \x20 p (0, 50) 590x10\n\
+             \x20 p (0, 75) 590x10\n"
+        );
+    }
+
+    #[test]
+    fn test_parent_child_margin_collapsing() {
+        /* ボーダーとパディングのない親のマージンが、最初と最後の子のマージンと折り畳まれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>h1 { margin: 10px 0 } p { height: 10px; margin: 30px 0 } \
+             .border { border-top: 1px solid black; padding-bottom: 2px }</style></head>\
+             <body><h1><p></p></h1><h1 class=\"border\"><p></p></h1></body></html>",
+        );
+        // 1つ目のh1は子のマージンを外に持ち、2つ目は上のボーダーと下のパディングで折り畳みが妨げられる
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x153\n\
+             \x20 h1 (0, 30) 590x10\n\
+             \x20   p (0, 30) 590x10\n\
+             \x20 h1 (0, 70) 590x73\n\
+             \x20   p (0, 101) 590x10\n"
+        );
+    }
+
+    #[test]
+    fn test_empty_block_margin_collapsing() {
+        /* 高さも内容もないブロックでは、上下のマージンが前後のマージンと一緒に折り畳まれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { height: 10px; margin: 10px 0 } h1 { margin: 25px 0 15px }</style></head>\
+             <body><p></p><h1></h1><p></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x65\n\
+             \x20 p (0, 10) 590x10\n\
+             \x20 h1 (0, 45) 590x0\n\
+             \x20 p (0, 45) 590x10\n"
+        );
+    }
+
+    #[test]
+    fn test_line_breaking() {
+        /* テキストが空白の後で折り返され、行末の空白が取り除かれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { width: 80px; line-height: 20px }</style></head>\
+             <body><p>hello world  foo</p><p>a<a>b</a>c</p><p><a></a></p></body></html>",
+        );
+        // 内容のない行は高さが0になる
+        assert_eq!(
+            dump_fragments(&view),
+            "line (0, 0) 80x20 14\n\
+             line (0, 20) 80x20 14\n\
+             \"hello\" (0, 0) 40x20\n\
+             \"world foo\" (0, 20) 72x20\n\
+             line (0, 40) 80x20 14\n\
+             \"a\" (0, 40) 8x20\n\
+             a (8, 42) 8x16\n\
+             \"b\" (8, 40) 8x20\n\
+             \"c\" (16, 40) 8x20\n\
+             line (0, 60) 80x0 0\n\
+             a (0, 48) 0x16\n"
+        );
+    }
+
+    #[test]
+    fn test_cjk_line_breaking() {
+        /* 漢字や仮名の間で折り返され、句読点が行頭に来ないことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { width: 40px; line-height: 16px }</style></head>\
+             <body><p>日本語の文章です。</p><p>「あいう」、えお</p></body></html>",
+        );
+        assert_eq!(
+            dump_fragments(&view),
+            "line (0, 0) 40x16 12\n\
+             line (0, 16) 40x16 12\n\
+             \"日本語の文\" (0, 0) 40x16\n\
+             \"章です。\" (0, 16) 32x16\n\
+             line (0, 32) 40x16 12\n\
+             line (0, 48) 40x16 12\n\
+             \"「あい\" (0, 32) 24x16\n\
+             \"う」、えお\" (0, 48) 40x16\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_box_fragments() {
+        /* 行をまたぐインラインボックスが分割され、分割された側には左右のマージン・ボーダー・パディングが置かれないことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { width: 80px; line-height: 20px } \
+             a { padding: 0 8px; border-left: 2px solid black; margin-right: 4px }</style></head>\
+             <body><p>x <a>aaa bbb</a> y</p></body></html>",
+        );
+        assert_eq!(
+            dump_fragments(&view),
+            "line (0, 0) 80x20 14\n\
+             line (0, 20) 80x20 14\n\
+             \"x \" (0, 0) 16x20\n\
+             a (16, 2) 34x16\n\
+             a (0, 22) 32x16\n\
+             \"aaa\" (26, 0) 24x20\n\
+             \"bbb\" (0, 20) 24x20\n\
+             \" y\" (36, 20) 16x20\n"
+        );
+        let a = view
+            .root()
+            .and_then(|body| body.borrow().first_child())
+            .and_then(|p| p.borrow().first_child())
+            .and_then(|text| text.borrow().next_sibling())
+            .expect("a should exist");
+        assert_eq!(a.borrow().point(), LayoutPoint::new(0, 2));
+        assert_eq!(a.borrow().size(), LayoutSize::new(50, 36));
+    }
+
+    #[test]
+    fn test_text_align() {
+        /* text-alignによって、行の中で内容が右寄せ・中央寄せ・両端揃えされることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { width: 80px; line-height: 20px } .r { text-align: right } \
+             .c { text-align: center } .j { text-align: justify }</style></head>\
+             <body><p class=\"r\">ab</p><p class=\"c\">ab</p><p class=\"j\">aa bb cc dd</p></body></html>",
+        );
+        // 両端揃えでは、最後の行以外の行の余白が単語の間の空白に配分される
+        assert_eq!(
+            dump_fragments(&view),
+            "line (0, 0) 80x20 14\n\
+             \"ab\" (64, 0) 16x20\n\
+             line (0, 20) 80x20 14\n\
+             \"ab\" (32, 20) 16x20\n\
+             line (0, 40) 80x20 14\n\
+             line (0, 60) 80x20 14\n\
+             \"aa bb cc\" (0, 40) 80x20\n\
+             \"dd\" (0, 60) 16x20\n"
+        );
+    }
+
+    #[test]
+    fn test_baseline_alignment() {
+        /* フォントサイズの異なるテキストがベースラインに揃えられ、行の高さが広がることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { line-height: 20px } a { font-size: 32px }</style></head>\
+             <body><p>x<a>y</a></p></body></html>",
+        );
+        // 32pxのフォントのアセントは24pxで、行の高さ20pxとの差の半分だけ上下が削られる
+        assert_eq!(
+            dump_fragments(&view),
+            "line (0, 0) 590x24 18\n\
+             \"x\" (0, 4) 8x20\n\
+             a (8, -6) 16x32\n\
+             \"y\" (8, 0) 16x20\n"
+        );
+        let p = view
+            .root()
+            .and_then(|body| body.borrow().first_child())
+            .expect("p should exist");
+        assert_eq!(p.borrow().size(), LayoutSize::new(CONTENT_AREA_WIDTH, 24));
+    }
+
+    #[test]
+    fn test_anonymous_block_boxes() {
+        /* ブロックレベルの兄弟を持つインラインレベルの子が、匿名ブロックボックスで包まれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>.b { display: block }</style></head>\
+             <body>a<a>b</a><p>c</p>d<a class=\"b\">e</a> <a class=\"b\">f</a></body></html>",
+        );
+        // ブロックの間の空白だけのテキストはボックスを作らない
+        assert_eq!(
+            dump(&view),
+            "Block body\n\
+             \x20 Block (anonymous)\n\
+             \x20   Text \"a\"\n\
+             \x20   Inline a\n\
+             \x20     Text \"b\"\n\
+             \x20 Block p\n\
+             \x20   Text \"c\"\n\
+             \x20 Block (anonymous)\n\
+             \x20   Text \"d\"\n\
+             \x20 Block a\n\
+             \x20   Text \"e\"\n\
+             \x20 Block a\n\
+             \x20   Text \"f\"\n"
+        );
+    }
+
+    #[test]
+    fn test_split_inline_around_block() {
+        /* ブロックを含むインラインボックスが分割され、分割された側の辺にパディングが置かれないことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { line-height: 20px } a { padding: 0 4px }</style></head>\
+             <body><p>x<a>y<h1>z</h1>w</a></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x60\n\
+             \x20 p (0, 0) 590x60\n\
+             \x20   (anonymous) (0, 0) 590x20\n\
+             \x20     #text (0, 0) 8x20\n\
+             \x20     a (8, 2) 12x16\n\
+             \x20       #text (12, 0) 8x20\n\
+             \x20   h1 (0, 20) 590x20\n\
+             \x20     #text (0, 20) 16x20\n\
+             \x20   (anonymous) (0, 40) 590x20\n\
+             \x20     a (0, 42) 12x16\n\
+             \x20       #text (0, 40) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_flex_grow() {
+        /* 余白がflex-growの比で配分され、テキストが匿名のフレックスアイテムになることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: flex; line-height: 20px } \
+             a { width: 100px; height: 10px } .one { flex-grow: 1 } .two { flex-grow: 2 }</style></head>\
+             <body><p><a>x</a><a class=\"one\">y</a> <a class=\"two\">z</a>w</p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x20\n\
+             \x20 p (0, 0) 590x20\n\
+             \x20   a (0, 0) 100x10\n\
+             \x20     #text (0, 0) 8x20\n\
+             \x20   a (100, 0) 194x10\n\
+             \x20     #text (100, 0) 8x20\n\
+             \x20   a (294, 0) 288x10\n\
+             \x20     #text (294, 0) 8x20\n\
+             \x20   (anonymous) (582, 0) 8x20\n\
+             \x20     #text (582, 0) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_flex_shrink() {
+        /* はみ出す分がflex-shrinkとフレックスベースサイズの積の比で縮められ、最小コンテンツ幅より小さくならないことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: flex; width: 300px; line-height: 20px } \
+             a { flex: 0 1 200px } .three { flex: 0 3 100px } .text { flex-basis: 100px }</style></head>\
+             <body><p><a>x</a><a class=\"three\">y</a><a class=\"text\">abcdefghijklmnop</a></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x20\n\
+             \x20 p (0, 0) 300x20\n\
+             \x20   a (0, 0) 149x20\n\
+             \x20     #text (0, 0) 8x20\n\
+             \x20   a (149, 0) 23x20\n\
+             \x20     #text (149, 0) 8x20\n\
+             \x20   a (172, 0) 128x20\n\
+             \x20     #text (172, 0) 128x20\n"
+        );
+    }
+
+    #[test]
+    fn test_justify_content_and_auto_margins() {
+        /* justify-contentで余白が配分され、autoのマージンがあればautoのマージンが余白を受け取ることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: flex; height: 10px } a { width: 100px } \
+             .between { justify-content: space-between } .around { justify-content: space-around } \
+             .center { justify-content: center; flex-direction: row-reverse } \
+             .push { margin-left: auto }</style></head>\
+             <body><p class=\"between\"><a></a><a></a><a></a></p>\
+             <p class=\"around\"><a></a><a></a></p>\
+             <p class=\"center\"><a></a><a></a></p>\
+             <p class=\"between\"><a></a><a class=\"push\"></a><a></a></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x40\n\
+             \x20 p (0, 0) 590x10\n\
+             \x20   a (0, 0) 100x10\n\
+             \x20   a (245, 0) 100x10\n\
+             \x20   a (490, 0) 100x10\n\
+             \x20 p (0, 10) 590x10\n\
+             \x20   a (98, 10) 100x10\n\
+             \x20   a (393, 10) 100x10\n\
+             \x20 p (0, 20) 590x10\n\
+             \x20   a (295, 20) 100x10\n\
+             \x20   a (195, 20) 100x10\n\
+             \x20 p (0, 30) 590x10\n\
+             \x20   a (0, 30) 100x10\n\
+             \x20   a (390, 30) 100x10\n\
+             \x20   a (490, 30) 100x10\n"
+        );
+    }
+
+    #[test]
+    fn test_flex_wrap_and_gap() {
+        /* 行に収まらないアイテムが次の行に折り返され、gapとalign-contentが行の配置に使われることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: flex; flex-wrap: wrap; gap: 10px 20px; width: 250px } \
+             a { width: 100px; height: 30px } .tall { height: 50px } \
+             .reverse { flex-wrap: wrap-reverse; height: 120px; align-content: space-between }</style></head>\
+             <body><p><a></a><a class=\"tall\"></a><a></a></p>\
+             <p class=\"reverse\"><a></a><a class=\"tall\"></a><a></a></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x210\n\
+             \x20 p (0, 0) 250x90\n\
+             \x20   a (0, 0) 100x30\n\
+             \x20   a (120, 0) 100x50\n\
+             \x20   a (0, 60) 100x30\n\
+             \x20 p (0, 90) 250x120\n\
+             \x20   a (0, 180) 100x30\n\
+             \x20   a (120, 160) 100x50\n\
+             \x20   a (0, 90) 100x30\n"
+        );
+    }
+
+    #[test]
+    fn test_align_items() {
+        /* 交差軸方向のstretch、center、flex-end、baselineの配置を確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: flex; height: 60px; align-items: center } \
+             a { width: 50px; height: 20px } .auto { height: auto } \
+             .stretch { align-self: stretch } .end { align-self: flex-end } \
+             h1 { display: flex; align-items: baseline; line-height: 20px } h2 { font-size: 32px; line-height: 40px } \
+             .small { height: auto; font-size: 16px; padding-top: 5px }</style></head>\
+             <body><p><a></a><a class=\"auto stretch\"></a><a class=\"end\"></a></p>\
+             <h1><a class=\"small\">x</a><h2>y</h2></h1></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x100\n\
+             \x20 p (0, 0) 590x60\n\
+             \x20   a (0, 20) 50x20\n\
+             \x20   a (50, 0) 50x60\n\
+             \x20   a (100, 40) 50x20\n\
+             \x20 h1 (0, 60) 590x40\n\
+             \x20   a (0, 69) 50x25\n\
+             \x20     #text (0, 74) 8x20\n\
+             \x20   h2 (50, 60) 16x40\n\
+             \x20     #text (50, 60) 16x40\n"
+        );
+    }
+
+    #[test]
+    fn test_flex_column_and_order() {
+        /* 縦方向のフレックスコンテナで、orderの順にアイテムが並び、幅が伸ばされることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: flex; flex-direction: column; line-height: 20px } \
+             .first { order: -1 } .grow { flex-grow: 1 } .fit { align-self: flex-start; padding: 0 5px } \
+             h1 { display: flex; flex-direction: column-reverse; height: 100px; font-size: 16px; line-height: 20px }</style></head>\
+             <body><p><a>a</a><a class=\"first\">b</a><a class=\"fit\">cc</a></p>\
+             <h1><a>d</a><a class=\"grow\">e</a></h1></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x160\n\
+             \x20 p (0, 0) 590x60\n\
+             \x20   a (0, 20) 590x20\n\
+             \x20     #text (0, 20) 8x20\n\
+             \x20   a (0, 0) 590x20\n\
+             \x20     #text (0, 0) 8x20\n\
+             \x20   a (0, 40) 26x20\n\
+             \x20     #text (5, 40) 16x20\n\
+             \x20 h1 (0, 60) 590x100\n\
+             \x20   a (0, 140) 590x20\n\
+             \x20     #text (0, 140) 8x20\n\
+             \x20   a (0, 60) 590x80\n\
+             \x20     #text (0, 60) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_flex() {
+        /* インラインレベルのフレックスコンテナが、内容に合わせた幅で行に並ぶことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { line-height: 20px } h1 { display: inline-flex; font-size: 16px; line-height: 20px; gap: 4px }</style></head>\
+             <body><p>a <h1><a>bb</a><a>c</a></h1>d</p></body></html>",
+        );
+        assert_eq!(
+            dump(&view),
+            "Block body\n\
+             \x20 Block p\n\
+             \x20   Text \"a \"\n\
+             \x20   AtomicInline h1\n\
+             \x20     Block a\n\
+             \x20       Text \"bb\"\n\
+             \x20     Block a\n\
+             \x20       Text \"c\"\n\
+             \x20   Text \"d\"\n"
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x20\n\
+             \x20 p (0, 0) 590x20\n\
+             \x20   #text (0, 0) 16x20\n\
+             \x20   h1 (16, 0) 28x20\n\
+             \x20     a (16, 0) 16x20\n\
+             \x20       #text (16, 0) 16x20\n\
+             \x20     a (36, 0) 8x20\n\
+             \x20       #text (36, 0) 8x20\n\
+             \x20   #text (44, 0) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_grid_template_tracks() {
+        /* 固定の大きさのトラックを除いた残りがfrの比で列に配分され、アイテムが行ごとに並ぶことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: grid; grid-template-columns: 100px 1fr 2fr; gap: 10px; line-height: 20px }</style></head>\
+             <body><p><a>x</a><a>y</a><a>z</a><a>w</a></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x50\n\
+             \x20 p (0, 0) 590x50\n\
+             \x20   a (0, 0) 100x20\n\
+             \x20     #text (0, 0) 8x20\n\
+             \x20   a (110, 0) 157x20\n\
+             \x20     #text (110, 0) 8x20\n\
+             \x20   a (277, 0) 313x20\n\
+             \x20     #text (277, 0) 8x20\n\
+             \x20   a (0, 30) 100x20\n\
+             \x20     #text (0, 30) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_grid_placement() {
+        /* 線の番号とspanで指定したアイテムを先に置き、残りのアイテムが空いているセルに自動で置かれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: grid; grid-template-columns: repeat(3, 50px); grid-auto-rows: 20px; line-height: 20px } \
+             .last { grid-column: -2; grid-row: 1 } .wide { grid-column: span 2 } .tall { grid-row: 2 / span 2 }</style></head>\
+             <body><p><a class=\"last\">a</a><a class=\"wide\">b</a><a>c</a><a>d</a><a class=\"tall\">e</a></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x60\n\
+             \x20 p (0, 0) 590x60\n\
+             \x20   a (100, 0) 50x20\n\
+             \x20     #text (100, 0) 8x20\n\
+             \x20   a (0, 0) 100x20\n\
+             \x20     #text (0, 0) 8x20\n\
+             \x20   a (50, 20) 50x20\n\
+             \x20     #text (50, 20) 8x20\n\
+             \x20   a (100, 20) 50x20\n\
+             \x20     #text (100, 20) 8x20\n\
+             \x20   a (0, 20) 50x40\n\
+             \x20     #text (0, 20) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_grid_auto_flow() {
+        /* 列方向の自動配置と、前の空いているセルに詰める自動配置を確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: grid; line-height: 20px } \
+             .column { grid-template-rows: 20px 20px; grid-auto-flow: column; grid-auto-columns: 50px } \
+             .dense { grid-template-columns: repeat(3, 50px); grid-auto-flow: dense } .wide { grid-column: span 3 }</style></head>\
+             <body><p class=\"column\"><a>a</a><a>b</a><a>c</a></p>\
+             <p class=\"dense\"><a>d</a><a class=\"wide\">e</a><a>f</a></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x80\n\
+             \x20 p (0, 0) 590x40\n\
+             \x20   a (0, 0) 50x20\n\
+             \x20     #text (0, 0) 8x20\n\
+             \x20   a (0, 20) 50x20\n\
+             \x20     #text (0, 20) 8x20\n\
+             \x20   a (50, 0) 50x20\n\
+             \x20     #text (50, 0) 8x20\n\
+             \x20 p (0, 40) 590x40\n\
+             \x20   a (0, 40) 50x20\n\
+             \x20     #text (0, 40) 8x20\n\
+             \x20   a (0, 60) 150x20\n\
+             \x20     #text (0, 60) 8x20\n\
+             \x20   a (50, 40) 50x20\n\
+             \x20     #text (50, 40) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_grid_intrinsic_tracks() {
+        /* autoとminmax()のトラックが内容に合わせた大きさになり、justify-contentでトラックが揃えられることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: grid; line-height: 20px } \
+             .flexible { grid-template-columns: auto minmax(50px, max-content) 1fr } \
+             .centered { grid-template-columns: auto auto; justify-content: center }</style></head>\
+             <body><p class=\"flexible\"><a>abcd</a><a>ab</a><a>a</a></p>\
+             <p class=\"centered\"><a>abcd</a><a>ab</a></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x40\n\
+             \x20 p (0, 0) 590x20\n\
+             \x20   a (0, 0) 32x20\n\
+             \x20     #text (0, 0) 32x20\n\
+             \x20   a (32, 0) 50x20\n\
+             \x20     #text (32, 0) 16x20\n\
+             \x20   a (82, 0) 508x20\n\
+             \x20     #text (82, 0) 8x20\n\
+             \x20 p (0, 20) 590x20\n\
+             \x20   a (271, 20) 32x20\n\
+             \x20     #text (271, 20) 32x20\n\
+             \x20   a (303, 20) 16x20\n\
+             \x20     #text (303, 20) 16x20\n"
+        );
+    }
+
+    #[test]
+    fn test_grid_auto_fill_and_item_alignment() {
+        /* auto-fillで収まるだけ列が繰り返され、アイテムがjustify-itemsとalign-itemsでグリッド領域の中に揃えられることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { display: grid; grid-template-columns: repeat(auto-fill, 100px); column-gap: 10px; \
+             grid-auto-rows: 40px; justify-items: center; align-items: end; line-height: 20px } \
+             .start { justify-self: start; align-self: start }</style></head>\
+             <body><p><a>ab</a><a>ab</a><a>ab</a><a>ab</a><a>ab</a><a class=\"start\">ab</a></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x80\n\
+             \x20 p (0, 0) 590x80\n\
+             \x20   a (42, 20) 16x20\n\
+             \x20     #text (42, 20) 16x20\n\
+             \x20   a (152, 20) 16x20\n\
+             \x20     #text (152, 20) 16x20\n\
+             \x20   a (262, 20) 16x20\n\
+             \x20     #text (262, 20) 16x20\n\
+             \x20   a (372, 20) 16x20\n\
+             \x20     #text (372, 20) 16x20\n\
+             \x20   a (482, 20) 16x20\n\
+             \x20     #text (482, 20) 16x20\n\
+             \x20   a (0, 40) 16x20\n\
+             \x20     #text (0, 40) 16x20\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_grid() {
+        /* インラインレベルのグリッドコンテナが、列の大きさの合計の幅で行に並ぶことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { line-height: 20px } h1 { display: inline-grid; grid-template-columns: auto auto; \
+             font-size: 16px; line-height: 20px; column-gap: 4px }</style></head>\
+             <body><p>a <h1><a>bb</a><a>c</a></h1>d</p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x20\n\
+             \x20 p (0, 0) 590x20\n\
+             \x20   #text (0, 0) 16x20\n\
+             \x20   h1 (16, 0) 28x20\n\
+             \x20     a (16, 0) 16x20\n\
+             \x20       #text (16, 0) 16x20\n\
+             \x20     a (36, 0) 8x20\n\
+             \x20       #text (36, 0) 8x20\n\
+             \x20   #text (44, 0) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_relative_position() {
+        /* 相対位置指定されたボックスが子孫と一緒にずれ、後ろの兄弟の位置は変わらないことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { line-height: 20px } .moved { position: relative; top: 5px; left: 10%; \
+             bottom: 100px }</style></head>\
+             <body><p class=\"moved\">a<a class=\"moved\">b</a></p><p>c</p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x40\n\
+             \x20 p (59, 5) 590x20\n\
+             \x20   #text (59, 5) 8x20\n\
+             \x20   a (126, 12) 8x16\n\
+             \x20     #text (126, 10) 8x20\n\
+             \x20 p (0, 20) 590x20\n\
+             \x20   #text (0, 20) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_absolute_position() {
+        /* 絶対位置指定されたボックスが通常フローから取り除かれ、位置指定された祖先のパディングボックスを基準に置かれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>body { position: relative; padding: 10px } p { line-height: 20px } \
+             h1 { position: absolute; top: 5px; right: 0; width: 100px; height: 30px; font-size: 16px; \
+             line-height: 20px } h2 { position: absolute; left: 0; right: 0; bottom: 10%; width: 100px; \
+             margin: 0 auto; line-height: 20px }</style></head>\
+             <body><p>a</p><h1>x</h1><p>b</p><h2>y</h2></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x60\n\
+             \x20 p (10, 10) 570x20\n\
+             \x20   #text (10, 10) 8x20\n\
+             \x20 h1 (490, 5) 100x30\n\
+             \x20   #text (490, 5) 8x20\n\
+             \x20 p (10, 30) 570x20\n\
+             \x20   #text (10, 30) 8x20\n\
+             \x20 h2 (245, 34) 100x20\n\
+             \x20   #text (245, 34) 12x20\n"
+        );
+    }
+
+    #[test]
+    fn test_absolute_static_position() {
+        /* insetがautoの絶対位置指定されたボックスが、静的位置に内容に合わせた幅で置かれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { line-height: 20px; display: flex } \
+             .inline { display: block; line-height: 20px } \
+             .abs { position: absolute } .fixed { position: fixed; bottom: 0; left: 0; right: 0; \
+             line-height: 20px }\
+             </style></head><body><p><a>x</a><a class=\"abs\">yy</a></p>\
+             <h1 class=\"inline\">ab <a class=\"abs\">cd</a>ef</h1>\
+             <h2 class=\"abs fixed\">z</h2></body></html>",
+        );
+        let bottom = CONTENT_AREA_HEIGHT - 20;
+        assert_eq!(
+            dump_geometry(&view),
+            format!(
+                "body (0, 0) 590x40\n\
+                 \x20 p (0, 0) 590x20\n\
+                 \x20   a (0, 0) 8x20\n\
+                 \x20     #text (0, 0) 8x20\n\
+                 \x20   a (0, 0) 16x20\n\
+                 \x20     #text (0, 0) 16x20\n\
+                 \x20 h1 (0, 20) 590x20\n\
+                 \x20   #text (0, 20) 48x20\n\
+                 \x20   a (48, 20) 32x20\n\
+                 \x20     #text (48, 20) 32x20\n\
+                 \x20   #text (48, 20) 32x20\n\
+                 \x20 h2 (0, {}) 590x20\n\
+                 \x20   #text (0, {}) 12x20\n",
+                bottom, bottom
+            )
+        );
+    }
+
+    #[test]
+    fn test_float_line_shortening() {
+        /* 左右のフロートが内容の両端に置かれ、重なる行ボックスが短くなってテキストが回り込むことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { width: 200px; line-height: 20px } \
+             .left { float: left; width: 100px; height: 40px } \
+             .right { float: right; width: 50px; height: 20px }</style></head>\
+             <body><p><a class=\"left\"></a><a class=\"right\"></a>aaaa bbbb cccc dddd</p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x60\n\
+             \x20 p (0, 0) 200x60\n\
+             \x20   a (0, 0) 100x40\n\
+             \x20   a (150, 0) 50x20\n\
+             \x20   #text (0, 0) 172x60\n"
+        );
+        assert_eq!(
+            dump_fragments(&view),
+            "line (100, 0) 50x20 14\n\
+             line (100, 20) 100x20 14\n\
+             line (0, 40) 200x20 14\n\
+             \"aaaa\" (100, 0) 32x20\n\
+             \"bbbb cccc\" (100, 20) 72x20\n\
+             \"dddd\" (0, 40) 32x20\n"
+        );
+    }
+
+    #[test]
+    fn test_clear() {
+        /* clearを指定されたブロックとフロートが、避けるフロートの下端まで下げられることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { line-height: 20px; margin: 0 } \
+             .left { float: left; width: 100px; height: 40px } \
+             .right { float: right; width: 50px; height: 30px } \
+             .clear { clear: both } .clear-left { float: right; clear: left; width: 50px; height: 10px }\
+             </style></head><body><p><a class=\"left\"></a><a class=\"right\"></a>a</p>\
+             <p class=\"clear\">b<a class=\"clear-left\"></a></p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x60\n\
+             \x20 p (0, 0) 590x20\n\
+             \x20   a (0, 0) 100x40\n\
+             \x20   a (540, 0) 50x30\n\
+             \x20   #text (100, 0) 8x20\n\
+             \x20 p (0, 40) 590x20\n\
+             \x20   #text (0, 40) 8x20\n\
+             \x20   a (540, 40) 50x10\n"
+        );
+    }
+
+    #[test]
+    fn test_block_formatting_context_roots() {
+        /* 新しいブロック整形コンテキストを作るブロックが、フロートを避けて横に置かれ、中のフロートを高さに含めることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { line-height: 20px; margin: 0 } \
+             .left { float: left; width: 100px; height: 30px } \
+             .hidden { overflow: hidden } .root { display: flow-root }</style></head>\
+             <body><h1 class=\"left\"></h1><p class=\"hidden\">a</p><p>b</p>\
+             <p class=\"root\"><a class=\"left\"></a>c</p><p>d</p></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x90\n\
+             \x20 h1 (0, 0) 100x30\n\
+             \x20 p (100, 0) 490x20\n\
+             \x20   #text (100, 0) 8x20\n\
+             \x20 p (0, 20) 590x20\n\
+             \x20   #text (100, 20) 8x20\n\
+             \x20 p (0, 40) 590x30\n\
+             \x20   a (0, 40) 100x30\n\
+             \x20   #text (100, 40) 8x20\n\
+             \x20 p (0, 70) 590x20\n\
+             \x20   #text (0, 70) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_table_auto_layout() {
+        /* 自動レイアウトの表で、列の幅がセルの内容の幅から、行の高さがセルの高さから決まることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>td { line-height: 20px }</style></head><body>\
+             <table><tr><td>ab</td><td>abcd</td></tr><tr><td>a</td></tr></table></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x50\n\
+             \x20 table (0, 0) 58x50\n\
+             \x20   tbody (2, 2) 54x46\n\
+             \x20     tr (2, 2) 54x22\n\
+             \x20       td (2, 2) 18x22\n\
+             \x20         #text (3, 3) 16x20\n\
+             \x20       td (22, 2) 34x22\n\
+             \x20         #text (23, 3) 32x20\n\
+             \x20     tr (2, 26) 54x22\n\
+             \x20       td (2, 26) 18x22\n\
+             \x20         #text (3, 27) 8x20\n"
+        );
+    }
+
+    fn collect_text_decorations(
+        node: &Option<Rc<RefCell<LayoutObject>>>,
+        result: &mut Vec<(String, TextDecoration)>,
+    ) {
+        let mut current = node.clone();
+        while let Some(n) = current {
+            for fragment in n.borrow().fragments() {
+                if !fragment.text().is_empty() {
+                    result.push((fragment.text(), fragment.decoration()));
+                }
+            }
+            collect_text_decorations(&n.borrow().first_child(), result);
+            current = n.borrow().next_sibling();
+        }
+    }
+
+    #[test]
+    fn test_text_decoration_propagation() {
+        /* 装飾が継承されずに子孫のテキストへ伝わり、子のnoneで消えず、フロートには伝わらないことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>.u { text-decoration: underline } .n { text-decoration: none } \
+             .f { float: left }</style></head><body><p class=\"u\">a<a class=\"n\">b</a>\
+             <a class=\"f n\">c</a></p><p>d</p></body></html>",
+        );
+        let mut decorations = Vec::new();
+        collect_text_decorations(&view.root(), &mut decorations);
+        assert_eq!(
+            decorations,
+            vec![
+                ("a".to_string(), TextDecoration::Underline),
+                ("b".to_string(), TextDecoration::Underline),
+                ("c".to_string(), TextDecoration::None),
+                ("d".to_string(), TextDecoration::None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table_spans_and_vertical_align() {
+        /* colspanとrowspanのセルがスロットにまたがって置かれ、vertical-alignで内容が揃うことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>table { border-spacing: 0 } td { line-height: 20px; padding: 0 } \
+             .tall { height: 60px } .top { vertical-align: top }</style></head><body>\
+             <table><tr><td rowspan=\"2\" class=\"tall\">a</td><td class=\"top\">b</td>\
+             <td>c</td></tr><tr><td colspan=\"2\">dddd</td></tr></table></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x60\n\
+             \x20 table (0, 0) 40x60\n\
+             \x20   tbody (0, 0) 40x60\n\
+             \x20     tr (0, 0) 40x30\n\
+             \x20       td (0, 0) 8x60\n\
+             \x20         #text (0, 20) 8x20\n\
+             \x20       td (8, 0) 16x30\n\
+             \x20         #text (8, 0) 8x20\n\
+             \x20       td (24, 0) 16x30\n\
+             \x20         #text (24, 5) 8x20\n\
+             \x20     tr (0, 30) 40x30\n\
+             \x20       td (8, 30) 32x30\n\
+             \x20         #text (8, 35) 32x20\n"
+        );
+    }
+
+    #[test]
+    fn test_table_fixed_layout_and_captions() {
+        /* 固定レイアウトの表で、列の幅が列の要素と最初の行から決まり、キャプションが上下に置かれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>table { table-layout: fixed; width: 200px; border-spacing: 0 } \
+             td { line-height: 20px; padding: 0 } caption { line-height: 20px } \
+             col { width: 50px } .narrow { width: 30px } .bottom { caption-side: bottom }\
+             </style></head><body>\
+             <table><caption>t</caption><colgroup><col></colgroup>\
+             <tr><td>a</td><td class=\"narrow\">b</td><td>c</td></tr></table>\
+             <table><caption class=\"bottom\">u</caption><tr><td>d</td></tr></table></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x80\n\
+             \x20 table (0, 0) 200x40\n\
+             \x20   caption (0, 0) 200x20\n\
+             \x20     #text (96, 0) 8x20\n\
+             \x20   colgroup (0, 20) 0x0\n\
+             \x20     col (0, 20) 0x0\n\
+             \x20   tbody (0, 20) 200x20\n\
+             \x20     tr (0, 20) 200x20\n\
+             \x20       td (0, 20) 50x20\n\
+             \x20         #text (0, 20) 8x20\n\
+             \x20       td (50, 20) 30x20\n\
+             \x20         #text (50, 20) 8x20\n\
+             \x20       td (80, 20) 120x20\n\
+             \x20         #text (80, 20) 8x20\n\
+             \x20 table (0, 40) 200x40\n\
+             \x20   caption (0, 60) 200x20\n\
+             \x20     #text (96, 60) 8x20\n\
+             \x20   tbody (0, 40) 200x20\n\
+             \x20     tr (0, 40) 200x20\n\
+             \x20       td (0, 40) 200x20\n\
+             \x20         #text (0, 40) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_table_border_collapse() {
+        /* border-collapseで、隣り合うセルのボーダーが太い方に決まり、表とセルで半分ずつ分け合うことを確認する */
+        let view = create_layout_view(
+            "<html><head><style>table { border-collapse: collapse; border: 4px solid } \
+             td { line-height: 20px; padding: 0; border: 2px solid } \
+             .thick { border-right: 6px solid }</style></head><body>\
+             <table><tr><td class=\"thick\">a</td><td>b</td></tr></table></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x28\n\
+             \x20 table (0, 0) 30x28\n\
+             \x20   tbody (2, 2) 26x24\n\
+             \x20     tr (2, 2) 26x24\n\
+             \x20       td (2, 2) 13x24\n\
+             \x20         #text (4, 4) 8x20\n\
+             \x20       td (15, 2) 13x24\n\
+             \x20         #text (18, 4) 8x20\n"
+        );
+    }
+
+    #[test]
+    fn test_list_markers() {
+        /* リスト項目の最初の子に::markerのボックスが作られ、番号と入れ子のulの記号が決まることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>.none { list-style-type: none } .alpha::marker { content: \"(\" counter(list-item, lower-alpha) \")\" }</style></head>\
+             <body><ol start=\"2\"><li>a</li><li class=\"none\">b</li><li>c</li></ol>\
+             <ul><li>d<ul><li>e</li></ul></li></ul><ol><li class=\"alpha\">f</li></ol></body></html>",
+        );
+        assert_eq!(
+            dump(&view),
+            "Block body\n\
+             \x20 Block ol\n\
+             \x20   Block li\n\
+             \x20     Inline ::marker\n\
+             \x20       Text \"2. \"\n\
+             \x20     Text \"a\"\n\
+             \x20   Block li\n\
+             \x20     Text \"b\"\n\
+             \x20   Block li\n\
+             \x20     Inline ::marker\n\
+             \x20       Text \"4. \"\n\
+             \x20     Text \"c\"\n\
+             \x20 Block ul\n\
+             \x20   Block li\n\
+             \x20     Inline ::marker\n\
+             \x20       Text \"• \"\n\
+             \x20     Block (anonymous)\n\
+             \x20       Text \"d\"\n\
+             \x20     Block ul\n\
+             \x20       Block li\n\
+             \x20         Inline ::marker\n\
+             \x20           Text \"◦ \"\n\
+             \x20         Text \"e\"\n\
+             \x20 Block ol\n\
+             \x20   Block li\n\
+             \x20     Inline ::marker\n\
+             \x20       Text \"(a)\"\n\
+             \x20     Text \"f\"\n"
+        );
+    }
+
+    #[test]
+    fn test_list_marker_position() {
+        /* 外側のマーカーはコンテンツ領域の左に、内側のマーカーは行の先頭にインラインボックスとして置かれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>li { line-height: 20px } .inside { list-style-position: inside } \
+             p { line-height: 30px }</style></head>\
+             <body><ul><li>a</li><li class=\"inside\">b</li><li><p>c</p></li></ul></body></html>",
+        );
+        assert_eq!(
+            dump_fragments(&view),
+            "line (40, 0) 550x20 14\n\
+             ::marker (24, 2) 16x16\n\
+             \"• \" (24, 0) 16x20\n\
+             \"a\" (40, 0) 8x20\n\
+             line (40, 20) 550x20 14\n\
+             ::marker (40, 22) 16x16\n\
+             \"• \" (40, 20) 16x20\n\
+             \"b\" (56, 20) 8x20\n\
+             ::marker (24, 47) 16x16\n\
+             \"• \" (24, 45) 16x20\n\
+             line (40, 40) 550x30 19\n\
+             \"c\" (40, 40) 8x30\n"
+        );
+    }
+
+    #[test]
+    fn test_sticky_position() {
+        /* 粘着位置指定されたボックスが、ビューポートのinsetの内側に包含ブロックからはみ出さない範囲でずれることを確認する */
+        let view = create_layout_view(
+            "<html><head><style>p { line-height: 20px } .sticky { position: sticky; top: 10px } \
+             h1 { position: sticky; top: 50px; font-size: 16px; line-height: 20px }</style></head>\
+             <body><p class=\"sticky\">a</p><p>b</p><h1>c</h1></body></html>",
+        );
+        assert_eq!(
+            dump_geometry(&view),
+            "body (0, 0) 590x60\n\
+             \x20 p (0, 10) 590x20\n\
+             \x20   #text (0, 10) 8x20\n\
+             \x20 p (0, 20) 590x20\n\
+             \x20   #text (0, 20) 8x20\n\
+             \x20 h1 (0, 40) 590x20\n\
+             \x20   #text (0, 40) 8x20\n"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use crate::renderer::layout::computed_style::TextDecoration;
+use crate::renderer::layout::layout_point::LayoutPoint;
+use crate::renderer::layout::layout_size::LayoutSize;
+use alloc::string::String;
+
+/// https://www.w3.org/TR/css-inline-3/#line-box
+/// インラインレベルの内容を1行に並べた矩形. ブロックコンテナのコンテンツ領域の幅いっぱいに広がる
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineBox {
+    point: LayoutPoint,
+    size: LayoutSize,
+    /// 行ボックスの上端からベースラインまでの距離
+    baseline: i64,
+}
+
+impl LineBox {
+    pub fn new(point: LayoutPoint, size: LayoutSize, baseline: i64) -> Self {
+        Self {
+            point,
+            size,
+            baseline,
+        }
+    }
+
+    pub fn point(&self) -> LayoutPoint {
+        self.point
+    }
+
+    pub fn set_point(&mut self, point: LayoutPoint) {
+        self.point = point;
+    }
+
+    pub fn size(&self) -> LayoutSize {
+        self.size
+    }
+
+    pub fn baseline(&self) -> i64 {
+        self.baseline
+    }
+}
+
+/// https://www.w3.org/TR/css-break-3/#box-fragment
+/// インラインボックスまたはテキストのうち、1つの行ボックスに置かれた部分
+/// インラインボックスの場合はボーダーボックスを、テキストの場合は行の高さ分の矩形を表す
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineFragment {
+    point: LayoutPoint,
+    size: LayoutSize,
+    /// テキストの場合、この行に置かれた部分の文字列. インラインボックスの場合は空
+    text: String,
+    /// https://www.w3.org/TR/css-text-decor-3/#line-decoration
+    /// テキストの場合、祖先のボックスから伝わった、テキストに描く装飾
+    decoration: TextDecoration,
+}
+
+impl LineFragment {
+    pub fn new(point: LayoutPoint, size: LayoutSize, text: String) -> Self {
+        Self {
+            point,
+            size,
+            text,
+            decoration: TextDecoration::None,
+        }
+    }
+
+    pub fn point(&self) -> LayoutPoint {
+        self.point
+    }
+
+    pub fn set_point(&mut self, point: LayoutPoint) {
+        self.point = point;
+    }
+
+    pub fn size(&self) -> LayoutSize {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: LayoutSize) {
+        self.size = size;
+    }
+
+    pub fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    pub fn decoration(&self) -> TextDecoration {
+        self.decoration
+    }
+
+    pub fn set_decoration(&mut self, decoration: TextDecoration) {
+        self.decoration = decoration;
+    }
+}
@@ -0,0 +1,201 @@
+use alloc::vec::Vec;
+
+/// https://www.unicode.org/reports/tr14/#Table1
+/// 改行の機会を判定するための文字の分類. UAX #14の行分割クラスを簡略化したもの
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum BreakClass {
+    /// 空白 (SP)
+    Space,
+    /// 開き括弧 (OP)
+    Open,
+    /// 閉じ括弧と句読点 (CL, CP, IS)
+    Close,
+    /// 感嘆符と疑問符 (EX)
+    Exclamation,
+    /// 行頭に置けない文字 (NS). 小書きの仮名、長音記号、繰り返し記号など
+    NonStarter,
+    /// 直後で改行できる文字 (HY, BA). ハイフンや全角空白
+    BreakAfter,
+    /// 前後で改行できる文字 (ID). 漢字、仮名、ハングル、全角の英数字など
+    Ideographic,
+    /// 数字 (NU)
+    Numeric,
+    /// 英字などのその他の文字 (AL)
+    Alphabetic,
+}
+
+impl BreakClass {
+    fn of(c: char) -> Self {
+        match c {
+            ' ' | '\t' | '\n' | '\r' | '\u{0C}' => BreakClass::Space,
+            '(' | '[' | '{' | '“' | '‘' | '（' | '［' | '｛' | '「' | '『' | '【' | '〔' | '〈'
+            | '《' | '〖' | '〘' | '｢' => BreakClass::Open,
+            ')' | ']' | '}' | '”' | '’' | ',' | '.' | ':' | ';' | '）' | '］' | '｝' | '」'
+            | '』' | '】' | '〕' | '〉' | '》' | '〗' | '〙' | '｣' | '、' | '。' | '，' | '．'
+            | '｡' | '､' => BreakClass::Close,
+            '!' | '?' | '！' | '？' => BreakClass::Exclamation,
+            'ぁ'
+            | 'ぃ'
+            | 'ぅ'
+            | 'ぇ'
+            | 'ぉ'
+            | 'っ'
+            | 'ゃ'
+            | 'ゅ'
+            | 'ょ'
+            | 'ゎ'
+            | 'ゕ'
+            | 'ゖ'
+            | 'ァ'
+            | 'ィ'
+            | 'ゥ'
+            | 'ェ'
+            | 'ォ'
+            | 'ッ'
+            | 'ャ'
+            | 'ュ'
+            | 'ョ'
+            | 'ヮ'
+            | 'ヵ'
+            | 'ヶ'
+            | 'ー'
+            | '々'
+            | '〻'
+            | 'ゝ'
+            | 'ゞ'
+            | 'ヽ'
+            | 'ヾ'
+            | '・'
+            | '：'
+            | '；'
+            | '\u{31F0}'..='\u{31FF}' => BreakClass::NonStarter,
+            '-' | '\u{2010}' | '\u{2013}' | '\u{3000}' => BreakClass::BreakAfter,
+            '0'..='9' => BreakClass::Numeric,
+            '\u{1100}'..='\u{115F}'
+            | '\u{2E80}'..='\u{2FFF}'
+            | '\u{3001}'..='\u{303F}'
+            | '\u{3040}'..='\u{30FF}'
+            | '\u{3100}'..='\u{31EF}'
+            | '\u{3200}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{A960}'..='\u{A97F}'
+            | '\u{AC00}'..='\u{D7A3}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FE30}'..='\u{FE4F}'
+            | '\u{FF01}'..='\u{FF60}'
+            | '\u{FFE0}'..='\u{FFE6}'
+            | '\u{20000}'..='\u{3FFFD}' => BreakClass::Ideographic,
+            _ => BreakClass::Alphabetic,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-text-3/#soft-wrap-opportunity
+/// テキストの中で改行できる位置（その位置の文字の直前で改行できる文字の添字）を、昇順に返す
+/// 空白の直後と、漢字や仮名の前後で改行できる. 禁則処理として、閉じ括弧・句読点・小書きの仮名などの前と、
+/// 開き括弧の後では改行しない. テキストの先頭と末尾は含まない
+pub fn soft_wrap_opportunities(text: &str) -> Vec<usize> {
+    let classes: Vec<BreakClass> = text.chars().map(BreakClass::of).collect();
+    let mut opportunities = Vec::new();
+    // 直前の空白以外の文字の分類
+    let mut previous: Option<BreakClass> = None;
+
+    for (i, &class) in classes.iter().enumerate() {
+        if i > 0 && is_break_allowed(previous, classes[i - 1] == BreakClass::Space, class) {
+            opportunities.push(i);
+        }
+        if class != BreakClass::Space {
+            previous = Some(class);
+        }
+    }
+
+    opportunities
+}
+
+/// https://www.unicode.org/reports/tr14/#Algorithm
+/// 空白以外の文字`before`（空白を挟む場合は`after_space`がtrue）と、文字`after`の間で改行できるか
+fn is_break_allowed(before: Option<BreakClass>, after_space: bool, after: BreakClass) -> bool {
+    // LB7: 空白の前では改行しない. 空白は行末にぶら下がる
+    if after == BreakClass::Space {
+        return false;
+    }
+    // 行頭の空白の後では改行しない
+    let before = match before {
+        Some(before) => before,
+        None => return false,
+    };
+
+    match (before, after) {
+        // LB13: 閉じ括弧と感嘆符の前では、空白を挟んでも改行しない
+        (_, BreakClass::Close | BreakClass::Exclamation) => false,
+        // LB14: 開き括弧の後では、空白を挟んでも改行しない
+        (BreakClass::Open, _) => false,
+        // LB18: 空白の後で改行できる
+        _ if after_space => true,
+        // LB21: 行頭禁則の文字とハイフンの前では改行しない
+        (_, BreakClass::NonStarter | BreakClass::BreakAfter) => false,
+        // LB25: 負の数のように、数字の前のハイフンの後では改行しない
+        (BreakClass::BreakAfter, BreakClass::Numeric) => false,
+        (BreakClass::BreakAfter, _) => true,
+        // LB31: 漢字や仮名の前後で改行できる
+        (BreakClass::Ideographic, _) | (_, BreakClass::Ideographic) => true,
+        // 閉じ括弧などの後の開き括弧の前では改行できるが、LB30により英数字の直後の開き括弧の前では改行しない
+        (
+            BreakClass::Close | BreakClass::Exclamation | BreakClass::NonStarter,
+            BreakClass::Open,
+        ) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec;
+
+    /// 改行できる位置でテキストを分割する
+    fn split(text: &str) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut result = Vec::new();
+        let mut start = 0;
+        for end in soft_wrap_opportunities(text)
+            .into_iter()
+            .chain(vec![chars.len()])
+        {
+            result.push(chars[start..end].iter().collect());
+            start = end;
+        }
+        result
+    }
+
+    #[test]
+    fn test_spaces() {
+        /* 空白の直後で改行でき、空白の前や行頭の空白の後では改行しないことを確認する */
+        assert_eq!(split("hello world"), ["hello ", "world"]);
+        assert_eq!(split("a  b\nc"), ["a  ", "b\n", "c"]);
+        assert_eq!(split(" lead"), [" lead"]);
+        assert_eq!(split("end. (note) !"), ["end. ", "(note) !"]);
+    }
+
+    #[test]
+    fn test_hyphens() {
+        /* ハイフンの後で改行でき、負の数の符号の後では改行しないことを確認する */
+        assert_eq!(split("well-known"), ["well-", "known"]);
+        assert_eq!(split("a -1"), ["a ", "-1"]);
+        assert_eq!(split("f(x)"), ["f(x)"]);
+    }
+
+    #[test]
+    fn test_cjk() {
+        /* 漢字や仮名の間で改行でき、禁則処理が行われることを確認する */
+        assert_eq!(split("日本語"), ["日", "本", "語"]);
+        assert_eq!(
+            split("「ちょっと」、待って。"),
+            ["「ちょっ", "と」、", "待っ", "て。"]
+        );
+        assert_eq!(split("コーヒー"), ["コー", "ヒー"]);
+        assert_eq!(split("東京Tokyo"), ["東", "京", "Tokyo"]);
+        assert_eq!(split("本当！？"), ["本", "当！？"]);
+    }
+}
@@ -1,5 +1,13 @@
 pub mod computed_style;
+pub mod float_context;
+pub mod font_metrics;
+pub mod generated_content;
+pub mod layout_edges;
 pub mod layout_object;
 pub mod layout_point;
+pub mod layout_rect;
 pub mod layout_size;
 pub mod layout_view;
+pub mod line_box;
+pub mod line_break;
+pub mod style_engine;
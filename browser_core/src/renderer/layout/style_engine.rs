@@ -0,0 +1,539 @@
+use crate::error::Error;
+use crate::renderer::css::cascade::cascade_sorted_declarations;
+use crate::renderer::css::cssom::PseudoElement;
+use crate::renderer::css::cssom::QualifiedRule;
+use crate::renderer::css::cssom::StyleSheet;
+use crate::renderer::css::invalidation::changed_rules;
+use crate::renderer::css::invalidation::InvalidationSet;
+use crate::renderer::css::media::MediaEnvironment;
+use crate::renderer::css::token::CssToken;
+use crate::renderer::css::value::LengthUnit;
+use crate::renderer::dom::api::mark_style_dirty;
+use crate::renderer::dom::node::Node;
+use crate::renderer::dom::node::NodeKind;
+use crate::renderer::layout::computed_style::ComputedStyle;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+/// DOMツリーの各ノードのスタイルを管理する
+/// 計算したスタイルはノードに保持し、DOMやスタイルシートの変更で影響を受けるノードだけに
+/// 再計算が必要な印をつける. `restyle`は印のついた部分木だけを再計算する
+#[derive(Debug, Clone)]
+pub struct StyleEngine {
+    /// 作成者のスタイルシート
+    style_sheet: StyleSheet,
+    media: MediaEnvironment,
+    /// @mediaなどの条件を評価した後の、適用されるスタイルルール
+    rules: Vec<QualifiedRule>,
+    /// 適用されるスタイルルールのセレクタから作った無効化の集合
+    invalidation: InvalidationSet,
+}
+
+impl StyleEngine {
+    pub fn new(style_sheet: StyleSheet, media: MediaEnvironment) -> Self {
+        let rules = applicable_rules(&style_sheet, &media);
+        let mut invalidation = InvalidationSet::new();
+        for rule in &rules {
+            invalidation.add_style_rule(rule);
+        }
+
+        Self {
+            style_sheet,
+            media,
+            rules,
+            invalidation,
+        }
+    }
+
+    pub fn style_sheet(&self) -> &StyleSheet {
+        &self.style_sheet
+    }
+
+    pub fn media(&self) -> &MediaEnvironment {
+        &self.media
+    }
+
+    /// スタイルシートを置き換え、追加・削除されたルールに一致しうる要素に印をつける
+    pub fn set_style_sheet(&mut self, root: &Rc<RefCell<Node>>, style_sheet: StyleSheet) {
+        self.style_sheet = style_sheet;
+        self.update_rules(root);
+    }
+
+    /// @mediaの評価に使う環境を変更し、適用されるかどうかが変わったルールに一致しうる要素に印をつける
+    pub fn set_media(&mut self, root: &Rc<RefCell<Node>>, media: MediaEnvironment) {
+        // ビューポートの大きさが変わると、vwやvhを使う値はどの要素でも変わりうる
+        let resized = media.width() != self.media.width() || media.height() != self.media.height();
+        self.media = media;
+        if resized && uses_viewport_units(&self.rules) {
+            mark_root_elements_dirty(root);
+        }
+        self.update_rules(root);
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstylesheet-insertrule
+    pub fn insert_rule(
+        &mut self,
+        root: &Rc<RefCell<Node>>,
+        rule: &str,
+        index: usize,
+    ) -> Result<usize, Error> {
+        let index = self.style_sheet.insert_rule(rule, index)?;
+        self.update_rules(root);
+        Ok(index)
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstylesheet-deleterule
+    pub fn delete_rule(&mut self, root: &Rc<RefCell<Node>>, index: usize) -> Result<(), Error> {
+        self.style_sheet.delete_rule(index)?;
+        self.update_rules(root);
+        Ok(())
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-element-setattribute
+    /// 要素の属性を変更し、一致するセレクタが変わりうる場合は印をつける
+    pub fn set_attribute(&self, node: &Rc<RefCell<Node>>, name: &str, value: &str) {
+        let name = name.to_ascii_lowercase();
+        let old = match node.borrow().get_element() {
+            Some(element) => element.get_attribute(&name),
+            None => return,
+        };
+
+        if let Some(element) = node.borrow_mut().get_element_mut() {
+            element.set_attribute(&name, value);
+        }
+
+        if self
+            .invalidation
+            .is_affected_by_attribute_change(&name, old.as_deref(), Some(value))
+        {
+            mark_style_dirty(node);
+        }
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-element-removeattribute
+    pub fn remove_attribute(&self, node: &Rc<RefCell<Node>>, name: &str) {
+        let name = name.to_ascii_lowercase();
+        let old = match node.borrow().get_element() {
+            Some(element) => element.get_attribute(&name),
+            None => return,
+        };
+
+        if let Some(element) = node.borrow_mut().get_element_mut() {
+            element.remove_attribute(&name);
+        }
+
+        if self
+            .invalidation
+            .is_affected_by_attribute_change(&name, old.as_deref(), None)
+        {
+            mark_style_dirty(node);
+        }
+    }
+
+    /// 印のついたノードのスタイルを再計算し、再計算した要素の数を返す
+    /// スタイルが変わった要素の子孫は、継承する値が変わりうるのですべて再計算する
+    pub fn restyle(&self, root: &Rc<RefCell<Node>>) -> usize {
+        let mut count = 0;
+        self.restyle_node(root, None, false, &mut count);
+        count
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-window-getcomputedstyle
+    /// ノードの計算値を返す. display: noneの要素など、レイアウトオブジェクトを作らない要素でも
+    /// カスケードを行う. 再計算済みのスタイルが最新の場合はそれを使い、そうでなければ
+    /// ルートから順に計算する（ノードに保持したスタイルや印は変更しない）
+    pub fn get_computed_style(&self, node: &Rc<RefCell<Node>>) -> ComputedStyle {
+        // ルートからノードまでの経路
+        let mut path = Vec::new();
+        let mut current = Some(node.clone());
+        while let Some(n) = current {
+            current = n.borrow().parent().upgrade();
+            path.push(n);
+        }
+        path.reverse();
+
+        let mut parent_style: Option<ComputedStyle> = None;
+        let mut up_to_date = true;
+        for n in path {
+            if n.borrow().kind() == NodeKind::Document {
+                continue;
+            }
+
+            // 祖先のスタイルが最新でなければ、子孫のスタイルも最新ではない
+            up_to_date = up_to_date && !n.borrow().style_dirty();
+            let cached = n.borrow().computed_style();
+            let style = match cached {
+                Some(style) if up_to_date => style,
+                _ => {
+                    up_to_date = false;
+                    self.compute_style(&n, parent_style.clone())
+                }
+            };
+            parent_style = Some(style);
+        }
+
+        // ドキュメントはスタイルを持たないので、すべてのプロパティが初期値になる
+        parent_style.unwrap_or_default()
+    }
+
+    /// https://www.w3.org/TR/css-pseudo-4/#generated-content
+    /// 要素の疑似要素のスタイル. ::beforeと::afterのcontentがnormalまたはnoneの場合は、
+    /// ボックスを生成しないのでNoneを返す. ::markerは一致するルールがなくてもスタイルを返す
+    pub fn get_pseudo_element_style(
+        &self,
+        node: &Rc<RefCell<Node>>,
+        pseudo: PseudoElement,
+    ) -> Option<ComputedStyle> {
+        let element = node.borrow().get_element()?;
+        let mut style = ComputedStyle::new();
+        for declaration in cascade_sorted_declarations(&self.rules, &element, Some(pseudo)) {
+            style.cascade_declaration(&declaration);
+        }
+        style.defaulting_pseudo_element(self.get_computed_style(node));
+
+        match pseudo {
+            PseudoElement::Before | PseudoElement::After if style.content().is_none() => None,
+            _ => Some(style),
+        }
+    }
+
+    fn restyle_node(
+        &self,
+        node: &Rc<RefCell<Node>>,
+        parent_style: Option<&ComputedStyle>,
+        force: bool,
+        count: &mut usize,
+    ) {
+        let kind = node.borrow().kind();
+        let style_dirty = node.borrow().style_dirty();
+        let child_style_dirty = node.borrow().child_style_dirty();
+
+        let mut force_children = false;
+        let style = match kind {
+            // ドキュメントはスタイルを持たず、ルート要素は親要素のスタイルを持たない
+            NodeKind::Document => None,
+            NodeKind::Element(_) | NodeKind::Text(_) => {
+                if force || style_dirty {
+                    let style = self.compute_style(node, parent_style.cloned());
+                    if let NodeKind::Element(_) = kind {
+                        *count += 1;
+                    }
+                    force_children = node.borrow().computed_style().as_ref() != Some(&style);
+                    node.borrow_mut().set_computed_style(Some(style.clone()));
+                    Some(style)
+                } else {
+                    node.borrow().computed_style()
+                }
+            }
+        };
+        node.borrow_mut().set_style_dirty(false);
+
+        if force_children || child_style_dirty {
+            let mut child = node.borrow().first_child();
+            while let Some(c) = child {
+                self.restyle_node(&c, style.as_ref(), force_children, count);
+                child = c.borrow().next_sibling();
+            }
+        }
+        node.borrow_mut().set_child_style_dirty(false);
+    }
+
+    /// https://www.w3.org/TR/css-cascade-4/#cascading
+    /// セレクタが一致するルールの宣言を、レイアウトと同じくカスケードの順に並べて適用し、計算値を求める
+    fn compute_style(
+        &self,
+        node: &Rc<RefCell<Node>>,
+        parent_style: Option<ComputedStyle>,
+    ) -> ComputedStyle {
+        let mut style = ComputedStyle::new();
+        style.set_viewport(self.media.width(), self.media.height());
+
+        if let Some(element) = node.borrow().get_element() {
+            for declaration in cascade_sorted_declarations(&self.rules, &element, None) {
+                style.cascade_declaration(&declaration);
+            }
+        }
+
+        style.defaulting(node, parent_style);
+        style
+    }
+
+    /// 適用されるスタイルルールを求め直し、変わったルールに一致しうる要素に印をつける
+    fn update_rules(&mut self, root: &Rc<RefCell<Node>>) {
+        let rules = applicable_rules(&self.style_sheet, &self.media);
+        let changed = changed_rules(&self.rules, &rules);
+
+        self.invalidation = InvalidationSet::new();
+        for rule in &rules {
+            self.invalidation.add_style_rule(rule);
+        }
+        self.rules = rules;
+
+        if !changed.is_empty() {
+            invalidate_matching_elements(root, &changed);
+        }
+    }
+}
+
+fn applicable_rules(style_sheet: &StyleSheet, media: &MediaEnvironment) -> Vec<QualifiedRule> {
+    style_sheet.evaluate(media).style_rules().cloned().collect()
+}
+
+/// https://www.w3.org/TR/css-values-4/#viewport-relative-lengths
+/// ルールの宣言にビューポートの大きさを基準にする長さが含まれるかどうか
+fn uses_viewport_units(rules: &[QualifiedRule]) -> bool {
+    rules
+        .iter()
+        .flat_map(|rule| &rule.declarations)
+        .flat_map(|declaration| &declaration.value)
+        .any(|token| match token {
+            CssToken::Dimension { unit, .. } => matches!(
+                LengthUnit::from_unit(unit),
+                Ok(LengthUnit::Vw) | Ok(LengthUnit::Vh)
+            ),
+            _ => false,
+        })
+}
+
+/// ルート要素に再計算が必要な印をつける. ルート要素の計算値が変われば子孫もすべて再計算される
+fn mark_root_elements_dirty(root: &Rc<RefCell<Node>>) {
+    let mut child = root.borrow().first_child();
+    while let Some(c) = child {
+        let is_element = matches!(c.borrow().kind(), NodeKind::Element(_));
+        if is_element {
+            mark_style_dirty(&c);
+        }
+        child = c.borrow().next_sibling();
+    }
+}
+
+/// 無効化の集合のセレクタに一致しうる要素に、再計算が必要な印をつける
+fn invalidate_matching_elements(node: &Rc<RefCell<Node>>, invalidation: &InvalidationSet) {
+    let matched = match node.borrow().get_element() {
+        Some(element) => invalidation.may_match(&element),
+        None => false,
+    };
+    if matched {
+        mark_style_dirty(node);
+    }
+
+    let mut child = node.borrow().first_child();
+    while let Some(c) = child {
+        invalidate_matching_elements(&c, invalidation);
+        child = c.borrow().next_sibling();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::color::Color;
+    use crate::renderer::css::cssom::CssParser;
+    use crate::renderer::css::token::CssTokenizer;
+    use crate::renderer::css::value::ComputedLength;
+    use crate::renderer::dom::api::get_target_element_node;
+    use crate::renderer::dom::api::get_target_element_nodes;
+    use crate::renderer::dom::node::ElementKind;
+    use crate::renderer::html::parser::HtmlParser;
+    use crate::renderer::html::token::HtmlTokenizer;
+    use crate::renderer::layout::computed_style::DisplayType;
+    use alloc::string::ToString;
+
+    const HTML: &str =
+        "<html><head></head><body><p class=\"a\">x</p><p>y</p><h1 id=\"t\">z</h1></body></html>";
+
+    fn setup(style: &str, media: MediaEnvironment) -> (Rc<RefCell<Node>>, StyleEngine) {
+        let window = HtmlParser::new(HtmlTokenizer::new(HTML.to_string())).construct_tree();
+        let document = window.borrow().document();
+        let sheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+        (document, StyleEngine::new(sheet, media))
+    }
+
+    fn element(document: &Rc<RefCell<Node>>, kind: ElementKind) -> Rc<RefCell<Node>> {
+        get_target_element_node(Some(document.clone()), kind).expect("element should exist")
+    }
+
+    fn color(node: &Rc<RefCell<Node>>) -> Color {
+        node.borrow()
+            .computed_style()
+            .expect("style should be computed")
+            .color()
+    }
+
+    #[test]
+    fn test_initial_restyle() {
+        /* 初回はすべての要素が、変更がなければどの要素も再計算されないことを確認する */
+        let (document, engine) = setup("p { color: red }", MediaEnvironment::default());
+
+        // html, head, body, p, p, h1
+        assert_eq!(engine.restyle(&document), 6);
+        assert_eq!(engine.restyle(&document), 0);
+
+        let p = element(&document, ElementKind::P);
+        assert_eq!(color(&p), Color::rgb(255, 0, 0));
+        // テキストも親要素から継承したスタイルを持つ
+        let text = p.borrow().first_child().expect("text should exist");
+        assert_eq!(color(&text), Color::rgb(255, 0, 0));
+    }
+
+    #[test]
+    fn test_attribute_invalidation() {
+        /* セレクタに関係する属性の変更だけが、その要素を再計算させることを確認する */
+        let (document, engine) = setup(
+            ".b { color: blue } #u { color: lime }",
+            MediaEnvironment::default(),
+        );
+        engine.restyle(&document);
+        let p = element(&document, ElementKind::P);
+        let h1 = element(&document, ElementKind::H1);
+
+        engine.set_attribute(&p, "class", "a b");
+        assert_eq!(engine.restyle(&document), 1);
+        assert_eq!(color(&p), Color::rgb(0, 0, 255));
+
+        // セレクタに使われていないクラスや属性の変更は再計算しない
+        engine.set_attribute(&p, "class", "b c");
+        engine.set_attribute(&p, "title", "hello");
+        assert_eq!(engine.restyle(&document), 0);
+        assert_eq!(p.borrow().get_element().unwrap().class_list(), ["b", "c"]);
+
+        engine.set_attribute(&h1, "ID", "u");
+        engine.remove_attribute(&p, "class");
+        assert_eq!(engine.restyle(&document), 2);
+        assert_eq!(color(&h1), Color::rgb(0, 255, 0));
+        assert_eq!(color(&p), Color::black());
+    }
+
+    #[test]
+    fn test_get_computed_style() {
+        /* 再計算の前後や、display: noneの要素でも計算値を取得できることを確認する */
+        let (document, engine) = setup(
+            "body { color: red } .b { display: none; background-color: blue }",
+            MediaEnvironment::default(),
+        );
+        let p = element(&document, ElementKind::P);
+
+        // 再計算の前でも、ルートから順にカスケードを行う
+        let style = engine.get_computed_style(&p);
+        assert_eq!(style.color(), Color::rgb(255, 0, 0));
+        assert!(p.borrow().computed_style().is_none());
+
+        engine.restyle(&document);
+        engine.set_attribute(&p, "class", "b");
+        let style = engine.get_computed_style(&p);
+        assert_eq!(style.display(), DisplayType::DisplayNone);
+        assert_eq!(style.background_color(), Color::rgb(0, 0, 255));
+        assert_eq!(style.color(), Color::rgb(255, 0, 0));
+        // 保持しているスタイルと印は変更しない
+        assert!(p.borrow().style_dirty());
+
+        let text = p.borrow().first_child().expect("text should exist");
+        assert_eq!(
+            engine.get_computed_style(&text).color(),
+            Color::rgb(255, 0, 0)
+        );
+        assert_eq!(
+            engine.get_computed_style(&document).display(),
+            DisplayType::Inline
+        );
+    }
+
+    #[test]
+    fn test_cascade_specificity() {
+        /* 計算値の取得と再計算が、詳細度と出現順に従ってカスケードすることを確認する */
+        let (document, engine) = setup(
+            "#t { color: red } h1 { color: blue } .a::before { content: \"x\"; color: lime } \
+             p::before { content: \"y\"; color: blue }",
+            MediaEnvironment::default(),
+        );
+        let h1 = element(&document, ElementKind::H1);
+        assert_eq!(
+            engine.get_computed_style(&h1).color(),
+            Color::rgb(255, 0, 0)
+        );
+        engine.restyle(&document);
+        assert_eq!(color(&h1), Color::rgb(255, 0, 0));
+
+        let p = element(&document, ElementKind::P);
+        let before = engine
+            .get_pseudo_element_style(&p, PseudoElement::Before)
+            .expect("::before should exist");
+        assert_eq!(before.color(), Color::rgb(0, 255, 0));
+    }
+
+    #[test]
+    fn test_viewport_units() {
+        /* vwとvhがメディア環境のビューポートを基準にし、大きさが変わると再計算されることを確認する */
+        let (document, mut engine) = setup(
+            "p { width: 50vw; font-size: 5vh }",
+            MediaEnvironment::new(400.0, 300.0),
+        );
+        engine.restyle(&document);
+        let p = element(&document, ElementKind::P);
+        let style = p
+            .borrow()
+            .computed_style()
+            .expect("style should be computed");
+        assert_eq!(style.width(), ComputedLength::Px(200.0));
+        assert_eq!(style.font_size(), 15.0);
+
+        engine.set_media(&document, MediaEnvironment::new(800.0, 300.0));
+        assert_eq!(engine.restyle(&document), 6);
+        let style = p
+            .borrow()
+            .computed_style()
+            .expect("style should be computed");
+        assert_eq!(style.width(), ComputedLength::Px(400.0));
+    }
+
+    #[test]
+    fn test_inherited_change() {
+        /* スタイルが変わった要素の子孫が再計算されることを確認する */
+        let (document, engine) = setup(".dark { color: white }", MediaEnvironment::default());
+        engine.restyle(&document);
+        let body = element(&document, ElementKind::Body);
+
+        engine.set_attribute(&body, "class", "dark");
+        // body, p, p, h1
+        assert_eq!(engine.restyle(&document), 4);
+        let h1 = element(&document, ElementKind::H1);
+        assert_eq!(color(&h1), Color::white());
+    }
+
+    #[test]
+    fn test_style_sheet_change() {
+        /* ルールの追加・削除や環境の変更で、影響を受ける要素だけが再計算されることを確認する */
+        let (document, mut engine) = setup(
+            "@media (min-width: 800px) { p { color: red } }",
+            MediaEnvironment::new(600.0, 400.0),
+        );
+        assert_eq!(engine.restyle(&document), 6);
+
+        let index = engine
+            .insert_rule(&document, "h1 { color: blue }", 1)
+            .expect("rule should be inserted");
+        assert_eq!(index, 1);
+        assert_eq!(engine.restyle(&document), 1);
+        assert_eq!(
+            color(&element(&document, ElementKind::H1)),
+            Color::rgb(0, 0, 255)
+        );
+
+        engine.set_media(&document, MediaEnvironment::new(1000.0, 400.0));
+        assert_eq!(engine.restyle(&document), 2);
+        for p in get_target_element_nodes(Some(document.clone()), ElementKind::P) {
+            assert_eq!(color(&p), Color::rgb(255, 0, 0));
+        }
+
+        // 適用されるルールが変わらない場合は再計算しない
+        engine.set_media(&document, MediaEnvironment::new(1200.0, 400.0));
+        assert_eq!(engine.restyle(&document), 0);
+
+        engine
+            .delete_rule(&document, 1)
+            .expect("rule should be deleted");
+        assert_eq!(engine.restyle(&document), 1);
+        assert_eq!(color(&element(&document, ElementKind::H1)), Color::black());
+    }
+}
@@ -1,19 +1,41 @@
 use crate::browser::Browser;
+use crate::http::Fetcher;
 use crate::http::HttpResponse;
+use crate::renderer::css::cssom::CssParser;
+use crate::renderer::css::cssom::CssRule;
+use crate::renderer::css::cssom::StyleSheet;
+use crate::renderer::css::media::MediaEnvironment;
+use crate::renderer::css::token::CssTokenizer;
+use crate::renderer::dom::api::get_style_contents;
+use crate::renderer::dom::node::Node;
 use crate::renderer::dom::node::Window;
 use crate::renderer::html::parser::HtmlParser;
 use crate::renderer::html::token::HtmlTokenizer;
+use crate::renderer::layout::computed_style::ComputedStyle;
+use crate::renderer::layout::layout_view::LayoutView;
+use crate::renderer::layout::style_engine::StyleEngine;
+use crate::url::Url;
 use crate::utils::convert_dom_to_string;
 use alloc::rc::Rc;
 use alloc::rc::Weak;
 use alloc::string::String;
 use alloc::string::ToString;
+use alloc::vec::Vec;
 use core::cell::RefCell;
 
+/// @importの入れ子の上限. 循環していない長い連鎖で取得し続けないようにする
+const MAX_IMPORT_DEPTH: usize = 8;
+
 #[derive(Debug, Clone, Default)]
 pub struct Page {
     browser: Weak<RefCell<Browser>>,
     frame: Option<Rc<RefCell<Window>>>,
+    style: Option<StyleSheet>,
+    style_engine: Option<StyleEngine>,
+    layout_view: Option<LayoutView>,
+    url: Option<Url>,
+    fetcher: Option<Rc<dyn Fetcher>>,
+    media: MediaEnvironment,
 }
 
 impl Page {
@@ -21,6 +43,12 @@ impl Page {
         Self {
             browser: Weak::new(),
             frame: None,
+            style: None,
+            style_engine: None,
+            layout_view: None,
+            url: None,
+            fetcher: None,
+            media: MediaEnvironment::default(),
         }
     }
 
@@ -28,8 +56,29 @@ impl Page {
         self.browser = browser;
     }
 
+    /// ページのURL. @importなどの相対URLを解決するベースになる
+    pub fn set_url(&mut self, url: Url) {
+        self.url = Some(url);
+    }
+
+    /// @importの取得に使う. 相対URLは`set_url`で設定したページのURLを基準に解決するので、URLも設定する
+    pub fn set_fetcher(&mut self, fetcher: Rc<dyn Fetcher>) {
+        self.fetcher = Some(fetcher);
+    }
+
+    /// @mediaの評価に使う環境（ウィンドウサイズなど）を設定する
+    /// ページを読み込み済みの場合は、適用されるルールが変わりうるのでレイアウトツリーを作り直す
+    pub fn set_media(&mut self, media: MediaEnvironment) {
+        self.media = media;
+        if let (Some(frame), Some(engine)) = (&self.frame, &mut self.style_engine) {
+            engine.set_media(&frame.borrow().document(), media);
+            self.set_layout_view();
+        }
+    }
+
     pub fn receive_response(&mut self, response: HttpResponse) -> String {
         self.create_frame(response.body());
+        self.set_layout_view();
 
         // デバッグ用にDOMツリーを文字列として返す
         if let Some(frame) = &self.frame {
@@ -41,9 +90,322 @@ impl Page {
         "".to_string()
     }
 
+    pub fn style(&self) -> Option<StyleSheet> {
+        self.style.clone()
+    }
+
+    pub fn style_engine(&self) -> Option<&StyleEngine> {
+        self.style_engine.as_ref()
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-window-getcomputedstyle
+    /// レイアウトツリーを作らずに、DOMノードの計算値を求める
+    pub fn get_computed_style(&self, node: &Rc<RefCell<Node>>) -> Option<ComputedStyle> {
+        self.style_engine
+            .as_ref()
+            .map(|engine| engine.get_computed_style(node))
+    }
+
+    pub fn layout_view(&self) -> Option<LayoutView> {
+        self.layout_view.clone()
+    }
+
     fn create_frame(&mut self, html: String) {
         let html_tokenizer = HtmlTokenizer::new(html);
         let frame = HtmlParser::new(html_tokenizer).construct_tree();
+        let dom = frame.borrow().document();
+
+        // すべての<style>要素をツリー順にパースし、1つのスタイルシートにまとめる
+        // 要素ごとにパースするので、ある<style>の閉じ忘れなどが後続の<style>に影響しない
+        let mut style = StyleSheet::new();
+        for content in get_style_contents(dom.clone()) {
+            let css_tokenizer = CssTokenizer::new(content);
+            let mut sheet = CssParser::new(css_tokenizer).parse_stylesheet();
+            self.resolve_imports(&mut sheet, self.url.clone(), &mut Vec::new());
+            style.rules.extend(sheet.rules);
+        }
+
+        let engine = StyleEngine::new(style.clone(), self.media);
+        engine.restyle(&dom);
+
         self.frame = Some(frame);
+        self.style = Some(style);
+        self.style_engine = Some(engine);
+    }
+
+    /// https://www.w3.org/TR/css-cascade-4/#at-import
+    /// @importされたスタイルシートを取得し、ImportRuleに格納する
+    /// 取得に失敗した@importは、空のスタイルシートとして扱われる（stylesheetがNoneのまま）
+    /// `chain`は、インポート元をたどったスタイルシートのURL. 循環する@importは取得しない
+    fn resolve_imports(&self, sheet: &mut StyleSheet, base: Option<Url>, chain: &mut Vec<String>) {
+        let fetcher = match &self.fetcher {
+            Some(fetcher) => fetcher,
+            None => return,
+        };
+        if chain.len() >= MAX_IMPORT_DEPTH {
+            return;
+        }
+
+        for rule in sheet.rules.iter_mut() {
+            let import_rule = match rule {
+                CssRule::Import(import_rule) => import_rule,
+                _ => continue,
+            };
+
+            let url = match &base {
+                Some(base) => base.resolve(&import_rule.href),
+                None => Url::new(import_rule.href.clone()).parse(),
+            };
+            let url = match url {
+                Ok(url) => url,
+                Err(_) => continue,
+            };
+            if chain.contains(&url.url()) {
+                continue;
+            }
+
+            let response = match fetcher.fetch(url.url()) {
+                Ok(response) => response,
+                Err(_) => continue,
+            };
+            if !(200..300).contains(&response.status_code()) {
+                continue;
+            }
+
+            let css_tokenizer = CssTokenizer::new(response.body());
+            let mut imported = CssParser::new(css_tokenizer).parse_stylesheet();
+            // インポートされたスタイルシート内の相対URLは、そのスタイルシートのURLを基準にする
+            chain.push(url.url());
+            self.resolve_imports(&mut imported, Some(url), chain);
+            chain.pop();
+            import_rule.stylesheet = Some(imported);
+        }
+    }
+
+    /// DOMツリーとCSSOMからレイアウトツリーを構築する
+    fn set_layout_view(&mut self) {
+        let dom = match &self.frame {
+            Some(frame) => frame.borrow().document(),
+            None => return,
+        };
+
+        // スタイルエンジンが@mediaと@supportsを評価し、適用されるスタイルルールで計算したスタイルを使う
+        let engine = match &self.style_engine {
+            Some(engine) => engine,
+            None => return,
+        };
+        self.layout_view = Some(LayoutView::new(dom, engine));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::renderer::css::color::Color;
+    use crate::renderer::css::cssom::Selector;
+    use crate::renderer::dom::api::get_target_element_node;
+    use crate::renderer::dom::node::ElementKind;
+    use crate::renderer::layout::computed_style::DisplayType;
+    use alloc::format;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn create_page(body: &str) -> Page {
+        let raw = format!("HTTP/1.1 200 OK\nData: xx xx xx\n\n{}", body);
+        let response = HttpResponse::new(raw).expect("failed to parse http response");
+        let mut page = Page::new();
+        page.receive_response(response);
+        page
+    }
+
+    #[test]
+    fn test_no_style() {
+        /* <style>要素がない場合、空のスタイルシートが作られることを確認する */
+        let page = create_page("<html><head></head><body><p>text</p></body></html>");
+        let style = page.style().expect("style should exist");
+
+        assert_eq!(style.rules.len(), 0);
+        assert!(page.layout_view().is_some());
+    }
+
+    #[test]
+    fn test_multiple_style_elements() {
+        /* 複数の<style>要素が、ツリー順に1つのスタイルシートへまとめられることを確認する */
+        let page = create_page(
+            "<html><head><style>h1 { color: red; }</style><style>#id { color: blue; }</style></head>\
+             <body><p>text</p><style>.class { display: none; }</style></body></html>",
+        );
+        let style = page.style().expect("style should exist");
+
+        let selectors: Vec<Selector> = style
+            .style_rules()
+            .map(|rule| rule.selector.clone())
+            .collect();
+        assert_eq!(
+            selectors,
+            vec![
+                Selector::TypeSelector("h1".to_string()),
+                Selector::IdSelector("id".to_string()),
+                Selector::ClassSelector("class".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_computed_style() {
+        /* レイアウトツリーに含まれない要素でも、計算値を取得できることを確認する */
+        let page = create_page(
+            "<html><head><style>.hidden { display: none; color: red; } body { color: blue; }</style></head>\
+             <body><p class=\"hidden\">text</p></body></html>",
+        );
+        let document = page
+            .frame
+            .clone()
+            .expect("frame should exist")
+            .borrow()
+            .document();
+        let p = get_target_element_node(Some(document), ElementKind::P).expect("p should exist");
+
+        let style = page.get_computed_style(&p).expect("style should exist");
+        assert_eq!(style.display(), DisplayType::DisplayNone);
+        assert_eq!(style.color(), Color::rgb(255, 0, 0));
+        assert_eq!(style.get_property_value("display"), "none");
+    }
+
+    #[test]
+    fn test_layout_agrees_with_computed_style() {
+        /* レイアウトツリーのスタイルと計算値の取得で、<html>からの継承とremの基準が一致することを確認する */
+        let page = create_page(
+            "<html><head><style>html { color: red; font-size: 32px } p { font-size: 0.5rem }</style></head>\
+             <body><p>text</p></body></html>",
+        );
+        let document = page
+            .frame
+            .clone()
+            .expect("frame should exist")
+            .borrow()
+            .document();
+        let body_node =
+            get_target_element_node(Some(document.clone()), ElementKind::Body).expect("body");
+        let p_node = get_target_element_node(Some(document), ElementKind::P).expect("p");
+
+        let view = page.layout_view().expect("layout view should exist");
+        let body = view.root().expect("root should exist");
+        let p = body.borrow().first_child().expect("p should exist");
+        assert_eq!(body.borrow().style().color(), Color::rgb(255, 0, 0));
+        assert_eq!(p.borrow().style().font_size(), 16.0);
+        for (node, object) in [(body_node, body), (p_node, p)] {
+            let computed = page.get_computed_style(&node).expect("style should exist");
+            let laid_out = object.borrow().style();
+            assert_eq!(laid_out.color(), computed.color());
+            assert_eq!(laid_out.font_size(), computed.font_size());
+            assert_eq!(laid_out.root_font_size(), computed.root_font_size());
+        }
+    }
+
+    #[test]
+    fn test_set_media_relayout() {
+        /* 環境を変更すると、@mediaの評価が変わったレイアウトツリーに作り直されることを確認する */
+        let mut page = create_page(
+            "<html><head><style>@media (max-width: 500px) { p { display: none } }</style></head>\
+             <body><p>a</p><h1>b</h1></body></html>",
+        );
+        let first_child_kind = |page: &Page| {
+            page.layout_view()
+                .and_then(|view| view.root())
+                .and_then(|body| body.borrow().first_child())
+                .and_then(|child| child.borrow().node().borrow().element_kind())
+        };
+        assert_eq!(first_child_kind(&page), Some(ElementKind::P));
+
+        page.set_media(MediaEnvironment::new(400.0, 300.0));
+        assert_eq!(first_child_kind(&page), Some(ElementKind::H1));
+
+        page.set_media(MediaEnvironment::new(800.0, 300.0));
+        assert_eq!(first_child_kind(&page), Some(ElementKind::P));
+    }
+
+    /// 決められたURLに対して決められたCSSを返すFetcher
+    #[derive(Debug)]
+    struct TestFetcher {
+        resources: Vec<(String, String)>,
+    }
+
+    impl Fetcher for TestFetcher {
+        fn fetch(&self, url: String) -> Result<HttpResponse, Error> {
+            for (resource_url, body) in &self.resources {
+                if *resource_url == url {
+                    return HttpResponse::new(format!("HTTP/1.1 200 OK\n\n{}", body));
+                }
+            }
+            HttpResponse::new("HTTP/1.1 404 NotFound\n\n".to_string())
+        }
+    }
+
+    fn create_page_with_fetcher(body: &str, resources: &[(&str, &str)]) -> Page {
+        let raw = format!("HTTP/1.1 200 OK\nData: xx xx xx\n\n{}", body);
+        let response = HttpResponse::new(raw).expect("failed to parse http response");
+        let mut page = Page::new();
+        page.set_url(
+            Url::new("http://example.com/dir/index.html".to_string())
+                .parse()
+                .expect("failed to parse url"),
+        );
+        page.set_fetcher(Rc::new(TestFetcher {
+            resources: resources
+                .iter()
+                .map(|(url, body)| (url.to_string(), body.to_string()))
+                .collect(),
+        }));
+        page.receive_response(response);
+        page
+    }
+
+    #[test]
+    fn test_import() {
+        /* @importされたスタイルシートが相対URLで取得され、@importの位置に展開されることを確認する */
+        let page = create_page_with_fetcher(
+            "<html><head><style>@import url(a.css); @import \"/missing.css\"; p { color: red; }</style></head></html>",
+            &[
+                ("http://example.com/dir/a.css", "@import \"../b.css\"; h1 { color: blue; }"),
+                ("http://example.com/b.css", "h2 { color: green; }"),
+            ],
+        );
+        let style = page
+            .style()
+            .expect("style should exist")
+            .evaluate(&MediaEnvironment::default());
+
+        let selectors: Vec<Selector> = style
+            .style_rules()
+            .map(|rule| rule.selector.clone())
+            .collect();
+        assert_eq!(
+            selectors,
+            vec![
+                Selector::TypeSelector("h2".to_string()),
+                Selector::TypeSelector("h1".to_string()),
+                Selector::TypeSelector("p".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_cycle() {
+        /* 循環した@importは取得せず、ルールが一度だけ含まれることを確認する */
+        let page = create_page_with_fetcher(
+            "<html><head><style>@import url(a.css);</style></head></html>",
+            &[(
+                "http://example.com/dir/a.css",
+                "@import url(a.css); p { color: red; }",
+            )],
+        );
+        let style = page
+            .style()
+            .expect("style should exist")
+            .evaluate(&MediaEnvironment::default());
+
+        assert_eq!(style.style_rules().count(), 1);
     }
 }
@@ -1,3 +1,4 @@
+use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
@@ -41,6 +42,10 @@ impl Url {
         false
     }
 
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
     pub fn host(&self) -> String {
         self.host.clone()
     }
@@ -98,6 +103,85 @@ impl Url {
         path_and_searchpart[0].to_string()
     }
 
+    /// https://url.spec.whatwg.org/#concept-basic-url-parser
+    /// このURLをベースとして、相対URLを絶対URLに解決する
+    /// `self`はパース済みであることを前提とする
+    pub fn resolve(&self, reference: &str) -> Result<Self, String> {
+        let reference = reference.trim();
+
+        if reference.starts_with("http://") {
+            return Url::new(reference.to_string()).parse();
+        }
+        // "//example.com/a.css"のようなスキーム相対URL
+        if let Some(rest) = reference.strip_prefix("//") {
+            return Url::new(format!("http://{}", rest)).parse();
+        }
+        // ほかのスキームはサポートしない
+        if let Some(index) = reference.find(':') {
+            if !reference[..index].contains(['/', '?']) {
+                return Err("Only HTTP scheme is supported.".to_string());
+            }
+        }
+
+        let (reference_path, search_part) = match reference.split_once('?') {
+            Some((path, search)) => (path, Some(search)),
+            None => (reference, None),
+        };
+
+        let mut segments: Vec<&str> = Vec::new();
+        if let Some(absolute_path) = reference_path.strip_prefix('/') {
+            segments.extend(absolute_path.split('/'));
+        } else if reference_path.is_empty() {
+            segments.extend(self.path.split('/'));
+        } else {
+            // ベースURLのパスの最後のセグメントを取り除いて連結する
+            let mut base: Vec<&str> = self.path.split('/').collect();
+            base.pop();
+            segments.extend(base);
+            segments.extend(reference_path.split('/'));
+        }
+
+        // "."と".."のセグメントを取り除く
+        let mut path: Vec<&str> = Vec::new();
+        let last = segments.len().saturating_sub(1);
+        for (i, segment) in segments.into_iter().enumerate() {
+            match segment {
+                "." => {}
+                ".." => {
+                    path.pop();
+                }
+                _ => {
+                    path.push(segment);
+                    continue;
+                }
+            }
+            // 末尾の"."と".."はディレクトリを指す
+            if i == last {
+                path.push("");
+            }
+        }
+
+        let search_part = match search_part {
+            Some(search) => search.to_string(),
+            None if reference_path.is_empty() => self.search_part.clone(),
+            None => "".to_string(),
+        };
+
+        let mut url = format!("http://{}", self.host);
+        if self.port != "80" {
+            url.push(':');
+            url.push_str(&self.port);
+        }
+        url.push('/');
+        url.push_str(&path.join("/"));
+        if !search_part.is_empty() {
+            url.push('?');
+            url.push_str(&search_part);
+        }
+
+        Url::new(url).parse()
+    }
+
     pub fn search_part(&self) -> String {
         self.search_part.clone()
     }
@@ -198,4 +282,55 @@ mod tests {
         let expected = Err("Only HTTP scheme is supported.".to_string());
         assert_eq!(expected, Url::new(url).parse());
     }
+
+    #[test]
+    fn test_resolve_relative_path() {
+        /* 正常系：相対パスがベースURLのディレクトリを基準に解決されること */
+        let base = Url::new("http://example.com:8888/dir/index.html".to_string())
+            .parse()
+            .expect("failed to parse base url");
+        assert_eq!(
+            base.resolve("style.css").map(|url| url.url),
+            Ok("http://example.com:8888/dir/style.css".to_string())
+        );
+        assert_eq!(
+            base.resolve("./css/a.css?v=1").map(|url| url.url),
+            Ok("http://example.com:8888/dir/css/a.css?v=1".to_string())
+        );
+        assert_eq!(
+            base.resolve("../b.css").map(|url| url.url),
+            Ok("http://example.com:8888/b.css".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_absolute() {
+        /* 正常系：絶対パス、スキーム相対URL、絶対URLが解決されること */
+        let base = Url::new("http://example.com/dir/index.html".to_string())
+            .parse()
+            .expect("failed to parse base url");
+        assert_eq!(
+            base.resolve("/root.css").map(|url| url.url),
+            Ok("http://example.com/root.css".to_string())
+        );
+        assert_eq!(
+            base.resolve("//other.com/a.css").map(|url| url.url),
+            Ok("http://other.com/a.css".to_string())
+        );
+        assert_eq!(
+            base.resolve("http://other.com:8000/a.css")
+                .map(|url| url.path()),
+            Ok("a.css".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_unsupported_scheme() {
+        /* 異常系：HTTP以外のスキームは解決できないこと */
+        let base = Url::new("http://example.com/index.html".to_string())
+            .parse()
+            .expect("failed to parse base url");
+        assert!(base.resolve("https://example.com/a.css").is_err());
+        assert!(base.resolve("data:text/css,p{}").is_err());
+    }
 }
@@ -4,11 +4,14 @@ use alloc::string::String;
 use alloc::string::ToString;
 use alloc::vec::Vec;
 use browser_core::error::Error;
+use browser_core::http::Fetcher;
 use browser_core::http::HttpResponse;
+use browser_core::url::Url;
 use noli::net::lookup_host;
 use noli::net::SocketAddr;
 use noli::net::TcpStream;
 
+#[derive(Debug)]
 pub struct HttpClient {}
 
 impl HttpClient {
@@ -91,3 +94,24 @@ impl HttpClient {
         }
     }
 }
+
+impl Fetcher for HttpClient {
+    fn fetch(&self, url: String) -> Result<HttpResponse, Error> {
+        let url = match Url::new(url).parse() {
+            Ok(url) => url,
+            Err(e) => return Err(Error::Network(e)),
+        };
+        let port = match url.port().parse::<u16>() {
+            Ok(port) => port,
+            Err(e) => return Err(Error::Network(format!("Invalid port number: {}", e))),
+        };
+
+        let mut path = url.path();
+        if !url.search_part().is_empty() {
+            path.push('?');
+            path.push_str(&url.search_part());
+        }
+
+        self.get(url.host(), port, path)
+    }
+}